@@ -0,0 +1,292 @@
+//! automount_autofs模块封装autofs的内核接口，包括/dev/autofs上的ioctl和autofs管道中的请求包。
+//! ioctl通过AutofsIoctl trait调用，测试时可替换为桩。
+//!
+
+use std::{
+    cell::RefCell,
+    ffi::CString,
+    mem,
+    os::unix::{ffi::OsStrExt, io::RawFd},
+    path::Path,
+    ptr,
+};
+
+use nix::errno::Errno;
+use nix::fcntl::{self, OFlag};
+use nix::libc;
+use nix::sys::stat::Mode;
+use nix::unistd::{self, ForkResult};
+use utils::fd_util;
+
+const DEV_AUTOFS: &str = "/dev/autofs";
+
+// the version of the autofs protocol used, whose packets are autofs_v5_packet
+pub(super) const AUTOFS_PROTO_VERSION: u32 = 5;
+
+// the size of union autofs_v5_packet_union, which is written by the kernel at once
+pub(super) const AUTOFS_PACKET_SIZE: usize = 304;
+
+// the packet types of the direct mount, defined in <linux/auto_fs.h>
+const AUTOFS_PTYPE_MISSING_DIRECT: i32 = 5;
+const AUTOFS_PTYPE_EXPIRE_DIRECT: i32 = 6;
+
+// the wait queue token follows the header of two ints
+const AUTOFS_TOKEN_OFFSET: usize = 8;
+
+// the ioctls on /dev/autofs, defined in <linux/auto_dev-ioctl.h>
+const AUTOFS_IOCTL: u8 = 0x93;
+const AUTOFS_DEV_IOCTL_VERSION_MAJOR: u32 = 1;
+const AUTOFS_DEV_IOCTL_VERSION_MINOR: u32 = 1;
+
+nix::ioctl_readwrite!(autofs_dev_ioctl_version, AUTOFS_IOCTL, 0x71, AutofsDevIoctl);
+nix::ioctl_readwrite!(
+    autofs_dev_ioctl_protover,
+    AUTOFS_IOCTL,
+    0x72,
+    AutofsDevIoctl
+);
+nix::ioctl_readwrite!(
+    autofs_dev_ioctl_openmount,
+    AUTOFS_IOCTL,
+    0x74,
+    AutofsDevIoctl
+);
+nix::ioctl_readwrite!(autofs_dev_ioctl_ready, AUTOFS_IOCTL, 0x76, AutofsDevIoctl);
+nix::ioctl_readwrite!(autofs_dev_ioctl_fail, AUTOFS_IOCTL, 0x77, AutofsDevIoctl);
+nix::ioctl_readwrite!(autofs_dev_ioctl_timeout, AUTOFS_IOCTL, 0x7a, AutofsDevIoctl);
+nix::ioctl_readwrite!(autofs_dev_ioctl_expire, AUTOFS_IOCTL, 0x7c, AutofsDevIoctl);
+
+/// struct autofs_dev_ioctl, the arguments are the union of all ioctls, whose
+/// largest member is 8 bytes.
+#[repr(C)]
+pub struct AutofsDevIoctl {
+    ver_major: u32,
+    ver_minor: u32,
+    size: u32,
+    ioctlfd: i32,
+    args: [u32; 2],
+}
+
+impl AutofsDevIoctl {
+    fn new(ioctlfd: RawFd) -> Self {
+        AutofsDevIoctl {
+            ver_major: AUTOFS_DEV_IOCTL_VERSION_MAJOR,
+            ver_minor: AUTOFS_DEV_IOCTL_VERSION_MINOR,
+            size: mem::size_of::<AutofsDevIoctl>() as u32,
+            ioctlfd,
+            args: [0; 2],
+        }
+    }
+
+    // the u64 argument takes the whole union, such as the timeout
+    fn set_u64_arg(&mut self, v: u64) {
+        let b = v.to_ne_bytes();
+        self.args = [
+            u32::from_ne_bytes([b[0], b[1], b[2], b[3]]),
+            u32::from_ne_bytes([b[4], b[5], b[6], b[7]]),
+        ];
+    }
+}
+
+/// the requests from the kernel, the token is acknowledged by ready or fail
+#[derive(PartialEq, Eq, Debug)]
+pub(super) enum AutofsPacket {
+    Missing(u32),
+    Expire(u32),
+    Unknown(i32),
+}
+
+/// parse the autofs_v5_packet read from the pipe, the header is the protocol
+/// version and the packet type, followed by the wait queue token, which is an
+/// unsigned long. the token acknowledged by the ioctls is 32 bits.
+pub(super) fn autofs_packet_parse(buf: &[u8]) -> Option<AutofsPacket> {
+    let mut token = [0u8; mem::size_of::<libc::c_ulong>()];
+    let end = AUTOFS_TOKEN_OFFSET + token.len();
+    if buf.len() < end {
+        return None;
+    }
+
+    let ptype = i32::from_ne_bytes([buf[4], buf[5], buf[6], buf[7]]);
+    token.copy_from_slice(&buf[AUTOFS_TOKEN_OFFSET..end]);
+    let token = libc::c_ulong::from_ne_bytes(token) as u32;
+    Some(match ptype {
+        AUTOFS_PTYPE_MISSING_DIRECT => AutofsPacket::Missing(token),
+        AUTOFS_PTYPE_EXPIRE_DIRECT => AutofsPacket::Expire(token),
+        t => AutofsPacket::Unknown(t),
+    })
+}
+
+/// the ioctls of autofs, the ioctl fd is the one of the autofs mount point.
+pub(super) trait AutofsIoctl {
+    /// open the ioctl fd of the autofs mounted on {path}, whose device is {dev}
+    fn open_ioctl_fd(&self, dev: u64, path: &Path) -> Result<RawFd, Errno>;
+
+    fn protocol_version(&self, ioctl_fd: RawFd) -> Result<u32, Errno>;
+
+    /// the idle timeout in seconds, after which the mount point can be expired
+    fn set_timeout(&self, ioctl_fd: RawFd, sec: u64) -> Result<(), Errno>;
+
+    fn send_ready(&self, ioctl_fd: RawFd, token: u32) -> Result<(), Errno>;
+
+    fn send_fail(&self, ioctl_fd: RawFd, token: u32, status: i32) -> Result<(), Errno>;
+
+    /// ask the kernel to expire the idle mount point, which is blocked until the
+    /// expire request is acknowledged, so it can not be done in the event loop.
+    fn expire_in_background(&self, ioctl_fd: RawFd);
+}
+
+/// the ioctls through /dev/autofs, which is opened on the first use.
+pub(super) struct AutofsDev {
+    fd: RefCell<RawFd>,
+}
+
+impl AutofsDev {
+    pub(super) fn new() -> Self {
+        AutofsDev {
+            fd: RefCell::new(-1),
+        }
+    }
+
+    fn dev_fd(&self) -> Result<RawFd, Errno> {
+        if *self.fd.borrow() >= 0 {
+            return Ok(*self.fd.borrow());
+        }
+
+        let fd = fcntl::open(
+            DEV_AUTOFS,
+            OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+            Mode::empty(),
+        )?;
+        let mut param = AutofsDevIoctl::new(-1);
+        if let Err(e) = unsafe { autofs_dev_ioctl_version(fd, &mut param) } {
+            fd_util::close(fd);
+            return Err(e);
+        }
+
+        log::debug!(
+            "autofs kernel ioctl version {}.{}",
+            param.ver_major,
+            param.ver_minor
+        );
+        *self.fd.borrow_mut() = fd;
+        Ok(fd)
+    }
+}
+
+impl AutofsIoctl for AutofsDev {
+    fn open_ioctl_fd(&self, dev: u64, path: &Path) -> Result<RawFd, Errno> {
+        let dev_fd = self.dev_fd()?;
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Errno::EINVAL)?;
+        let path = path.as_bytes_with_nul();
+
+        // the path follows the parameters, the buffer of u32 keeps the alignment
+        let hdr = mem::size_of::<AutofsDevIoctl>();
+        let size = hdr + path.len();
+        let mut buf = vec![0u32; size.div_ceil(4)];
+        let mut param = AutofsDevIoctl::new(-1);
+        param.size = size as u32;
+        param.args[0] = dev as u32;
+        unsafe {
+            let p = buf.as_mut_ptr() as *mut u8;
+            ptr::copy_nonoverlapping(&param as *const AutofsDevIoctl as *const u8, p, hdr);
+            ptr::copy_nonoverlapping(path.as_ptr(), p.add(hdr), path.len());
+            autofs_dev_ioctl_openmount(dev_fd, buf.as_mut_ptr() as *mut AutofsDevIoctl)?;
+            Ok((*(buf.as_ptr() as *const AutofsDevIoctl)).ioctlfd)
+        }
+    }
+
+    fn protocol_version(&self, ioctl_fd: RawFd) -> Result<u32, Errno> {
+        let mut param = AutofsDevIoctl::new(ioctl_fd);
+        unsafe { autofs_dev_ioctl_protover(self.dev_fd()?, &mut param) }?;
+        Ok(param.args[0])
+    }
+
+    fn set_timeout(&self, ioctl_fd: RawFd, sec: u64) -> Result<(), Errno> {
+        let mut param = AutofsDevIoctl::new(ioctl_fd);
+        param.set_u64_arg(sec);
+        unsafe { autofs_dev_ioctl_timeout(self.dev_fd()?, &mut param) }.map(drop)
+    }
+
+    fn send_ready(&self, ioctl_fd: RawFd, token: u32) -> Result<(), Errno> {
+        let mut param = AutofsDevIoctl::new(ioctl_fd);
+        param.args[0] = token;
+        unsafe { autofs_dev_ioctl_ready(self.dev_fd()?, &mut param) }.map(drop)
+    }
+
+    fn send_fail(&self, ioctl_fd: RawFd, token: u32, status: i32) -> Result<(), Errno> {
+        let mut param = AutofsDevIoctl::new(ioctl_fd);
+        param.args = [token, status as u32];
+        unsafe { autofs_dev_ioctl_fail(self.dev_fd()?, &mut param) }.map(drop)
+    }
+
+    fn expire_in_background(&self, ioctl_fd: RawFd) {
+        let dev_fd = match self.dev_fd() {
+            Ok(fd) => fd,
+            Err(e) => {
+                log::warn!("failed to open {}: {}", DEV_AUTOFS, e);
+                return;
+            }
+        };
+
+        // the child has its own copies of the fds, which the stop of the automount does not
+        // close. it is reaped by the manager, and its exit code is the errno of the failure.
+        let ret = unsafe { unistd::fork() };
+        match ret {
+            Ok(ForkResult::Child) => {
+                // expire the idle mount points one by one, until none is left
+                let ret = loop {
+                    let mut param = AutofsDevIoctl::new(ioctl_fd);
+                    if let Err(e) = unsafe { autofs_dev_ioctl_expire(dev_fd, &mut param) } {
+                        break e;
+                    }
+                };
+                let code = match ret {
+                    Errno::EAGAIN => 0,
+                    e => e as i32,
+                };
+                unsafe { libc::_exit(code) }
+            }
+            Ok(ForkResult::Parent { child }) => {
+                log::debug!("expire the automount in the process {}", child);
+            }
+            Err(e) => log::warn!("failed to fork the expire process: {}", e),
+        }
+    }
+}
+
+impl Drop for AutofsDev {
+    fn drop(&mut self) {
+        if *self.fd.borrow() >= 0 {
+            fd_util::close(*self.fd.borrow());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autofs_packet_parse() {
+        let mut buf = [0u8; AUTOFS_PACKET_SIZE];
+        buf[0..4].copy_from_slice(&5i32.to_ne_bytes());
+        buf[4..8].copy_from_slice(&AUTOFS_PTYPE_MISSING_DIRECT.to_ne_bytes());
+        let size = mem::size_of::<libc::c_ulong>();
+        buf[8..8 + size].copy_from_slice(&(42 as libc::c_ulong).to_ne_bytes());
+        assert_eq!(autofs_packet_parse(&buf), Some(AutofsPacket::Missing(42)));
+
+        buf[4..8].copy_from_slice(&AUTOFS_PTYPE_EXPIRE_DIRECT.to_ne_bytes());
+        assert_eq!(autofs_packet_parse(&buf), Some(AutofsPacket::Expire(42)));
+
+        buf[4..8].copy_from_slice(&3i32.to_ne_bytes());
+        assert_eq!(autofs_packet_parse(&buf), Some(AutofsPacket::Unknown(3)));
+
+        assert_eq!(autofs_packet_parse(&buf[..8]), None);
+        assert_eq!(autofs_packet_parse(&buf[..8 + size - 1]), None);
+    }
+
+    #[test]
+    fn test_autofs_dev_ioctl_size() {
+        assert_eq!(mem::size_of::<AutofsDevIoctl>(), 24);
+    }
+}
@@ -0,0 +1,71 @@
+//! automount_config模块automount类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+
+pub(super) struct AutomountConfig {
+    data: Rc<RefCell<AutomountConfigData>>,
+}
+
+impl AutomountConfig {
+    pub(super) fn new() -> Self {
+        AutomountConfig {
+            data: Rc::new(RefCell::new(AutomountConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = AutomountConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<AutomountConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct AutomountConfigData {
+    #[config(nested)]
+    pub Automount: SectionAutomount,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionAutomount {
+    pub Where: Option<String>,
+    pub DirectoryMode: Option<String>,
+    pub TimeoutIdleSec: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::automount_config::AutomountConfig;
+    use utils::test_util::get_project_root;
+
+    #[test]
+    fn test_automount_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.automount.toml");
+        let paths = vec![file_path];
+
+        let config = AutomountConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let automount = &data.borrow().Automount;
+        assert_eq!(automount.Where, Some("/mnt/data".to_string()));
+        assert_eq!(automount.DirectoryMode, Some("0700".to_string()));
+        assert_eq!(automount.TimeoutIdleSec, Some("5min".to_string()));
+    }
+}
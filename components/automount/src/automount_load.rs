@@ -0,0 +1,125 @@
+//! automount_load模块实现automount配置文件的解析，以及对触发的mount的依赖。
+//!
+
+use std::{error::Error, path::Path, rc::Rc};
+
+use process1::manager::{UnitRelations, UnitType};
+use utils::{time_util, unit_name};
+
+use crate::automount_comm::AutomountComm;
+use crate::automount_config::AutomountConfig;
+use crate::automount_mng::AutomountMng;
+
+const LOCAL_FS_TARGET: &str = "local-fs.target";
+const SHUTDOWN_TARGET: &str = "shutdown.target";
+
+pub(super) struct AutomountLoad {
+    config: Rc<AutomountConfig>,
+    comm: Rc<AutomountComm>,
+    mng: Rc<AutomountMng>,
+}
+
+impl AutomountLoad {
+    pub(super) fn new(
+        configr: &Rc<AutomountConfig>,
+        commr: &Rc<AutomountComm>,
+        mngr: &Rc<AutomountMng>,
+    ) -> Self {
+        AutomountLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse automount section");
+        let data = self.config.config_data();
+        let automount = &data.borrow().Automount;
+
+        self.mng.set_where(&self.automount_where());
+
+        // the mount triggered has the same name with the automount
+        let id = self.comm.unit().get_id().to_string();
+        let stem = unit_name::unit_name_without_suffix(&id)
+            .ok_or_else(|| format!("invalid automount name {}", id))?;
+        self.mng
+            .set_unit(format!("{}.{}", stem, String::from(UnitType::UnitMount)));
+
+        if let Some(mode) = &automount.DirectoryMode {
+            let mode = u32::from_str_radix(mode.trim(), 8)
+                .map_err(|_| format!("invalid DirectoryMode {}", mode))?;
+            self.mng.set_directory_mode(mode);
+        }
+
+        if let Some(v) = &automount.TimeoutIdleSec {
+            self.mng
+                .set_timeout_idle_usec(time_util::parse_timespan(v)?);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn automount_add_extras(&self) -> Result<(), Box<dyn Error>> {
+        let unit = self.comm.unit();
+        let mount = self.mng.unit();
+        if !self.comm.um().load_unit_success(&mount) {
+            return Err(format!("failed to load unit {}", mount).into());
+        }
+        unit.insert_two_deps(
+            UnitRelations::UnitBefore,
+            UnitRelations::UnitTriggers,
+            mount,
+        );
+
+        // the parent mount points are mounted first
+        let automount_where = self.automount_where();
+        if let Some(parent) = Path::new(&automount_where).parent() {
+            unit.requires_mounts_for(&parent.to_string_lossy());
+        }
+
+        if !unit.default_dependencies() {
+            return Ok(());
+        }
+
+        unit.insert_dep(UnitRelations::UnitBefore, LOCAL_FS_TARGET.to_string());
+        unit.insert_two_deps(
+            UnitRelations::UnitConflicts,
+            UnitRelations::UnitBefore,
+            SHUTDOWN_TARGET.to_string(),
+        );
+        Ok(())
+    }
+
+    pub(super) fn automount_verify(&self) -> Result<(), Box<dyn Error>> {
+        let id = self.comm.unit().get_id().to_string();
+        let automount_where = self.automount_where();
+        // the name of the automount is the escaped path of Where=
+        let name = unit_name::unit_name_from_path(&automount_where, "automount");
+        if name != id {
+            return Err(format!(
+                "automount {} does not match Where={}, expect {}",
+                id, automount_where, name
+            )
+            .into());
+        }
+
+        if automount_where == "/" {
+            return Err(format!("automount {} can not be on the root directory", id).into());
+        }
+        Ok(())
+    }
+
+    // Where= is optional, which is the unescaped name of the automount
+    fn automount_where(&self) -> String {
+        if let Some(w) = &self.config.config_data().borrow().Automount.Where {
+            return w.to_string();
+        }
+
+        let id = self.comm.unit().get_id().to_string();
+        match unit_name::unit_name_without_suffix(&id).as_deref() {
+            Some("-") | None => "/".to_string(),
+            Some(stem) => format!("/{}", unit_name::unit_name_unescape(stem)),
+        }
+    }
+}
@@ -0,0 +1,674 @@
+//! automount_mng模块是automount类型的核心逻辑，启动时在Where=上挂载autofs，
+//! 通过管道读取内核的挂载请求，启动对应的mount，并在mount状态变化后通过ioctl应答内核。
+//! 配置了TimeoutIdleSec=时，定时请求内核卸载空闲的挂载点。
+//!
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs::{self, DirBuilder},
+    os::unix::{fs::DirBuilderExt, fs::MetadataExt, io::RawFd},
+    path::PathBuf,
+    rc::{Rc, Weak},
+};
+
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::libc;
+use nix::mount::{self, MntFlags, MsFlags};
+use nix::time::{clock_gettime, ClockId};
+use nix::unistd;
+use process1::manager::{UnitActionError, UnitActiveState, UnitNotifyFlags, UnitRelationAtom};
+use utils::{fd_util, Error, IN_SET};
+
+use crate::automount_autofs::{self, AutofsIoctl, AutofsPacket};
+use crate::automount_comm::AutomountComm;
+
+// the default of DirectoryMode=
+pub(super) const DEFAULT_DIRECTORY_MODE: u32 = 0o755;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum AutomountState {
+    Dead,
+    Waiting,
+    Running,
+    Failed,
+}
+
+impl AutomountState {
+    fn to_unit_active_state(self) -> UnitActiveState {
+        match self {
+            AutomountState::Dead => UnitActiveState::UnitInActive,
+            AutomountState::Waiting | AutomountState::Running => UnitActiveState::UnitActive,
+            AutomountState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum AutomountResult {
+    Success,
+    FailureResources,
+}
+
+pub(super) struct AutomountMng {
+    comm: Rc<AutomountComm>,
+    ioctl: Box<dyn AutofsIoctl>,
+    state: RefCell<AutomountState>,
+    result: RefCell<AutomountResult>,
+    mount_where: RefCell<PathBuf>,
+    // the mount unit triggered
+    unit: RefCell<String>,
+    directory_mode: RefCell<u32>,
+    // 0 means the mount point is never expired
+    timeout_idle_usec: RefCell<u64>,
+    // whether the autofs is mounted on Where= by us
+    autofs_mounted: RefCell<bool>,
+    // the fd to acknowledge the requests, opened on the autofs mount point
+    ioctl_fd: RefCell<RawFd>,
+    // the tokens of the requests waiting for mounting and unmounting
+    tokens: RefCell<HashSet<u32>>,
+    expire_tokens: RefCell<HashSet<u32>>,
+    pipe: Rc<AutofsPipe>,
+    timer: Rc<ExpireTimer>,
+}
+
+impl AutomountMng {
+    pub(super) fn new(commr: &Rc<AutomountComm>, ioctl: Box<dyn AutofsIoctl>) -> Rc<AutomountMng> {
+        let mng = Rc::new(AutomountMng {
+            comm: Rc::clone(commr),
+            ioctl,
+            state: RefCell::new(AutomountState::Dead),
+            result: RefCell::new(AutomountResult::Success),
+            mount_where: RefCell::new(PathBuf::new()),
+            unit: RefCell::new(String::new()),
+            directory_mode: RefCell::new(DEFAULT_DIRECTORY_MODE),
+            timeout_idle_usec: RefCell::new(0),
+            autofs_mounted: RefCell::new(false),
+            ioctl_fd: RefCell::new(-1),
+            tokens: RefCell::new(HashSet::new()),
+            expire_tokens: RefCell::new(HashSet::new()),
+            pipe: Rc::new(AutofsPipe::new()),
+            timer: Rc::new(ExpireTimer::new()),
+        });
+
+        mng.pipe.attach(Rc::downgrade(&mng));
+        mng.timer.attach(Rc::downgrade(&mng));
+        mng
+    }
+
+    pub(super) fn set_where(&self, mount_where: &str) {
+        *self.mount_where.borrow_mut() = PathBuf::from(mount_where);
+    }
+
+    pub(super) fn set_unit(&self, unit: String) {
+        *self.unit.borrow_mut() = unit;
+    }
+
+    pub(super) fn unit(&self) -> String {
+        self.unit.borrow().clone()
+    }
+
+    pub(super) fn set_directory_mode(&self, mode: u32) {
+        *self.directory_mode.borrow_mut() = mode;
+    }
+
+    pub(super) fn set_timeout_idle_usec(&self, usec: u64) {
+        *self.timeout_idle_usec.borrow_mut() = usec;
+    }
+
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(
+            self.state(),
+            AutomountState::Waiting,
+            AutomountState::Running
+        ) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        self.set_result(AutomountResult::Success);
+        if let Err(e) = self.autofs_mount() {
+            log::error!(
+                "failed to mount autofs on {:?}: {}",
+                self.mount_where.borrow(),
+                e
+            );
+            self.autofs_unmount();
+            return self.enter_dead(AutomountResult::FailureResources);
+        }
+
+        self.set_state(AutomountState::Waiting);
+    }
+
+    pub(super) fn stop_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), AutomountState::Dead, AutomountState::Failed) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        self.autofs_unmount();
+        self.enter_dead(AutomountResult::Success);
+    }
+
+    pub(super) fn current_active_state(&self) -> UnitActiveState {
+        self.state().to_unit_active_state()
+    }
+
+    // the triggered mount changes its state, acknowledge the requests waiting for it
+    pub(super) fn trigger_notify(&self, new_state: UnitActiveState) {
+        if IN_SET!(self.state(), AutomountState::Dead, AutomountState::Failed) {
+            return;
+        }
+
+        match new_state {
+            UnitActiveState::UnitActive | UnitActiveState::UnitReloading => {
+                self.send_ready(&self.tokens, 0);
+                self.set_state(AutomountState::Running);
+                self.enable_timer();
+            }
+            UnitActiveState::UnitInActive | UnitActiveState::UnitFailed => {
+                // the mounting failed, or the mount point is unmounted
+                self.send_ready(&self.tokens, -(Errno::ENODEV as i32));
+                let status = if new_state == UnitActiveState::UnitInActive {
+                    0
+                } else {
+                    -(Errno::ENODEV as i32)
+                };
+                self.send_ready(&self.expire_tokens, status);
+                self.disable_timer();
+                self.set_state(AutomountState::Waiting);
+            }
+            _ => {}
+        }
+    }
+
+    fn autofs_mount(&self) -> Result<(), Errno> {
+        let mount_where = self.mount_where.borrow().clone();
+        DirBuilder::new()
+            .recursive(true)
+            .mode(*self.directory_mode.borrow())
+            .create(&mount_where)
+            .map_err(|e| Errno::from_i32(e.raw_os_error().unwrap_or(libc::EIO)))?;
+
+        // the kernel writes the requests into the pipe, the processes in our
+        // process group are not blocked by the autofs, such as mount(8).
+        let (rfd, wfd) = unistd::pipe2(OFlag::O_CLOEXEC)?;
+        self.pipe.set_fd(rfd);
+        let options = format!(
+            "fd={},pgrp={},minproto={},maxproto={},direct",
+            wfd,
+            unistd::getpgrp(),
+            automount_autofs::AUTOFS_PROTO_VERSION,
+            automount_autofs::AUTOFS_PROTO_VERSION
+        );
+        let ret = mount::mount(
+            Some("process1"),
+            &mount_where,
+            Some("autofs"),
+            MsFlags::empty(),
+            Some(options.as_str()),
+        );
+        fd_util::close(wfd);
+        ret?;
+        *self.autofs_mounted.borrow_mut() = true;
+
+        let dev = fs::metadata(&mount_where)
+            .map_err(|e| Errno::from_i32(e.raw_os_error().unwrap_or(libc::EIO)))?
+            .dev();
+        let ioctl_fd = self.ioctl.open_ioctl_fd(dev, &mount_where)?;
+        *self.ioctl_fd.borrow_mut() = ioctl_fd;
+
+        let version = self.ioctl.protocol_version(ioctl_fd)?;
+        if version < automount_autofs::AUTOFS_PROTO_VERSION {
+            log::error!("the autofs protocol version {} is not supported", version);
+            return Err(Errno::EPROTONOSUPPORT);
+        }
+
+        // the kernel counts the idle time in seconds
+        let usec = *self.timeout_idle_usec.borrow();
+        self.ioctl.set_timeout(ioctl_fd, usec.div_ceil(1_000_000))?;
+
+        self.pipe.set_nonblock()?;
+        if !self.pipe.registered() {
+            self.comm.um().register(self.pipe.clone());
+            self.pipe.set_registered();
+        }
+        self.comm.um().enable(self.pipe.clone(), EventState::On);
+        Ok(())
+    }
+
+    // the requests not acknowledged yet are released by the unmounting
+    fn autofs_unmount(&self) {
+        self.disable_timer();
+        if self.pipe.fd() >= 0 {
+            if self.pipe.registered() {
+                self.comm.um().enable(self.pipe.clone(), EventState::Off);
+            }
+            self.pipe.close();
+        }
+
+        let ioctl_fd = self.ioctl_fd.replace(-1);
+        if ioctl_fd >= 0 {
+            fd_util::close(ioctl_fd);
+        }
+        self.tokens.borrow_mut().clear();
+        self.expire_tokens.borrow_mut().clear();
+
+        if !self.autofs_mounted.replace(false) {
+            return;
+        }
+        let mount_where = self.mount_where.borrow().clone();
+        match mount::umount2(&mount_where, MntFlags::MNT_DETACH) {
+            Ok(_) | Err(Errno::EINVAL) | Err(Errno::ENOENT) => {}
+            Err(e) => log::warn!("failed to unmount the autofs on {:?}: {}", mount_where, e),
+        }
+    }
+
+    fn dispatch_packet(&self, packet: AutofsPacket) {
+        let unit = self.unit();
+        match packet {
+            AutofsPacket::Missing(token) => {
+                log::debug!("got the request to mount {}, token {}", unit, token);
+                self.tokens.borrow_mut().insert(token);
+                if let Err(e) = self.comm.um().start_unit(&unit) {
+                    log::error!("failed to start {}: {:?}", unit, e);
+                    self.send_ready(&self.tokens, -(Errno::ENODEV as i32));
+                    return;
+                }
+
+                self.set_state(AutomountState::Running);
+                // the mount is active already, its state is not changed any more
+                if self.triggered_state() == Some(UnitActiveState::UnitActive) {
+                    self.send_ready(&self.tokens, 0);
+                    self.enable_timer();
+                }
+            }
+            AutofsPacket::Expire(token) => {
+                log::debug!("got the request to expire {}, token {}", unit, token);
+                self.expire_tokens.borrow_mut().insert(token);
+                if let Err(e) = self.comm.um().stop_unit(&unit) {
+                    log::error!("failed to stop {}: {:?}", unit, e);
+                    self.send_ready(&self.expire_tokens, -(Errno::ENODEV as i32));
+                }
+            }
+            AutofsPacket::Unknown(ptype) => {
+                log::warn!("unknown autofs packet type {}", ptype);
+            }
+        }
+    }
+
+    fn triggered_state(&self) -> Option<UnitActiveState> {
+        let unit = self.unit();
+        self.comm
+            .um()
+            .get_dependency_list(
+                self.comm.unit().get_id(),
+                UnitRelationAtom::UnitAtomTriggers,
+            )
+            .iter()
+            .find(|u| u.get_id() == unit)
+            .map(|u| u.current_active_state())
+    }
+
+    // acknowledge the requests of {tokens}, a negative errno {status} fails them
+    fn send_ready(&self, tokens: &RefCell<HashSet<u32>>, status: i32) {
+        let ioctl_fd = *self.ioctl_fd.borrow();
+        for token in tokens.borrow_mut().drain() {
+            log::debug!("acknowledge the autofs token {} with {}", token, status);
+            let ret = if status == 0 {
+                self.ioctl.send_ready(ioctl_fd, token)
+            } else {
+                self.ioctl.send_fail(ioctl_fd, token, status)
+            };
+            if let Err(e) = ret {
+                log::warn!("failed to acknowledge the autofs token {}: {}", token, e);
+            }
+        }
+    }
+
+    fn dispatch_timer(&self) {
+        if self.state() != AutomountState::Running {
+            return;
+        }
+
+        self.ioctl.expire_in_background(*self.ioctl_fd.borrow());
+        self.enable_timer();
+    }
+
+    // check the idle mount point every tenth of TimeoutIdleSec=, at least 1s
+    fn enable_timer(&self) {
+        let idle = *self.timeout_idle_usec.borrow();
+        if idle == 0 {
+            return;
+        }
+
+        if !self.timer.registered() {
+            self.comm.um().register(self.timer.clone());
+            self.timer.set_registered();
+        }
+
+        let usec = (idle / 10).max(1_000_000);
+        log::debug!("enable automount expire timer after {} usec", usec);
+        self.timer.arm(usec);
+        self.comm.um().enable(self.timer.clone(), EventState::On);
+    }
+
+    fn disable_timer(&self) {
+        if self.timer.armed() {
+            self.timer.disarm();
+            self.comm.um().enable(self.timer.clone(), EventState::Off);
+        }
+    }
+
+    fn enter_dead(&self, res: AutomountResult) {
+        if self.result() == AutomountResult::Success {
+            self.set_result(res);
+        }
+
+        if self.result() == AutomountResult::Success {
+            self.set_state(AutomountState::Dead);
+        } else {
+            self.set_state(AutomountState::Failed);
+        }
+    }
+
+    fn set_state(&self, state: AutomountState) {
+        let original_state = self.state.replace(state);
+        if original_state != state {
+            log::debug!(
+                "automount {} original state[{:?}] -> new state[{:?}]",
+                self.comm.unit().get_id(),
+                original_state,
+                state
+            );
+        }
+
+        self.comm.unit().notify(
+            original_state.to_unit_active_state(),
+            state.to_unit_active_state(),
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+    }
+
+    fn state(&self) -> AutomountState {
+        *self.state.borrow()
+    }
+
+    fn set_result(&self, res: AutomountResult) {
+        *self.result.borrow_mut() = res;
+    }
+
+    fn result(&self) -> AutomountResult {
+        *self.result.borrow()
+    }
+}
+
+/// the read end of the autofs pipe, where the kernel writes the requests.
+struct AutofsPipe {
+    fd: RefCell<RawFd>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<AutomountMng>>,
+}
+
+impl AutofsPipe {
+    fn new() -> Self {
+        AutofsPipe {
+            fd: RefCell::new(-1),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<AutomountMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn set_fd(&self, fd: RawFd) {
+        self.close();
+        *self.fd.borrow_mut() = fd;
+    }
+
+    fn set_nonblock(&self) -> Result<(), Errno> {
+        let fd = *self.fd.borrow();
+        let flags = OFlag::from_bits_truncate(nix::fcntl::fcntl(fd, nix::fcntl::F_GETFL)?);
+        nix::fcntl::fcntl(fd, nix::fcntl::F_SETFL(flags | OFlag::O_NONBLOCK)).map(drop)
+    }
+
+    fn close(&self) {
+        let fd = self.fd.replace(-1);
+        if fd >= 0 {
+            fd_util::close(fd);
+        }
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    // read the packets until the pipe is drained
+    fn read_packets(&self) -> Vec<AutofsPacket> {
+        let mut packets = Vec::new();
+        let mut buf = [0u8; automount_autofs::AUTOFS_PACKET_SIZE];
+        loop {
+            match unistd::read(*self.fd.borrow(), &mut buf) {
+                Ok(n) if n > 0 => {
+                    if let Some(p) = automount_autofs::autofs_packet_parse(&buf[..n]) {
+                        packets.push(p);
+                    }
+                }
+                Err(Errno::EINTR) => continue,
+                Ok(_) | Err(Errno::EAGAIN) => break,
+                Err(e) => {
+                    log::error!("failed to read the autofs pipe: {}", e);
+                    break;
+                }
+            }
+        }
+        packets
+    }
+
+    fn mng(&self) -> Option<Rc<AutomountMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+impl Source for AutofsPipe {
+    fn fd(&self) -> RawFd {
+        *self.fd.borrow()
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        log::debug!("dispatch the autofs requests");
+        let packets = self.read_packets();
+        if let Some(mng) = self.mng() {
+            for p in packets {
+                mng.dispatch_packet(p);
+            }
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+/// the timer to expire the idle mount point, for TimeoutIdleSec=.
+struct ExpireTimer {
+    // the absolute time in monotonic clock, u64::MAX means the timer is disarmed.
+    deadline: RefCell<u64>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<AutomountMng>>,
+}
+
+impl ExpireTimer {
+    fn new() -> Self {
+        ExpireTimer {
+            deadline: RefCell::new(u64::MAX),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<AutomountMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn arm(&self, usec: u64) {
+        *self.deadline.borrow_mut() = monotonic_usec().saturating_add(usec);
+    }
+
+    fn disarm(&self) {
+        *self.deadline.borrow_mut() = u64::MAX;
+    }
+
+    fn armed(&self) -> bool {
+        *self.deadline.borrow() != u64::MAX
+    }
+
+    fn expired(&self) -> bool {
+        self.armed() && monotonic_usec() >= *self.deadline.borrow()
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    fn mng(&self) -> Option<Rc<AutomountMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+fn monotonic_usec() -> u64 {
+    match clock_gettime(ClockId::CLOCK_MONOTONIC) {
+        Ok(ts) => ts.tv_sec() as u64 * 1_000_000 + ts.tv_nsec() as u64 / 1_000,
+        Err(_) => 0,
+    }
+}
+
+impl Source for ExpireTimer {
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn time(&self) -> u64 {
+        *self.deadline.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        // the timer queued before re-arming is expired earlier, ignore it.
+        if !self.expired() {
+            return Ok(0);
+        }
+
+        log::debug!("dispatch automount expire timer");
+        self.disarm();
+        if let Some(mng) = self.mng() {
+            mng.dispatch_timer();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    // record the acknowledgements instead of the ioctls
+    struct StubIoctl {
+        acks: Rc<RefCell<Vec<(u32, i32)>>>,
+    }
+
+    impl AutofsIoctl for StubIoctl {
+        fn open_ioctl_fd(&self, _dev: u64, _path: &Path) -> Result<RawFd, Errno> {
+            Ok(100)
+        }
+
+        fn protocol_version(&self, _ioctl_fd: RawFd) -> Result<u32, Errno> {
+            Ok(5)
+        }
+
+        fn set_timeout(&self, _ioctl_fd: RawFd, _sec: u64) -> Result<(), Errno> {
+            Ok(())
+        }
+
+        fn send_ready(&self, _ioctl_fd: RawFd, token: u32) -> Result<(), Errno> {
+            self.acks.borrow_mut().push((token, 0));
+            Ok(())
+        }
+
+        fn send_fail(&self, _ioctl_fd: RawFd, token: u32, status: i32) -> Result<(), Errno> {
+            self.acks.borrow_mut().push((token, status));
+            Ok(())
+        }
+
+        fn expire_in_background(&self, _ioctl_fd: RawFd) {}
+    }
+
+    #[test]
+    fn test_automount_send_ready() {
+        let acks = Rc::new(RefCell::new(Vec::new()));
+        let comm = Rc::new(AutomountComm::new());
+        let mng = AutomountMng::new(
+            &comm,
+            Box::new(StubIoctl {
+                acks: Rc::clone(&acks),
+            }),
+        );
+
+        mng.tokens.borrow_mut().insert(1);
+        mng.send_ready(&mng.tokens, 0);
+        assert_eq!(*acks.borrow(), vec![(1, 0)]);
+        assert!(mng.tokens.borrow().is_empty());
+
+        // the tokens are acknowledged only once
+        mng.send_ready(&mng.tokens, 0);
+        assert_eq!(acks.borrow().len(), 1);
+
+        mng.expire_tokens.borrow_mut().insert(2);
+        mng.send_ready(&mng.expire_tokens, -(Errno::ENODEV as i32));
+        assert_eq!(acks.borrow()[1], (2, -(Errno::ENODEV as i32)));
+        assert!(mng.expire_tokens.borrow().is_empty());
+    }
+}
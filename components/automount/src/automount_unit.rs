@@ -0,0 +1,109 @@
+//! AutomountUnit是automount类型的总入口，需要实现UnitObj,UnitMngUtil,以及UnitSubClass三个trait,
+//! UnitObj是Unit的抽象，定义对process1提供的具体行为，
+//! UnitMngUtil是为了关联subUnit和Manger，由于rust不支持继承和多态，因此需要采用这种方式来间接支持
+//! UnitSubClass为了实现SubUnit到UnitObj的转换，简介达成多态的目的
+
+use process1::manager::{
+    Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj, UnitSubClass,
+};
+use std::{error::Error, path::PathBuf, rc::Rc};
+
+use crate::{
+    automount_autofs::AutofsDev, automount_comm::AutomountComm, automount_config::AutomountConfig,
+    automount_load::AutomountLoad, automount_mng::AutomountMng,
+};
+use utils::logger;
+
+// the structure of the automount unit type
+struct AutomountUnit {
+    comm: Rc<AutomountComm>,
+    config: Rc<AutomountConfig>,
+    mng: Rc<AutomountMng>,
+    load: AutomountLoad,
+}
+
+impl UnitObj for AutomountUnit {
+    fn load(&self, paths: &Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        log::debug!("automount begin to load conf file");
+        self.config.load(paths)?;
+
+        self.load.parse()?;
+
+        self.load.automount_add_extras()?;
+
+        self.load.automount_verify()
+    }
+
+    // the function entrance to start the unit
+    fn start(&self) -> Result<(), UnitActionError> {
+        self.mng.start_check()?;
+
+        self.mng.start_action();
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), UnitActionError> {
+        self.mng.stop_check()?;
+
+        self.mng.stop_action();
+        Ok(())
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.current_active_state()
+    }
+
+    fn attach_unit(&self, unit: Rc<Unit>) {
+        self.comm.attach_unit(unit);
+    }
+
+    fn trigger(&self, other: Rc<Unit>) {
+        self.mng.trigger_notify(other.current_active_state());
+    }
+}
+
+// attach the UnitManager for weak reference
+impl UnitMngUtil for AutomountUnit {
+    fn attach(&self, um: Rc<UnitManager>) {
+        self.comm.attach_um(um);
+    }
+}
+
+impl UnitSubClass for AutomountUnit {
+    fn into_unitobj(self: Box<Self>) -> Box<dyn UnitObj> {
+        Box::new(*self)
+    }
+}
+
+impl AutomountUnit {
+    fn new() -> AutomountUnit {
+        let comm = Rc::new(AutomountComm::new());
+        let config = Rc::new(AutomountConfig::new());
+        let mng = AutomountMng::new(&comm, Box::new(AutofsDev::new()));
+        AutomountUnit {
+            comm: Rc::clone(&comm),
+            config: Rc::clone(&config),
+            mng: Rc::clone(&mng),
+            load: AutomountLoad::new(&config, &comm, &mng),
+        }
+    }
+}
+
+impl Default for AutomountUnit {
+    fn default() -> Self {
+        AutomountUnit::new()
+    }
+}
+
+const LOG_LEVEL: u32 = 4;
+const PLUGIN_NAME: &str = "AutomountUnit";
+
+use process1::declure_unitobj_plugin;
+
+// define the method to create the instance of the unit
+declure_unitobj_plugin!(
+    AutomountUnit,
+    AutomountUnit::default,
+    PLUGIN_NAME,
+    LOG_LEVEL
+);
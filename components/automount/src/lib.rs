@@ -0,0 +1,56 @@
+//! automount是process1的自动挂载类型，启动时在Where=上挂载autofs，首次访问挂载点时才启动同名的mount完成挂载，
+//! 适用于网络文件系统或较大的数据盘等不需要在开机时挂载的场景。
+//! automount的名称必须是Where=路径转义后的名称，如/mnt/data对应mnt-data.automount，触发mnt-data.mount。
+//!
+//! 内核的挂载请求通过autofs管道读取，mount挂载成功或失败后，通过/dev/autofs的ioctl应答内核，访问挂载点的进程随之返回。
+//! 配置了TimeoutIdleSec=时，挂载点空闲超过该时间后由内核请求卸载，process1停止对应的mount。
+//!
+//! # Example:
+//! ```toml
+//! [Unit]
+//! Description="the data disk mounted on access"
+//!
+//! [Automount]
+//! Where="/mnt/data"
+//! TimeoutIdleSec="5min"
+//! ```
+
+//! [Automount] section相关的配置
+//!
+//! Where
+//!
+//! 挂载点的路径，不配置时由automount的名称反转义得到，不能是根目录，挂载点不存在时会被创建。
+//!
+//! DirectoryMode
+//!
+//! 创建挂载点及其父目录时使用的权限，八进制，默认为0755。
+//!
+//! TimeoutIdleSec
+//!
+//! 挂载点空闲多长时间后卸载，默认为0，表示不卸载。
+//!
+//! ## 自动依赖
+//! ### 隐含依赖
+//! + 触发的mount，即Before与Triggers依赖
+//! + 挂载点的父目录所在的mount，即Requires与After依赖
+//!
+//! ### 默认依赖
+//! 如果没有设置DefaultDependencies = no，会默认增加如下依赖关系：
+//! + Before = local-fs.target
+//! + Conflicts = shutdown.target 与 Before = shutdown.target的依赖
+
+// dependency:
+// automount_comm -> automount_config -> automount_autofs
+// {automount_mng | automount_load} -> automount_unit
+
+mod automount_autofs;
+
+mod automount_comm;
+
+mod automount_config;
+
+mod automount_mng;
+
+mod automount_load;
+
+mod automount_unit;
@@ -0,0 +1,67 @@
+//! device_config模块device类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+
+pub(super) struct DeviceConfig {
+    data: Rc<RefCell<DeviceConfigData>>,
+}
+
+impl DeviceConfig {
+    pub(super) fn new() -> Self {
+        DeviceConfig {
+            data: Rc::new(RefCell::new(DeviceConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = DeviceConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<DeviceConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct DeviceConfigData {
+    #[config(nested)]
+    pub Device: SectionDevice,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionDevice {
+    pub JobTimeoutSec: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::device_config::DeviceConfig;
+    use utils::test_util::get_project_root;
+
+    #[test]
+    fn test_device_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.device.toml");
+        let paths = vec![file_path];
+
+        let config = DeviceConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let device = &data.borrow().Device;
+        assert_eq!(device.JobTimeoutSec, Some("30s".to_string()));
+    }
+}
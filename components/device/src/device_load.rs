@@ -0,0 +1,54 @@
+//! device_load模块实现device配置文件的解析，device通常没有配置文件，由uevent直接加载。
+//!
+
+use std::{error::Error, rc::Rc};
+
+use utils::time_util;
+
+use crate::device_comm::DeviceComm;
+use crate::device_config::DeviceConfig;
+use crate::device_mng::DeviceMng;
+
+pub(super) struct DeviceLoad {
+    config: Rc<DeviceConfig>,
+    comm: Rc<DeviceComm>,
+    mng: Rc<DeviceMng>,
+}
+
+impl DeviceLoad {
+    pub(super) fn new(
+        configr: &Rc<DeviceConfig>,
+        commr: &Rc<DeviceComm>,
+        mngr: &Rc<DeviceMng>,
+    ) -> Self {
+        DeviceLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse device section");
+        let data = self.config.config_data();
+        let device = &data.borrow().Device;
+
+        if let Some(v) = &device.JobTimeoutSec {
+            let usec = if v.trim() == "infinity" {
+                u64::MAX
+            } else {
+                time_util::parse_timespan(v)?
+            };
+            self.mng.set_timeout_usec(usec);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn device_add_extras(&self) {
+        // the devices come and go with the hardware, they are not stopped by isolating
+        if let Some(unit) = self.comm.unit() {
+            unit.set_ignore_on_isolate(true);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! device_mng模块是device类型的核心逻辑，设备的状态由process1收到的uevent决定，
+//! 启动device时等待设备出现，超过JobTimeoutSec=仍未出现时启动失败。
+//!
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use event::{EventState, EventType, Events, Source};
+use nix::libc;
+use nix::time::{clock_gettime, ClockId};
+use process1::manager::{UnitActionError, UnitActiveState, UnitNotifyFlags};
+use utils::Error;
+
+use crate::device_comm::DeviceComm;
+
+// the time waiting for the device, the same as the default timeout of starting the units
+const DEFAULT_TIMEOUT_USEC: u64 = 90 * 1_000_000;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub(super) enum DeviceState {
+    Dead,
+    Plugged,
+}
+
+impl DeviceState {
+    fn to_unit_active_state(self) -> UnitActiveState {
+        match self {
+            DeviceState::Dead => UnitActiveState::UnitInActive,
+            DeviceState::Plugged => UnitActiveState::UnitActive,
+        }
+    }
+}
+
+pub(super) struct DeviceMng {
+    comm: Rc<DeviceComm>,
+    state: RefCell<DeviceState>,
+    // the time waiting for the device, u64::MAX means waiting forever
+    timeout_usec: RefCell<u64>,
+    // the last waiting is timed out, which fails the start
+    timed_out: RefCell<bool>,
+    timer: Rc<DeviceTimer>,
+}
+
+impl DeviceMng {
+    pub(super) fn new(commr: &Rc<DeviceComm>) -> Rc<DeviceMng> {
+        let mng = Rc::new(DeviceMng {
+            comm: Rc::clone(commr),
+            state: RefCell::new(DeviceState::Dead),
+            timeout_usec: RefCell::new(DEFAULT_TIMEOUT_USEC),
+            timed_out: RefCell::new(false),
+            timer: Rc::new(DeviceTimer::new()),
+        });
+
+        mng.timer.attach(Rc::downgrade(&mng));
+        mng
+    }
+
+    pub(super) fn set_timeout_usec(&self, usec: u64) {
+        *self.timeout_usec.borrow_mut() = usec;
+    }
+
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        if self.state() == DeviceState::Plugged {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+
+        if self.timed_out.replace(false) {
+            log::warn!("timed out waiting for device {}", self.unit_id());
+            return Err(UnitActionError::UnitActionETimedOut);
+        }
+        Ok(())
+    }
+
+    /// the device can not be plugged by process1, the job waits until the device
+    /// shows up or the timer expires.
+    pub(super) fn start_action(&self) -> Result<(), UnitActionError> {
+        if !self.timer.armed() {
+            log::debug!("waiting for device {}", self.unit_id());
+            self.enable_timer(*self.timeout_usec.borrow());
+        }
+        Err(UnitActionError::UnitActionEAgain)
+    }
+
+    /// and the job stopping the device waits until it is unplugged.
+    pub(super) fn stop_action(&self) -> Result<(), UnitActionError> {
+        Err(UnitActionError::UnitActionEAgain)
+    }
+
+    pub(super) fn current_active_state(&self) -> UnitActiveState {
+        self.state().to_unit_active_state()
+    }
+
+    // the device is plugged or unplugged, which is announced by udev
+    pub(super) fn device_update(&self, found: bool) {
+        if found {
+            self.disable_timer();
+            self.timed_out.replace(false);
+            self.set_state(DeviceState::Plugged);
+        } else {
+            self.set_state(DeviceState::Dead);
+        }
+    }
+
+    fn dispatch_timer(&self) {
+        if self.state() == DeviceState::Plugged {
+            return;
+        }
+
+        // the waiting job is woken up by the notification, and fails on the retried start
+        self.timed_out.replace(true);
+        self.set_state(DeviceState::Dead);
+    }
+
+    fn enable_timer(&self, usec: u64) {
+        if usec == u64::MAX {
+            return;
+        }
+
+        if !self.timer.registered() {
+            self.comm.um().register(self.timer.clone());
+            self.timer.set_registered();
+        }
+
+        self.timer.arm(usec);
+        self.comm.um().enable(self.timer.clone(), EventState::On);
+    }
+
+    fn disable_timer(&self) {
+        if self.timer.armed() {
+            self.timer.disarm();
+            self.comm.um().enable(self.timer.clone(), EventState::Off);
+        }
+    }
+
+    fn unit_id(&self) -> String {
+        self.comm
+            .unit()
+            .map_or_else(|| "0".to_string(), |u| u.get_id().to_string())
+    }
+
+    fn set_state(&self, state: DeviceState) {
+        let original_state = self.state.replace(state);
+        if original_state != state {
+            log::debug!(
+                "device {} original state[{:?}] -> new state[{:?}]",
+                self.unit_id(),
+                original_state,
+                state
+            );
+        }
+
+        if let Some(u) = self.comm.unit() {
+            u.notify(
+                original_state.to_unit_active_state(),
+                state.to_unit_active_state(),
+                UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+            );
+        }
+    }
+
+    pub(super) fn state(&self) -> DeviceState {
+        *self.state.borrow()
+    }
+}
+
+/// the timer of waiting for the device.
+struct DeviceTimer {
+    // the absolute time in monotonic clock, u64::MAX means the timer is disarmed.
+    deadline: RefCell<u64>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<DeviceMng>>,
+}
+
+impl DeviceTimer {
+    fn new() -> Self {
+        DeviceTimer {
+            deadline: RefCell::new(u64::MAX),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<DeviceMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn arm(&self, usec: u64) {
+        *self.deadline.borrow_mut() = monotonic_usec().saturating_add(usec);
+    }
+
+    fn disarm(&self) {
+        *self.deadline.borrow_mut() = u64::MAX;
+    }
+
+    fn armed(&self) -> bool {
+        *self.deadline.borrow() != u64::MAX
+    }
+
+    fn expired(&self) -> bool {
+        self.armed() && monotonic_usec() >= *self.deadline.borrow()
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    fn mng(&self) -> Option<Rc<DeviceMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+fn monotonic_usec() -> u64 {
+    match clock_gettime(ClockId::CLOCK_MONOTONIC) {
+        Ok(ts) => ts.tv_sec() as u64 * 1_000_000 + ts.tv_nsec() as u64 / 1_000,
+        Err(_) => 0,
+    }
+}
+
+impl Source for DeviceTimer {
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn time(&self) -> u64 {
+        *self.deadline.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        // the timer queued before re-arming is expired earlier, ignore it.
+        if !self.expired() {
+            return Ok(0);
+        }
+
+        log::debug!("dispatch device timer");
+        self.disarm();
+        if let Some(mng) = self.mng() {
+            mng.dispatch_timer();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{DeviceComm, DeviceMng, DeviceState};
+    use process1::manager::UnitActionError;
+
+    #[test]
+    fn test_device_update() {
+        let comm = Rc::new(DeviceComm::new());
+        let mng = DeviceMng::new(&comm);
+        assert_eq!(mng.state(), DeviceState::Dead);
+
+        mng.device_update(true);
+        assert_eq!(mng.state(), DeviceState::Plugged);
+        assert_eq!(mng.start_check(), Err(UnitActionError::UnitActionEAlready));
+
+        mng.device_update(false);
+        assert_eq!(mng.state(), DeviceState::Dead);
+        assert_eq!(mng.start_check(), Ok(()));
+    }
+
+    #[test]
+    fn test_device_timed_out() {
+        let comm = Rc::new(DeviceComm::new());
+        let mng = DeviceMng::new(&comm);
+        mng.dispatch_timer();
+        assert_eq!(mng.start_check(), Err(UnitActionError::UnitActionETimedOut));
+
+        // the timeout is reported once, the next start waits again
+        assert_eq!(mng.start_check(), Ok(()));
+    }
+}
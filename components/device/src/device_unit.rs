@@ -0,0 +1,100 @@
+//! DeviceUnit是device类型的总入口，需要实现UnitObj,UnitMngUtil,以及UnitSubClass三个trait,
+//! UnitObj是Unit的抽象，定义对process1提供的具体行为，
+//! UnitMngUtil是为了关联subUnit和Manger，由于rust不支持继承和多态，因此需要采用这种方式来间接支持
+//! UnitSubClass为了实现SubUnit到UnitObj的转换，简介达成多态的目的
+
+use process1::manager::{
+    Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj, UnitSubClass,
+};
+use std::{error::Error, path::PathBuf, rc::Rc};
+
+use crate::{
+    device_comm::DeviceComm, device_config::DeviceConfig, device_load::DeviceLoad,
+    device_mng::DeviceMng,
+};
+use utils::logger;
+
+// the structure of the device unit type
+struct DeviceUnit {
+    comm: Rc<DeviceComm>,
+    config: Rc<DeviceConfig>,
+    mng: Rc<DeviceMng>,
+    load: DeviceLoad,
+}
+
+impl UnitObj for DeviceUnit {
+    // the devices announced by udev are loaded without unit file
+    fn load(&self, paths: &Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        log::debug!("device begin to load conf file");
+        self.config.load(paths)?;
+
+        self.load.parse()?;
+
+        self.load.device_add_extras();
+        Ok(())
+    }
+
+    // the function entrance to start the unit, which waits for the device
+    fn start(&self) -> Result<(), UnitActionError> {
+        self.mng.start_check()?;
+
+        self.mng.start_action()
+    }
+
+    fn stop(&self) -> Result<(), UnitActionError> {
+        self.mng.stop_action()
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.current_active_state()
+    }
+
+    fn attach_unit(&self, unit: Rc<Unit>) {
+        self.comm.attach_unit(unit);
+    }
+
+    fn device_update(&self, found: bool) {
+        self.mng.device_update(found)
+    }
+}
+
+// attach the UnitManager for weak reference
+impl UnitMngUtil for DeviceUnit {
+    fn attach(&self, um: Rc<UnitManager>) {
+        self.comm.attach_um(um);
+    }
+}
+
+impl UnitSubClass for DeviceUnit {
+    fn into_unitobj(self: Box<Self>) -> Box<dyn UnitObj> {
+        Box::new(*self)
+    }
+}
+
+impl DeviceUnit {
+    fn new() -> DeviceUnit {
+        let comm = Rc::new(DeviceComm::new());
+        let config = Rc::new(DeviceConfig::new());
+        let mng = DeviceMng::new(&comm);
+        DeviceUnit {
+            comm: Rc::clone(&comm),
+            config: Rc::clone(&config),
+            mng: Rc::clone(&mng),
+            load: DeviceLoad::new(&config, &comm, &mng),
+        }
+    }
+}
+
+impl Default for DeviceUnit {
+    fn default() -> Self {
+        DeviceUnit::new()
+    }
+}
+
+const LOG_LEVEL: u32 = 4;
+const PLUGIN_NAME: &str = "DeviceUnit";
+
+use process1::declure_unitobj_plugin;
+
+// define the method to create the instance of the unit
+declure_unitobj_plugin!(DeviceUnit, DeviceUnit::default, PLUGIN_NAME, LOG_LEVEL);
@@ -0,0 +1,42 @@
+//! device是process1的设备类型，表示udev处理完成的设备，服务可以通过BindsTo=与After=依赖设备，在设备出现后启动，设备拔出后停止。
+//! process1监听NETLINK_KOBJECT_UEVENT套接字上udev广播的uevent，只有被udev规则打上process1标签的设备才会生成device，
+//! 如在udev规则中配置 SUBSYSTEM=="block", TAG+="process1"。
+//!
+//! 一个设备对应多个device，名称分别为sysfs路径、设备节点及其符号链接转义后的名称，
+//! 如/dev/sda1对应dev-sda1.device，/dev/disk/by-uuid/下的链接对应dev-disk-by\x2duuid-....device。
+//! process1启动前已存在的设备从/run/udev/tags/process1中获取。
+//!
+//! device不能由process1启动或停止，启动device时等待设备出现，超过JobTimeoutSec=仍未出现时启动失败，依赖它的单元随之失败，启动过程继续进行。
+//! device通常没有配置文件，需要修改等待时间时可以提供同名的配置文件。
+//!
+//! # Example:
+//! ```toml
+//! [Unit]
+//! Description="the data disk"
+//!
+//! [Device]
+//! JobTimeoutSec="30s"
+//! ```
+
+//! [Device] section相关的配置
+//!
+//! JobTimeoutSec
+//!
+//! 启动时等待设备出现的时间，默认为90s，配置为infinity时一直等待。
+//!
+//! ## 自动依赖
+//! device没有隐含依赖和默认依赖，不会被isolate停止。
+
+// dependency:
+// device_comm -> device_config
+// {device_mng | device_load} -> device_unit
+
+mod device_comm;
+
+mod device_config;
+
+mod device_mng;
+
+mod device_load;
+
+mod device_unit;
@@ -1,16 +1,74 @@
-//! #mount 是process1的挂载点监控的入口，process1参考systemd，但不完全相同。
-//! process1主要提供监控功能，不主动挂载/卸载挂载点，这由其他进程实现。
-//! mount不支持配置文件。
+//! #mount 是process1的挂载点类型，process1参考systemd，但不完全相同。
+//! 配置了mount配置文件的挂载点，启动时在子进程中通过mount(2)挂载，停止时通过umount2(2)卸载。
+//! mount的名称必须是Where=路径转义后的名称，如/mnt/data对应mnt-data.mount。
+//!
+//! process1监听/proc/self/mountinfo的变化，由其他进程挂载的挂载点没有配置文件时也会生成对应的mount，
+//! 由其他进程挂载或卸载的挂载点，其mount的状态也会随之变化。
+//!
+//! # Example:
+//! ```toml
+//! [Unit]
+//! Description="the data disk"
+//!
+//! [Mount]
+//! What="/dev/mapper/vg-data"
+//! Where="/mnt/data"
+//! Type="ext4"
+//! Options="noatime,nodev"
+//! ```
+
+//! [Mount] section相关的配置
+//!
+//! What
+//!
+//! 挂载的设备、文件或其他资源，有配置文件时必须配置。
+//!
+//! Where
+//!
+//! 挂载点的路径，不配置时由mount的名称反转义得到，挂载点不存在时会被创建。
+//!
+//! Type
+//!
+//! 文件系统类型，除bind挂载外必须配置。
+//!
+//! Options
+//!
+//! 以","分隔的挂载选项，如ro、nosuid、bind等转换为mount(2)的flags，auto、nofail、_netdev、x-*等仅用于fstab的选项被忽略，
+//! 其他选项传递给文件系统。
+//!
+//! DirectoryMode
+//!
+//! 创建挂载点及其父目录时使用的权限，八进制，默认为0755。
+//!
+//! TimeoutSec
+//!
+//! 挂载和卸载的超时时间，超时后杀死执行挂载或卸载的子进程，mount进入失败状态，默认为90s，infinity表示不限制。
+//!
+//! LazyUnmount
+//!
+//! 卸载时使用MNT_DETACH，默认为false。
+//!
+//! ForceUnmount
+//!
+//! 卸载时使用MNT_FORCE，默认为false。
 //!
 //! ## 自动依赖
-//! 无。
 //! ### 隐含依赖
-//! 无。
+//! + 挂载点的父目录所在的mount，即Requires与After依赖，与[Unit] section中的RequiresMountsFor=相同。
+//! + What=为/dev下的设备时，对应的device，即BindsTo与After依赖，设备出现后才挂载，拔出后停止。
 //!
 //! ### 默认依赖
-//! 无。
+//! 有配置文件且没有设置DefaultDependencies = no时，会默认增加如下依赖关系：
+//! + 网络文件系统（如nfs、cifs）或配置了_netdev选项时，Before = remote-fs.target，After = network.target；否则Before = local-fs.target
+//! + 除根目录外，Conflicts = shutdown.target 与 Before = shutdown.target的依赖，关机时卸载
+
+// dependency:
+// mount_comm -> mount_config
+// {mount_mng | mount_load} -> mount_unit
 
 mod mount_comm;
+mod mount_config;
+mod mount_load;
 mod mount_mng;
 mod mount_unit;
 
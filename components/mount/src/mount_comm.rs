@@ -6,7 +6,7 @@
 * mount_unit->mount_mng->mount_comm
 */
 
-use process1::manager::Unit;
+use process1::manager::{Unit, UnitManager};
 use std::{
     cell::RefCell,
     rc::{Rc, Weak},
@@ -26,24 +26,44 @@ impl MountComm {
         self.data.borrow_mut().attach_unit(unit);
     }
 
+    pub(super) fn attach_um(&self, um: Rc<UnitManager>) {
+        self.data.borrow_mut().attach_um(um);
+    }
+
     pub(super) fn unit(&self) -> Option<Rc<Unit>> {
         self.data.borrow().unit()
     }
+
+    pub(super) fn um(&self) -> Rc<UnitManager> {
+        self.data.borrow().um()
+    }
 }
 struct MountCommData {
     unit: Weak<Unit>,
+    um: Weak<UnitManager>,
 }
 
 impl MountCommData {
     pub(self) fn new() -> MountCommData {
-        MountCommData { unit: Weak::new() }
+        MountCommData {
+            unit: Weak::new(),
+            um: Weak::new(),
+        }
     }
 
     fn attach_unit(&mut self, unit: Rc<Unit>) {
         self.unit = Rc::downgrade(&unit);
     }
 
+    fn attach_um(&mut self, um: Rc<UnitManager>) {
+        self.um = Rc::downgrade(&um);
+    }
+
     pub(self) fn unit(&self) -> Option<Rc<Unit>> {
         self.unit.clone().upgrade()
     }
+
+    pub(self) fn um(&self) -> Rc<UnitManager> {
+        self.um.clone().upgrade().unwrap()
+    }
 }
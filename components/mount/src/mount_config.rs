@@ -0,0 +1,105 @@
+//! mount_config模块mount类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+
+pub(super) struct MountConfig {
+    data: Rc<RefCell<MountConfigData>>,
+}
+
+impl MountConfig {
+    pub(super) fn new() -> Self {
+        MountConfig {
+            data: Rc::new(RefCell::new(MountConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = MountConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<MountConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct MountConfigData {
+    #[config(nested)]
+    pub Mount: SectionMount,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionMount {
+    pub What: Option<String>,
+    pub Where: Option<String>,
+    pub Type: Option<String>,
+    pub Options: Option<String>,
+    pub DirectoryMode: Option<String>,
+    pub TimeoutSec: Option<String>,
+    pub LazyUnmount: Option<bool>,
+    pub ForceUnmount: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mount_config::MountConfig;
+    use std::{
+        env,
+        ffi::OsString,
+        fs::read_dir,
+        io::{self, ErrorKind},
+        path::PathBuf,
+    };
+
+    #[test]
+    fn test_mount_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.mount.toml");
+        let paths = vec![file_path];
+
+        let config = MountConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let mount = &data.borrow().Mount;
+        assert_eq!(mount.What, Some("/dev/mapper/vg-data".to_string()));
+        assert_eq!(mount.Where, Some("/mnt/data".to_string()));
+        assert_eq!(mount.Type, Some("ext4".to_string()));
+        assert_eq!(mount.Options, Some("noatime,nodev".to_string()));
+        assert_eq!(mount.DirectoryMode, Some("0700".to_string()));
+        assert_eq!(mount.TimeoutSec, Some("30s".to_string()));
+        assert_eq!(mount.LazyUnmount, Some(true));
+        assert_eq!(mount.ForceUnmount, None);
+    }
+
+    fn get_project_root() -> io::Result<PathBuf> {
+        let path = env::current_dir()?;
+        let mut path_ancestors = path.as_path().ancestors();
+
+        while let Some(p) = path_ancestors.next() {
+            let has_cargo = read_dir(p)?
+                .into_iter()
+                .any(|p| p.unwrap().file_name() == OsString::from("Cargo.lock"));
+            if has_cargo {
+                return Ok(PathBuf::from(p));
+            }
+        }
+        Err(io::Error::new(
+            ErrorKind::NotFound,
+            "Ran out of places to find Cargo.toml",
+        ))
+    }
+}
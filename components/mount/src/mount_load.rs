@@ -0,0 +1,262 @@
+//! mount_load模块实现mount配置文件的解析，以及mount的默认依赖。
+//!
+
+use std::{error::Error, path::Path, rc::Rc};
+
+use nix::mount::MsFlags;
+use process1::manager::UnitRelations;
+use utils::{mount_util, time_util, unit_name};
+
+use crate::mount_comm::MountComm;
+use crate::mount_config::MountConfig;
+use crate::mount_mng::MountMng;
+
+const LOCAL_FS_TARGET: &str = "local-fs.target";
+const REMOTE_FS_TARGET: &str = "remote-fs.target";
+const NETWORK_TARGET: &str = "network.target";
+const SHUTDOWN_TARGET: &str = "shutdown.target";
+
+// the file systems which need the network
+const REMOTE_FS_TYPES: [&str; 9] = [
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "ceph",
+    "glusterfs",
+    "sshfs",
+    "fuse.sshfs",
+    "davfs",
+];
+
+pub(super) struct MountLoad {
+    config: Rc<MountConfig>,
+    comm: Rc<MountComm>,
+    mng: Rc<MountMng>,
+}
+
+impl MountLoad {
+    pub(super) fn new(
+        configr: &Rc<MountConfig>,
+        commr: &Rc<MountComm>,
+        mngr: &Rc<MountMng>,
+    ) -> Self {
+        MountLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse mount section");
+        let data = self.config.config_data();
+        let mount = &data.borrow().Mount;
+
+        self.mng
+            .set_what(mount.What.as_deref().map(mount_util::what_to_node));
+        self.mng.set_where(&self.mount_where());
+        self.mng.set_fstype(mount.Type.clone());
+
+        let (flags, data) = mount_options(mount.Options.as_deref().unwrap_or(""));
+        self.mng.set_options(flags, data);
+
+        if let Some(mode) = &mount.DirectoryMode {
+            let mode = u32::from_str_radix(mode.trim(), 8)
+                .map_err(|_| format!("invalid DirectoryMode {}", mode))?;
+            self.mng.set_directory_mode(mode);
+        }
+
+        if let Some(v) = &mount.TimeoutSec {
+            let usec = if v.trim() == "infinity" {
+                u64::MAX
+            } else {
+                time_util::parse_timespan(v)?
+            };
+            self.mng.set_timeout_usec(usec);
+        }
+
+        self.mng.set_unmount_flags(
+            mount.LazyUnmount.unwrap_or(false),
+            mount.ForceUnmount.unwrap_or(false),
+        );
+        Ok(())
+    }
+
+    // the mount points mounted by others have no unit file, the dependencies
+    // of their own are not added.
+    pub(super) fn mount_add_extras(&self, has_fragment: bool) -> Result<(), Box<dyn Error>> {
+        let unit = self.comm.unit().ok_or("the mount is not attached")?;
+        unit.set_ignore_on_isolate(true);
+
+        // the parent mount points are mounted first
+        let mount_where = self.mount_where();
+        if let Some(parent) = Path::new(&mount_where).parent() {
+            unit.requires_mounts_for(&parent.to_string_lossy());
+        }
+
+        if !has_fragment {
+            return Ok(());
+        }
+
+        // the device is waited for, and the mount is stopped once it is unplugged
+        if let Some(what) = self.config.config_data().borrow().Mount.What.as_deref() {
+            unit.bind_to_device(what);
+        }
+
+        if !unit.default_dependencies() {
+            return Ok(());
+        }
+
+        if self.is_remote() {
+            unit.insert_dep(UnitRelations::UnitBefore, REMOTE_FS_TARGET.to_string());
+            unit.insert_dep(UnitRelations::UnitAfter, NETWORK_TARGET.to_string());
+        } else {
+            unit.insert_dep(UnitRelations::UnitBefore, LOCAL_FS_TARGET.to_string());
+        }
+
+        // the root file system is never unmounted
+        if mount_where != "/" {
+            unit.insert_two_deps(
+                UnitRelations::UnitConflicts,
+                UnitRelations::UnitBefore,
+                SHUTDOWN_TARGET.to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    pub(super) fn mount_verify(&self, has_fragment: bool) -> Result<(), Box<dyn Error>> {
+        let id = self
+            .comm
+            .unit()
+            .ok_or("the mount is not attached")?
+            .get_id()
+            .to_string();
+        let mount_where = self.mount_where();
+        // the name of the mount is the escaped path of Where=
+        let name = unit_name::unit_name_from_path(&mount_where, "mount");
+        if name != id {
+            return Err(format!(
+                "mount {} does not match Where={}, expect {}",
+                id, mount_where, name
+            )
+            .into());
+        }
+
+        if !has_fragment {
+            if !mount_util::path_is_mount_point(Path::new(&mount_where)) {
+                return Err(format!("mount {} has no unit file and is not mounted", id).into());
+            }
+            return Ok(());
+        }
+
+        let data = self.config.config_data();
+        let mount = &data.borrow().Mount;
+        if mount.What.is_none() {
+            return Err(format!("mount {} has no What=", id).into());
+        }
+
+        let (flags, _) = mount_options(mount.Options.as_deref().unwrap_or(""));
+        if mount.Type.is_none() && !flags.contains(MsFlags::MS_BIND) {
+            return Err(format!("mount {} has no Type=", id).into());
+        }
+        Ok(())
+    }
+
+    pub(super) fn mount_coldplug(&self) {
+        let mounted = mount_util::path_is_mount_point(Path::new(&self.mount_where()));
+        self.mng.coldplug(mounted);
+    }
+
+    // Where= is optional, which is the unescaped name of the mount
+    fn mount_where(&self) -> String {
+        if let Some(w) = &self.config.config_data().borrow().Mount.Where {
+            return w.to_string();
+        }
+
+        let id = match self.comm.unit() {
+            Some(u) => u.get_id().to_string(),
+            None => return String::new(),
+        };
+        match unit_name::unit_name_without_suffix(&id).as_deref() {
+            Some("-") | None => "/".to_string(),
+            Some(stem) => format!("/{}", unit_name::unit_name_unescape(stem)),
+        }
+    }
+
+    fn is_remote(&self) -> bool {
+        let data = self.config.config_data();
+        let mount = &data.borrow().Mount;
+        if let Some(t) = &mount.Type {
+            if REMOTE_FS_TYPES.contains(&t.as_str()) {
+                return true;
+            }
+        }
+
+        mount
+            .Options
+            .as_deref()
+            .unwrap_or("")
+            .split(',')
+            .any(|o| o.trim() == "_netdev")
+    }
+}
+
+// split Options= into the flags of mount(2) and the data passed to the file system,
+// the options only used by mount(8) and fstab are dropped.
+fn mount_options(options: &str) -> (MsFlags, String) {
+    let mut flags = MsFlags::empty();
+    let mut data = Vec::new();
+    for opt in options
+        .split(',')
+        .map(|o| o.trim())
+        .filter(|o| !o.is_empty())
+    {
+        match opt {
+            "defaults" | "rw" | "suid" | "dev" | "exec" | "async" | "atime" | "diratime"
+            | "norelatime" | "auto" | "noauto" | "nofail" | "user" | "nouser" | "users"
+            | "owner" | "group" | "_netdev" => {}
+            "ro" => flags |= MsFlags::MS_RDONLY,
+            "nosuid" => flags |= MsFlags::MS_NOSUID,
+            "nodev" => flags |= MsFlags::MS_NODEV,
+            "noexec" => flags |= MsFlags::MS_NOEXEC,
+            "sync" => flags |= MsFlags::MS_SYNCHRONOUS,
+            "dirsync" => flags |= MsFlags::MS_DIRSYNC,
+            "mand" => flags |= MsFlags::MS_MANDLOCK,
+            "noatime" => flags |= MsFlags::MS_NOATIME,
+            "nodiratime" => flags |= MsFlags::MS_NODIRATIME,
+            "relatime" => flags |= MsFlags::MS_RELATIME,
+            "strictatime" => flags |= MsFlags::MS_STRICTATIME,
+            "silent" => flags |= MsFlags::MS_SILENT,
+            "remount" => flags |= MsFlags::MS_REMOUNT,
+            "bind" => flags |= MsFlags::MS_BIND,
+            "rbind" => flags |= MsFlags::MS_BIND | MsFlags::MS_REC,
+            o if o.starts_with("x-") || o.starts_with("X-") || o.starts_with("comment=") => {}
+            o => data.push(o),
+        }
+    }
+    (flags, data.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_options() {
+        assert_eq!(mount_options(""), (MsFlags::empty(), String::new()));
+        assert_eq!(mount_options("defaults"), (MsFlags::empty(), String::new()));
+        assert_eq!(
+            mount_options("ro,nosuid,size=10%,x-systemd.automount,mode=0755"),
+            (
+                MsFlags::MS_RDONLY | MsFlags::MS_NOSUID,
+                "size=10%,mode=0755".to_string()
+            )
+        );
+        assert_eq!(
+            mount_options("rbind,_netdev,nofail"),
+            (MsFlags::MS_BIND | MsFlags::MS_REC, String::new())
+        );
+    }
+}
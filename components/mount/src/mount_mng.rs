@@ -1,59 +1,420 @@
-//! mount子类的核心逻辑
+//! mount子类的核心逻辑，启动时在子进程中通过mount(2)挂载，停止时通过umount2(2)卸载，
+//! 并根据/proc/self/mountinfo的变化，同步由其他进程挂载或卸载的挂载点的状态。
 //!
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    fs::DirBuilder,
+    os::unix::fs::DirBuilderExt,
+    path::PathBuf,
+    rc::{Rc, Weak},
+};
 
 use crate::mount_comm::MountComm;
-use process1::manager::{UnitActiveState, UnitNotifyFlags};
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::libc;
+use nix::mount::{self, MntFlags, MsFlags};
+use nix::sys::signal::{self, Signal};
+use nix::time::{clock_gettime, ClockId};
+use nix::unistd::{self, ForkResult, Pid};
+use process1::manager::{UnitActionError, UnitActiveState, UnitNotifyFlags};
+use utils::{Error, IN_SET};
 
-// Mount包含两个状态，未挂载Dead，已挂载Mounted。对应到unit状态为inactive和active
+// the default of TimeoutSec=, in useconds
+pub(super) const DEFAULT_TIMEOUT_USEC: u64 = 90_000_000;
+
+// the default of DirectoryMode=
+pub(super) const DEFAULT_DIRECTORY_MODE: u32 = 0o755;
+
+// Mount在挂载和卸载的过程中分别处于Mounting和Unmounting，对应到unit状态为activating和deactivating
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 enum MountState {
     Dead,
+    Mounting,
     Mounted,
+    Unmounting,
+    Failed,
 }
 
 impl MountState {
-    fn to_unit_state(&self) -> UnitActiveState {
-        match *self {
+    fn to_unit_state(self) -> UnitActiveState {
+        match self {
             MountState::Dead => UnitActiveState::UnitInActive,
+            MountState::Mounting => UnitActiveState::UnitActivating,
             MountState::Mounted => UnitActiveState::UnitActive,
+            MountState::Unmounting => UnitActiveState::UnitDeActivating,
+            MountState::Failed => UnitActiveState::UnitFailed,
         }
     }
 }
 
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum MountResult {
+    Success,
+    FailureResources,
+    FailureExitCode,
+    FailureSignal,
+    FailureTimeout,
+}
+
 pub(super) struct MountMng {
     comm: Rc<MountComm>,
     state: RefCell<MountState>,
+    result: RefCell<MountResult>,
+    // the parameters of mount(2), which come from What=, Where=, Type= and Options=
+    what: RefCell<Option<String>>,
+    mount_where: RefCell<PathBuf>,
+    fstype: RefCell<Option<String>>,
+    flags: RefCell<MsFlags>,
+    data: RefCell<String>,
+    directory_mode: RefCell<u32>,
+    // u64::MAX means mounting and unmounting without limit
+    timeout_usec: RefCell<u64>,
+    lazy_unmount: RefCell<bool>,
+    force_unmount: RefCell<bool>,
+    // the child process running mount(2) or umount2(2)
+    control_pid: RefCell<Option<Pid>>,
+    // whether the mount point is listed in /proc/self/mountinfo
+    mounted: RefCell<bool>,
+    timer: Rc<MountTimer>,
 }
 
 impl MountMng {
-    pub(super) fn new(_comm: &Rc<MountComm>) -> Self {
-        MountMng {
+    pub(super) fn new(_comm: &Rc<MountComm>) -> Rc<MountMng> {
+        let mng = Rc::new(MountMng {
             comm: Rc::clone(_comm),
             state: RefCell::new(MountState::Dead),
+            result: RefCell::new(MountResult::Success),
+            what: RefCell::new(None),
+            mount_where: RefCell::new(PathBuf::new()),
+            fstype: RefCell::new(None),
+            flags: RefCell::new(MsFlags::empty()),
+            data: RefCell::new(String::new()),
+            directory_mode: RefCell::new(DEFAULT_DIRECTORY_MODE),
+            timeout_usec: RefCell::new(DEFAULT_TIMEOUT_USEC),
+            lazy_unmount: RefCell::new(false),
+            force_unmount: RefCell::new(false),
+            control_pid: RefCell::new(None),
+            mounted: RefCell::new(false),
+            timer: Rc::new(MountTimer::new()),
+        });
+
+        mng.timer.attach(Rc::downgrade(&mng));
+        mng
+    }
+
+    pub(super) fn set_what(&self, what: Option<String>) {
+        *self.what.borrow_mut() = what;
+    }
+
+    pub(super) fn set_where(&self, mount_where: &str) {
+        *self.mount_where.borrow_mut() = PathBuf::from(mount_where);
+    }
+
+    pub(super) fn set_fstype(&self, fstype: Option<String>) {
+        *self.fstype.borrow_mut() = fstype;
+    }
+
+    pub(super) fn set_options(&self, flags: MsFlags, data: String) {
+        *self.flags.borrow_mut() = flags;
+        *self.data.borrow_mut() = data;
+    }
+
+    pub(super) fn set_directory_mode(&self, mode: u32) {
+        *self.directory_mode.borrow_mut() = mode;
+    }
+
+    pub(super) fn set_timeout_usec(&self, usec: u64) {
+        *self.timeout_usec.borrow_mut() = usec;
+    }
+
+    pub(super) fn set_unmount_flags(&self, lazy: bool, force: bool) {
+        *self.lazy_unmount.borrow_mut() = lazy;
+        *self.force_unmount.borrow_mut() = force;
+    }
+
+    // sync the state with /proc/self/mountinfo without notifying
+    pub(super) fn coldplug(&self, mounted: bool) {
+        *self.mounted.borrow_mut() = mounted;
+        if self.state() == MountState::Dead && mounted {
+            *self.state.borrow_mut() = MountState::Mounted;
         }
     }
 
-    // process doesn't support manually mount/umount like systemd.
-    // We only monitor the state of mountpoint.
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        match self.state() {
+            MountState::Mounted => return Err(UnitActionError::UnitActionEAlready),
+            // wait for the unmounting finished
+            MountState::Unmounting => return Err(UnitActionError::UnitActionEAgain),
+            _ => {}
+        }
+
+        // the mount points mounted by others can not be mounted again without What=
+        if self.what.borrow().is_none() {
+            log::error!("mount {} has no What= to mount", self.unit_id());
+            return Err(UnitActionError::UnitActionEOpNotSupp);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        if self.state() == MountState::Mounting {
+            return;
+        }
+        self.set_result(MountResult::Success);
+
+        let mount_where = self.mount_where.borrow().clone();
+        if let Err(e) = DirBuilder::new()
+            .recursive(true)
+            .mode(*self.directory_mode.borrow())
+            .create(&mount_where)
+        {
+            log::error!("failed to create the mount point {:?}: {}", mount_where, e);
+            self.update_result(MountResult::FailureResources);
+            return self.enter_dead();
+        }
+
+        let what = self.what.borrow().clone().unwrap_or_default();
+        let fstype = self.fstype.borrow().clone();
+        let flags = *self.flags.borrow();
+        let data = self.data.borrow().clone();
+        log::debug!(
+            "mount {} on {:?}, type: {:?}, flags: {:?}, data: {}",
+            what,
+            mount_where,
+            fstype,
+            flags,
+            data
+        );
+        let ret = self.spawn_control(|| {
+            mount::mount(
+                Some(what.as_str()),
+                &mount_where,
+                fstype.as_deref(),
+                flags,
+                if data.is_empty() {
+                    None
+                } else {
+                    Some(data.as_str())
+                },
+            )
+        });
+        self.enter_control(ret, MountState::Mounting);
+    }
+
+    pub(super) fn stop_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), MountState::Dead, MountState::Failed) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        if self.state() == MountState::Unmounting {
+            return;
+        }
+
+        // the mounting is abandoned
+        if self.state() == MountState::Mounting {
+            self.kill_control();
+        }
+        self.set_result(MountResult::Success);
+
+        let mount_where = self.mount_where.borrow().clone();
+        let mut flags = MntFlags::empty();
+        if *self.lazy_unmount.borrow() {
+            flags |= MntFlags::MNT_DETACH;
+        }
+        if *self.force_unmount.borrow() {
+            flags |= MntFlags::MNT_FORCE;
+        }
+        log::debug!("umount {:?} with flags {:?}", mount_where, flags);
+        let ret = self.spawn_control(|| mount::umount2(&mount_where, flags));
+        self.enter_control(ret, MountState::Unmounting);
+    }
+
+    pub(super) fn sigchld_events(&self, pid: Pid, code: i32, signal: Signal) {
+        if *self.control_pid.borrow() != Some(pid) {
+            return;
+        }
+        self.comm.um().child_unwatch_pid(pid);
+        *self.control_pid.borrow_mut() = None;
+        self.disable_timer();
+
+        // the child exits with the errno of mount(2) or umount2(2)
+        let res = if signal != Signal::SIGCHLD {
+            log::error!(
+                "the control process of {} is killed by {}",
+                self.unit_id(),
+                signal
+            );
+            MountResult::FailureSignal
+        } else if code != 0 {
+            log::error!(
+                "failed to {} {}: {}",
+                if self.state() == MountState::Mounting {
+                    "mount"
+                } else {
+                    "umount"
+                },
+                self.unit_id(),
+                Errno::from_i32(code)
+            );
+            MountResult::FailureExitCode
+        } else {
+            MountResult::Success
+        };
+
+        let mounted = *self.mounted.borrow();
+        match self.state() {
+            MountState::Mounting => {
+                // the mount point may be mounted by others meanwhile
+                self.update_result(res);
+                if res == MountResult::Success || mounted {
+                    self.enter_mounted();
+                } else {
+                    self.enter_dead();
+                }
+            }
+            MountState::Unmounting => {
+                if res == MountResult::Success || !mounted {
+                    self.enter_dead();
+                } else {
+                    self.update_result(res);
+                    self.enter_mounted();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // the mount point is found in or gone from /proc/self/mountinfo, the changes
+    // during mounting and unmounting are left to the control process.
+    pub(super) fn mountinfo_update(&self, mounted: bool) {
+        *self.mounted.borrow_mut() = mounted;
+        match self.state() {
+            MountState::Dead | MountState::Failed if mounted => {
+                log::debug!("{} is mounted", self.unit_id());
+                self.set_result(MountResult::Success);
+                self.enter_mounted();
+            }
+            MountState::Mounted if !mounted => {
+                log::debug!("{} is unmounted", self.unit_id());
+                self.set_result(MountResult::Success);
+                self.enter_dead();
+            }
+            _ => {}
+        }
+    }
 
     pub(super) fn enter_dead(&self) {
-        self.set_state(MountState::Dead);
+        self.disable_timer();
+        if self.result() == MountResult::Success {
+            self.set_state(MountState::Dead);
+        } else {
+            self.set_state(MountState::Failed);
+        }
     }
 
     pub(super) fn enter_mounted(&self) {
+        self.disable_timer();
         self.set_state(MountState::Mounted);
     }
 
+    fn enter_control(&self, ret: nix::Result<Pid>, state: MountState) {
+        match ret {
+            Ok(pid) => {
+                self.comm.um().child_watch_pid(pid, &self.unit_id());
+                *self.control_pid.borrow_mut() = Some(pid);
+                self.enable_timer(*self.timeout_usec.borrow());
+                self.set_state(state);
+            }
+            Err(e) => {
+                log::error!("failed to fork the control process: {}", e);
+                self.update_result(MountResult::FailureResources);
+                if state == MountState::Mounting {
+                    self.enter_dead();
+                } else {
+                    self.enter_mounted();
+                }
+            }
+        }
+    }
+
+    // run mount(2) or umount2(2) in the child, whose exit code is the errno
+    fn spawn_control(&self, f: impl FnOnce() -> nix::Result<()>) -> nix::Result<Pid> {
+        match unsafe { unistd::fork() }? {
+            ForkResult::Child => {
+                let code = match f() {
+                    Ok(_) => 0,
+                    Err(e) => e as i32,
+                };
+                unsafe { libc::_exit(code) }
+            }
+            ForkResult::Parent { child } => Ok(child),
+        }
+    }
+
+    fn kill_control(&self) {
+        if let Some(pid) = self.control_pid.take() {
+            if let Err(e) = signal::kill(pid, Signal::SIGKILL) {
+                log::warn!("failed to kill the control process {}: {}", pid, e);
+            }
+            self.comm.um().child_unwatch_pid(pid);
+        }
+        self.disable_timer();
+    }
+
+    fn dispatch_timer(&self) {
+        if !IN_SET!(self.state(), MountState::Mounting, MountState::Unmounting) {
+            return;
+        }
+
+        // the state is decided once the killed control process is reaped
+        log::warn!("{} timed out, killing the control process", self.unit_id());
+        self.update_result(MountResult::FailureTimeout);
+        if let Some(pid) = *self.control_pid.borrow() {
+            if let Err(e) = signal::kill(pid, Signal::SIGKILL) {
+                log::warn!("failed to kill the control process {}: {}", pid, e);
+            }
+        }
+    }
+
+    fn enable_timer(&self, usec: u64) {
+        if usec == u64::MAX {
+            return;
+        }
+
+        if !self.timer.registered() {
+            self.comm.um().register(self.timer.clone());
+            self.timer.set_registered();
+        }
+
+        log::debug!("enable mount timer after {} usec", usec);
+        self.timer.arm(usec);
+        self.comm.um().enable(self.timer.clone(), EventState::On);
+    }
+
+    fn disable_timer(&self) {
+        if self.timer.armed() {
+            self.timer.disarm();
+            self.comm.um().enable(self.timer.clone(), EventState::Off);
+        }
+    }
+
+    fn unit_id(&self) -> String {
+        self.comm
+            .unit()
+            .map_or_else(|| "0".to_string(), |u| u.get_id().to_string())
+    }
+
     fn set_state(&self, new_state: MountState) {
         let old_state = self.state();
         self.state.replace(new_state);
         if new_state != old_state {
             log::debug!(
                 "{} original state[{:?}] -> new state[{:?}]",
-                self.comm
-                    .unit()
-                    .map_or_else(|| "0".to_string(), |u| u.get_id().to_string()),
+                self.unit_id(),
                 old_state,
                 new_state,
             );
@@ -73,11 +434,120 @@ impl MountMng {
         *self.state.borrow()
     }
 
+    // the first failure is kept
+    fn update_result(&self, res: MountResult) {
+        if self.result() == MountResult::Success {
+            self.set_result(res);
+        }
+    }
+
+    fn set_result(&self, res: MountResult) {
+        *self.result.borrow_mut() = res;
+    }
+
+    fn result(&self) -> MountResult {
+        *self.result.borrow()
+    }
+
     pub fn to_unit_state(&self) -> UnitActiveState {
         self.state().to_unit_state()
     }
 }
 
+/// the timer of the mount, for the timeout of mounting and unmounting.
+struct MountTimer {
+    // the absolute time in monotonic clock, u64::MAX means the timer is disarmed.
+    deadline: RefCell<u64>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<MountMng>>,
+}
+
+impl MountTimer {
+    fn new() -> Self {
+        MountTimer {
+            deadline: RefCell::new(u64::MAX),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<MountMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn arm(&self, usec: u64) {
+        *self.deadline.borrow_mut() = monotonic_usec().saturating_add(usec);
+    }
+
+    fn disarm(&self) {
+        *self.deadline.borrow_mut() = u64::MAX;
+    }
+
+    fn armed(&self) -> bool {
+        *self.deadline.borrow() != u64::MAX
+    }
+
+    fn expired(&self) -> bool {
+        self.armed() && monotonic_usec() >= *self.deadline.borrow()
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    fn mng(&self) -> Option<Rc<MountMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+fn monotonic_usec() -> u64 {
+    match clock_gettime(ClockId::CLOCK_MONOTONIC) {
+        Ok(ts) => ts.tv_sec() as u64 * 1_000_000 + ts.tv_nsec() as u64 / 1_000,
+        Err(_) => 0,
+    }
+}
+
+impl Source for MountTimer {
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn time(&self) -> u64 {
+        *self.deadline.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        // the timer queued before re-arming is expired earlier, ignore it.
+        if !self.expired() {
+            return Ok(0);
+        }
+
+        log::debug!("dispatch mount timer");
+        self.disarm();
+        if let Some(mng) = self.mng() {
+            mng.dispatch_timer();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::rc::Rc;
@@ -109,6 +579,21 @@ mod tests {
         assert_eq!(tm.state(), MountState::Mounted)
     }
 
+    #[test]
+    fn test_mount_mountinfo_update() {
+        let _comm = Rc::new(MountComm::new());
+        let tm = MountMng::new(&_comm);
+        tm.mountinfo_update(true);
+        assert_eq!(tm.state(), MountState::Mounted);
+        tm.mountinfo_update(false);
+        assert_eq!(tm.state(), MountState::Dead);
+
+        // the changes during mounting are left to the control process
+        tm.set_state(MountState::Mounting);
+        tm.mountinfo_update(true);
+        assert_eq!(tm.state(), MountState::Mounting);
+    }
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
@@ -9,31 +9,46 @@ use std::path::PathBuf;
 use std::rc::Rc;
 
 use super::mount_comm::MountComm;
+use super::mount_config::MountConfig;
+use super::mount_load::MountLoad;
 use super::mount_mng::MountMng;
 use process1::manager::{UnitActiveState, UnitMngUtil, UnitObj, UnitSubClass};
 use utils::logger;
 
 struct MountUnit {
     comm: Rc<MountComm>,
+    config: Rc<MountConfig>,
     mng: Rc<MountMng>,
+    load: MountLoad,
 }
 
 impl MountUnit {
     fn new() -> MountUnit {
         let _comm = Rc::new(MountComm::new());
+        let _config = Rc::new(MountConfig::new());
+        let _mng = MountMng::new(&_comm);
         MountUnit {
             comm: Rc::clone(&_comm),
-            mng: Rc::new(MountMng::new(&_comm)),
+            config: Rc::clone(&_config),
+            mng: Rc::clone(&_mng),
+            load: MountLoad::new(&_config, &_comm, &_mng),
         }
     }
 }
 
 impl UnitObj for MountUnit {
+    // the mount points mounted by others are loaded without unit file
     fn load(&self, paths: &Vec<PathBuf>) -> utils::Result<(), Box<dyn std::error::Error>> {
-        if self.comm.unit().is_some() {
-            self.comm.unit().unwrap().set_ignore_on_isolate(true);
-        }
+        log::debug!("mount begin to load conf file");
+        self.config.load(paths)?;
+
+        self.load.parse()?;
+
+        self.load.mount_add_extras(!paths.is_empty())?;
 
+        self.load.mount_verify(!paths.is_empty())?;
+
+        self.load.mount_coldplug();
         Ok(())
     }
 
@@ -54,12 +69,16 @@ impl UnitObj for MountUnit {
     fn dump(&self) {}
 
     fn start(&self) -> utils::Result<(), process1::manager::UnitActionError> {
-        self.mng.enter_mounted();
+        self.mng.start_check()?;
+
+        self.mng.start_action();
         Ok(())
     }
 
     fn stop(&self) -> utils::Result<(), process1::manager::UnitActionError> {
-        self.mng.enter_dead();
+        self.mng.stop_check()?;
+
+        self.mng.stop_action();
         Ok(())
     }
 
@@ -69,9 +88,15 @@ impl UnitObj for MountUnit {
 
     fn release_resources(&self) {}
 
-    fn sigchld_events(&self, pid: Pid, code: i32, status: Signal) {}
+    fn sigchld_events(&self, pid: Pid, code: i32, status: Signal) {
+        self.mng.sigchld_events(pid, code, status)
+    }
 
     fn reset_failed(&self) {}
+
+    fn mountinfo_update(&self, mounted: bool) {
+        self.mng.mountinfo_update(mounted)
+    }
 }
 
 impl UnitSubClass for MountUnit {
@@ -82,7 +107,7 @@ impl UnitSubClass for MountUnit {
 
 impl UnitMngUtil for MountUnit {
     fn attach(&self, _um: Rc<process1::manager::UnitManager>) {
-        return;
+        self.comm.attach_um(_um);
     }
 }
 
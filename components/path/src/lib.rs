@@ -0,0 +1,65 @@
+//! path是process1的路径监控类型，通过inotify监控文件或目录，条件满足时拉起对应的unit(默认为同名的service)。
+//! path配置文件包含Unit、Path、Install三个Section。
+//!
+//! # Example:
+//! ```toml
+//! [Unit]
+//! Description="spool directory watcher"
+//!
+//! [Path]
+//! DirectoryNotEmpty="/var/spool/backup"
+//! MakeDirectory=true
+//! DirectoryMode="0700"
+//! Unit="backup.service"
+//!
+//! [Install]
+//! WantedBy="paths.target"
+//! ```
+
+//! [Path] section相关的配置
+//!
+//! PathExists、PathExistsGlob、PathChanged、PathModified、DirectoryNotEmpty
+//!
+//! 监控的绝对路径，多个路径以";"分隔，任一路径满足条件即触发：
+//! PathExists在路径存在时触发，PathExistsGlob在存在匹配通配符的路径时触发，
+//! PathChanged在文件写入后关闭、或路径被创建、删除、重命名时触发，PathModified在PathChanged的基础上，文件每次写入时也触发，
+//! DirectoryNotEmpty在目录非空时触发。
+//! 路径不存在时监控其最近的已存在的父目录，路径出现后再监控路径本身。
+//!
+//! MakeDirectory
+//!
+//! 可设为true或false(默认)，为true时启动前创建PathExists和PathExistsGlob以外的路径对应的目录。
+//!
+//! DirectoryMode
+//!
+//! MakeDirectory创建目录的权限，八进制格式，默认为"0755"。
+//!
+//! Unit
+//!
+//! 触发时拉起的unit，默认为同名的service，不能是path。被触发的unit运行期间不再监控，unit停止后重新监控。
+//!
+//! TriggerLimitIntervalSec、TriggerLimitBurst
+//!
+//! 触发频率限制，TriggerLimitIntervalSec时间段内最多触发TriggerLimitBurst次，默认为"2s"内200次，
+//! 超出限制时path进入failed状态。任一项设为0时不限制。
+//!
+//! ## 自动依赖
+//!
+//! ### 隐含依赖
+//! path对Unit=指定的unit添加Before=和Triggers=依赖。
+
+// dependency:
+// path_base -> {path_comm | path_config}
+// {path_mng | path_load} -> path_unit
+
+mod path_base;
+
+mod path_comm;
+
+mod path_config;
+
+mod path_mng;
+
+mod path_load;
+
+mod path_unit;
@@ -0,0 +1,158 @@
+//! path_base模块定义path的监控类型及其inotify标志，以及触发频率限制等辅助函数。
+//!
+
+use nix::sys::inotify::AddWatchFlags;
+use nix::time::{clock_gettime, ClockId};
+use std::fs;
+use std::path::Path;
+use utils::path_util;
+
+pub(super) const USEC_PER_SEC: u64 = 1_000_000;
+
+// the default of TriggerLimitIntervalSec= and TriggerLimitBurst=
+pub(super) const DEFAULT_TRIGGER_LIMIT_INTERVAL_USEC: u64 = 2 * USEC_PER_SEC;
+pub(super) const DEFAULT_TRIGGER_LIMIT_BURST: u32 = 200;
+
+// the default of DirectoryMode=
+pub(super) const DEFAULT_DIRECTORY_MODE: u32 = 0o755;
+
+/// the condition which a path is watched for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PathType {
+    // PathExists=, the path exists
+    Exists,
+    // PathExistsGlob=, any path matching the glob pattern exists
+    ExistsGlob,
+    // PathChanged=, the file is closed after written, or the path is created, removed or renamed
+    Changed,
+    // PathModified=, as PathChanged=, and the file is written
+    Modified,
+    // DirectoryNotEmpty=, the directory contains at least one entry
+    DirectoryNotEmpty,
+}
+
+impl PathType {
+    // the events watched on the path itself
+    pub(super) fn flags(self) -> AddWatchFlags {
+        let base =
+            AddWatchFlags::IN_DELETE_SELF | AddWatchFlags::IN_MOVE_SELF | AddWatchFlags::IN_ATTRIB;
+        match self {
+            PathType::Exists | PathType::ExistsGlob => base,
+            PathType::Changed => {
+                base | AddWatchFlags::IN_CLOSE_WRITE
+                    | AddWatchFlags::IN_CREATE
+                    | AddWatchFlags::IN_DELETE
+                    | AddWatchFlags::IN_MOVED_FROM
+                    | AddWatchFlags::IN_MOVED_TO
+            }
+            PathType::Modified => PathType::Changed.flags() | AddWatchFlags::IN_MODIFY,
+            PathType::DirectoryNotEmpty => {
+                base | AddWatchFlags::IN_CREATE | AddWatchFlags::IN_MOVED_TO
+            }
+        }
+    }
+
+    // the events watched on the ancestor of the path, when the path doesn't exist
+    pub(super) fn parent_flags() -> AddWatchFlags {
+        AddWatchFlags::IN_DELETE_SELF
+            | AddWatchFlags::IN_MOVE_SELF
+            | AddWatchFlags::IN_ATTRIB
+            | AddWatchFlags::IN_CREATE
+            | AddWatchFlags::IN_MOVED_TO
+    }
+
+    // whether the directory is created when MakeDirectory= is set
+    pub(super) fn make_directory(self) -> bool {
+        !matches!(self, PathType::Exists | PathType::ExistsGlob)
+    }
+
+    /// whether the condition of the path is met now, the change types are
+    /// only met by the inotify events.
+    pub(super) fn check(self, path: &str) -> bool {
+        match self {
+            PathType::Exists => Path::new(path).exists(),
+            PathType::ExistsGlob => path_util::glob_exists(path),
+            PathType::DirectoryNotEmpty => match fs::read_dir(path) {
+                Ok(mut entries) => entries.next().is_some(),
+                Err(_) => false,
+            },
+            PathType::Changed | PathType::Modified => false,
+        }
+    }
+}
+
+/// the current monotonic time in useconds
+pub(super) fn now_usec() -> u64 {
+    match clock_gettime(ClockId::CLOCK_MONOTONIC) {
+        Ok(ts) => ts.tv_sec() as u64 * USEC_PER_SEC + ts.tv_nsec() as u64 / 1_000,
+        Err(_) => 0,
+    }
+}
+
+/// limit the triggers to {burst} in every {interval} useconds
+pub(super) struct RateLimit {
+    interval: u64,
+    burst: u32,
+    begin: u64,
+    num: u32,
+}
+
+impl RateLimit {
+    pub(super) fn new(interval: u64, burst: u32) -> Self {
+        RateLimit {
+            interval,
+            burst,
+            begin: 0,
+            num: 0,
+        }
+    }
+
+    /// count one trigger at {now}, return false if the limit is hit
+    pub(super) fn below(&mut self, now: u64) -> bool {
+        // the limit is disabled
+        if self.interval == 0 || self.burst == 0 {
+            return true;
+        }
+
+        if self.begin == 0 || now.saturating_sub(self.begin) >= self.interval {
+            self.begin = now;
+            self.num = 0;
+        }
+
+        if self.num < self.burst {
+            self.num += 1;
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathType, RateLimit, USEC_PER_SEC};
+
+    #[test]
+    fn test_rate_limit() {
+        let mut limit = RateLimit::new(2 * USEC_PER_SEC, 2);
+        assert!(limit.below(USEC_PER_SEC));
+        assert!(limit.below(USEC_PER_SEC + 1));
+        assert!(!limit.below(2 * USEC_PER_SEC));
+        // a new interval begins
+        assert!(limit.below(3 * USEC_PER_SEC));
+
+        let mut unlimited = RateLimit::new(0, 0);
+        for i in 1..10 {
+            assert!(unlimited.below(i));
+        }
+    }
+
+    #[test]
+    fn test_path_check() {
+        assert!(PathType::Exists.check("/proc/self"));
+        assert!(!PathType::Exists.check("/proc/self/nonexistent"));
+        assert!(PathType::ExistsGlob.check("/proc/self/st*"));
+        assert!(PathType::DirectoryNotEmpty.check("/proc/self"));
+        assert!(!PathType::Changed.check("/proc/self"));
+    }
+}
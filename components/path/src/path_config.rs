@@ -0,0 +1,87 @@
+//! path_config模块path类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+use process1::manager::DeserializeWith;
+
+pub(super) struct PathConfig {
+    data: Rc<RefCell<PathConfigData>>,
+}
+
+impl PathConfig {
+    pub(super) fn new() -> Self {
+        PathConfig {
+            data: Rc::new(RefCell::new(PathConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = PathConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<PathConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct PathConfigData {
+    #[config(nested)]
+    pub Path: SectionPath,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionPath {
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathExists: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathExistsGlob: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathChanged: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub PathModified: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub DirectoryNotEmpty: Option<Vec<String>>,
+    pub MakeDirectory: Option<bool>,
+    pub DirectoryMode: Option<String>,
+    pub Unit: Option<String>,
+    pub TriggerLimitIntervalSec: Option<String>,
+    pub TriggerLimitBurst: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path_config::PathConfig;
+    use utils::test_util::get_project_root;
+
+    #[test]
+    fn test_path_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.path.toml");
+        let paths = vec![file_path];
+
+        let config = PathConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let path = &data.borrow().Path;
+        assert_eq!(path.PathExists, Some(vec!["/run/test/ready".to_string()]));
+        assert_eq!(path.DirectoryNotEmpty.as_ref().unwrap().len(), 2);
+        assert_eq!(path.MakeDirectory, Some(true));
+        assert_eq!(path.DirectoryMode, Some("0700".to_string()));
+        assert_eq!(path.TriggerLimitBurst, Some(10));
+        assert_eq!(path.Unit, Some("test.service".to_string()));
+    }
+}
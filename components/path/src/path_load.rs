@@ -0,0 +1,121 @@
+//! path_load模块实现path配置文件的解析，以及对被触发unit的依赖。
+//!
+
+use std::{error::Error, path::Path, rc::Rc};
+
+use process1::manager::{UnitRelations, UnitType};
+use utils::time_util;
+
+use crate::path_base::{
+    PathType, DEFAULT_TRIGGER_LIMIT_BURST, DEFAULT_TRIGGER_LIMIT_INTERVAL_USEC,
+};
+use crate::path_comm::PathComm;
+use crate::path_config::PathConfig;
+use crate::path_mng::{PathMng, PathSpec};
+
+pub(super) struct PathLoad {
+    config: Rc<PathConfig>,
+    comm: Rc<PathComm>,
+    mng: Rc<PathMng>,
+}
+
+impl PathLoad {
+    pub(super) fn new(configr: &Rc<PathConfig>, commr: &Rc<PathComm>, mngr: &Rc<PathMng>) -> Self {
+        PathLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse path section");
+        let data = self.config.config_data();
+        let path = &data.borrow().Path;
+
+        for (p_type, values) in [
+            (PathType::Exists, &path.PathExists),
+            (PathType::ExistsGlob, &path.PathExistsGlob),
+            (PathType::Changed, &path.PathChanged),
+            (PathType::Modified, &path.PathModified),
+            (PathType::DirectoryNotEmpty, &path.DirectoryNotEmpty),
+        ] {
+            for value in values.iter().flatten() {
+                if value.is_empty() {
+                    continue;
+                }
+                if !Path::new(value).is_absolute() {
+                    return Err(format!("the path {} is not absolute", value).into());
+                }
+
+                let spec = Rc::new(PathSpec::new(value.to_string(), p_type));
+                spec.attach(Rc::downgrade(&self.mng));
+                self.mng.add_spec(spec);
+            }
+        }
+
+        if let Some(make_directory) = path.MakeDirectory {
+            self.mng.set_make_directory(make_directory);
+        }
+
+        if let Some(mode) = &path.DirectoryMode {
+            let mode = u32::from_str_radix(mode, 8)
+                .map_err(|_e| format!("invalid DirectoryMode {}", mode))?;
+            self.mng.set_directory_mode(mode);
+        }
+
+        let interval = match &path.TriggerLimitIntervalSec {
+            Some(v) => time_util::parse_timespan(v)?,
+            None => DEFAULT_TRIGGER_LIMIT_INTERVAL_USEC,
+        };
+        let burst = path
+            .TriggerLimitBurst
+            .unwrap_or(DEFAULT_TRIGGER_LIMIT_BURST);
+        self.mng.set_trigger_limit(interval, burst);
+
+        let unit = match &path.Unit {
+            Some(u) => u.clone(),
+            None => {
+                // the service with the same name as the path by default
+                let id = self.comm.unit().get_id().to_string();
+                let stem = Path::new(&id)
+                    .file_stem()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string();
+                format!("{}.{}", stem, String::from(UnitType::UnitService))
+            }
+        };
+        self.mng.set_unit(unit);
+
+        Ok(())
+    }
+
+    pub(super) fn path_add_extras(&self) -> Result<(), Box<dyn Error>> {
+        let unit = self.mng.unit();
+        if !self.comm.um().load_unit_success(&unit) {
+            return Err(format!("failed to load unit {}", unit).into());
+        }
+
+        self.comm.unit().insert_two_deps(
+            UnitRelations::UnitBefore,
+            UnitRelations::UnitTriggers,
+            unit,
+        );
+        Ok(())
+    }
+
+    pub(super) fn path_verify(&self) -> Result<(), Box<dyn Error>> {
+        let id = self.comm.unit().get_id().to_string();
+        if !self.mng.has_specs() {
+            return Err(format!("path {} has no path configured", id).into());
+        }
+
+        let unit = self.mng.unit();
+        if unit.ends_with(".path") {
+            return Err(format!("path {} can not trigger the path {}", id, unit).into());
+        }
+
+        Ok(())
+    }
+}
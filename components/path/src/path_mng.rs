@@ -0,0 +1,508 @@
+//! path_mng模块是path类型的核心逻辑，维护path的状态机，通过inotify监控路径，条件满足时拉起对应的unit。
+//!
+
+use std::{
+    cell::RefCell,
+    fmt,
+    fs::DirBuilder,
+    os::unix::{
+        fs::DirBuilderExt,
+        io::{AsRawFd, FromRawFd, RawFd},
+    },
+    path::Path,
+    rc::{Rc, Weak},
+};
+
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::libc;
+use nix::sys::inotify::{InitFlags, Inotify, WatchDescriptor};
+use process1::manager::{UnitActionError, UnitActiveState, UnitNotifyFlags};
+use utils::{fd_util, Error, IN_SET};
+
+use crate::path_base::{
+    self, PathType, RateLimit, DEFAULT_DIRECTORY_MODE, DEFAULT_TRIGGER_LIMIT_BURST,
+    DEFAULT_TRIGGER_LIMIT_INTERVAL_USEC,
+};
+use crate::path_comm::PathComm;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum PathState {
+    Dead,
+    Waiting,
+    Running,
+    Failed,
+}
+
+impl PathState {
+    fn to_unit_active_state(self) -> UnitActiveState {
+        match self {
+            PathState::Dead => UnitActiveState::UnitInActive,
+            PathState::Waiting | PathState::Running => UnitActiveState::UnitActive,
+            PathState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum PathResult {
+    Success,
+    FailureResources,
+    FailureTriggerLimitHit,
+}
+
+pub(super) struct PathMng {
+    comm: Rc<PathComm>,
+    state: RefCell<PathState>,
+    result: RefCell<PathResult>,
+    specs: RefCell<Vec<Rc<PathSpec>>>,
+
+    unit: RefCell<String>,
+    make_directory: RefCell<bool>,
+    directory_mode: RefCell<u32>,
+    trigger_limit: RefCell<RateLimit>,
+}
+
+impl PathMng {
+    pub(super) fn new(commr: &Rc<PathComm>) -> PathMng {
+        PathMng {
+            comm: Rc::clone(commr),
+            state: RefCell::new(PathState::Dead),
+            result: RefCell::new(PathResult::Success),
+            specs: RefCell::new(Vec::new()),
+            unit: RefCell::new(String::new()),
+            make_directory: RefCell::new(false),
+            directory_mode: RefCell::new(DEFAULT_DIRECTORY_MODE),
+            trigger_limit: RefCell::new(RateLimit::new(
+                DEFAULT_TRIGGER_LIMIT_INTERVAL_USEC,
+                DEFAULT_TRIGGER_LIMIT_BURST,
+            )),
+        }
+    }
+
+    pub(super) fn add_spec(&self, spec: Rc<PathSpec>) {
+        self.specs.borrow_mut().push(spec);
+    }
+
+    pub(super) fn has_specs(&self) -> bool {
+        !self.specs.borrow().is_empty()
+    }
+
+    pub(super) fn set_unit(&self, unit: String) {
+        *self.unit.borrow_mut() = unit;
+    }
+
+    pub(super) fn unit(&self) -> String {
+        self.unit.borrow().clone()
+    }
+
+    pub(super) fn set_make_directory(&self, make_directory: bool) {
+        *self.make_directory.borrow_mut() = make_directory;
+    }
+
+    pub(super) fn set_directory_mode(&self, mode: u32) {
+        *self.directory_mode.borrow_mut() = mode;
+    }
+
+    pub(super) fn set_trigger_limit(&self, interval: u64, burst: u32) {
+        *self.trigger_limit.borrow_mut() = RateLimit::new(interval, burst);
+    }
+
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), PathState::Waiting, PathState::Running) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+
+        if self.unit().is_empty() {
+            return Err(UnitActionError::UnitActionENoent);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        self.mkdir();
+
+        *self.result.borrow_mut() = PathResult::Success;
+        self.enter_waiting(true, false);
+    }
+
+    pub(super) fn stop_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), PathState::Dead, PathState::Failed) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        self.enter_dead(PathResult::Success);
+    }
+
+    pub(super) fn current_active_state(&self) -> UnitActiveState {
+        self.state().to_unit_active_state()
+    }
+
+    // the triggered unit changes its state
+    pub(super) fn trigger_notify(&self, new_state: UnitActiveState) {
+        let inactive = IN_SET!(
+            new_state,
+            UnitActiveState::UnitInActive,
+            UnitActiveState::UnitFailed
+        );
+
+        match self.state() {
+            // the triggered unit is finished, watch the paths again
+            PathState::Running if inactive => {
+                log::debug!(
+                    "unit triggered by {} is finished, watch again",
+                    self.comm.unit().get_id()
+                );
+                self.enter_waiting(false, true);
+            }
+            // the triggered unit is started by others, so are we
+            PathState::Waiting if !inactive => {
+                self.unwatch();
+                self.set_state(PathState::Running);
+            }
+            _ => {}
+        }
+    }
+
+    // create the watched directories when MakeDirectory= is set
+    fn mkdir(&self) {
+        if !*self.make_directory.borrow() {
+            return;
+        }
+
+        let mut builder = DirBuilder::new();
+        builder.recursive(true).mode(*self.directory_mode.borrow());
+        for spec in self.specs.borrow().iter() {
+            if !spec.p_type.make_directory() {
+                continue;
+            }
+
+            if let Err(e) = builder.create(&spec.path) {
+                log::warn!("failed to create directory {}: {}", spec.path, e);
+            }
+        }
+    }
+
+    // whether any of the paths meets its condition
+    fn check_good(&self, initial: bool, from_trigger_notify: bool) -> bool {
+        self.specs
+            .borrow()
+            .iter()
+            .any(|spec| spec.check_good(initial, from_trigger_notify))
+    }
+
+    fn enter_waiting(&self, initial: bool, from_trigger_notify: bool) {
+        let id = self.comm.unit().get_id().to_string();
+
+        // the triggered unit is running already, so are we
+        if self.comm.um().relation_active_or_pending(&id) {
+            self.unwatch();
+            self.set_state(PathState::Running);
+            return;
+        }
+
+        if self.check_good(initial, from_trigger_notify) {
+            self.enter_running();
+            return;
+        }
+
+        if let Err(e) = self.watch() {
+            log::error!("failed to watch the paths of {}: {}", id, e);
+            self.enter_dead(PathResult::FailureResources);
+            return;
+        }
+
+        // the paths may be changed before watched, check them again
+        if self.check_good(false, from_trigger_notify) {
+            self.enter_running();
+            return;
+        }
+
+        self.set_state(PathState::Waiting);
+    }
+
+    // the condition is met, start the triggered unit
+    fn enter_running(&self) {
+        let id = self.comm.unit().get_id().to_string();
+        if self.comm.um().has_stop_job(&id) {
+            return;
+        }
+
+        if !self.trigger_limit.borrow_mut().below(path_base::now_usec()) {
+            log::error!("path {} hits the trigger limit, refuse to trigger", id);
+            self.enter_dead(PathResult::FailureTriggerLimitHit);
+            return;
+        }
+
+        let unit = self.unit();
+        log::info!("path {} is triggered, start {}", id, unit);
+        if let Err(e) = self.comm.um().start_unit(&unit) {
+            log::error!("failed to start unit {} triggered by {}: {:?}", unit, id, e);
+            self.enter_dead(PathResult::FailureResources);
+            return;
+        }
+
+        self.unwatch();
+        self.set_state(PathState::Running);
+    }
+
+    fn enter_dead(&self, result: PathResult) {
+        if *self.result.borrow() == PathResult::Success {
+            *self.result.borrow_mut() = result;
+        }
+
+        self.unwatch();
+        if *self.result.borrow() == PathResult::Success {
+            self.set_state(PathState::Dead);
+        } else {
+            self.set_state(PathState::Failed);
+        }
+    }
+
+    fn watch(&self) -> Result<(), Error> {
+        for spec in self.specs.borrow().iter() {
+            // the inotify fd is recreated, so stop listening on the old one before closing it.
+            self.disable(spec);
+            spec.watch()?;
+
+            if !spec.registered() {
+                self.comm.um().register(spec.clone());
+                spec.set_registered();
+            }
+            self.comm.um().enable(spec.clone(), EventState::On);
+        }
+
+        Ok(())
+    }
+
+    fn unwatch(&self) {
+        for spec in self.specs.borrow().iter() {
+            self.disable(spec);
+            spec.unwatch();
+        }
+    }
+
+    fn disable(&self, spec: &Rc<PathSpec>) {
+        if spec.watched() {
+            self.comm.um().enable(spec.clone(), EventState::Off);
+        }
+    }
+
+    // one of the watched paths is changed
+    fn dispatch_io(&self, changed: bool) {
+        if !IN_SET!(self.state(), PathState::Waiting, PathState::Running) {
+            return;
+        }
+
+        if changed {
+            self.enter_running();
+        } else {
+            self.enter_waiting(false, false);
+        }
+    }
+
+    fn set_state(&self, state: PathState) {
+        let original_state = self.state.replace(state);
+        if original_state != state {
+            log::debug!(
+                "path {} original state[{:?}] -> new state[{:?}]",
+                self.comm.unit().get_id(),
+                original_state,
+                state
+            );
+        }
+
+        self.comm.unit().notify(
+            original_state.to_unit_active_state(),
+            state.to_unit_active_state(),
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+    }
+
+    fn state(&self) -> PathState {
+        *self.state.borrow()
+    }
+}
+
+/// one watched path of the path unit, which is an event source of its own inotify fd
+pub(super) struct PathSpec {
+    path: String,
+    p_type: PathType,
+    inotify: RefCell<RawFd>,
+    primary_wd: RefCell<Option<WatchDescriptor>>,
+    previous_exists: RefCell<bool>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<PathMng>>,
+}
+
+impl fmt::Display for PathSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "path: {}, path type: {:?}, inotify fd: {}",
+            self.path,
+            self.p_type,
+            *self.inotify.borrow()
+        )
+    }
+}
+
+impl PathSpec {
+    pub(super) fn new(path: String, p_type: PathType) -> Self {
+        let previous_exists = Path::new(&path).exists();
+        PathSpec {
+            path,
+            p_type,
+            inotify: RefCell::new(-1),
+            primary_wd: RefCell::new(None),
+            previous_exists: RefCell::new(previous_exists),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    pub(super) fn attach(&self, mng: Weak<PathMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn check_good(&self, initial: bool, from_trigger_notify: bool) -> bool {
+        match self.p_type {
+            PathType::Changed | PathType::Modified => {
+                // the path is created or removed when it is not watched
+                let exists = Path::new(&self.path).exists();
+                let previous = self.previous_exists.replace(exists);
+                !initial && !from_trigger_notify && exists != previous
+            }
+            _ => self.p_type.check(&self.path),
+        }
+    }
+
+    // watch the path, or the nearest existing ancestor if the path doesn't exist yet
+    fn watch(&self) -> Result<(), Error> {
+        self.unwatch();
+
+        let inotify =
+            Inotify::init(InitFlags::IN_CLOEXEC | InitFlags::IN_NONBLOCK).map_err(|_e| {
+                Error::Other {
+                    msg: "create inotify fd err",
+                }
+            })?;
+        *self.inotify.borrow_mut() = inotify.as_raw_fd();
+
+        let mut primary = true;
+        for ancestor in Path::new(&self.path).ancestors() {
+            let flags = if primary {
+                self.p_type.flags()
+            } else {
+                PathType::parent_flags()
+            };
+
+            match inotify.add_watch(ancestor, flags) {
+                Ok(wd) => {
+                    if primary {
+                        *self.primary_wd.borrow_mut() = Some(wd);
+                    }
+                    log::debug!("watch {:?} for {}", ancestor, self);
+                    return Ok(());
+                }
+                Err(Errno::ENOENT) | Err(Errno::ENOTDIR) => {}
+                Err(err) => {
+                    log::error!("watch on path {:?} error: {:?}", ancestor, err);
+                }
+            }
+
+            primary = false;
+        }
+
+        Err(Error::Other {
+            msg: "watch on any of the ancestors failed",
+        })
+    }
+
+    fn unwatch(&self) {
+        fd_util::close(*self.inotify.borrow());
+        *self.inotify.borrow_mut() = -1;
+        *self.primary_wd.borrow_mut() = None;
+    }
+
+    fn watched(&self) -> bool {
+        *self.inotify.borrow() >= 0
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    // read the inotify events, return whether the change of the path is reported
+    fn read_fd_event(&self) -> Result<bool, Error> {
+        let inotify = unsafe { Inotify::from_raw_fd(*self.inotify.borrow()) };
+        let events = match inotify.read_events() {
+            Ok(events) => events,
+            Err(Errno::EAGAIN) | Err(Errno::EINTR) => return Ok(false),
+            Err(_) => {
+                return Err(Error::Other {
+                    msg: "read events from inotify error",
+                })
+            }
+        };
+
+        if IN_SET!(self.p_type, PathType::Changed, PathType::Modified) {
+            if let Some(ref wd) = *self.primary_wd.borrow() {
+                return Ok(events.iter().any(|event| event.wd == *wd));
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn mng(&self) -> Option<Rc<PathMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+impl Source for PathSpec {
+    fn fd(&self) -> RawFd {
+        *self.inotify.borrow()
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        log::debug!("dispatch inotify of {}", self);
+        let changed = match self.read_fd_event() {
+            Ok(changed) => changed,
+            Err(e) => {
+                log::error!("in inotify dispatch, read event error: {}", e);
+                false
+            }
+        };
+
+        if let Some(mng) = self.mng() {
+            mng.dispatch_io(changed);
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
@@ -0,0 +1,101 @@
+//! PathUnit是path类型的总入口，需要实现UnitObj,UnitMngUtil,以及UnitSubClass三个trait,
+//! UnitObj是Unit的抽象，定义对process1提供的具体行为，
+//! UnitMngUtil是为了关联subUnit和Manger，由于rust不支持继承和多态，因此需要采用这种方式来间接支持
+//! UnitSubClass为了实现SubUnit到UnitObj的转换，简介达成多态的目的
+
+use process1::manager::{
+    Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj, UnitSubClass,
+};
+use std::{error::Error, path::PathBuf, rc::Rc};
+
+use crate::{path_comm::PathComm, path_config::PathConfig, path_load::PathLoad, path_mng::PathMng};
+use utils::logger;
+
+// the structure of the path unit type
+struct PathUnit {
+    comm: Rc<PathComm>,
+    config: Rc<PathConfig>,
+    mng: Rc<PathMng>,
+    load: PathLoad,
+}
+
+impl UnitObj for PathUnit {
+    fn load(&self, paths: &Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        log::debug!("path begin to load conf file");
+        self.config.load(paths)?;
+
+        self.load.parse()?;
+
+        self.load.path_add_extras()?;
+
+        self.load.path_verify()
+    }
+
+    // the function entrance to start the unit
+    fn start(&self) -> Result<(), UnitActionError> {
+        self.mng.start_check()?;
+
+        self.mng.start_action();
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), UnitActionError> {
+        self.mng.stop_check()?;
+
+        self.mng.stop_action();
+        Ok(())
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.current_active_state()
+    }
+
+    fn attach_unit(&self, unit: Rc<Unit>) {
+        self.comm.attach_unit(unit);
+    }
+
+    fn trigger(&self, other: Rc<Unit>) {
+        self.mng.trigger_notify(other.current_active_state());
+    }
+}
+
+// attach the UnitManager for weak reference
+impl UnitMngUtil for PathUnit {
+    fn attach(&self, um: Rc<UnitManager>) {
+        self.comm.attach_um(um);
+    }
+}
+
+impl UnitSubClass for PathUnit {
+    fn into_unitobj(self: Box<Self>) -> Box<dyn UnitObj> {
+        Box::new(*self)
+    }
+}
+
+impl PathUnit {
+    fn new() -> PathUnit {
+        let comm = Rc::new(PathComm::new());
+        let config = Rc::new(PathConfig::new());
+        let mng = Rc::new(PathMng::new(&comm));
+        PathUnit {
+            comm: Rc::clone(&comm),
+            config: Rc::clone(&config),
+            mng: Rc::clone(&mng),
+            load: PathLoad::new(&config, &comm, &mng),
+        }
+    }
+}
+
+impl Default for PathUnit {
+    fn default() -> Self {
+        PathUnit::new()
+    }
+}
+
+const LOG_LEVEL: u32 = 4;
+const PLUGIN_NAME: &str = "PathUnit";
+
+use process1::declure_unitobj_plugin;
+
+// define the method to create the instance of the unit
+declure_unitobj_plugin!(PathUnit, PathUnit::default, PLUGIN_NAME, LOG_LEVEL);
@@ -0,0 +1,59 @@
+//! scope是process1管理外部创建的进程的类型，scope不负责创建进程，而是在启动时将已经存在的进程移入scope的cgroup中，
+//! 由process1对这些进程进行资源限制和生命周期的管理。
+//! scope不能通过配置文件定义，只能在运行时通过控制协议创建，创建时指定scope的名称、进程的PID列表以及属性，
+//! process1根据这些内容在/run/process1/transient下生成临时的配置文件，加载后立即启动，scope结束后删除该配置文件。
+//!
+//! # Example:
+//! ```shell
+//! pctrl scope session-1.scope --pid 1000 --pid 1001 -p RuntimeMaxSec=1h -p MemoryMax=1073741824
+//! ```
+
+//! [Scope] section相关的配置
+//!
+//! PIDs
+//!
+//! 移入scope的进程列表，由控制协议的PID列表生成，不能为空，进程只在启动时移入一次，scope结束后不能再次启动。
+//!
+//! Slice
+//!
+//! scope所在的slice，默认为system.slice。
+//!
+//! RuntimeMaxSec
+//!
+//! scope运行的最长时间，超时后scope被停止并进入failed状态，默认为infinity。
+//!
+//! TimeoutStopSec
+//!
+//! 停止时等待进程退出的时间，超时后发送SIGKILL，默认为90s。
+//!
+//! KillSignal、SendSIGKILL
+//!
+//! 停止时发送的信号，默认为SIGTERM，以及超时后是否发送SIGKILL，默认为true。
+//!
+//! CPUWeight、CPUQuota、MemoryMin、MemoryLow、MemoryHigh、MemoryMax、MemorySwapMax、TasksMax
+//!
+//! 资源限制，含义与slice的同名配置相同。
+//!
+//! ## 生命周期
+//!
+//! scope中的进程全部退出后，scope进入inactive状态，process1通过监听cgroup的cgroup.events感知cgroup的释放，
+//! 并使用cg_is_empty_recursive确认cgroup为空。scope进入inactive或failed状态后，其临时配置文件被删除。
+//!
+//! ## 自动依赖
+//!
+//! ### 隐含依赖
+//! scope对其所在的slice添加Requires=和After=依赖。
+
+// dependency:
+// scope_comm -> scope_config
+// {scope_mng | scope_load} -> scope_unit
+
+mod scope_comm;
+
+mod scope_config;
+
+mod scope_mng;
+
+mod scope_load;
+
+mod scope_unit;
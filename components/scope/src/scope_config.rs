@@ -0,0 +1,86 @@
+//! scope_config模块scope类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+
+pub(super) struct ScopeConfig {
+    data: Rc<RefCell<ScopeConfigData>>,
+}
+
+impl ScopeConfig {
+    pub(super) fn new() -> Self {
+        ScopeConfig {
+            data: Rc::new(RefCell::new(ScopeConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = ScopeConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<ScopeConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct ScopeConfigData {
+    #[config(nested)]
+    pub Scope: SectionScope,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionScope {
+    pub PIDs: Option<Vec<i32>>,
+    pub Slice: Option<String>,
+    pub RuntimeMaxSec: Option<String>,
+    pub TimeoutStopSec: Option<String>,
+    pub KillSignal: Option<String>,
+    pub SendSIGKILL: Option<bool>,
+    pub CPUWeight: Option<u64>,
+    pub CPUQuota: Option<String>,
+    pub MemoryMin: Option<u64>,
+    pub MemoryLow: Option<u64>,
+    pub MemoryHigh: Option<u64>,
+    pub MemoryMax: Option<u64>,
+    pub MemorySwapMax: Option<u64>,
+    pub TasksMax: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scope_config::ScopeConfig;
+    use utils::test_util::get_project_root;
+
+    #[test]
+    fn test_scope_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.scope.toml");
+        let paths = vec![file_path];
+
+        let config = ScopeConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let scope = &data.borrow().Scope;
+        assert_eq!(scope.PIDs, Some(vec![1000, 1001]));
+        assert_eq!(scope.Slice, Some("machine.slice".to_string()));
+        assert_eq!(scope.RuntimeMaxSec, Some("1h".to_string()));
+        assert_eq!(scope.KillSignal, Some("SIGINT".to_string()));
+        assert_eq!(scope.MemoryMax, Some(1073741824));
+        assert_eq!(scope.TasksMax, Some(64));
+        assert_eq!(scope.CPUWeight, None);
+    }
+}
@@ -0,0 +1,134 @@
+//! scope_load模块实现scope配置文件的解析，以及对所在slice的依赖。
+//!
+
+use std::{error::Error, rc::Rc};
+
+use nix::sys::signal::Signal;
+use nix::unistd::Pid;
+use process1::manager::KillContext;
+use utils::{process_util, time_util};
+
+use crate::scope_comm::ScopeComm;
+use crate::scope_config::ScopeConfig;
+use crate::scope_mng::ScopeMng;
+
+pub(super) struct ScopeLoad {
+    config: Rc<ScopeConfig>,
+    comm: Rc<ScopeComm>,
+    mng: Rc<ScopeMng>,
+    kill_ctx: Rc<KillContext>,
+}
+
+impl ScopeLoad {
+    pub(super) fn new(
+        configr: &Rc<ScopeConfig>,
+        commr: &Rc<ScopeComm>,
+        mngr: &Rc<ScopeMng>,
+        kill_ctx: &Rc<KillContext>,
+    ) -> Self {
+        ScopeLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+            kill_ctx: kill_ctx.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse scope section");
+        let data = self.config.config_data();
+        let scope = &data.borrow().Scope;
+
+        let mut pids = Vec::new();
+        for pid in scope.PIDs.iter().flatten() {
+            if *pid <= 0 {
+                return Err(format!("invalid PID {}", pid).into());
+            }
+            pids.push(Pid::from_raw(*pid));
+        }
+        self.mng.set_pids(pids);
+
+        let mut resources = Vec::new();
+        if let Some(v) = scope.CPUWeight {
+            if !(1..=10000).contains(&v) {
+                return Err(format!("invalid CPUWeight {}", v).into());
+            }
+            resources.push(("cpu.weight", v.to_string()));
+        }
+
+        if let Some(v) = &scope.CPUQuota {
+            let max = cgroup::cg_cpu_quota(v).ok_or_else(|| format!("invalid CPUQuota {}", v))?;
+            resources.push(("cpu.max", max));
+        }
+
+        for (attr, value) in [
+            ("memory.min", scope.MemoryMin),
+            ("memory.low", scope.MemoryLow),
+            ("memory.high", scope.MemoryHigh),
+            ("memory.max", scope.MemoryMax),
+            ("memory.swap.max", scope.MemorySwapMax),
+        ] {
+            if let Some(v) = value {
+                resources.push((attr, v.to_string()));
+            }
+        }
+
+        if let Some(v) = scope.TasksMax {
+            resources.push(("pids.max", v.to_string()));
+        }
+        self.mng.set_resources(resources);
+
+        if let Some(v) = &scope.RuntimeMaxSec {
+            self.mng.set_runtime_max_usec(parse_timespan_infinity(v)?);
+        }
+
+        if let Some(v) = &scope.TimeoutStopSec {
+            self.mng.set_timeout_stop_usec(parse_timespan_infinity(v)?);
+        }
+
+        let signal = |s: &String| -> Result<Signal, Box<dyn Error>> {
+            process_util::parse_signal(s).ok_or_else(|| format!("invalid signal: {}", s).into())
+        };
+        if let Some(sig) = &scope.KillSignal {
+            self.kill_ctx.set_kill_signal(signal(sig)?);
+        }
+
+        if let Some(send) = scope.SendSIGKILL {
+            self.kill_ctx.set_send_sigkill(send);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn scope_add_extras(&self) -> Result<(), Box<dyn Error>> {
+        let unit = self.comm.unit();
+        let slice = match &self.config.config_data().borrow().Scope.Slice {
+            Some(s) => s.to_string(),
+            None => unit.default_slice(),
+        };
+
+        if !self.comm.um().load_unit_success(&slice) {
+            return Err(format!("failed to load slice {}", slice).into());
+        }
+        unit.set_slice(&slice)
+    }
+
+    pub(super) fn scope_verify(&self) -> Result<(), Box<dyn Error>> {
+        if !self.mng.has_pids() {
+            return Err(format!(
+                "scope {} has no process configured by PIDs",
+                self.comm.unit().get_id()
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_timespan_infinity(s: &str) -> Result<u64, String> {
+    if s.trim() == "infinity" {
+        return Ok(u64::MAX);
+    }
+    time_util::parse_timespan(s)
+}
@@ -0,0 +1,572 @@
+//! scope_mng模块是scope类型的核心逻辑，维护scope的状态机，启动时将外部创建的进程移入scope的cgroup，
+//! 通过cgroup.events的变化感知cgroup为空，cgroup为空或运行超过RuntimeMaxSec时scope结束。
+//!
+
+use std::{
+    cell::RefCell,
+    os::unix::io::{AsRawFd, FromRawFd, RawFd},
+    rc::{Rc, Weak},
+};
+
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::libc;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use nix::time::{clock_gettime, ClockId};
+use nix::unistd::Pid;
+use process1::manager::{
+    KillContext, KillOperation, UnitActionError, UnitActiveState, UnitNotifyFlags,
+};
+use utils::{fd_util, Error, IN_SET};
+
+use crate::scope_comm::ScopeComm;
+
+// the default of TimeoutStopSec=, in useconds
+pub(super) const DEFAULT_TIMEOUT_STOP_USEC: u64 = 90_000_000;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum ScopeState {
+    Dead,
+    Running,
+    StopSigterm,
+    StopSigkill,
+    Failed,
+}
+
+impl ScopeState {
+    fn to_unit_active_state(self) -> UnitActiveState {
+        match self {
+            ScopeState::Dead => UnitActiveState::UnitInActive,
+            ScopeState::Running => UnitActiveState::UnitActive,
+            ScopeState::StopSigterm | ScopeState::StopSigkill => UnitActiveState::UnitDeActivating,
+            ScopeState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum ScopeResult {
+    Success,
+    FailureResources,
+    FailureTimeout,
+}
+
+pub(super) struct ScopeMng {
+    comm: Rc<ScopeComm>,
+    kill_ctx: Rc<KillContext>,
+    state: RefCell<ScopeState>,
+    result: RefCell<ScopeResult>,
+    // the processes moved into the scope on starting
+    pids: RefCell<Vec<Pid>>,
+    // the resource settings, each one is the attribute of the cgroup and its value
+    resources: RefCell<Vec<(&'static str, String)>>,
+    // u64::MAX means the scope runs without limit
+    runtime_max_usec: RefCell<u64>,
+    timeout_stop_usec: RefCell<u64>,
+    timer: Rc<ScopeTimer>,
+    watch: Rc<CgroupWatch>,
+}
+
+impl ScopeMng {
+    pub(super) fn new(commr: &Rc<ScopeComm>, kill_ctx: &Rc<KillContext>) -> Rc<ScopeMng> {
+        let mng = Rc::new(ScopeMng {
+            comm: Rc::clone(commr),
+            kill_ctx: Rc::clone(kill_ctx),
+            state: RefCell::new(ScopeState::Dead),
+            result: RefCell::new(ScopeResult::Success),
+            pids: RefCell::new(Vec::new()),
+            resources: RefCell::new(Vec::new()),
+            runtime_max_usec: RefCell::new(u64::MAX),
+            timeout_stop_usec: RefCell::new(DEFAULT_TIMEOUT_STOP_USEC),
+            timer: Rc::new(ScopeTimer::new()),
+            watch: Rc::new(CgroupWatch::new()),
+        });
+
+        mng.timer.attach(Rc::downgrade(&mng));
+        mng.watch.attach(Rc::downgrade(&mng));
+        mng
+    }
+
+    pub(super) fn set_pids(&self, pids: Vec<Pid>) {
+        *self.pids.borrow_mut() = pids;
+    }
+
+    pub(super) fn has_pids(&self) -> bool {
+        !self.pids.borrow().is_empty()
+    }
+
+    pub(super) fn set_resources(&self, resources: Vec<(&'static str, String)>) {
+        *self.resources.borrow_mut() = resources;
+    }
+
+    pub(super) fn set_runtime_max_usec(&self, usec: u64) {
+        *self.runtime_max_usec.borrow_mut() = usec;
+    }
+
+    pub(super) fn set_timeout_stop_usec(&self, usec: u64) {
+        *self.timeout_stop_usec.borrow_mut() = usec;
+    }
+
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(
+            self.state(),
+            ScopeState::Running,
+            ScopeState::StopSigterm,
+            ScopeState::StopSigkill
+        ) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+
+        // the processes are moved only once, the scope can not be started again
+        if !self.has_pids() {
+            log::error!(
+                "scope {} has no process to start with",
+                self.comm.unit().get_id()
+            );
+            return Err(UnitActionError::UnitActionEOpNotSupp);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        self.set_result(ScopeResult::Success);
+
+        let unit = self.comm.unit();
+        if let Err(e) = unit.cg_realize() {
+            log::error!("failed to create the cgroup of {}: {}", unit.get_id(), e);
+            return self.enter_dead(ScopeResult::FailureResources);
+        }
+
+        let cg_path = unit.cg_path();
+        let resources = self.resources.borrow().clone();
+        if !resources.is_empty() {
+            if let Err(e) = cgroup::cg_set_resources(&cg_path, &resources) {
+                log::warn!("failed to set the resources of {}: {}", unit.get_id(), e);
+            }
+        }
+
+        let pids = self.pids.replace(Vec::new());
+        let mut attached = 0;
+        for pid in pids {
+            match cgroup::cg_attach(pid, &cg_path) {
+                Ok(_) => attached += 1,
+                Err(e) => log::warn!("failed to move {} into {}: {}", pid, unit.get_id(), e),
+            }
+        }
+        if attached == 0 {
+            log::error!("none of the processes is moved into {}", unit.get_id());
+            return self.enter_dead(ScopeResult::FailureResources);
+        }
+
+        self.watch_cgroup();
+        self.set_state(ScopeState::Running);
+
+        // the processes may have exited before being watched
+        if self.cgroup_empty() {
+            return self.enter_dead(ScopeResult::Success);
+        }
+
+        let runtime_max = *self.runtime_max_usec.borrow();
+        if runtime_max != u64::MAX {
+            self.enable_timer(runtime_max);
+        }
+    }
+
+    pub(super) fn stop_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), ScopeState::Dead, ScopeState::Failed) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        if IN_SET!(
+            self.state(),
+            ScopeState::StopSigterm,
+            ScopeState::StopSigkill
+        ) {
+            return;
+        }
+
+        self.enter_signal(ScopeState::StopSigterm, ScopeResult::Success);
+    }
+
+    pub(super) fn current_active_state(&self) -> UnitActiveState {
+        self.state().to_unit_active_state()
+    }
+
+    fn enter_signal(&self, state: ScopeState, res: ScopeResult) {
+        if self.result() == ScopeResult::Success {
+            self.set_result(res);
+        }
+
+        let op = if state == ScopeState::StopSigterm {
+            KillOperation::KillTerminate
+        } else {
+            KillOperation::KillKill
+        };
+        let unit = self.comm.unit();
+        if let Err(e) = unit.kill_context(&self.kill_ctx, None, None, op) {
+            log::warn!("failed to kill the processes of {}: {}", unit.get_id(), e);
+        }
+
+        if self.cgroup_empty() {
+            return self.enter_dead(ScopeResult::Success);
+        }
+
+        // wait for the cgroup becoming empty, or escalate when timeout.
+        self.set_state(state);
+        self.enable_timer(*self.timeout_stop_usec.borrow());
+    }
+
+    fn enter_dead(&self, res: ScopeResult) {
+        if self.result() == ScopeResult::Success {
+            self.set_result(res);
+        }
+
+        self.disable_timer();
+        self.unwatch_cgroup();
+
+        if self.result() == ScopeResult::Success {
+            self.set_state(ScopeState::Dead);
+        } else {
+            self.set_state(ScopeState::Failed);
+        }
+    }
+
+    fn dispatch_timer(&self) {
+        let id = self.comm.unit().get_id().to_string();
+        match self.state() {
+            ScopeState::Running => {
+                log::warn!("scope {} reached the runtime limit, stopping", id);
+                self.enter_signal(ScopeState::StopSigterm, ScopeResult::FailureTimeout);
+            }
+            ScopeState::StopSigterm => {
+                if self.kill_ctx.send_sigkill() {
+                    log::warn!("scope {} is still around after terminating, killing", id);
+                    self.enter_signal(ScopeState::StopSigkill, ScopeResult::FailureTimeout);
+                } else {
+                    log::warn!("scope {} is still around, skipping SIGKILL", id);
+                    self.enter_dead(ScopeResult::FailureTimeout);
+                }
+            }
+            ScopeState::StopSigkill => {
+                log::warn!("scope {} is still around after SIGKILL, ignoring", id);
+                self.enter_dead(ScopeResult::FailureTimeout);
+            }
+            _ => {}
+        }
+    }
+
+    // the cgroup.events of the scope is modified, it may become empty
+    fn dispatch_cgroup(&self) {
+        if !IN_SET!(
+            self.state(),
+            ScopeState::Running,
+            ScopeState::StopSigterm,
+            ScopeState::StopSigkill
+        ) {
+            return;
+        }
+
+        if self.cgroup_empty() {
+            log::debug!("scope {} becomes empty", self.comm.unit().get_id());
+            self.enter_dead(ScopeResult::Success);
+        }
+    }
+
+    fn cgroup_empty(&self) -> bool {
+        let unit = self.comm.unit();
+        match cgroup::cg_is_empty_recursive(&unit.cg_path()) {
+            Ok(empty) => empty,
+            Err(e) => {
+                log::debug!("failed to check the cgroup of {}: {}", unit.get_id(), e);
+                false
+            }
+        }
+    }
+
+    fn watch_cgroup(&self) {
+        let unit = self.comm.unit();
+        let path = match cgroup::cg_events_path(&unit.cg_path()) {
+            Ok(p) => p,
+            Err(e) => {
+                log::debug!("the release of {} is not notified: {}", unit.get_id(), e);
+                return;
+            }
+        };
+
+        self.disable_watch();
+        if let Err(e) = self.watch.watch(&path) {
+            log::warn!("failed to watch {:?}: {}", path, e);
+            return;
+        }
+
+        if !self.watch.registered() {
+            self.comm.um().register(self.watch.clone());
+            self.watch.set_registered();
+        }
+        self.comm.um().enable(self.watch.clone(), EventState::On);
+    }
+
+    fn unwatch_cgroup(&self) {
+        self.disable_watch();
+        self.watch.unwatch();
+    }
+
+    fn disable_watch(&self) {
+        if self.watch.watched() {
+            self.comm.um().enable(self.watch.clone(), EventState::Off);
+        }
+    }
+
+    fn enable_timer(&self, usec: u64) {
+        if !self.timer.registered() {
+            self.comm.um().register(self.timer.clone());
+            self.timer.set_registered();
+        }
+
+        log::debug!("enable scope timer after {} usec", usec);
+        self.timer.arm(usec);
+        self.comm.um().enable(self.timer.clone(), EventState::On);
+    }
+
+    fn disable_timer(&self) {
+        if self.timer.armed() {
+            self.timer.disarm();
+            self.comm.um().enable(self.timer.clone(), EventState::Off);
+        }
+    }
+
+    fn set_state(&self, state: ScopeState) {
+        let original_state = self.state.replace(state);
+        if original_state != state {
+            log::debug!(
+                "scope {} original state[{:?}] -> new state[{:?}]",
+                self.comm.unit().get_id(),
+                original_state,
+                state
+            );
+        }
+
+        self.comm.unit().notify(
+            original_state.to_unit_active_state(),
+            state.to_unit_active_state(),
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+    }
+
+    fn state(&self) -> ScopeState {
+        *self.state.borrow()
+    }
+
+    fn set_result(&self, res: ScopeResult) {
+        *self.result.borrow_mut() = res;
+    }
+
+    fn result(&self) -> ScopeResult {
+        *self.result.borrow()
+    }
+}
+
+/// the timer of the scope, for RuntimeMaxSec= and the timeout of stopping.
+struct ScopeTimer {
+    // the absolute time in monotonic clock, u64::MAX means the timer is disarmed.
+    deadline: RefCell<u64>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<ScopeMng>>,
+}
+
+impl ScopeTimer {
+    fn new() -> Self {
+        ScopeTimer {
+            deadline: RefCell::new(u64::MAX),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<ScopeMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn arm(&self, usec: u64) {
+        *self.deadline.borrow_mut() = monotonic_usec().saturating_add(usec);
+    }
+
+    fn disarm(&self) {
+        *self.deadline.borrow_mut() = u64::MAX;
+    }
+
+    fn armed(&self) -> bool {
+        *self.deadline.borrow() != u64::MAX
+    }
+
+    fn expired(&self) -> bool {
+        self.armed() && monotonic_usec() >= *self.deadline.borrow()
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    fn mng(&self) -> Option<Rc<ScopeMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+fn monotonic_usec() -> u64 {
+    match clock_gettime(ClockId::CLOCK_MONOTONIC) {
+        Ok(ts) => ts.tv_sec() as u64 * 1_000_000 + ts.tv_nsec() as u64 / 1_000,
+        Err(_) => 0,
+    }
+}
+
+impl Source for ScopeTimer {
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn time(&self) -> u64 {
+        *self.deadline.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        // the timer queued before re-arming is expired earlier, ignore it.
+        if !self.expired() {
+            return Ok(0);
+        }
+
+        log::debug!("dispatch scope timer");
+        self.disarm();
+        if let Some(mng) = self.mng() {
+            mng.dispatch_timer();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+/// the watch on cgroup.events of the scope, which is an event source of its own inotify fd,
+/// the file is modified when the cgroup is released.
+struct CgroupWatch {
+    inotify: RefCell<RawFd>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<ScopeMng>>,
+}
+
+impl CgroupWatch {
+    fn new() -> Self {
+        CgroupWatch {
+            inotify: RefCell::new(-1),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<ScopeMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn watch(&self, path: &std::path::Path) -> Result<(), Error> {
+        self.unwatch();
+
+        let inotify =
+            Inotify::init(InitFlags::IN_CLOEXEC | InitFlags::IN_NONBLOCK).map_err(|_e| {
+                Error::Other {
+                    msg: "create inotify fd err",
+                }
+            })?;
+        *self.inotify.borrow_mut() = inotify.as_raw_fd();
+
+        inotify
+            .add_watch(path, AddWatchFlags::IN_MODIFY)
+            .map_err(|_e| Error::Other {
+                msg: "watch on cgroup.events err",
+            })?;
+        Ok(())
+    }
+
+    fn unwatch(&self) {
+        fd_util::close(*self.inotify.borrow());
+        *self.inotify.borrow_mut() = -1;
+    }
+
+    fn watched(&self) -> bool {
+        *self.inotify.borrow() >= 0
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    // drain the events, only the modification is cared about
+    fn read_fd_event(&self) -> Result<(), Error> {
+        let inotify = unsafe { Inotify::from_raw_fd(*self.inotify.borrow()) };
+        match inotify.read_events() {
+            Ok(_) | Err(Errno::EAGAIN) | Err(Errno::EINTR) => Ok(()),
+            Err(_) => Err(Error::Other {
+                msg: "read events from inotify error",
+            }),
+        }
+    }
+
+    fn mng(&self) -> Option<Rc<ScopeMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+impl Source for CgroupWatch {
+    fn fd(&self) -> RawFd {
+        *self.inotify.borrow()
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        log::debug!("dispatch cgroup events of the scope");
+        if let Err(e) = self.read_fd_event() {
+            log::error!("in inotify dispatch, read event error: {}", e);
+        }
+
+        if let Some(mng) = self.mng() {
+            mng.dispatch_cgroup();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
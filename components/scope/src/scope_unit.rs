@@ -0,0 +1,101 @@
+//! ScopeUnit是scope类型的总入口，需要实现UnitObj,UnitMngUtil,以及UnitSubClass三个trait,
+//! UnitObj是Unit的抽象，定义对process1提供的具体行为，
+//! UnitMngUtil是为了关联subUnit和Manger，由于rust不支持继承和多态，因此需要采用这种方式来间接支持
+//! UnitSubClass为了实现SubUnit到UnitObj的转换，简介达成多态的目的
+
+use process1::manager::{
+    KillContext, Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj,
+    UnitSubClass,
+};
+use std::{error::Error, path::PathBuf, rc::Rc};
+
+use crate::{
+    scope_comm::ScopeComm, scope_config::ScopeConfig, scope_load::ScopeLoad, scope_mng::ScopeMng,
+};
+use utils::logger;
+
+// the structure of the scope unit type
+struct ScopeUnit {
+    comm: Rc<ScopeComm>,
+    config: Rc<ScopeConfig>,
+    mng: Rc<ScopeMng>,
+    load: ScopeLoad,
+}
+
+impl UnitObj for ScopeUnit {
+    fn load(&self, paths: &Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        log::debug!("scope begin to load conf file");
+        self.config.load(paths)?;
+
+        self.load.parse()?;
+
+        self.load.scope_add_extras()?;
+
+        self.load.scope_verify()
+    }
+
+    // the function entrance to start the unit
+    fn start(&self) -> Result<(), UnitActionError> {
+        self.mng.start_check()?;
+
+        self.mng.start_action();
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), UnitActionError> {
+        self.mng.stop_check()?;
+
+        self.mng.stop_action();
+        Ok(())
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.current_active_state()
+    }
+
+    fn attach_unit(&self, unit: Rc<Unit>) {
+        self.comm.attach_unit(unit);
+    }
+}
+
+// attach the UnitManager for weak reference
+impl UnitMngUtil for ScopeUnit {
+    fn attach(&self, um: Rc<UnitManager>) {
+        self.comm.attach_um(um);
+    }
+}
+
+impl UnitSubClass for ScopeUnit {
+    fn into_unitobj(self: Box<Self>) -> Box<dyn UnitObj> {
+        Box::new(*self)
+    }
+}
+
+impl ScopeUnit {
+    fn new() -> ScopeUnit {
+        let kill_ctx = Rc::new(KillContext::new());
+        let comm = Rc::new(ScopeComm::new());
+        let config = Rc::new(ScopeConfig::new());
+        let mng = ScopeMng::new(&comm, &kill_ctx);
+        ScopeUnit {
+            comm: Rc::clone(&comm),
+            config: Rc::clone(&config),
+            mng: Rc::clone(&mng),
+            load: ScopeLoad::new(&config, &comm, &mng, &kill_ctx),
+        }
+    }
+}
+
+impl Default for ScopeUnit {
+    fn default() -> Self {
+        ScopeUnit::new()
+    }
+}
+
+const LOG_LEVEL: u32 = 4;
+const PLUGIN_NAME: &str = "ScopeUnit";
+
+use process1::declure_unitobj_plugin;
+
+// define the method to create the instance of the unit
+declure_unitobj_plugin!(ScopeUnit, ScopeUnit::default, PLUGIN_NAME, LOG_LEVEL);
@@ -1,5 +1,9 @@
+use nix::sys::signal::Signal;
 use process1::manager::DeserializeWith;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::HashSet;
+use std::str::FromStr;
+use utils::process_util;
 
 #[derive(PartialEq, EnumString, Display, Debug)]
 pub(super) enum ServiceTimeoutFailureMode {
@@ -19,10 +23,141 @@ impl Default for ServiceTimeoutFailureMode {
     }
 }
 
-#[derive(PartialEq, Default, Debug)]
-pub(super) struct ExitStatusSet {}
+/// the exit statuses and the signals which are configured in SuccessExitStatus=,
+/// RestartPreventExitStatus= and RestartForceExitStatus=.
+#[derive(PartialEq, Default, Debug, Clone)]
+pub(super) struct ExitStatusSet {
+    status: HashSet<i32>,
+    signal: HashSet<Signal>,
+}
 
-#[derive(PartialEq, EnumString, Display, Debug)]
+impl ExitStatusSet {
+    pub(super) fn contains_status(&self, status: i32) -> bool {
+        self.status.contains(&status)
+    }
+
+    pub(super) fn contains_signal(&self, signal: Signal) -> bool {
+        self.signal.contains(&signal)
+    }
+
+    /// check whether the exit of the process is in the set, the code is the exit code when the
+    /// process exited normally, and the signal is SIGCHLD in that case.
+    pub(super) fn contains(&self, code: i32, signal: Signal) -> bool {
+        if signal == Signal::SIGCHLD {
+            self.contains_status(code)
+        } else {
+            self.contains_signal(signal)
+        }
+    }
+}
+
+impl FromStr for ExitStatusSet {
+    type Err = String;
+
+    /// the items are separated by whitespaces, each one is an exit status number, an exit status
+    /// name such as "NOPERMISSION", or a signal name such as "SIGTERM" or "TERM".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = ExitStatusSet::default();
+
+        for item in s.split_whitespace() {
+            if let Ok(status) = item.parse::<i32>() {
+                if !(0..=255).contains(&status) {
+                    return Err(format!("exit status out of range: {}", item));
+                }
+                set.status.insert(status);
+            } else if let Some(status) = exit_status_from_name(item) {
+                set.status.insert(status);
+            } else if let Some(signal) = process_util::parse_signal(item) {
+                set.signal.insert(signal);
+            } else {
+                return Err(format!("invalid exit status: {}", item));
+            }
+        }
+
+        Ok(set)
+    }
+}
+
+const EXIT_STATUS_NAMES: &[(&str, i32)] = &[
+    // the LSB statuses
+    ("SUCCESS", 0),
+    ("FAILURE", 1),
+    ("INVALIDARGUMENT", 2),
+    ("NOTIMPLEMENTED", 3),
+    ("NOPERMISSION", 4),
+    ("NOTINSTALLED", 5),
+    ("NOTCONFIGURED", 6),
+    ("NOTRUNNING", 7),
+    // the BSD statuses from sysexits.h
+    ("USAGE", 64),
+    ("DATAERR", 65),
+    ("NOINPUT", 66),
+    ("NOUSER", 67),
+    ("NOHOST", 68),
+    ("UNAVAILABLE", 69),
+    ("SOFTWARE", 70),
+    ("OSERR", 71),
+    ("OSFILE", 72),
+    ("CANTCREAT", 73),
+    ("IOERR", 74),
+    ("TEMPFAIL", 75),
+    ("PROTOCOL", 76),
+    ("NOPERM", 77),
+    ("CONFIG", 78),
+    // the statuses of the failed steps when spawning the process
+    ("CHDIR", 200),
+    ("NICE", 201),
+    ("FDS", 202),
+    ("EXEC", 203),
+    ("MEMORY", 204),
+    ("LIMITS", 205),
+    ("OOM_ADJUST", 206),
+    ("SIGNAL_MASK", 207),
+    ("STDIN", 208),
+    ("STDOUT", 209),
+    ("CHROOT", 210),
+    ("IOPRIO", 211),
+    ("TIMERSLACK", 212),
+    ("SECUREBITS", 213),
+    ("SETSCHEDULER", 214),
+    ("CPUAFFINITY", 215),
+    ("GROUP", 216),
+    ("USER", 217),
+    ("CAPABILITIES", 218),
+    ("CGROUP", 219),
+    ("SETSID", 220),
+    ("CONFIRM", 221),
+    ("STDERR", 222),
+    ("PAM", 224),
+    ("NETWORK", 225),
+    ("NAMESPACE", 226),
+    ("NO_NEW_PRIVILEGES", 227),
+    ("SECCOMP", 228),
+    ("SELINUX_CONTEXT", 229),
+    ("PERSONALITY", 230),
+    ("APPARMOR_PROFILE", 231),
+    ("ADDRESS_FAMILIES", 232),
+    ("RUNTIME_DIRECTORY", 233),
+    ("CHOWN", 235),
+    ("SMACK_PROCESS_LABEL", 236),
+    ("KEYRING", 237),
+    ("STATE_DIRECTORY", 238),
+    ("CACHE_DIRECTORY", 239),
+    ("LOGS_DIRECTORY", 240),
+    ("CONFIGURATION_DIRECTORY", 241),
+    ("NUMA_POLICY", 242),
+    ("CREDENTIALS", 243),
+    ("BPF", 245),
+];
+
+fn exit_status_from_name(name: &str) -> Option<i32> {
+    EXIT_STATUS_NAMES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, status)| *status)
+}
+
+#[derive(PartialEq, Eq, EnumString, Display, Debug, Clone, Copy)]
 pub(super) enum ServiceRestart {
     #[strum(serialize = "no")]
     RestartNo,
@@ -61,9 +196,14 @@ pub(super) enum ServiceType {
     #[serde(alias = "notify")]
     Notify,
     #[strum(serialize = "idle")]
+    #[serde(alias = "idle")]
     Idle,
     #[strum(serialize = "exec")]
+    #[serde(alias = "exec")]
     Exec,
+    #[strum(serialize = "notify-reload")]
+    #[serde(alias = "notify-reload")]
+    NotifyReload,
     TypeMax,
     TypeInvalid = -1,
 }
@@ -74,6 +214,13 @@ impl Default for ServiceType {
     }
 }
 
+impl ServiceType {
+    /// the service notifies its readiness through the notify socket.
+    pub(super) fn is_notify(&self) -> bool {
+        *self == ServiceType::Notify || *self == ServiceType::NotifyReload
+    }
+}
+
 impl DeserializeWith for ServiceType {
     fn deserialize_with<'de, D>(de: D) -> Result<Self, D::Error>
     where
@@ -81,12 +228,19 @@ impl DeserializeWith for ServiceType {
     {
         let s = String::deserialize(de)?;
 
-        match s.as_ref() {
+        // the old unit files write the type capitalized, such as "Simple"
+        match s.to_lowercase().as_ref() {
             "simple" => Ok(ServiceType::Simple),
             "forking" => Ok(ServiceType::Forking),
             "oneshot" => Ok(ServiceType::Oneshot),
             "notify" => Ok(ServiceType::Notify),
-            &_ => Ok(ServiceType::Simple),
+            "idle" => Ok(ServiceType::Idle),
+            "exec" => Ok(ServiceType::Exec),
+            "notify-reload" => Ok(ServiceType::NotifyReload),
+            &_ => Err(de::Error::invalid_value(
+                de::Unexpected::Str(&s),
+                &"simple, forking, oneshot, notify, idle, exec or notify-reload",
+            )),
         }
     }
 }
@@ -116,10 +270,33 @@ pub(super) enum NotifyAccess {
     Main,
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub(super) enum NotifyState {
     Unknown,
     Ready,
     Reloading,
     Stoping,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ExitStatusSet;
+    use nix::sys::signal::Signal;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_exit_status_set_parse() {
+        let set = ExitStatusSet::from_str("143 NOPERMISSION SIGTERM HUP").unwrap();
+        assert!(set.contains_status(143));
+        assert!(set.contains_status(4));
+        assert!(!set.contains_status(0));
+        assert!(set.contains_signal(Signal::SIGTERM));
+        assert!(set.contains_signal(Signal::SIGHUP));
+        assert!(set.contains(143, Signal::SIGCHLD));
+        assert!(!set.contains(15, Signal::SIGCHLD));
+
+        assert_eq!(ExitStatusSet::from_str("").unwrap(), ExitStatusSet::default());
+        assert!(ExitStatusSet::from_str("256").is_err());
+        assert!(ExitStatusSet::from_str("NOSUCHSTATUS").is_err());
+    }
+}
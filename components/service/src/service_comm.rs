@@ -21,11 +21,12 @@ impl ServiceComm {
         self.data.borrow_mut().attach_um(um)
     }
 
-    pub(super) fn unit(&self) -> Rc<Unit> {
+    /// the unit and the manager are None before they are attached, such as in the tests.
+    pub(super) fn unit(&self) -> Option<Rc<Unit>> {
         self.data.borrow().unit()
     }
 
-    pub(super) fn um(&self) -> Rc<UnitManager> {
+    pub(super) fn um(&self) -> Option<Rc<UnitManager>> {
         self.data.borrow().um()
     }
 }
@@ -52,11 +53,11 @@ impl ServiceCommData {
         self.um = Rc::downgrade(&um);
     }
 
-    pub(self) fn unit(&self) -> Rc<Unit> {
-        self.unit.clone().upgrade().unwrap()
+    pub(self) fn unit(&self) -> Option<Rc<Unit>> {
+        self.unit.clone().upgrade()
     }
 
-    pub(self) fn um(&self) -> Rc<UnitManager> {
-        self.um.clone().upgrade().unwrap()
+    pub(self) fn um(&self) -> Option<Rc<UnitManager>> {
+        self.um.clone().upgrade()
     }
 }
@@ -2,14 +2,23 @@
 use std::cell::RefCell;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::str::FromStr;
 
-use super::service_base::ServiceType;
+use super::service_base::{ExitStatusSet, ServiceRestart, ServiceType};
 use crate::service_base::NotifyAccess;
 use crate::service_base::ServiceCommand;
 use confique::Config;
 use confique::Error;
+use nix::sys::signal::Signal;
 use process1::manager::DeserializeWith;
 use process1::manager::ExecCommand;
+use process1::manager::ExecDirectoryType;
+use process1::manager::ExecNamespace;
+use utils::process_util;
+
+const DEFAULT_TIMEOUT_SEC: u64 = 90;
+const USEC_PER_SEC: u64 = 1_000_000;
+const DEFAULT_RESTART_USEC: u64 = 100_000;
 
 pub(super) struct ServiceConfig {
     data: Rc<RefCell<ServiceConfigData>>,
@@ -58,6 +67,108 @@ impl ServiceConfig {
         }
     }
 
+    pub(super) fn namespace(&self) -> ExecNamespace {
+        let data = self.data.borrow();
+        ExecNamespace {
+            private_network: data.Service.PrivateNetwork,
+            private_users: data.Service.PrivateUsers,
+            private_ipc: data.Service.PrivateIPC,
+            protect_hostname: data.Service.ProtectHostname,
+            network_namespace_path: data
+                .Service
+                .NetworkNamespacePath
+                .as_ref()
+                .map(PathBuf::from),
+        }
+    }
+
+    /// the directories and its mode configured, such as RuntimeDirectory= and RuntimeDirectoryMode=
+    pub(super) fn directory(&self, dt: ExecDirectoryType) -> (Option<Vec<String>>, Option<String>) {
+        let data = self.data.borrow();
+        let (paths, mode) = match dt {
+            ExecDirectoryType::Runtime => (
+                &data.Service.RuntimeDirectory,
+                &data.Service.RuntimeDirectoryMode,
+            ),
+            ExecDirectoryType::State => (
+                &data.Service.StateDirectory,
+                &data.Service.StateDirectoryMode,
+            ),
+            ExecDirectoryType::Cache => (
+                &data.Service.CacheDirectory,
+                &data.Service.CacheDirectoryMode,
+            ),
+            ExecDirectoryType::Logs => {
+                (&data.Service.LogsDirectory, &data.Service.LogsDirectoryMode)
+            }
+            ExecDirectoryType::Configuration => (
+                &data.Service.ConfigurationDirectory,
+                &data.Service.ConfigurationDirectoryMode,
+            ),
+        };
+        (paths.clone(), mode.clone())
+    }
+
+    /// the signal sent to the main process of Type=notify-reload service when reloading.
+    pub(super) fn reload_signal(&self) -> Option<Signal> {
+        match &self.data.borrow().Service.ReloadSignal {
+            Some(sig) => process_util::parse_signal(sig),
+            None => Some(Signal::SIGHUP),
+        }
+    }
+
+    /// TimeoutStartSec= applies to both the start and the reload of the service, in useconds.
+    pub(super) fn timeout_start_usec(&self) -> u64 {
+        self.data
+            .borrow()
+            .Service
+            .TimeoutStartSec
+            .unwrap_or(DEFAULT_TIMEOUT_SEC)
+            * USEC_PER_SEC
+    }
+
+    /// TimeoutStopSec= applies to each step of stopping the service, in useconds.
+    pub(super) fn timeout_stop_usec(&self) -> u64 {
+        self.data
+            .borrow()
+            .Service
+            .TimeoutStopSec
+            .unwrap_or(DEFAULT_TIMEOUT_SEC)
+            * USEC_PER_SEC
+    }
+
+    /// the restart policy configured in Restart=, the default is "no".
+    pub(super) fn restart(&self) -> Result<ServiceRestart, String> {
+        match &self.data.borrow().Service.Restart {
+            Some(restart) => ServiceRestart::from_str(restart.trim())
+                .map_err(|_e| format!("invalid restart policy: {}", restart)),
+            None => Ok(ServiceRestart::default()),
+        }
+    }
+
+    /// RestartSec= is the time to sleep before restarting the service, in useconds.
+    pub(super) fn restart_usec(&self) -> u64 {
+        match self.data.borrow().Service.RestartSec {
+            Some(sec) => sec * USEC_PER_SEC,
+            None => DEFAULT_RESTART_USEC,
+        }
+    }
+
+    /// the exit statuses of the main process considered as success besides 0 and the clean signals.
+    pub(super) fn success_exit_status(&self) -> Result<ExitStatusSet, String> {
+        exit_status_set(&self.data.borrow().Service.SuccessExitStatus)
+    }
+
+    /// the exit statuses of the main process which prevent the service from being restarted.
+    pub(super) fn restart_prevent_exit_status(&self) -> Result<ExitStatusSet, String> {
+        exit_status_set(&self.data.borrow().Service.RestartPreventExitStatus)
+    }
+
+    /// the exit statuses of the main process which force the service to be restarted.
+    pub(super) fn restart_force_exit_status(&self) -> Result<ExitStatusSet, String> {
+        exit_status_set(&self.data.borrow().Service.RestartForceExitStatus)
+    }
+
     pub(super) fn sockets(&self) -> Option<Vec<String>> {
         match &self.data.borrow().Service.Sockets {
             Some(v) => Some(v.iter().map(|v| v.to_string()).collect()),
@@ -66,6 +177,13 @@ impl ServiceConfig {
     }
 }
 
+fn exit_status_set(value: &Option<String>) -> Result<ExitStatusSet, String> {
+    match value {
+        Some(v) => ExitStatusSet::from_str(v),
+        None => Ok(ExitStatusSet::default()),
+    }
+}
+
 #[derive(Config, Default, Debug)]
 pub(super) struct ServiceConfigData {
     #[config(nested)]
@@ -99,12 +217,23 @@ pub(super) struct SectionService {
     pub ExecCondition: Option<Vec<ExecCommand>>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     pub Sockets: Option<Vec<String>>,
-    // #[config(deserialize_with = Vec::<ExecCommand>::deserialize_with)]
-    // pub Restart: Option<Vec<ExecCommand>>,
+    pub Restart: Option<String>,
     pub RestrictRealtime: Option<String>,
     pub RebootArgument: Option<String>,
     pub OOMScoreAdjust: Option<String>,
     pub RestartSec: Option<u64>,
+    pub SuccessExitStatus: Option<String>,
+    pub RestartPreventExitStatus: Option<String>,
+    pub RestartForceExitStatus: Option<String>,
+    pub TimeoutStartSec: Option<u64>,
+    pub TimeoutStopSec: Option<u64>,
+    pub ReloadSignal: Option<String>,
+    pub KillMode: Option<String>,
+    pub KillSignal: Option<String>,
+    pub RestartKillSignal: Option<String>,
+    pub FinalKillSignal: Option<String>,
+    pub SendSIGKILL: Option<bool>,
+    pub SendSIGHUP: Option<bool>,
     pub WatchdogUSec: Option<u64>,
     pub Slice: Option<String>,
     pub MemoryLimit: Option<u64>,
@@ -114,10 +243,39 @@ pub(super) struct SectionService {
     pub MemoryHigh: Option<u64>,
     pub MemorySwapMax: Option<u64>,
     pub PIDFile: Option<String>,
+    #[config(default = true)]
+    pub GuessMainPID: bool,
     pub RemainAfterExit: Option<bool>,
     pub NotifyAccess: Option<NotifyAccess>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     pub Environment: Option<Vec<String>>,
+    #[config(default = false)]
+    pub PrivateNetwork: bool,
+    #[config(default = false)]
+    pub PrivateUsers: bool,
+    #[config(default = false)]
+    pub PrivateIPC: bool,
+    #[config(default = false)]
+    pub ProtectHostname: bool,
+    pub NetworkNamespacePath: Option<String>,
+    pub User: Option<String>,
+    pub Group: Option<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub RuntimeDirectory: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub StateDirectory: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub CacheDirectory: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub LogsDirectory: Option<Vec<String>>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub ConfigurationDirectory: Option<Vec<String>>,
+    pub RuntimeDirectoryMode: Option<String>,
+    pub StateDirectoryMode: Option<String>,
+    pub CacheDirectoryMode: Option<String>,
+    pub LogsDirectoryMode: Option<String>,
+    pub ConfigurationDirectoryMode: Option<String>,
+    pub RuntimeDirectoryPreserve: Option<String>,
 }
 
 impl SectionService {
@@ -151,6 +309,7 @@ mod tests {
         path::PathBuf,
     };
 
+    use crate::service_base::ServiceType;
     use crate::service_config::ServiceConfig;
 
     fn get_project_root() -> io::Result<PathBuf> {
@@ -184,4 +343,31 @@ mod tests {
 
         assert_eq!(result.is_err(), false);
     }
+
+    #[test]
+    fn test_service_type_parse() {
+        let dir = env::temp_dir().join(format!("service_type_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.service.toml");
+
+        std::fs::write(
+            &path,
+            "[Service]\nType=\"Forking\"\nExecStart=\"/bin/true\"\n",
+        )
+        .unwrap();
+        let config = ServiceConfig::new();
+        assert!(config.load(&vec![path.clone()]).is_ok());
+        assert_eq!(config.service_type(), ServiceType::Forking);
+
+        // the unknown type fails the loading
+        std::fs::write(
+            &path,
+            "[Service]\nType=\"nosuch\"\nExecStart=\"/bin/true\"\n",
+        )
+        .unwrap();
+        let config = ServiceConfig::new();
+        assert!(config.load(&vec![path]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
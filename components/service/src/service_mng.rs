@@ -1,6 +1,6 @@
 use crate::service_base::NotifyState;
 
-use super::service_base::{ServiceCommand, ServiceType};
+use super::service_base::{ServiceCommand, ServiceRestart, ServiceType};
 use super::service_comm::ServiceComm;
 use super::service_config::ServiceConfig;
 use super::service_pid::ServicePid;
@@ -9,14 +9,17 @@ use log;
 use nix::errno::Errno;
 use nix::sys::signal::Signal;
 use nix::sys::socket::UnixCredentials;
+use nix::time::{clock_gettime, ClockId};
 use nix::unistd::Pid;
 use process1::manager::{
-    ExecCommand, ExecContext, ExecFlags, KillOperation, UnitActionError, UnitActiveState,
-    UnitNotifyFlags,
+    ExecCommand, ExecContext, ExecFlags, KillContext, KillMode, KillOperation, UnitActionError,
+    UnitActiveState, UnitNotifyFlags,
 };
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
 use std::os::unix::prelude::AsRawFd;
 use std::path::Path;
 use std::rc::Rc;
@@ -66,6 +69,7 @@ impl Default for ServiceState {
 pub(self) enum ServiceResult {
     Success,
     FailureProtocol,
+    FailureExitCode,
     FailureResources,
     FailureTimeout,
     FailureSignal,
@@ -79,6 +83,22 @@ impl Default for ServiceResult {
     }
 }
 
+impl fmt::Display for ServiceResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result = match self {
+            ServiceResult::Success => "success",
+            ServiceResult::FailureProtocol => "protocol",
+            ServiceResult::FailureExitCode => "exit-code",
+            ServiceResult::FailureResources => "resources",
+            ServiceResult::FailureTimeout => "timeout",
+            ServiceResult::FailureSignal => "signal",
+            ServiceResult::FailureKill => "kill",
+            ServiceResult::ResultInvalid => "invalid",
+        };
+        write!(f, "{}", result)
+    }
+}
+
 pub(super) struct ServiceMng {
     // associated objects
     comm: Rc<ServiceComm>,
@@ -92,6 +112,11 @@ pub(super) struct ServiceMng {
     main_command: RefCell<Vec<ExecCommand>>,
     control_command: RefCell<Vec<ExecCommand>>,
     rd: Rc<RunningData>,
+    exec_ctx: Rc<ExecContext>,
+    kill_ctx: Rc<KillContext>,
+    main_exit_status: RefCell<Option<(i32, Signal)>>,
+    forbid_restart: RefCell<bool>,
+    will_auto_restart: RefCell<bool>,
 }
 
 impl ServiceMng {
@@ -100,6 +125,7 @@ impl ServiceMng {
         configr: &Rc<ServiceConfig>,
         rd: &Rc<RunningData>,
         exec_ctx: &Rc<ExecContext>,
+        kill_ctx: &Rc<KillContext>,
     ) -> ServiceMng {
         let _pid = Rc::new(ServicePid::new(commr));
         ServiceMng {
@@ -112,6 +138,11 @@ impl ServiceMng {
             main_command: RefCell::new(Vec::new()),
             control_command: RefCell::new(Vec::new()),
             rd: rd.clone(),
+            exec_ctx: exec_ctx.clone(),
+            kill_ctx: kill_ctx.clone(),
+            main_exit_status: RefCell::new(None),
+            forbid_restart: RefCell::new(false),
+            will_auto_restart: RefCell::new(false),
         }
     }
 
@@ -147,6 +178,9 @@ impl ServiceMng {
 
     pub(super) fn start_action(&self) {
         self.set_result(ServiceResult::Success);
+        *self.main_exit_status.borrow_mut() = None;
+        *self.forbid_restart.borrow_mut() = false;
+        *self.will_auto_restart.borrow_mut() = false;
         self.enter_contion();
     }
 
@@ -167,6 +201,15 @@ impl ServiceMng {
     }
 
     pub(super) fn stop_action(&self) {
+        // the service is stopped manually, do not restart it automatically.
+        *self.forbid_restart.borrow_mut() = true;
+
+        if self.state() == ServiceState::AutoRestart {
+            self.set_state(ServiceState::Dead);
+            *self.will_auto_restart.borrow_mut() = false;
+            return;
+        }
+
         let starting_state = vec![
             ServiceState::Condition,
             ServiceState::StartPre,
@@ -252,20 +295,21 @@ impl ServiceMng {
             return;
         }
 
-        let ret = self
+        let pid = match self
             .spawn
-            .start_service(&cmd.unwrap(), 0, ExecFlags::PASS_FDS);
-
-        if ret.is_err() {
-            log::error!("failed to start service: {}", self.comm.unit().get_id());
-            self.enter_signal(ServiceState::StopSigterm, ServiceResult::FailureResources);
-        }
-
-        let pid = ret.unwrap();
+            .start_service(&cmd.unwrap(), 0, ExecFlags::PASS_FDS)
+        {
+            Ok(pid) => pid,
+            Err(_e) => {
+                log::error!("failed to start service: {}", self.unit_id());
+                self.enter_signal(ServiceState::StopSigterm, ServiceResult::FailureResources);
+                return;
+            }
+        };
         log::debug!("service type is: {}, forking pid is: {}", service_type, pid);
 
         match service_type {
-            ServiceType::Simple => {
+            ServiceType::Simple | ServiceType::Idle => {
                 self.pid.set_main(pid);
                 self.enter_start_post();
             }
@@ -275,13 +319,19 @@ impl ServiceMng {
                 self.pid.set_control(pid);
                 self.set_state(ServiceState::Start);
             }
-            ServiceType::Oneshot | ServiceType::Notify => {
+            ServiceType::Oneshot | ServiceType::Notify | ServiceType::NotifyReload => {
                 self.pid.set_main(pid);
                 self.set_state(ServiceState::Start);
             }
-
-            ServiceType::Idle => todo!(),
-            ServiceType::Exec => todo!(),
+            ServiceType::Exec => {
+                // the start is finished when the execve of the main process succeeded.
+                self.pid.set_main(pid);
+                self.set_state(ServiceState::Start);
+                match self.spawn.take_exec_fd() {
+                    Some(fd) => self.watch_exec_fd(fd),
+                    None => self.enter_start_post(),
+                }
+            }
             _ => {}
         }
 
@@ -297,10 +347,7 @@ impl ServiceMng {
                 match self.spawn.start_service(&cmd, 0, ExecFlags::CONTROL) {
                     Ok(pid) => self.pid.set_control(pid),
                     Err(_e) => {
-                        log::error!(
-                            "Failed to run start post service: {}",
-                            self.comm.unit().get_id()
-                        );
+                        log::error!("Failed to run start post service: {}", self.unit_id());
                     }
                 }
                 self.set_state(ServiceState::StartPost);
@@ -346,10 +393,11 @@ impl ServiceMng {
                 match self.spawn.start_service(&cmd, 0, ExecFlags::CONTROL) {
                     Ok(pid) => self.pid.set_control(pid),
                     Err(_e) => {
-                        log::error!("Failed to run stop service: {}", self.comm.unit().get_id());
+                        log::error!("Failed to run stop service: {}", self.unit_id());
                     }
                 }
                 self.set_state(ServiceState::Stop);
+                self.enable_timer(self.config.timeout_stop_usec());
             }
             None => self.enter_signal(ServiceState::StopSigterm, ServiceResult::Success),
         }
@@ -357,6 +405,7 @@ impl ServiceMng {
 
     fn enter_stop_by_notify(&self) {
         self.set_state(ServiceState::StopSigterm);
+        self.enable_timer(self.config.timeout_stop_usec());
     }
 
     fn enter_stop_post(&self, res: ServiceResult) {
@@ -375,10 +424,11 @@ impl ServiceMng {
                             ServiceState::FinalSigterm,
                             ServiceResult::FailureResources,
                         );
-                        log::error!("Failed to run stop service: {}", self.comm.unit().get_id());
+                        log::error!("Failed to run stop service: {}", self.unit_id());
                     }
                 }
                 self.set_state(ServiceState::StopPost);
+                self.enable_timer(self.config.timeout_stop_usec());
             }
             None => self.enter_signal(ServiceState::FinalSigterm, ServiceResult::Success),
         }
@@ -396,27 +446,184 @@ impl ServiceMng {
             ServiceState::Failed
         };
 
+        // the handlers of OnFailure=/OnSuccess= are not triggered if the service will be restarted.
+        let restarting = self.shall_restart();
+        *self.will_auto_restart.borrow_mut() = restarting;
         self.set_state(state);
+
+        if restarting {
+            log::debug!(
+                "service {} will be restarted after {} usec",
+                self.unit_id(),
+                self.config.restart_usec()
+            );
+            self.set_state(ServiceState::AutoRestart);
+            self.enable_timer(self.config.restart_usec());
+        }
+
+        *self.forbid_restart.borrow_mut() = false;
+        if let Some(unit) = self.comm.unit() {
+            unit.destroy_runtime_data(&self.exec_ctx, restarting);
+        }
+    }
+
+    fn shall_restart(&self) -> bool {
+        if *self.forbid_restart.borrow() {
+            return false;
+        }
+
+        if let Some((code, signal)) = *self.main_exit_status.borrow() {
+            if self
+                .config
+                .restart_prevent_exit_status()
+                .unwrap_or_default()
+                .contains(code, signal)
+            {
+                return false;
+            }
+
+            if self
+                .config
+                .restart_force_exit_status()
+                .unwrap_or_default()
+                .contains(code, signal)
+            {
+                return true;
+            }
+        }
+
+        let result = self.result();
+        match self.config.restart().unwrap_or_default() {
+            ServiceRestart::RestartAlways => true,
+            ServiceRestart::RestartOnSuccess => result == ServiceResult::Success,
+            ServiceRestart::RestartOnFailure => result != ServiceResult::Success,
+            ServiceRestart::RestartOnAbnormal => !IN_SET!(
+                result,
+                ServiceResult::Success,
+                ServiceResult::FailureExitCode
+            ),
+            ServiceRestart::RestartOnAbort => result == ServiceResult::FailureSignal,
+            _ => false,
+        }
+    }
+
+    /// $MONITOR_SERVICE_RESULT, $MONITOR_EXIT_CODE and $MONITOR_EXIT_STATUS for the handlers
+    /// of OnFailure=/OnSuccess=.
+    pub(super) fn monitor_envs(&self) -> Vec<(String, String)> {
+        let mut envs = vec![(
+            "MONITOR_SERVICE_RESULT".to_string(),
+            self.result().to_string(),
+        )];
+
+        if let Some((code, signal)) = *self.main_exit_status.borrow() {
+            let (exit_code, exit_status) = if signal == Signal::SIGCHLD {
+                ("exited", code.to_string())
+            } else {
+                (
+                    "killed",
+                    signal.as_str().trim_start_matches("SIG").to_string(),
+                )
+            };
+            envs.push(("MONITOR_EXIT_CODE".to_string(), exit_code.to_string()));
+            envs.push(("MONITOR_EXIT_STATUS".to_string(), exit_status));
+        }
+
+        envs
+    }
+
+    fn enter_auto_restart(&self) {
+        let id = self.unit_id();
+        log::debug!("restarting service {} automatically", id);
+        let um = match self.comm.um() {
+            Some(um) => um,
+            None => return,
+        };
+        if let Err(e) = um.restart_unit(&id) {
+            log::error!("failed to schedule restart job of service {}: {:?}", id, e);
+            self.set_result(ServiceResult::FailureResources);
+            self.set_state(ServiceState::Failed);
+        }
     }
 
     fn enter_reload(&self) {
         log::debug!("running service reload command");
         self.control_command.borrow_mut().clear();
         self.pid.unwatch_control();
+
+        let mut reloading = false;
+        if self.config.service_type() == ServiceType::NotifyReload {
+            match self.send_reload_signal() {
+                Ok(_) => {
+                    // the reload is finished when the service sends READY=1 again.
+                    self.rd.set_notify_state(NotifyState::Reloading);
+                    reloading = true;
+                }
+                Err(e) => {
+                    log::error!(
+                        "failed to send the reload signal to service {}: {}",
+                        self.unit_id(),
+                        e
+                    );
+                    self.enter_running(ServiceResult::Success);
+                    return;
+                }
+            }
+        }
+
         self.control_command_fill(ServiceCommand::Reload);
         match self.control_command_pop() {
             Some(cmd) => {
                 match self.spawn.start_service(&cmd, 0, ExecFlags::CONTROL) {
                     Ok(pid) => self.pid.set_control(pid),
                     Err(_e) => {
-                        log::error!("failed to start service: {}", self.comm.unit().get_id());
+                        log::error!("failed to start service: {}", self.unit_id());
                         self.enter_running(ServiceResult::Success);
+                        return;
                     }
                 }
-                self.set_state(ServiceState::Reload);
+                reloading = true;
             }
-            None => self.enter_running(ServiceResult::Success),
+            None => {}
+        }
+
+        if !reloading {
+            self.enter_running(ServiceResult::Success);
+            return;
+        }
+
+        self.set_state(ServiceState::Reload);
+        self.enable_timer(self.config.timeout_start_usec());
+    }
+
+    fn send_reload_signal(&self) -> Result<(), Error> {
+        let sig = self.config.reload_signal().ok_or(Error::Other {
+            msg: "invalid reload signal",
+        })?;
+        let pid = self.pid.main().ok_or(Error::Other {
+            msg: "no main pid to send the reload signal",
+        })?;
+
+        log::debug!("send reload signal {} to main pid {}", sig, pid);
+        nix::sys::signal::kill(pid, sig).map_err(|e| Error::Syscall {
+            syscall: "kill",
+            ret: -1,
+            errno: e as i32,
+        })
+    }
+
+    fn finish_reload(&self) {
+        // both the reload command and the notification of READY=1 are finished.
+        if self.pid.control().is_some() {
+            return;
+        }
+
+        if self.config.service_type() == ServiceType::NotifyReload
+            && self.rd.notify_state() == NotifyState::Reloading
+        {
+            return;
         }
+
+        self.enter_running(ServiceResult::Success);
     }
 
     fn enter_signal(&self, state: ServiceState, res: ServiceResult) {
@@ -425,18 +632,34 @@ impl ServiceMng {
             state,
             res
         );
+        if self.result() == ServiceResult::Success {
+            self.set_result(res);
+        }
 
-        self.comm
-            .um()
-            .child_watch_all_pids(self.comm.unit().get_id());
+        let id = self.unit_id();
+        if let Some(um) = self.comm.um() {
+            um.child_watch_all_pids(&id);
+        }
 
-        let op = state.to_kill_operation();
-        match self
-            .comm
-            .unit()
-            .kill_context(self.pid.main(), self.pid.control(), op)
-        {
-            Ok(_) => {}
+        let restarting = match self.comm.um() {
+            Some(um) => um.has_restart_job(&id),
+            None => false,
+        };
+        let op = state.to_kill_operation(restarting);
+        let killed = match self.comm.unit() {
+            Some(unit) => {
+                unit.kill_context(&self.kill_ctx, self.pid.main(), self.pid.control(), op)
+            }
+            None => Ok(false),
+        };
+        match killed {
+            Ok(true) => {
+                // wait for the processes killed exiting, or escalate when timeout.
+                self.set_state(state);
+                self.enable_timer(self.config.timeout_stop_usec());
+                return;
+            }
+            Ok(false) => {}
             Err(_e) => {
                 if IN_SET!(
                     state,
@@ -470,6 +693,26 @@ impl ServiceMng {
         let original_state = self.state();
         *self.state.borrow_mut() = state;
 
+        if state != ServiceState::Start {
+            self.unwatch_exec_fd();
+        }
+
+        if !IN_SET!(
+            state,
+            ServiceState::Reload,
+            ServiceState::Stop,
+            ServiceState::StopWatchdog,
+            ServiceState::StopSigterm,
+            ServiceState::StopSigkill,
+            ServiceState::StopPost,
+            ServiceState::FinalWatchdog,
+            ServiceState::FinalSigterm,
+            ServiceState::FinalSigkill,
+            ServiceState::AutoRestart
+        ) {
+            self.disable_timer();
+        }
+
         // TODO
         // check the new state
         if !vec![
@@ -524,9 +767,13 @@ impl ServiceMng {
 
         let os = service_state_to_unit_state(self.config.service_type(), original_state);
         let ns = service_state_to_unit_state(self.config.service_type(), state);
-        self.comm
-            .unit()
-            .notify(os, ns, UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE);
+        let mut flags = UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE;
+        if *self.will_auto_restart.borrow() {
+            flags |= UnitNotifyFlags::UNIT_NOTIFY_WILL_AUTO_RESTART;
+        }
+        if let Some(unit) = self.comm.unit() {
+            unit.notify(os, ns, flags);
+        }
     }
 
     fn service_alive(&self) -> bool {
@@ -543,7 +790,7 @@ impl ServiceMng {
             match self.spawn.start_service(&cmd, 0, ExecFlags::CONTROL) {
                 Ok(pid) => self.pid.set_control(pid),
                 Err(_e) => {
-                    log::error!("failed to start service: {}", self.comm.unit().get_id());
+                    log::error!("failed to start service: {}", self.unit_id());
                 }
             }
         }
@@ -554,12 +801,18 @@ impl ServiceMng {
             match self.spawn.start_service(&cmd, 0, ExecFlags::PASS_FDS) {
                 Ok(pid) => self.pid.set_main(pid),
                 Err(_e) => {
-                    log::error!("failed to run main command: {}", self.comm.unit().get_id());
+                    log::error!("failed to run main command: {}", self.unit_id());
                 }
             }
         }
     }
 
+    fn unit_id(&self) -> String {
+        self.comm
+            .unit()
+            .map_or_else(|| "0".to_string(), |u| u.get_id().to_string())
+    }
+
     fn state(&self) -> ServiceState {
         *self.state.borrow()
     }
@@ -650,13 +903,31 @@ impl ServiceMng {
             return Ok(false);
         }
 
-        self.valid_main_pid(pid)?;
+        if !self.valid_main_pid(pid)? {
+            // the pid does not belong to us, only trust it if nobody but root could write the file.
+            if !pid_file_root_owned(pid_file_path) {
+                log::error!(
+                    "refuse to accept pid {} from pid file {:?} which is not owned by root",
+                    pid,
+                    pid_file_path
+                );
+                return Err(Error::Other {
+                    msg: "the pid from the unsafe pid file does not belong to the service",
+                });
+            }
+
+            log::warn!(
+                "supervising process {} which is not our child, it is from root owned pid file {:?}",
+                pid,
+                pid_file_path
+            );
+        }
 
         self.pid.unwatch_main();
         self.pid.set_main(pid);
-        self.comm
-            .um()
-            .child_watch_pid(pid, self.comm.unit().get_id());
+        if let Some(um) = self.comm.um() {
+            um.child_watch_pid(pid, &self.unit_id());
+        }
 
         Ok(true)
     }
@@ -679,42 +950,82 @@ impl ServiceMng {
                 msg: "main pid is not alive",
             });
         }
-        if self
-            .comm
-            .um()
-            .same_unit_with_pid(self.comm.unit().get_id(), pid)
-        {
-            return Ok(true);
+        let id = self.unit_id();
+        if let Some(um) = self.comm.um() {
+            if um.same_unit_with_pid(&id, pid) {
+                return Ok(true);
+            }
+        }
+
+        if let Some(unit) = self.comm.unit() {
+            if unit.pid_in_cgroup(pid) {
+                return Ok(true);
+            }
         }
 
         Ok(false)
     }
 
+    fn guess_main_pid(&self) {
+        if !self.config.config_data().borrow().Service.GuessMainPID {
+            return;
+        }
+
+        if self.pid.main().is_some() {
+            return;
+        }
+
+        if let Some(pid) = self.comm.unit().and_then(|u| u.guess_main_pid()) {
+            log::debug!("guessed the main pid of the service: {}", pid);
+            self.pid.set_main(pid);
+            if let Some(um) = self.comm.um() {
+                um.child_watch_pid(pid, &self.unit_id());
+            }
+        }
+    }
+
     pub fn demand_pid_file(&self) -> Result<(), Error> {
-        let pid_file_inotify = PathIntofy::new(PathBuf::from(
-            self.config
-                .config_data()
-                .borrow()
-                .Service
-                .PIDFile
-                .as_ref()
-                .unwrap(),
-        ));
+        if self.rd.path_inotify().is_none() {
+            let pid_file_inotify = PathIntofy::new(PathBuf::from(
+                self.config
+                    .config_data()
+                    .borrow()
+                    .Service
+                    .PIDFile
+                    .as_ref()
+                    .unwrap(),
+            ));
+
+            self.rd.attach_inotify(Rc::new(pid_file_inotify));
+        }
 
-        self.rd.attach_inotify(Rc::new(pid_file_inotify));
+        // give up waiting for the pid file when the start timeout expires.
+        self.enable_timer(self.config.timeout_start_usec());
 
         return self.watch_pid_file();
     }
 
     pub fn watch_pid_file(&self) -> Result<(), Error> {
-        let pid_file_inotify = self.rd.path_inotify();
+        let pid_file_inotify = match self.rd.path_inotify() {
+            Some(pid_file_inotify) => pid_file_inotify,
+            None => {
+                return Err(Error::Other {
+                    msg: "pid file is not demanded",
+                })
+            }
+        };
         log::debug!("watch pid file: {}", pid_file_inotify);
+
+        // the inotify fd is recreated, remove the old one from the event loop before closing it.
+        if let Some(um) = self.comm.um() {
+            um.unregister(pid_file_inotify.clone());
+        }
         match pid_file_inotify.add_watch_path() {
             Ok(_) => {
-                self.comm.um().register(pid_file_inotify.clone());
-                self.comm
-                    .um()
-                    .enable(pid_file_inotify.clone(), EventState::On);
+                if let Some(um) = self.comm.um() {
+                    um.register(pid_file_inotify.clone());
+                    um.enable(pid_file_inotify.clone(), EventState::On);
+                }
                 if let Err(e) = self.retry_pid_file() {
                     log::warn!("retry load pid file error: {}, Ignore and Continue", e);
                 }
@@ -735,12 +1046,19 @@ impl ServiceMng {
     }
 
     pub(super) fn unwatch_pid_file(&self) {
-        log::debug!("unwatch pid file {}", self.rd.path_inotify());
-        self.rd.path_inotify().unwatch();
+        self.disable_timer();
+
+        if let Some(pid_file_inotify) = self.rd.path_inotify() {
+            log::debug!("unwatch pid file {}", pid_file_inotify);
+            if let Some(um) = self.comm.um() {
+                um.unregister(pid_file_inotify.clone());
+            }
+            pid_file_inotify.unwatch();
+        }
     }
 
     pub(super) fn retry_pid_file(&self) -> Result<bool, Error> {
-        log::debug!("retry loading pid file: {}", self.rd.path_inotify());
+        log::debug!("retry loading pid file of service {}", self.unit_id());
         self.load_pid_file()?;
 
         self.unwatch_pid_file();
@@ -749,8 +1067,204 @@ impl ServiceMng {
         Ok(true)
     }
 
+    fn watch_exec_fd(&self, fd: RawFd) {
+        let exec_fd_watch = Rc::new(ExecFdWatch::new(fd));
+        self.rd.attach_exec_fd_watch(exec_fd_watch.clone());
+
+        log::debug!("watch exec fd: {}", fd);
+        if let Some(um) = self.comm.um() {
+            um.register(exec_fd_watch.clone());
+            um.enable(exec_fd_watch, EventState::On);
+        }
+    }
+
+    fn unwatch_exec_fd(&self) {
+        if let Some(exec_fd_watch) = self.rd.detach_exec_fd_watch() {
+            log::debug!("unwatch exec fd: {}", exec_fd_watch.fd());
+            if let Some(um) = self.comm.um() {
+                um.unregister(exec_fd_watch);
+            }
+        }
+    }
+
+    fn dispatch_exec_fd(&self, hot: bool) {
+        self.unwatch_exec_fd();
+
+        if self.state() != ServiceState::Start {
+            return;
+        }
+
+        if hot {
+            self.enter_start_post();
+        } else {
+            // the main process exits, and the failure is handled in sigchld event.
+            log::error!(
+                "failed to execute the main process of service: {}",
+                self.unit_id()
+            );
+        }
+    }
+
+    fn enable_timer(&self, usec: u64) {
+        let timer = match self.rd.timer() {
+            Some(timer) => timer,
+            None => {
+                let timer = Rc::new(ServiceTimer::new());
+                self.rd.attach_timer(timer.clone());
+                if let Some(um) = self.comm.um() {
+                    um.register(timer.clone());
+                }
+                timer
+            }
+        };
+
+        log::debug!("enable service timer after {} usec", usec);
+        timer.arm(usec);
+        if let Some(um) = self.comm.um() {
+            um.enable(timer, EventState::On);
+        }
+    }
+
+    fn disable_timer(&self) {
+        if let Some(timer) = self.rd.timer() {
+            if timer.armed() {
+                timer.disarm();
+                if let Some(um) = self.comm.um() {
+                    um.enable(timer, EventState::Off);
+                }
+            }
+        }
+    }
+
+    fn dispatch_timer(&self) {
+        let id = self.unit_id();
+        match self.state() {
+            ServiceState::Start | ServiceState::StartPost => {
+                if self.config.config_data().borrow().Service.PIDFile.is_some() {
+                    log::warn!("service {} timed out waiting for the pid file", id);
+                    self.unwatch_pid_file();
+                } else {
+                    log::warn!("start of service {} timed out, terminating", id);
+                }
+                self.enter_signal(ServiceState::StopSigterm, ServiceResult::FailureTimeout);
+            }
+            ServiceState::Reload => {
+                log::warn!(
+                    "reload of service {} timed out, kill the reload process",
+                    id
+                );
+                if let Some(pid) = self.pid.control() {
+                    if let Err(e) = nix::sys::signal::kill(pid, Signal::SIGKILL) {
+                        log::warn!("failed to kill the reload process: {}", e);
+                    }
+                }
+                self.pid.unwatch_control();
+                self.rd.set_notify_state(NotifyState::Ready);
+                self.enter_running(ServiceResult::Success);
+            }
+            ServiceState::Stop => {
+                log::warn!("stopping of service {} timed out, terminating", id);
+                self.enter_signal(ServiceState::StopSigterm, ServiceResult::FailureTimeout);
+            }
+            ServiceState::StopWatchdog | ServiceState::StopSigterm => {
+                if self.kill_ctx.send_sigkill() {
+                    log::warn!("service {} is still around after terminating, killing", id);
+                    self.enter_signal(ServiceState::StopSigkill, ServiceResult::FailureTimeout);
+                } else {
+                    log::warn!("service {} is still around, skipping SIGKILL", id);
+                    self.enter_stop_post(ServiceResult::FailureTimeout);
+                }
+            }
+            ServiceState::StopSigkill => {
+                log::warn!("service {} is still around after SIGKILL, ignoring", id);
+                self.enter_stop_post(ServiceResult::FailureTimeout);
+            }
+            ServiceState::StopPost => {
+                log::warn!("stop post of service {} timed out, terminating", id);
+                self.enter_signal(ServiceState::FinalSigterm, ServiceResult::FailureTimeout);
+            }
+            ServiceState::FinalWatchdog | ServiceState::FinalSigterm => {
+                if self.kill_ctx.send_sigkill() {
+                    log::warn!("service {} is still around after terminating, killing", id);
+                    self.enter_signal(ServiceState::FinalSigkill, ServiceResult::FailureTimeout);
+                } else {
+                    log::warn!("service {} is still around, skipping SIGKILL", id);
+                    self.enter_dead(ServiceResult::FailureTimeout);
+                }
+            }
+            ServiceState::FinalSigkill => {
+                log::warn!("service {} is still around after SIGKILL, ignoring", id);
+                self.enter_dead(ServiceResult::FailureTimeout);
+            }
+            ServiceState::AutoRestart => {
+                self.enter_auto_restart();
+            }
+            _ => {}
+        }
+    }
+
+    /// decide the result of the exited process, the clean signals of the daemon and
+    /// SuccessExitStatus= only apply to the main process.
+    fn exit_result(&self, code: i32, signal: Signal, main: bool) -> ServiceResult {
+        if signal == Signal::SIGCHLD && code == 0 {
+            return ServiceResult::Success;
+        }
+
+        if main {
+            if self.config.service_type() != ServiceType::Oneshot
+                && IN_SET!(
+                    signal,
+                    Signal::SIGHUP,
+                    Signal::SIGINT,
+                    Signal::SIGTERM,
+                    Signal::SIGPIPE
+                )
+            {
+                return ServiceResult::Success;
+            }
+
+            if self
+                .config
+                .success_exit_status()
+                .unwrap_or_default()
+                .contains(code, signal)
+            {
+                return ServiceResult::Success;
+            }
+        }
+
+        if signal == Signal::SIGCHLD {
+            ServiceResult::FailureExitCode
+        } else {
+            ServiceResult::FailureSignal
+        }
+    }
+
+    /// the processes killed are still around, the stopping goes on when all of them exit.
+    /// the rest of the cgroup is waited for only if it is killed too.
+    fn stop_waiting(&self) -> bool {
+        if self.pid.main().is_some() || self.pid.control().is_some() {
+            return true;
+        }
+
+        let kill_cgroup = match self.kill_ctx.kill_mode() {
+            KillMode::ControlGroup => true,
+            KillMode::Mixed => IN_SET!(
+                self.state(),
+                ServiceState::StopSigkill,
+                ServiceState::FinalSigkill
+            ),
+            _ => false,
+        };
+        kill_cgroup && self.cgroup_good()
+    }
+
     fn cgroup_good(&self) -> bool {
-        if let Ok(v) = cgroup::cg_is_empty_recursive(&self.comm.unit().cg_path()) {
+        let unit = match self.comm.unit() {
+            Some(unit) => unit,
+            None => return false,
+        };
+        if let Ok(v) = cgroup::cg_is_empty_recursive(&unit.cg_path()) {
             return !v;
         }
 
@@ -772,16 +1286,11 @@ impl ServiceMng {
             self.pid.control(),
             self.state()
         );
-        let res: ServiceResult;
-        if code == 0 {
-            res = ServiceResult::Success;
-        } else if status != Signal::SIGCHLD {
-            res = ServiceResult::FailureSignal;
-        } else {
-            res = ServiceResult::Success
-        }
+        let res = self.exit_result(code, status, self.pid.main() == Some(pid));
 
         if self.pid.main() == Some(pid) {
+            *self.main_exit_status.borrow_mut() = Some((code, status));
+
             // for main pid updated by the process before its exited, updated the main pid.
             if let Ok(v) = self.load_pid_file() {
                 if v {
@@ -791,6 +1300,11 @@ impl ServiceMng {
 
             self.pid.reset_main();
 
+            // the main pid is gone, track the remaining processes of the service.
+            if let Some(um) = self.comm.um() {
+                um.child_watch_all_pids(&self.unit_id());
+            }
+
             if self.result() == ServiceResult::Success {
                 self.set_result(res);
             }
@@ -811,7 +1325,7 @@ impl ServiceMng {
                             self.enter_signal(ServiceState::StopSigterm, res);
                         }
                     }
-                    ServiceState::Start if self.config.service_type() == ServiceType::Notify => {
+                    ServiceState::Start if self.config.service_type().is_notify() => {
                         if res != ServiceResult::Success {
                             self.enter_signal(ServiceState::StopSigterm, res);
                         } else {
@@ -834,10 +1348,14 @@ impl ServiceMng {
                     ServiceState::StopWatchdog
                     | ServiceState::StopSigkill
                     | ServiceState::StopSigterm => {
-                        self.enter_stop_post(res);
+                        if !self.stop_waiting() {
+                            self.enter_stop_post(res);
+                        }
                     }
                     ServiceState::FinalSigterm | ServiceState::FinalSigkill => {
-                        self.enter_dead(res);
+                        if !self.stop_waiting() {
+                            self.enter_dead(res);
+                        }
                     }
                     _ => {}
                 }
@@ -870,9 +1388,9 @@ impl ServiceMng {
                     // only forking type will be in Start state with the control pid exit.
                     if self.config.service_type() == ServiceType::Forking {
                         log::debug!("in sigchild, forking type control pid exit");
-                        if res == ServiceResult::Success
-                            && self.config.config_data().borrow().Service.PIDFile.is_some()
-                        {
+                        if res != ServiceResult::Success {
+                            self.enter_signal(ServiceState::StopSigterm, res);
+                        } else if self.config.config_data().borrow().Service.PIDFile.is_some() {
                             // will load the pid_file after the forking pid exist.
                             let start_post_exist = if self
                                 .config
@@ -916,7 +1434,8 @@ impl ServiceMng {
 
                             self.enter_start_post();
                         } else {
-                            self.enter_signal(ServiceState::StopSigterm, res);
+                            self.guess_main_pid();
+                            self.enter_start_post();
                         }
                     }
                 }
@@ -947,7 +1466,11 @@ impl ServiceMng {
                 }
                 ServiceState::Runing => todo!(),
                 ServiceState::Reload => {
-                    self.enter_running(res);
+                    if res == ServiceResult::Success {
+                        self.finish_reload();
+                    } else {
+                        self.enter_running(res);
+                    }
                 }
                 ServiceState::Stop => {
                     self.enter_signal(ServiceState::StopSigterm, res);
@@ -955,13 +1478,44 @@ impl ServiceMng {
                 ServiceState::StopSigterm
                 | ServiceState::StopSigkill
                 | ServiceState::StopWatchdog => {
-                    self.enter_stop_post(res);
+                    if self.result() == ServiceResult::Success {
+                        self.set_result(res);
+                    }
+                    if !self.stop_waiting() {
+                        self.enter_stop_post(res);
+                    }
                 }
                 ServiceState::StopPost => {
                     self.enter_signal(ServiceState::FinalSigterm, res);
                 }
                 ServiceState::FinalSigterm | ServiceState::FinalSigkill => {
-                    self.enter_dead(res);
+                    if self.result() == ServiceResult::Success {
+                        self.set_result(res);
+                    }
+                    if !self.stop_waiting() {
+                        self.enter_dead(res);
+                    }
+                }
+                _ => {}
+            }
+        } else {
+            // the other processes of the service, only care about them when all the known
+            // processes are gone and the cgroup is empty.
+            if self.pid.main().is_some() || self.pid.control().is_some() || self.cgroup_good() {
+                return;
+            }
+
+            match self.state() {
+                ServiceState::Runing => {
+                    self.enter_running(ServiceResult::Success);
+                }
+                ServiceState::StopWatchdog
+                | ServiceState::StopSigterm
+                | ServiceState::StopSigkill => {
+                    self.enter_stop_post(ServiceResult::Success);
+                }
+                ServiceState::FinalSigterm | ServiceState::FinalSigkill => {
+                    self.enter_dead(ServiceResult::Success);
                 }
                 _ => {}
             }
@@ -990,9 +1544,9 @@ impl ServiceMng {
 
                     if ucred.pid() == 0 || valid {
                         self.pid.set_main(main_pid);
-                        self.comm
-                            .um()
-                            .child_watch_pid(main_pid, self.comm.unit().get_id());
+                        if let Some(um) = self.comm.um() {
+                            um.child_watch_pid(main_pid, &self.unit_id());
+                        }
                     }
                 }
             }
@@ -1002,11 +1556,26 @@ impl ServiceMng {
             if key == "READY" && value == "1" {
                 log::debug!("service plugin get READY=1");
                 self.rd.set_notify_state(NotifyState::Ready);
-                if self.config.service_type() == ServiceType::Notify
-                    && self.state() == ServiceState::Start
-                {
+                if self.config.service_type().is_notify() && self.state() == ServiceState::Start {
                     self.enter_start_post();
                 }
+
+                if self.state() == ServiceState::Reload {
+                    self.finish_reload();
+                }
+            }
+
+            if key == "RELOADING" && value == "1" {
+                log::debug!("service plugin get RELOADING=1");
+                self.rd.set_notify_state(NotifyState::Reloading);
+                if self.state() == ServiceState::Runing {
+                    // the reload is initiated by the service itself.
+                    self.set_state(ServiceState::Reload);
+                    self.enable_timer(self.config.timeout_start_usec());
+                    if let Some(um) = self.comm.um() {
+                        um.propagate_reload(&self.unit_id());
+                    }
+                }
             }
 
             if key == "STOPPING" && value == "1" {
@@ -1079,9 +1648,11 @@ impl ServiceState {
         }
     }
 
-    fn to_kill_operation(&self) -> KillOperation {
+    /// RestartKillSignal= is sent instead of KillSignal= when stopping for a restart job.
+    fn to_kill_operation(&self, restarting: bool) -> KillOperation {
         match self {
             ServiceState::StopWatchdog => KillOperation::KillWatchdog,
+            ServiceState::StopSigterm if restarting => KillOperation::KillRestart,
             ServiceState::StopSigterm | ServiceState::FinalSigterm => KillOperation::KillTerminate,
             ServiceState::StopSigkill | ServiceState::FinalSigkill => KillOperation::KillKill,
             _ => KillOperation::KillInvalid,
@@ -1115,10 +1686,28 @@ impl RunningData {
         self.data.borrow_mut().attach_inotify(path_inotify);
     }
 
-    pub(self) fn path_inotify(&self) -> Rc<PathIntofy> {
+    pub(self) fn path_inotify(&self) -> Option<Rc<PathIntofy>> {
         self.data.borrow().path_inotify()
     }
 
+    pub(self) fn attach_exec_fd_watch(&self, exec_fd_watch: Rc<ExecFdWatch>) {
+        exec_fd_watch.attach(self.mng.borrow().clone());
+        self.data.borrow_mut().attach_exec_fd_watch(exec_fd_watch);
+    }
+
+    pub(self) fn detach_exec_fd_watch(&self) -> Option<Rc<ExecFdWatch>> {
+        self.data.borrow_mut().detach_exec_fd_watch()
+    }
+
+    pub(self) fn attach_timer(&self, timer: Rc<ServiceTimer>) {
+        timer.attach(self.mng.borrow().clone());
+        self.data.borrow_mut().attach_timer(timer);
+    }
+
+    pub(self) fn timer(&self) -> Option<Rc<ServiceTimer>> {
+        self.data.borrow().timer()
+    }
+
     pub(super) fn attach_mng(&self, mng: Rc<ServiceMng>) {
         *self.mng.borrow_mut() = Rc::downgrade(&mng);
     }
@@ -1140,6 +1729,8 @@ struct Rtdata {
     errno: i32,
     notify_state: NotifyState,
     path_inotify: Option<Rc<PathIntofy>>,
+    exec_fd_watch: Option<Rc<ExecFdWatch>>,
+    timer: Option<Rc<ServiceTimer>>,
 }
 
 impl Rtdata {
@@ -1148,6 +1739,8 @@ impl Rtdata {
             errno: 0,
             notify_state: NotifyState::Unknown,
             path_inotify: None,
+            exec_fd_watch: None,
+            timer: None,
         }
     }
 
@@ -1171,8 +1764,24 @@ impl Rtdata {
         self.path_inotify = Some(path_inotify)
     }
 
-    pub(self) fn path_inotify(&self) -> Rc<PathIntofy> {
-        self.path_inotify.as_ref().unwrap().clone()
+    pub(self) fn path_inotify(&self) -> Option<Rc<PathIntofy>> {
+        self.path_inotify.as_ref().cloned()
+    }
+
+    pub(self) fn attach_exec_fd_watch(&mut self, exec_fd_watch: Rc<ExecFdWatch>) {
+        self.exec_fd_watch = Some(exec_fd_watch)
+    }
+
+    pub(self) fn detach_exec_fd_watch(&mut self) -> Option<Rc<ExecFdWatch>> {
+        self.exec_fd_watch.take()
+    }
+
+    pub(self) fn attach_timer(&mut self, timer: Rc<ServiceTimer>) {
+        self.timer = Some(timer)
+    }
+
+    pub(self) fn timer(&self) -> Option<Rc<ServiceTimer>> {
+        self.timer.clone()
     }
 }
 
@@ -1366,3 +1975,272 @@ impl Source for PathIntofy {
         data
     }
 }
+
+/// watch the read end of the exec fd pipe for Type=exec service. The child writes 1 just before
+/// execve and 0 if execve failed, the pipe is closed by execve because of CLOEXEC.
+struct ExecFdWatch {
+    fd: RawFd,
+    hot: RefCell<bool>,
+    mng: RefCell<Weak<ServiceMng>>,
+}
+
+impl Drop for ExecFdWatch {
+    fn drop(&mut self) {
+        fd_util::close(self.fd);
+    }
+}
+
+impl ExecFdWatch {
+    fn new(fd: RawFd) -> Self {
+        ExecFdWatch {
+            fd,
+            hot: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    pub(super) fn attach(&self, mng: Weak<ServiceMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    pub(super) fn mng(&self) -> Rc<ServiceMng> {
+        self.mng.borrow().clone().upgrade().unwrap()
+    }
+}
+
+impl Source for ExecFdWatch {
+    fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        let mut buf = [0u8; 1];
+        match nix::unistd::read(self.fd, &mut buf) {
+            Ok(0) => {
+                let hot = *self.hot.borrow();
+                log::debug!("exec fd is closed, execve succeeded: {}", hot);
+                self.mng().dispatch_exec_fd(hot);
+            }
+            Ok(_) => *self.hot.borrow_mut() = buf[0] != 0,
+            Err(Errno::EAGAIN) | Err(Errno::EINTR) => {}
+            Err(e) => {
+                log::error!("failed to read from exec fd: {}", e);
+                self.mng().dispatch_exec_fd(false);
+            }
+        }
+
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+/// the timer of the service state, such as the timeout of reloading and stopping.
+struct ServiceTimer {
+    // the absolute time in monotonic clock, u64::MAX means the timer is disarmed.
+    deadline: RefCell<u64>,
+    mng: RefCell<Weak<ServiceMng>>,
+}
+
+impl ServiceTimer {
+    fn new() -> Self {
+        ServiceTimer {
+            deadline: RefCell::new(u64::MAX),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    pub(super) fn attach(&self, mng: Weak<ServiceMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn arm(&self, usec: u64) {
+        *self.deadline.borrow_mut() = monotonic_usec().saturating_add(usec);
+    }
+
+    fn disarm(&self) {
+        *self.deadline.borrow_mut() = u64::MAX;
+    }
+
+    fn armed(&self) -> bool {
+        *self.deadline.borrow() != u64::MAX
+    }
+
+    fn expired(&self) -> bool {
+        self.armed() && monotonic_usec() >= *self.deadline.borrow()
+    }
+
+    pub(super) fn mng(&self) -> Rc<ServiceMng> {
+        self.mng.borrow().clone().upgrade().unwrap()
+    }
+}
+
+/// the pid file is trusted only if it and the path it points to are owned by root.
+fn pid_file_root_owned(path: &Path) -> bool {
+    let owned = |meta: std::io::Result<fs::Metadata>| match meta {
+        Ok(m) => m.uid() == 0,
+        Err(_) => false,
+    };
+
+    owned(fs::symlink_metadata(path)) && owned(fs::metadata(path))
+}
+
+fn monotonic_usec() -> u64 {
+    match clock_gettime(ClockId::CLOCK_MONOTONIC) {
+        Ok(ts) => ts.tv_sec() as u64 * 1_000_000 + ts.tv_nsec() as u64 / 1_000,
+        Err(_) => 0,
+    }
+}
+
+impl Source for ServiceTimer {
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn time(&self) -> u64 {
+        *self.deadline.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        // the timer queued before re-arming is expired earlier, ignore it.
+        if !self.expired() {
+            return Ok(0);
+        }
+
+        log::debug!("dispatch service timer");
+        self.disarm();
+        self.mng().dispatch_timer();
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service_base::ServiceType;
+
+    fn mng_for_test() -> Rc<ServiceMng> {
+        let config = Rc::new(ServiceConfig::new());
+        {
+            let data = config.config_data();
+            let mut data = data.borrow_mut();
+            data.Service.Type = ServiceType::NotifyReload;
+            // ignored by default, it is sent to the test process itself
+            data.Service.ReloadSignal = Some("SIGURG".to_string());
+        }
+
+        let mng = Rc::new(ServiceMng::new(
+            &Rc::new(ServiceComm::new()),
+            &config,
+            &Rc::new(RunningData::new()),
+            &Rc::new(ExecContext::new()),
+            &Rc::new(KillContext::default()),
+        ));
+        mng.pid.set_main(Pid::this());
+        *mng.state.borrow_mut() = ServiceState::Runing;
+        mng.rd.set_notify_state(NotifyState::Ready);
+        mng
+    }
+
+    fn notify(mng: &ServiceMng, key: &str) {
+        let ucred = UnixCredentials::new();
+        let mut messages = HashMap::new();
+        messages.insert(key, "1");
+        mng.notify_message(&ucred, &messages, &Vec::new()).unwrap();
+    }
+
+    fn timer_armed(mng: &ServiceMng) -> bool {
+        match mng.rd.timer() {
+            Some(timer) => timer.armed(),
+            None => false,
+        }
+    }
+
+    #[test]
+    fn test_notify_reload() {
+        let mng = mng_for_test();
+
+        // the reload signal is sent, and the reload waits for READY=1
+        mng.enter_reload();
+        assert_eq!(mng.state(), ServiceState::Reload);
+        assert_eq!(mng.rd.notify_state(), NotifyState::Reloading);
+        assert!(timer_armed(&mng));
+
+        // RELOADING=1 during the reload changes nothing
+        notify(&mng, "RELOADING");
+        assert_eq!(mng.state(), ServiceState::Reload);
+
+        notify(&mng, "READY");
+        assert_eq!(mng.state(), ServiceState::Runing);
+        assert_eq!(mng.rd.notify_state(), NotifyState::Ready);
+        assert!(!timer_armed(&mng));
+    }
+
+    #[test]
+    fn test_notify_reload_by_service() {
+        let mng = mng_for_test();
+
+        // the reload initiated by the service itself
+        notify(&mng, "RELOADING");
+        assert_eq!(mng.state(), ServiceState::Reload);
+        assert_eq!(mng.rd.notify_state(), NotifyState::Reloading);
+        assert!(timer_armed(&mng));
+
+        notify(&mng, "READY");
+        assert_eq!(mng.state(), ServiceState::Runing);
+        assert!(!timer_armed(&mng));
+    }
+
+    #[test]
+    fn test_notify_reload_timeout() {
+        let mng = mng_for_test();
+        mng.enter_reload();
+        assert_eq!(mng.state(), ServiceState::Reload);
+
+        // the service never sends READY=1, the reload is given up when timed out
+        mng.dispatch_timer();
+        assert_eq!(mng.state(), ServiceState::Runing);
+        assert_eq!(mng.rd.notify_state(), NotifyState::Ready);
+        assert!(!timer_armed(&mng));
+    }
+
+    #[test]
+    fn test_notify_reload_invalid_signal() {
+        let mng = mng_for_test();
+        mng.config.config_data().borrow_mut().Service.ReloadSignal = Some("SIGNOSUCH".to_string());
+
+        // the reload signal can not be sent, nothing to wait for
+        mng.enter_reload();
+        assert_eq!(mng.state(), ServiceState::Runing);
+        assert_eq!(mng.rd.notify_state(), NotifyState::Ready);
+        assert!(!timer_armed(&mng));
+    }
+}
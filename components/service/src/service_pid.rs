@@ -29,7 +29,9 @@ impl ServicePid {
     pub(super) fn unwatch_main(&self) {
         match self.main() {
             Some(pid) => {
-                self.comm.um().child_unwatch_pid(pid);
+                if let Some(um) = self.comm.um() {
+                    um.child_unwatch_pid(pid);
+                }
                 self.data.borrow_mut().reset_main();
             }
             None => {}
@@ -47,7 +49,9 @@ impl ServicePid {
     pub(super) fn unwatch_control(&self) {
         match self.control() {
             Some(pid) => {
-                self.comm.um().child_unwatch_pid(pid);
+                if let Some(um) = self.comm.um() {
+                    um.child_unwatch_pid(pid);
+                }
                 self.data.borrow_mut().reset_control();
             }
             None => {}
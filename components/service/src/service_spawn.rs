@@ -3,17 +3,22 @@ use crate::service_config::ServiceConfig;
 
 use super::service_comm::ServiceComm;
 use super::service_pid::ServicePid;
-use nix::unistd::Pid;
+use nix::fcntl::OFlag;
+use nix::unistd::{self, Pid};
 use process1::manager::{ExecCommand, ExecContext, ExecFlags, ExecParameters};
+use std::cell::RefCell;
 use std::env;
 use std::error::Error;
+use std::os::unix::prelude::RawFd;
 use std::rc::Rc;
+use utils::fd_util;
 
 pub(super) struct ServiceSpawn {
     comm: Rc<ServiceComm>,
     pid: Rc<ServicePid>,
     config: Rc<ServiceConfig>,
     exec_ctx: Rc<ExecContext>,
+    exec_fd: RefCell<Option<RawFd>>,
 }
 
 impl ServiceSpawn {
@@ -28,6 +33,7 @@ impl ServiceSpawn {
             pid: Rc::clone(pidr),
             config: configr.clone(),
             exec_ctx: exec_ctx.clone(),
+            exec_fd: RefCell::new(None),
         }
     }
 
@@ -50,15 +56,17 @@ impl ServiceSpawn {
             params.add_env("MAINPID", format!("{}", pid));
         }
 
-        let unit = self.comm.unit();
-        let um = self.comm.um();
-        unit.prepare_exec()?;
+        let (unit, um) = match (self.comm.unit(), self.comm.um()) {
+            (Some(unit), Some(um)) => (unit, um),
+            _ => return Err("the service is not attached to the unit manager".into()),
+        };
+        unit.prepare_exec(&self.exec_ctx)?;
 
         if ec_flags.contains(ExecFlags::PASS_FDS) {
-            params.insert_fds(self.collect_socket_fds());
+            params.insert_fds(um.collect_socket_fds(unit.get_id()));
         }
 
-        if self.config.service_type() == ServiceType::Notify {
+        if self.config.service_type().is_notify() {
             let notify_sock = um.notify_socket().unwrap();
             log::debug!("add NOTIFY_SOCKET env: {}", notify_sock.to_str().unwrap());
             params.add_env(
@@ -68,8 +76,32 @@ impl ServiceSpawn {
             params.set_notify_sock(notify_sock);
         }
 
+        let main = !ec_flags.contains(ExecFlags::CONTROL);
+        let exec_fds = if main && self.config.service_type() == ServiceType::Exec {
+            let (r, w) = unistd::pipe2(OFlag::O_CLOEXEC)?;
+            params.set_exec_fd(w);
+            Some((r, w))
+        } else {
+            None
+        };
+
+        if main && self.config.service_type() == ServiceType::Idle {
+            params.set_idle(true);
+        }
+
         log::debug!("begin to exec spawn");
-        match um.exec_spawn(&unit, cmdline, &params, self.exec_ctx.clone()) {
+        let ret = um.exec_spawn(&unit, cmdline, &params, self.exec_ctx.clone());
+        if let Some((r, w)) = exec_fds {
+            // only the child holds the write end, so the read end sees EOF once execve succeeded.
+            fd_util::close(w);
+            if ret.is_ok() {
+                *self.exec_fd.borrow_mut() = Some(r);
+            } else {
+                fd_util::close(r);
+            }
+        }
+
+        match ret {
             Ok(pid) => {
                 um.child_watch_pid(pid, unit.get_id());
                 Ok(pid)
@@ -81,7 +113,8 @@ impl ServiceSpawn {
         }
     }
 
-    fn collect_socket_fds(&self) -> Vec<i32> {
-        self.comm.um().collect_socket_fds(self.comm.unit().get_id())
+    /// the read end of the pipe for Type=exec service, which is taken by the caller after spawn.
+    pub(super) fn take_exec_fd(&self) -> Option<RawFd> {
+        self.exec_fd.take()
     }
 }
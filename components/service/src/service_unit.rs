@@ -10,17 +10,20 @@ use nix::sys::signal::Signal;
 use nix::sys::socket::UnixCredentials;
 use nix::unistd::Pid;
 use process1::manager::{
-    ExecContext, Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj,
-    UnitRelations, UnitSubClass,
+    ExecContext, ExecDirectory, ExecDirectoryType, ExecPreserveMode, KillContext, KillMode, Unit,
+    UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj, UnitRelations,
+    UnitSubClass,
 };
 
 use std::collections::HashMap;
 use std::error::Error;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::str::FromStr;
 
 use utils::error::Error as ServiceError;
 use utils::logger;
+use utils::process_util;
 
 struct ServiceUnit {
     comm: Rc<ServiceComm>,
@@ -28,6 +31,7 @@ struct ServiceUnit {
     mng: Rc<ServiceMng>,
     monitor: ServiceMonitor,
     exec_ctx: Rc<ExecContext>,
+    kill_ctx: Rc<KillContext>,
 }
 
 impl UnitObj for ServiceUnit {
@@ -97,6 +101,10 @@ impl UnitObj for ServiceUnit {
         todo!()
     }
 
+    fn monitor_envs(&self) -> Vec<(String, String)> {
+        self.mng.monitor_envs()
+    }
+
     fn current_active_state(&self) -> UnitActiveState {
         self.mng.current_active_state()
     }
@@ -138,9 +146,10 @@ impl ServiceUnit {
         let comm = Rc::new(ServiceComm::new());
         let config = Rc::new(ServiceConfig::new());
         let context = Rc::new(ExecContext::new());
+        let kill_ctx = Rc::new(KillContext::new());
 
         let rt = Rc::new(RunningData::new());
-        let mng = Rc::new(ServiceMng::new(&comm, &config, &rt, &context));
+        let mng = Rc::new(ServiceMng::new(&comm, &config, &rt, &context, &kill_ctx));
         rt.attach_mng(mng.clone());
         ServiceUnit {
             comm: Rc::clone(&comm),
@@ -148,6 +157,7 @@ impl ServiceUnit {
             mng: mng.clone(),
             monitor: ServiceMonitor::new(&config),
             exec_ctx: context.clone(),
+            kill_ctx: kill_ctx.clone(),
         }
     }
 
@@ -167,18 +177,32 @@ impl ServiceUnit {
             None => {}
         }
 
+        self.exec_ctx.set_namespace(self.config.namespace());
+
+        self.parse_exec_directories()?;
+
+        self.parse_kill_context()?;
+
+        let unit = match self.comm.unit() {
+            Some(unit) => unit,
+            None => return Ok(()),
+        };
+
+        // RebootArgument= is used by the emergency actions of the unit, such as FailureAction=reboot
+        if let Some(arg) = &self.config.config_data().borrow().Service.RebootArgument {
+            unit.set_reboot_argument(arg);
+        }
+
         match self.config.sockets() {
             Some(sockets) => {
                 for socket in sockets {
-                    self.comm.unit().insert_two_deps(
+                    unit.insert_two_deps(
                         UnitRelations::UnitWants,
                         UnitRelations::UnitAfter,
                         socket.to_string(),
                     );
 
-                    self.comm
-                        .unit()
-                        .insert_dep(UnitRelations::UnitTriggeredBy, socket.clone());
+                    unit.insert_dep(UnitRelations::UnitTriggeredBy, socket.clone());
                 }
             }
             None => {}
@@ -187,11 +211,86 @@ impl ServiceUnit {
         Ok(())
     }
 
+    fn parse_exec_directories(&self) -> Result<(), Box<dyn Error>> {
+        let data = self.config.config_data();
+        self.exec_ctx.set_user(data.borrow().Service.User.clone());
+        self.exec_ctx.set_group(data.borrow().Service.Group.clone());
+
+        for dt in ExecDirectoryType::iter() {
+            let (paths, mode) = self.config.directory(dt);
+            let paths = match paths {
+                Some(p) if !p.is_empty() => p,
+                _ => continue,
+            };
+
+            let mode = match mode {
+                Some(m) => u32::from_str_radix(m.trim(), 8)
+                    .map_err(|_e| format!("invalid directory mode: {}", m))?,
+                None => 0o755,
+            };
+
+            self.exec_ctx
+                .insert_directory(dt, ExecDirectory::new(paths, mode)?);
+        }
+
+        if let Some(preserve) = &data.borrow().Service.RuntimeDirectoryPreserve {
+            self.exec_ctx
+                .set_runtime_directory_preserve(ExecPreserveMode::from_str(preserve)?);
+        }
+
+        Ok(())
+    }
+
+    fn parse_kill_context(&self) -> Result<(), Box<dyn Error>> {
+        let config = self.config.config_data();
+        let data = config.borrow();
+        let service = &data.Service;
+        let signal = |s: &String| -> Result<Signal, Box<dyn Error>> {
+            process_util::parse_signal(s).ok_or_else(|| format!("invalid signal: {}", s).into())
+        };
+
+        if let Some(mode) = &service.KillMode {
+            self.kill_ctx.set_kill_mode(KillMode::from_str(mode)?);
+        }
+
+        if let Some(sig) = &service.KillSignal {
+            self.kill_ctx.set_kill_signal(signal(sig)?);
+        }
+
+        if let Some(sig) = &service.RestartKillSignal {
+            self.kill_ctx.set_restart_kill_signal(Some(signal(sig)?));
+        }
+
+        if let Some(sig) = &service.FinalKillSignal {
+            self.kill_ctx.set_final_kill_signal(signal(sig)?);
+        }
+
+        if let Some(send) = service.SendSIGKILL {
+            self.kill_ctx.set_send_sigkill(send);
+        }
+
+        if let Some(send) = service.SendSIGHUP {
+            self.kill_ctx.set_send_sighup(send);
+        }
+
+        Ok(())
+    }
+
     pub fn service_add_extras(&self) -> Result<(), Box<dyn Error>> {
-        if self.config.service_type() == ServiceType::Notify {
+        if self.config.service_type().is_notify() {
             self.config.set_notify_access(NotifyAccess::Main);
         }
 
+        let unit = match self.comm.unit() {
+            Some(unit) => unit,
+            None => return Ok(()),
+        };
+        let slice = match &self.config.config_data().borrow().Service.Slice {
+            Some(s) => s.clone(),
+            None => unit.default_slice(),
+        };
+        unit.set_slice(&slice)?;
+
         Ok(())
     }
 
@@ -212,6 +311,19 @@ impl ServiceUnit {
                 .is_none()
         {}
 
+        if self.config.service_type() == ServiceType::NotifyReload
+            && self.config.reload_signal().is_none()
+        {
+            return Err(Box::new(ServiceError::Other {
+                msg: "invalid ReloadSignal is configured",
+            }));
+        }
+
+        self.config.restart()?;
+        self.config.success_exit_status()?;
+        self.config.restart_prevent_exit_status()?;
+        self.config.restart_force_exit_status()?;
+
         if self.config.service_type() != ServiceType::Oneshot
             && self.config.get_exec_cmds(ServiceCommand::Start).is_none()
         {
@@ -0,0 +1,58 @@
+//! slice是process1的资源分组类型，对应cgroup树中的一个节点，slice中的unit及其嵌套的slice共享slice的资源限制。
+//! slice配置文件包含Unit、Slice、Install三个Section，配置文件是可选的，没有配置文件的slice使用默认配置。
+//!
+//! slice的名称表示其嵌套关系，以"-"分隔父slice，如a-b.slice嵌套在a.slice中，对应的cgroup为a.slice/a-b.slice，
+//! 根slice为-.slice，对应cgroup的根节点，不能被停止。
+//! 内置的slice包括-.slice、system.slice(系统服务)、user.slice(用户会话)和machine.slice(虚拟机和容器)。
+//!
+//! # Example:
+//! ```toml
+//! [Unit]
+//! Description="the slice of the batch jobs"
+//!
+//! [Slice]
+//! CPUWeight=50
+//! MemoryMax=1073741824
+//! TasksMax=512
+//! ```
+
+//! [Slice] section相关的配置
+//!
+//! CPUWeight
+//!
+//! CPU的相对权重，取值范围为1到10000，对应cpu.weight。
+//!
+//! CPUQuota
+//!
+//! CPU的时间配额，为单个CPU的百分比，如"150%"，对应cpu.max。
+//!
+//! MemoryMin、MemoryLow、MemoryHigh、MemoryMax、MemorySwapMax
+//!
+//! 内存的保护和限制，单位为字节，分别对应memory.min、memory.low、memory.high、memory.max、memory.swap.max。
+//!
+//! TasksMax
+//!
+//! 进程数的上限，对应pids.max。
+//!
+//! 资源限制仅在cgroup v2下生效，作用于slice中的所有unit及其嵌套的slice。
+//!
+//! ## 自动依赖
+//!
+//! ### 隐含依赖
+//! slice对其父slice添加Requires=和After=依赖。
+//! service和socket通过Slice=放入指定的slice，默认为system.slice，模板实例默认为system-<模板名>.slice，
+//! 并对slice添加Requires=和After=依赖。
+
+// dependency:
+// slice_comm -> slice_config
+// {slice_mng | slice_load} -> slice_unit
+
+mod slice_comm;
+
+mod slice_config;
+
+mod slice_mng;
+
+mod slice_load;
+
+mod slice_unit;
@@ -0,0 +1,83 @@
+//! slice_config模块slice类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+
+pub(super) struct SliceConfig {
+    data: Rc<RefCell<SliceConfigData>>,
+}
+
+impl SliceConfig {
+    pub(super) fn new() -> Self {
+        SliceConfig {
+            data: Rc::new(RefCell::new(SliceConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = SliceConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<SliceConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SliceConfigData {
+    #[config(nested)]
+    pub Slice: SectionSlice,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionSlice {
+    pub CPUWeight: Option<u64>,
+    pub CPUQuota: Option<String>,
+    pub MemoryMin: Option<u64>,
+    pub MemoryLow: Option<u64>,
+    pub MemoryHigh: Option<u64>,
+    pub MemoryMax: Option<u64>,
+    pub MemorySwapMax: Option<u64>,
+    pub TasksMax: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::slice_config::SliceConfig;
+    use utils::test_util::get_project_root;
+
+    #[test]
+    fn test_slice_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.slice.toml");
+        let paths = vec![file_path];
+
+        let config = SliceConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let slice = &data.borrow().Slice;
+        assert_eq!(slice.CPUWeight, Some(50));
+        assert_eq!(slice.CPUQuota, Some("150%".to_string()));
+        assert_eq!(slice.MemoryMax, Some(1073741824));
+        assert_eq!(slice.TasksMax, Some(512));
+        assert_eq!(slice.MemoryMin, None);
+
+        // the slices without unit files are loaded with the default settings
+        let config = SliceConfig::new();
+        assert!(config.load(&[]).is_ok());
+        assert_eq!(config.config_data().borrow().Slice.MemoryMax, None);
+    }
+}
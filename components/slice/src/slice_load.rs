@@ -0,0 +1,90 @@
+//! slice_load模块实现slice配置文件的解析，以及对父slice的依赖。
+//!
+
+use std::{error::Error, rc::Rc};
+
+use utils::unit_name;
+
+use crate::slice_comm::SliceComm;
+use crate::slice_config::SliceConfig;
+use crate::slice_mng::SliceMng;
+
+pub(super) struct SliceLoad {
+    config: Rc<SliceConfig>,
+    comm: Rc<SliceComm>,
+    mng: Rc<SliceMng>,
+}
+
+impl SliceLoad {
+    pub(super) fn new(
+        configr: &Rc<SliceConfig>,
+        commr: &Rc<SliceComm>,
+        mngr: &Rc<SliceMng>,
+    ) -> Self {
+        SliceLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse slice section");
+        let data = self.config.config_data();
+        let slice = &data.borrow().Slice;
+        let mut resources = Vec::new();
+
+        if let Some(v) = slice.CPUWeight {
+            if !(1..=10000).contains(&v) {
+                return Err(format!("invalid CPUWeight {}", v).into());
+            }
+            resources.push(("cpu.weight", v.to_string()));
+        }
+
+        if let Some(v) = &slice.CPUQuota {
+            let max = cgroup::cg_cpu_quota(v).ok_or_else(|| format!("invalid CPUQuota {}", v))?;
+            resources.push(("cpu.max", max));
+        }
+
+        for (attr, value) in [
+            ("memory.min", slice.MemoryMin),
+            ("memory.low", slice.MemoryLow),
+            ("memory.high", slice.MemoryHigh),
+            ("memory.max", slice.MemoryMax),
+            ("memory.swap.max", slice.MemorySwapMax),
+        ] {
+            if let Some(v) = value {
+                resources.push((attr, v.to_string()));
+            }
+        }
+
+        if let Some(v) = slice.TasksMax {
+            resources.push(("pids.max", v.to_string()));
+        }
+
+        self.mng.set_resources(resources);
+        Ok(())
+    }
+
+    pub(super) fn slice_add_extras(&self) -> Result<(), Box<dyn Error>> {
+        let unit = self.comm.unit();
+        // the slice is nested in its parent, which is derived from the name
+        if let Some(parent) = unit_name::slice_name_parent(unit.get_id()) {
+            if !self.comm.um().load_unit_success(&parent) {
+                return Err(format!("failed to load slice {}", parent).into());
+            }
+            unit.set_slice(&parent)?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn slice_verify(&self) -> Result<(), Box<dyn Error>> {
+        let id = self.comm.unit().get_id().to_string();
+        if !unit_name::slice_name_is_valid(&id) {
+            return Err(format!("invalid slice name {}", id).into());
+        }
+
+        Ok(())
+    }
+}
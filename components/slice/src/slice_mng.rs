@@ -0,0 +1,120 @@
+//! slice_mng模块是slice类型的核心逻辑，维护slice的状态，启动时创建slice的cgroup并设置资源限制。
+//!
+
+use std::{cell::RefCell, rc::Rc};
+
+use process1::manager::{UnitActionError, UnitActiveState, UnitNotifyFlags};
+use utils::IN_SET;
+
+use crate::slice_comm::SliceComm;
+
+// the root slice, which can not be stopped
+const ROOT_SLICE: &str = "-.slice";
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum SliceState {
+    Dead,
+    Active,
+}
+
+impl SliceState {
+    fn to_unit_active_state(self) -> UnitActiveState {
+        match self {
+            SliceState::Dead => UnitActiveState::UnitInActive,
+            SliceState::Active => UnitActiveState::UnitActive,
+        }
+    }
+}
+
+pub(super) struct SliceMng {
+    comm: Rc<SliceComm>,
+    state: RefCell<SliceState>,
+    // the resource settings, each one is the attribute of the cgroup and its value
+    resources: RefCell<Vec<(&'static str, String)>>,
+}
+
+impl SliceMng {
+    pub(super) fn new(commr: &Rc<SliceComm>) -> SliceMng {
+        SliceMng {
+            comm: Rc::clone(commr),
+            state: RefCell::new(SliceState::Dead),
+            resources: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(super) fn set_resources(&self, resources: Vec<(&'static str, String)>) {
+        *self.resources.borrow_mut() = resources;
+    }
+
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), SliceState::Active) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        self.realize();
+        self.set_state(SliceState::Active);
+    }
+
+    pub(super) fn stop_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), SliceState::Dead) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+
+        if self.comm.unit().get_id() == ROOT_SLICE {
+            return Err(UnitActionError::UnitActionEOpNotSupp);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        self.set_state(SliceState::Dead);
+    }
+
+    pub(super) fn current_active_state(&self) -> UnitActiveState {
+        self.state().to_unit_active_state()
+    }
+
+    // create the cgroup of the slice, and apply the resource settings, which
+    // limit all the units nested in the slice.
+    fn realize(&self) {
+        let unit = self.comm.unit();
+        if let Err(e) = unit.cg_realize() {
+            log::warn!("failed to create the cgroup of {}: {}", unit.get_id(), e);
+            return;
+        }
+
+        let resources = self.resources.borrow();
+        if resources.is_empty() {
+            return;
+        }
+
+        if let Err(e) = cgroup::cg_set_resources(&unit.cg_path(), &resources) {
+            log::warn!("failed to set the resources of {}: {}", unit.get_id(), e);
+        }
+    }
+
+    fn set_state(&self, state: SliceState) {
+        let original_state = self.state.replace(state);
+        if original_state != state {
+            log::debug!(
+                "slice {} original state[{:?}] -> new state[{:?}]",
+                self.comm.unit().get_id(),
+                original_state,
+                state
+            );
+        }
+
+        self.comm.unit().notify(
+            original_state.to_unit_active_state(),
+            state.to_unit_active_state(),
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+    }
+
+    fn state(&self) -> SliceState {
+        *self.state.borrow()
+    }
+}
@@ -0,0 +1,99 @@
+//! SliceUnit是slice类型的总入口，需要实现UnitObj,UnitMngUtil,以及UnitSubClass三个trait,
+//! UnitObj是Unit的抽象，定义对process1提供的具体行为，
+//! UnitMngUtil是为了关联subUnit和Manger，由于rust不支持继承和多态，因此需要采用这种方式来间接支持
+//! UnitSubClass为了实现SubUnit到UnitObj的转换，简介达成多态的目的
+
+use process1::manager::{
+    Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj, UnitSubClass,
+};
+use std::{error::Error, path::PathBuf, rc::Rc};
+
+use crate::{
+    slice_comm::SliceComm, slice_config::SliceConfig, slice_load::SliceLoad, slice_mng::SliceMng,
+};
+use utils::logger;
+
+// the structure of the slice unit type
+struct SliceUnit {
+    comm: Rc<SliceComm>,
+    config: Rc<SliceConfig>,
+    mng: Rc<SliceMng>,
+    load: SliceLoad,
+}
+
+impl UnitObj for SliceUnit {
+    fn load(&self, paths: &Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        log::debug!("slice begin to load conf file");
+        self.config.load(paths)?;
+
+        self.load.parse()?;
+
+        self.load.slice_add_extras()?;
+
+        self.load.slice_verify()
+    }
+
+    // the function entrance to start the unit
+    fn start(&self) -> Result<(), UnitActionError> {
+        self.mng.start_check()?;
+
+        self.mng.start_action();
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), UnitActionError> {
+        self.mng.stop_check()?;
+
+        self.mng.stop_action();
+        Ok(())
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.current_active_state()
+    }
+
+    fn attach_unit(&self, unit: Rc<Unit>) {
+        self.comm.attach_unit(unit);
+    }
+}
+
+// attach the UnitManager for weak reference
+impl UnitMngUtil for SliceUnit {
+    fn attach(&self, um: Rc<UnitManager>) {
+        self.comm.attach_um(um);
+    }
+}
+
+impl UnitSubClass for SliceUnit {
+    fn into_unitobj(self: Box<Self>) -> Box<dyn UnitObj> {
+        Box::new(*self)
+    }
+}
+
+impl SliceUnit {
+    fn new() -> SliceUnit {
+        let comm = Rc::new(SliceComm::new());
+        let config = Rc::new(SliceConfig::new());
+        let mng = Rc::new(SliceMng::new(&comm));
+        SliceUnit {
+            comm: Rc::clone(&comm),
+            config: Rc::clone(&config),
+            mng: Rc::clone(&mng),
+            load: SliceLoad::new(&config, &comm, &mng),
+        }
+    }
+}
+
+impl Default for SliceUnit {
+    fn default() -> Self {
+        SliceUnit::new()
+    }
+}
+
+const LOG_LEVEL: u32 = 4;
+const PLUGIN_NAME: &str = "SliceUnit";
+
+use process1::declure_unitobj_plugin;
+
+// define the method to create the instance of the unit
+declure_unitobj_plugin!(SliceUnit, SliceUnit::default, PLUGIN_NAME, LOG_LEVEL);
@@ -65,6 +65,10 @@
 //! SocketMode
 //!
 //! 设置创建文件节点时的访问模式，适用于unix套接字时创建的文件。
+//!
+//! Slice
+//!
+//! socket执行ExecStartPre等命令时所在的slice，默认为system.slice，模板实例默认为system-<模板名>.slice。
 
 // dependency:
 // socket_base -> {socket_comm | socket_config}
@@ -11,6 +11,9 @@ use process1::manager::ExecCommand;
 
 use crate::socket_base::SocketCommand;
 
+const DEFAULT_TIMEOUT_SEC: u64 = 90;
+const USEC_PER_SEC: u64 = 1_000_000;
+
 pub(super) enum ListeningItem {
     Stream,
     Datagram,
@@ -47,6 +50,16 @@ impl SocketConfig {
     pub(super) fn get_exec_cmds(&self, cmd_type: SocketCommand) -> Option<Vec<ExecCommand>> {
         self.data.borrow().get_exec_cmds(cmd_type)
     }
+
+    /// TimeoutStopSec= applies to each step of killing the socket processes, in useconds.
+    pub(super) fn timeout_stop_usec(&self) -> u64 {
+        self.data
+            .borrow()
+            .Socket
+            .TimeoutStopSec
+            .unwrap_or(DEFAULT_TIMEOUT_SEC)
+            * USEC_PER_SEC
+    }
 }
 
 #[derive(Config, Default, Debug)]
@@ -80,6 +93,14 @@ pub(crate) struct SectionSocket {
     pub Symlinks: Option<Vec<String>>,
     pub PassSecurity: Option<bool>,
     pub SocketMode: Option<u32>,
+    pub KillMode: Option<String>,
+    pub KillSignal: Option<String>,
+    pub RestartKillSignal: Option<String>,
+    pub FinalKillSignal: Option<String>,
+    pub SendSIGKILL: Option<bool>,
+    pub SendSIGHUP: Option<bool>,
+    pub TimeoutStopSec: Option<u64>,
+    pub Slice: Option<String>,
 }
 
 impl SocketConfigData {
@@ -100,7 +121,7 @@ mod tests {
     use std::{
         env,
         ffi::OsString,
-        fs::read_dir,
+        fs::{self, read_dir},
         io::{self, ErrorKind},
         path::PathBuf,
     };
@@ -118,6 +139,32 @@ mod tests {
         assert_eq!(result.is_err(), false);
     }
 
+    #[test]
+    fn test_socket_timeout_stop() {
+        let dir = env::temp_dir().join(format!("socket_timeout_stop_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("test.socket.toml");
+
+        let config = SocketConfig::new();
+        fs::write(&file_path, "[Socket]\nListenStream=\"31972\"\n").unwrap();
+        config.load(&vec![file_path.clone()]).unwrap();
+        assert_eq!(config.timeout_stop_usec(), 90 * 1_000_000);
+
+        fs::write(
+            &file_path,
+            "[Socket]\nListenStream=\"31972\"\nTimeoutStopSec=5\nRestartKillSignal=\"SIGINT\"\n",
+        )
+        .unwrap();
+        config.load(&vec![file_path.clone()]).unwrap();
+        assert_eq!(config.timeout_stop_usec(), 5 * 1_000_000);
+        assert_eq!(
+            config.config_data().borrow().Socket.RestartKillSignal,
+            Some("SIGINT".to_string())
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     fn get_project_root() -> io::Result<PathBuf> {
         let path = env::current_dir()?;
         let mut path_ancestors = path.as_path().ancestors();
@@ -1,15 +1,17 @@
 //! socket_load模块实现socket配置文件的加载解析。
 //!
 
+use nix::sys::signal::Signal;
 use nix::sys::socket::{
     NetlinkAddr, SockProtocol, SockType, SockaddrIn, SockaddrIn6, SockaddrLike, UnixAddr,
 };
-use process1::manager::{UnitRelations, UnitType};
+use process1::manager::{KillContext, KillMode, UnitRelations, UnitType};
 use std::cell::RefCell;
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::{error::Error, rc::Rc};
-use utils::socket_util;
+use utils::{process_util, socket_util};
 
 use crate::socket_base::{NetlinkProtocol, PortType};
 use crate::socket_comm::SocketComm;
@@ -21,6 +23,7 @@ pub(super) struct SocketLoad {
     config: Rc<SocketConfig>,
     comm: Rc<SocketComm>,
     ports: Rc<SocketPorts>,
+    kill_ctx: Rc<KillContext>,
 }
 
 impl SocketLoad {
@@ -28,11 +31,13 @@ impl SocketLoad {
         configr: &Rc<SocketConfig>,
         commr: &Rc<SocketComm>,
         ports: &Rc<SocketPorts>,
+        kill_ctx: &Rc<KillContext>,
     ) -> Self {
         SocketLoad {
             config: configr.clone(),
             comm: commr.clone(),
             ports: ports.clone(),
+            kill_ctx: kill_ctx.clone(),
         }
     }
 
@@ -73,6 +78,60 @@ impl SocketLoad {
 
         self.parse_socket_service(mng)?;
 
+        self.parse_kill_context(socket_conf.clone())?;
+
+        self.parse_slice(socket_conf)?;
+
+        Ok(())
+    }
+
+    fn parse_slice(
+        &self,
+        socket_conf: Rc<RefCell<SocketConfigData>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let unit = self.comm.unit();
+        let slice = match &socket_conf.borrow().Socket.Slice {
+            Some(s) => s.clone(),
+            None => unit.default_slice(),
+        };
+
+        unit.set_slice(&slice)
+    }
+
+    fn parse_kill_context(
+        &self,
+        socket_conf: Rc<RefCell<SocketConfigData>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let data = socket_conf.borrow();
+        let socket = &data.Socket;
+        let signal = |s: &String| -> Result<Signal, Box<dyn Error>> {
+            process_util::parse_signal(s).ok_or_else(|| format!("invalid signal: {}", s).into())
+        };
+
+        if let Some(mode) = &socket.KillMode {
+            self.kill_ctx.set_kill_mode(KillMode::from_str(mode)?);
+        }
+
+        if let Some(sig) = &socket.KillSignal {
+            self.kill_ctx.set_kill_signal(signal(sig)?);
+        }
+
+        if let Some(sig) = &socket.RestartKillSignal {
+            self.kill_ctx.set_restart_kill_signal(Some(signal(sig)?));
+        }
+
+        if let Some(sig) = &socket.FinalKillSignal {
+            self.kill_ctx.set_final_kill_signal(signal(sig)?);
+        }
+
+        if let Some(send) = socket.SendSIGKILL {
+            self.kill_ctx.set_send_sigkill(send);
+        }
+
+        if let Some(send) = socket.SendSIGHUP {
+            self.kill_ctx.set_send_sighup(send);
+        }
+
         Ok(())
     }
 
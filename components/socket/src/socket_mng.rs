@@ -1,15 +1,24 @@
 //! socket_mng模块是socket类型的核心逻辑，主要实现socket端口的管理，子进程的拉起及子类型的状态管理。
 //!
 
-use std::{cell::RefCell, path::Path, rc::Rc};
+use std::{
+    cell::RefCell,
+    path::Path,
+    rc::{Rc, Weak},
+};
 
-use event::EventState;
-use nix::{sys::signal::Signal, unistd::Pid};
+use event::{EventState, EventType, Events, Source};
+use nix::{
+    libc,
+    sys::signal::Signal,
+    time::{clock_gettime, ClockId},
+    unistd::Pid,
+};
 use process1::manager::{
-    ExecCommand, ExecContext, KillOperation, UnitActionError, UnitActiveState, UnitNotifyFlags,
-    UnitRef, UnitType,
+    ExecCommand, ExecContext, KillContext, KillOperation, UnitActionError, UnitActiveState,
+    UnitNotifyFlags, UnitRef, UnitType,
 };
-use utils::IN_SET;
+use utils::{Error, IN_SET};
 
 use crate::{
     socket_base::SocketCommand, socket_comm::SocketComm, socket_config::SocketConfig,
@@ -56,13 +65,11 @@ impl SocketState {
         }
     }
 
-    fn to_kill_operation(&self) -> KillOperation {
+    /// RestartKillSignal= is sent instead of KillSignal= when stopping for a restart job.
+    fn to_kill_operation(&self, restarting: bool) -> KillOperation {
         match self {
-            SocketState::StopPreSigterm => {
-                // todo!() check has a restart job
-                KillOperation::KillKill
-            }
-            SocketState::FinalSigterm => KillOperation::KillTerminate,
+            SocketState::StopPreSigterm if restarting => KillOperation::KillRestart,
+            SocketState::StopPreSigterm | SocketState::FinalSigterm => KillOperation::KillTerminate,
             _ => KillOperation::KillKill,
         }
     }
@@ -96,6 +103,8 @@ pub(super) struct SocketMng {
     control_command: RefCell<Vec<ExecCommand>>,
     refused: RefCell<i32>,
     service: RefCell<UnitRef>,
+    kill_ctx: Rc<KillContext>,
+    timer: Rc<SocketTimer>,
 }
 
 impl SocketMng {
@@ -104,9 +113,10 @@ impl SocketMng {
         configr: &Rc<SocketConfig>,
         ports: &Rc<SocketPorts>,
         exec_ctx: &Rc<ExecContext>,
-    ) -> SocketMng {
+        kill_ctx: &Rc<KillContext>,
+    ) -> Rc<SocketMng> {
         let pid = Rc::new(SocketPid::new(commr));
-        SocketMng {
+        let mng = Rc::new(SocketMng {
             comm: commr.clone(),
             config: configr.clone(),
             ports: ports.clone(),
@@ -117,7 +127,12 @@ impl SocketMng {
             pid: pid.clone(),
             refused: RefCell::new(0),
             service: RefCell::new(UnitRef::new()),
-        }
+            kill_ctx: kill_ctx.clone(),
+            timer: Rc::new(SocketTimer::new()),
+        });
+
+        mng.timer.attach(Rc::downgrade(&mng));
+        mng
     }
 
     pub(super) fn set_ref(&self, source: String, target: String) {
@@ -356,6 +371,7 @@ impl SocketMng {
                         return;
                     }
                 }
+                self.enable_timer(self.config.timeout_stop_usec());
                 self.set_state(SocketState::StopPre);
             }
             None => self.enter_stop_post(SocketResult::Success),
@@ -386,6 +402,7 @@ impl SocketMng {
                         return;
                     }
                 }
+                self.enable_timer(self.config.timeout_stop_usec());
                 self.set_state(SocketState::StopPost);
             }
             None => self.enter_signal(SocketState::FinalSigterm, SocketResult::Success),
@@ -398,9 +415,25 @@ impl SocketMng {
             self.set_result(res);
         }
 
-        let op = state.to_kill_operation();
-        match self.comm.unit().kill_context(None, self.pid.control(), op) {
-            Ok(_) => {}
+        let restarting = self.comm.um().has_restart_job(self.comm.unit().get_id());
+        let op = state.to_kill_operation(restarting);
+        let ret = if op == KillOperation::KillKill && !self.kill_ctx.send_sigkill() {
+            log::debug!("SendSIGKILL= is disabled, skip killing the socket processes");
+            Ok(false)
+        } else {
+            self.comm
+                .unit()
+                .kill_context(&self.kill_ctx, None, self.pid.control(), op)
+        };
+
+        match ret {
+            Ok(true) => {
+                // wait for the killed processes, or escalate when TimeoutStopSec= is elapsed.
+                self.enable_timer(self.config.timeout_stop_usec());
+                self.set_state(state);
+                return;
+            }
+            Ok(false) => {}
             Err(_e) => {
                 if IN_SET!(
                     state,
@@ -523,6 +556,18 @@ impl SocketMng {
             self.unwatch_fds();
         }
 
+        if !IN_SET!(
+            state,
+            SocketState::StopPre,
+            SocketState::StopPreSigterm,
+            SocketState::StopPreSigkill,
+            SocketState::StopPost,
+            SocketState::FinalSigterm,
+            SocketState::FinalSigkill
+        ) {
+            self.disable_timer();
+        }
+
         if !vec![
             SocketState::StartChown,
             SocketState::StartPost,
@@ -569,6 +614,61 @@ impl SocketMng {
     fn set_result(&self, res: SocketResult) {
         *self.result.borrow_mut() = res;
     }
+
+    fn dispatch_timer(&self) {
+        log::warn!(
+            "{} timed out in state {:?}",
+            self.comm.unit().get_id(),
+            self.state()
+        );
+        match self.state() {
+            SocketState::StopPre => {
+                self.enter_signal(SocketState::StopPreSigterm, SocketResult::FailureTimeout);
+            }
+            SocketState::StopPreSigterm => {
+                if self.kill_ctx.send_sigkill() {
+                    self.enter_signal(SocketState::StopPreSigkill, SocketResult::FailureTimeout);
+                } else {
+                    self.enter_stop_post(SocketResult::FailureTimeout);
+                }
+            }
+            SocketState::StopPreSigkill => {
+                self.enter_stop_post(SocketResult::FailureTimeout);
+            }
+            SocketState::StopPost => {
+                self.enter_signal(SocketState::FinalSigterm, SocketResult::FailureTimeout);
+            }
+            SocketState::FinalSigterm => {
+                if self.kill_ctx.send_sigkill() {
+                    self.enter_signal(SocketState::FinalSigkill, SocketResult::FailureTimeout);
+                } else {
+                    self.enter_dead(SocketResult::FailureTimeout);
+                }
+            }
+            SocketState::FinalSigkill => {
+                self.enter_dead(SocketResult::FailureTimeout);
+            }
+            _ => {}
+        }
+    }
+
+    fn enable_timer(&self, usec: u64) {
+        if !self.timer.registered() {
+            self.comm.um().register(self.timer.clone());
+            self.timer.set_registered();
+        }
+
+        log::debug!("enable socket timer after {} usec", usec);
+        self.timer.arm(usec);
+        self.comm.um().enable(self.timer.clone(), EventState::On);
+    }
+
+    fn disable_timer(&self) {
+        if self.timer.armed() {
+            self.timer.disarm();
+            self.comm.um().enable(self.timer.clone(), EventState::Off);
+        }
+    }
 }
 
 impl SocketMng {
@@ -623,3 +723,97 @@ impl SocketMng {
         }
     }
 }
+
+/// the timer of the socket, for the timeout of the stop commands and the killed processes.
+struct SocketTimer {
+    // the absolute time in monotonic clock, u64::MAX means the timer is disarmed.
+    deadline: RefCell<u64>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<SocketMng>>,
+}
+
+impl SocketTimer {
+    fn new() -> Self {
+        SocketTimer {
+            deadline: RefCell::new(u64::MAX),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<SocketMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn arm(&self, usec: u64) {
+        *self.deadline.borrow_mut() = monotonic_usec().saturating_add(usec);
+    }
+
+    fn disarm(&self) {
+        *self.deadline.borrow_mut() = u64::MAX;
+    }
+
+    fn armed(&self) -> bool {
+        *self.deadline.borrow() != u64::MAX
+    }
+
+    fn expired(&self) -> bool {
+        self.armed() && monotonic_usec() >= *self.deadline.borrow()
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    fn mng(&self) -> Option<Rc<SocketMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+fn monotonic_usec() -> u64 {
+    match clock_gettime(ClockId::CLOCK_MONOTONIC) {
+        Ok(ts) => ts.tv_sec() as u64 * 1_000_000 + ts.tv_nsec() as u64 / 1_000,
+        Err(_) => 0,
+    }
+}
+
+impl Source for SocketTimer {
+    fn event_type(&self) -> EventType {
+        EventType::TimerMonotonic
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn time(&self) -> u64 {
+        *self.deadline.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        // the timer queued before re-arming is expired earlier, ignore it.
+        if !self.expired() {
+            return Ok(0);
+        }
+
+        log::debug!("dispatch socket timer");
+        self.disarm();
+        if let Some(mng) = self.mng() {
+            mng.dispatch_timer();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
@@ -25,7 +25,7 @@ impl SocketSpawn {
 
         let unit = self.comm.unit();
         let um = self.comm.um();
-        unit.prepare_exec()?;
+        unit.prepare_exec(&self.exec_ctx)?;
         match um.exec_spawn(&unit, cmdline, &params, self.exec_ctx.clone()) {
             Ok(pid) => {
                 um.child_watch_pid(pid, unit.get_id());
@@ -5,8 +5,8 @@
 
 use nix::{sys::signal::Signal, unistd::Pid};
 use process1::manager::{
-    ExecContext, Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj,
-    UnitSubClass,
+    ExecContext, KillContext, Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil,
+    UnitObj, UnitSubClass,
 };
 use std::{error::Error, path::PathBuf, rc::Rc};
 
@@ -128,16 +128,17 @@ impl UnitSubClass for SocketUnit {
 impl SocketUnit {
     fn new() -> SocketUnit {
         let context = Rc::new(ExecContext::new());
+        let kill_ctx = Rc::new(KillContext::new());
         let _comm = Rc::new(SocketComm::new());
         let _config = Rc::new(SocketConfig::new());
         let ports = Rc::new(SocketPorts::new());
-        let mng = Rc::new(SocketMng::new(&_comm, &_config, &ports, &context));
+        let mng = SocketMng::new(&_comm, &_config, &ports, &context, &kill_ctx);
         SocketUnit {
             comm: Rc::clone(&_comm),
             config: Rc::clone(&_config),
             mng: mng.clone(),
             ports: ports.clone(),
-            load: SocketLoad::new(&_config, &_comm, &ports),
+            load: SocketLoad::new(&_config, &_comm, &ports, &kill_ctx),
         }
     }
 }
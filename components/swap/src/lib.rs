@@ -0,0 +1,54 @@
+//! swap是process1的交换设备类型，对应一个交换分区或交换文件，启动时通过swapon(2)激活，停止时通过swapoff(2)去激活。
+//! swap配置文件包含Unit、Swap、Install三个Section，swap的名称必须是What=路径转义后的名称，
+//! 如/dev/mapper/vg-swap对应dev-mapper-vg\x2dswap.swap。
+//! /etc/fstab中的swap条目由fstab生成对应的swap配置文件，并加入swap.target.wants中。
+//!
+//! process1监听/proc/swaps的变化，由其他进程激活或去激活的交换设备，其swap的状态也会随之变化。
+//!
+//! # Example:
+//! ```toml
+//! [Unit]
+//! Description="the swap partition"
+//!
+//! [Swap]
+//! What="/dev/mapper/vg-swap"
+//! Priority=10
+//! Options="discard"
+//! ```
+
+//! [Swap] section相关的配置
+//!
+//! What
+//!
+//! 交换设备或交换文件的路径，必须配置。
+//!
+//! Priority
+//!
+//! 交换设备的优先级，取值范围为0到32767，-1表示由内核分配，默认由内核分配。
+//!
+//! Options
+//!
+//! 以","分隔的选项，支持pri=和discard[=once|pages]，Priority=优先于pri=，其他选项被忽略，以兼容/etc/fstab的选项。
+//!
+//! ## 自动依赖
+//! ### 隐含依赖
+//! + What=为/dev下的设备时，对应的device，即BindsTo与After依赖，设备出现后才激活，拔出后停止。
+//!
+//! ### 默认依赖
+//! 如果没有设置DefaultDependencies = no，会默认增加如下依赖关系：
+//! + Before = swap.target
+//! + Conflicts = shutdown.target 与 Before = shutdown.target的依赖，关机时去激活交换设备
+
+// dependency:
+// swap_comm -> swap_config
+// {swap_mng | swap_load} -> swap_unit
+
+mod swap_comm;
+
+mod swap_config;
+
+mod swap_mng;
+
+mod swap_load;
+
+mod swap_unit;
@@ -0,0 +1,71 @@
+//! swap_config模块swap类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+
+pub(super) struct SwapConfig {
+    data: Rc<RefCell<SwapConfigData>>,
+}
+
+impl SwapConfig {
+    pub(super) fn new() -> Self {
+        SwapConfig {
+            data: Rc::new(RefCell::new(SwapConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = SwapConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<SwapConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SwapConfigData {
+    #[config(nested)]
+    pub Swap: SectionSwap,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionSwap {
+    pub What: Option<String>,
+    pub Priority: Option<i32>,
+    pub Options: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::swap_config::SwapConfig;
+    use utils::test_util::get_project_root;
+
+    #[test]
+    fn test_swap_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.swap.toml");
+        let paths = vec![file_path];
+
+        let config = SwapConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let swap = &data.borrow().Swap;
+        assert_eq!(swap.What, Some("/dev/mapper/vg-swap".to_string()));
+        assert_eq!(swap.Priority, Some(10));
+        assert_eq!(swap.Options, Some("discard".to_string()));
+    }
+}
@@ -0,0 +1,144 @@
+//! swap_load模块实现swap配置文件的解析，以及swap的默认依赖。
+//!
+
+use std::{error::Error, rc::Rc};
+
+use process1::manager::UnitRelations;
+use utils::{mount_util, unit_name};
+
+use crate::swap_comm::SwapComm;
+use crate::swap_config::SwapConfig;
+use crate::swap_mng::SwapMng;
+
+// the flags of swapon(2), defined in <linux/swap.h>
+const SWAP_FLAG_PREFER: i32 = 0x8000;
+const SWAP_FLAG_PRIO_MASK: i32 = 0x7fff;
+const SWAP_FLAG_DISCARD: i32 = 0x10000;
+const SWAP_FLAG_DISCARD_ONCE: i32 = 0x20000;
+const SWAP_FLAG_DISCARD_PAGES: i32 = 0x40000;
+
+const SWAP_TARGET: &str = "swap.target";
+const SHUTDOWN_TARGET: &str = "shutdown.target";
+
+pub(super) struct SwapLoad {
+    config: Rc<SwapConfig>,
+    comm: Rc<SwapComm>,
+    mng: Rc<SwapMng>,
+}
+
+impl SwapLoad {
+    pub(super) fn new(configr: &Rc<SwapConfig>, commr: &Rc<SwapComm>, mngr: &Rc<SwapMng>) -> Self {
+        SwapLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse swap section");
+        let data = self.config.config_data();
+        let swap = &data.borrow().Swap;
+
+        let what = swap
+            .What
+            .as_ref()
+            .ok_or_else(|| format!("swap {} has no What=", self.comm.unit().get_id()))?;
+        self.mng.set_what(&mount_util::what_to_node(what));
+
+        let flags = swap_flags(swap.Priority, swap.Options.as_deref().unwrap_or(""))?;
+        self.mng.set_flags(flags);
+        Ok(())
+    }
+
+    pub(super) fn swap_add_extras(&self) -> Result<(), Box<dyn Error>> {
+        let unit = self.comm.unit();
+
+        // the swap device is waited for, and the swap is stopped once it is unplugged
+        if let Some(what) = self.config.config_data().borrow().Swap.What.as_deref() {
+            unit.bind_to_device(what);
+        }
+
+        if !unit.default_dependencies() {
+            return Ok(());
+        }
+
+        // the swaps are activated before swap.target, and deactivated on shutdown
+        unit.insert_dep(UnitRelations::UnitBefore, SWAP_TARGET.to_string());
+        unit.insert_two_deps(
+            UnitRelations::UnitConflicts,
+            UnitRelations::UnitBefore,
+            SHUTDOWN_TARGET.to_string(),
+        );
+        Ok(())
+    }
+
+    pub(super) fn swap_verify(&self) -> Result<(), Box<dyn Error>> {
+        let id = self.comm.unit().get_id().to_string();
+        let data = self.config.config_data();
+        let what = data.borrow().Swap.What.clone().unwrap_or_default();
+        // the name of the swap is the escaped path of What=
+        let name = unit_name::unit_name_from_path(&what, "swap");
+        if name != id {
+            return Err(
+                format!("swap {} does not match What={}, expect {}", id, what, name).into(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+// Priority= takes precedence over the "pri=" in Options=, and "discard" in Options=
+// selects the discard policy, the other options are ignored.
+fn swap_flags(priority: Option<i32>, options: &str) -> Result<i32, String> {
+    let mut prio = priority;
+    let mut flags = 0;
+    for opt in options.split(',').map(|o| o.trim()) {
+        let (key, value) = match opt.split_once('=') {
+            Some((k, v)) => (k, Some(v)),
+            None => (opt, None),
+        };
+        match (key, value) {
+            ("pri", Some(v)) if prio.is_none() => {
+                prio = Some(v.parse().map_err(|_| format!("invalid pri={}", v))?);
+            }
+            ("discard", None) => flags |= SWAP_FLAG_DISCARD,
+            ("discard", Some("once")) => flags |= SWAP_FLAG_DISCARD | SWAP_FLAG_DISCARD_ONCE,
+            ("discard", Some("pages")) => flags |= SWAP_FLAG_DISCARD | SWAP_FLAG_DISCARD_PAGES,
+            ("discard", Some(v)) => return Err(format!("invalid discard={}", v)),
+            _ => {}
+        }
+    }
+
+    match prio {
+        // a negative priority is assigned by the kernel
+        None | Some(-1) => {}
+        Some(p) if (0..=SWAP_FLAG_PRIO_MASK).contains(&p) => {
+            flags |= SWAP_FLAG_PREFER | (p & SWAP_FLAG_PRIO_MASK)
+        }
+        Some(p) => return Err(format!("invalid swap priority {}", p)),
+    }
+    Ok(flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_flags() {
+        assert_eq!(swap_flags(None, ""), Ok(0));
+        assert_eq!(swap_flags(None, "defaults"), Ok(0));
+        assert_eq!(swap_flags(Some(10), ""), Ok(SWAP_FLAG_PREFER | 10));
+        assert_eq!(swap_flags(None, "pri=5,nofail"), Ok(SWAP_FLAG_PREFER | 5));
+        assert_eq!(swap_flags(Some(1), "pri=5"), Ok(SWAP_FLAG_PREFER | 1));
+        assert_eq!(
+            swap_flags(None, "discard=once"),
+            Ok(SWAP_FLAG_DISCARD | SWAP_FLAG_DISCARD_ONCE)
+        );
+        assert!(swap_flags(Some(40000), "").is_err());
+        assert!(swap_flags(None, "pri=high").is_err());
+        assert!(swap_flags(None, "discard=all").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+//! swap_mng模块是swap类型的核心逻辑，启动时通过swapon(2)激活交换设备，停止时通过swapoff(2)去激活，
+//! 并监听/proc/swaps的变化，同步由其他进程激活或去激活的交换设备的状态。
+//!
+
+use std::{
+    cell::RefCell,
+    ffi::CString,
+    fs::{self, File},
+    os::unix::{ffi::OsStrExt, io::AsRawFd, io::RawFd},
+    path::{Path, PathBuf},
+    rc::{Rc, Weak},
+};
+
+use event::{EventState, EventType, Events, Source};
+use nix::errno::Errno;
+use nix::libc;
+use process1::manager::{UnitActionError, UnitActiveState, UnitNotifyFlags};
+use utils::{Error, IN_SET};
+
+use crate::swap_comm::SwapComm;
+
+const PROC_SWAPS: &str = "/proc/swaps";
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum SwapState {
+    Dead,
+    Active,
+    Failed,
+}
+
+impl SwapState {
+    fn to_unit_active_state(self) -> UnitActiveState {
+        match self {
+            SwapState::Dead => UnitActiveState::UnitInActive,
+            SwapState::Active => UnitActiveState::UnitActive,
+            SwapState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+pub(super) struct SwapMng {
+    comm: Rc<SwapComm>,
+    state: RefCell<SwapState>,
+    // the device or file configured in What=
+    what: RefCell<PathBuf>,
+    // the flags of swapon(2), which are the priority and the discard policy
+    flags: RefCell<i32>,
+    monitor: Rc<SwapMonitor>,
+}
+
+impl SwapMng {
+    pub(super) fn new(commr: &Rc<SwapComm>) -> Rc<SwapMng> {
+        let mng = Rc::new(SwapMng {
+            comm: Rc::clone(commr),
+            state: RefCell::new(SwapState::Dead),
+            what: RefCell::new(PathBuf::new()),
+            flags: RefCell::new(0),
+            monitor: Rc::new(SwapMonitor::new()),
+        });
+
+        mng.monitor.attach(Rc::downgrade(&mng));
+        mng
+    }
+
+    pub(super) fn set_what(&self, what: &str) {
+        *self.what.borrow_mut() = PathBuf::from(what);
+    }
+
+    pub(super) fn set_flags(&self, flags: i32) {
+        *self.flags.borrow_mut() = flags;
+    }
+
+    // sync the state with /proc/swaps without notifying, and watch on its changes
+    pub(super) fn coldplug(&self) {
+        if self.state() == SwapState::Dead && self.swap_listed() {
+            *self.state.borrow_mut() = SwapState::Active;
+        }
+
+        if self.monitor.registered() {
+            return;
+        }
+        if let Err(e) = self.monitor.open() {
+            log::warn!("failed to watch {}: {}", PROC_SWAPS, e);
+            return;
+        }
+        self.comm.um().register(self.monitor.clone());
+        self.comm.um().enable(self.monitor.clone(), EventState::On);
+        self.monitor.set_registered();
+    }
+
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        if self.state() == SwapState::Active {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        let what = self.what.borrow().clone();
+        let flags = *self.flags.borrow();
+        log::debug!("swapon {:?} with flags {:#x}", what, flags);
+        match swap_syscall(&what, |p| unsafe { libc::swapon(p, flags) }) {
+            // EBUSY means it is already in use as a swap
+            Ok(_) | Err(Errno::EBUSY) => self.set_state(SwapState::Active),
+            Err(e) => {
+                log::error!("failed to swapon {:?}: {}", what, e);
+                self.set_state(SwapState::Failed);
+            }
+        }
+    }
+
+    pub(super) fn stop_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), SwapState::Dead, SwapState::Failed) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        let what = self.what.borrow().clone();
+        log::debug!("swapoff {:?}", what);
+        match swap_syscall(&what, |p| unsafe { libc::swapoff(p) }) {
+            // EINVAL means it is not a swap in use
+            Ok(_) | Err(Errno::EINVAL) => self.set_state(SwapState::Dead),
+            Err(e) => {
+                log::error!("failed to swapoff {:?}: {}", what, e);
+                self.set_state(SwapState::Failed);
+            }
+        }
+    }
+
+    pub(super) fn current_active_state(&self) -> UnitActiveState {
+        self.state().to_unit_active_state()
+    }
+
+    // /proc/swaps is changed, the swap may be activated or deactivated by others
+    fn dispatch_swaps(&self) {
+        let listed = self.swap_listed();
+        match self.state() {
+            SwapState::Dead | SwapState::Failed if listed => {
+                log::debug!("swap {} is activated", self.comm.unit().get_id());
+                self.set_state(SwapState::Active);
+            }
+            SwapState::Active if !listed => {
+                log::debug!("swap {} is deactivated", self.comm.unit().get_id());
+                self.set_state(SwapState::Dead);
+            }
+            _ => {}
+        }
+    }
+
+    fn swap_listed(&self) -> bool {
+        let what = self.what.borrow();
+        // What= may be a symlink, such as the ones in /dev/disk/by-uuid
+        let what = fs::canonicalize(&*what).unwrap_or_else(|_| what.clone());
+        match fs::read_to_string(PROC_SWAPS) {
+            Ok(content) => proc_swaps_parse(&content).contains(&what),
+            Err(e) => {
+                log::debug!("failed to read {}: {}", PROC_SWAPS, e);
+                false
+            }
+        }
+    }
+
+    fn set_state(&self, state: SwapState) {
+        let original_state = self.state.replace(state);
+        if original_state != state {
+            log::debug!(
+                "swap {} original state[{:?}] -> new state[{:?}]",
+                self.comm.unit().get_id(),
+                original_state,
+                state
+            );
+        }
+
+        self.comm.unit().notify(
+            original_state.to_unit_active_state(),
+            state.to_unit_active_state(),
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+    }
+
+    fn state(&self) -> SwapState {
+        *self.state.borrow()
+    }
+}
+
+fn swap_syscall(path: &Path, f: impl Fn(*const libc::c_char) -> libc::c_int) -> Result<(), Errno> {
+    let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Errno::EINVAL)?;
+    Errno::result(f(path.as_ptr())).map(drop)
+}
+
+// the first column of /proc/swaps is the path of the swap, with the blanks escaped in octal
+fn proc_swaps_parse(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|l| l.split_whitespace().next())
+        .map(|p| PathBuf::from(p.replace("\\040", " ").replace("\\011", "\t")))
+        .collect()
+}
+
+/// the watch on /proc/swaps, which is notified with EPOLLPRI once the swaps are changed.
+struct SwapMonitor {
+    file: RefCell<Option<File>>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<SwapMng>>,
+}
+
+impl SwapMonitor {
+    fn new() -> Self {
+        SwapMonitor {
+            file: RefCell::new(None),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<SwapMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn open(&self) -> Result<(), std::io::Error> {
+        *self.file.borrow_mut() = Some(File::open(PROC_SWAPS)?);
+        Ok(())
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    fn mng(&self) -> Option<Rc<SwapMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+impl Source for SwapMonitor {
+    fn fd(&self) -> RawFd {
+        self.file.borrow().as_ref().map_or(-1, |f| f.as_raw_fd())
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLPRI) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        log::debug!("dispatch the changes of {}", PROC_SWAPS);
+        if let Some(mng) = self.mng() {
+            mng.dispatch_swaps();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::proc_swaps_parse;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_proc_swaps_parse() {
+        let content = "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority
+/dev/dm-1                               partition\t4194300\t\t0\t\t-2
+/var/swap\\040file                       file\t\t1048572\t\t0\t\t10
+";
+        assert_eq!(
+            proc_swaps_parse(content),
+            vec![PathBuf::from("/dev/dm-1"), PathBuf::from("/var/swap file")]
+        );
+        assert!(proc_swaps_parse("Filename\tType\tSize\tUsed\tPriority\n").is_empty());
+    }
+}
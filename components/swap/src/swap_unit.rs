@@ -0,0 +1,100 @@
+//! SwapUnit是swap类型的总入口，需要实现UnitObj,UnitMngUtil,以及UnitSubClass三个trait,
+//! UnitObj是Unit的抽象，定义对process1提供的具体行为，
+//! UnitMngUtil是为了关联subUnit和Manger，由于rust不支持继承和多态，因此需要采用这种方式来间接支持
+//! UnitSubClass为了实现SubUnit到UnitObj的转换，简介达成多态的目的
+
+use process1::manager::{
+    Unit, UnitActionError, UnitActiveState, UnitManager, UnitMngUtil, UnitObj, UnitSubClass,
+};
+use std::{error::Error, path::PathBuf, rc::Rc};
+
+use crate::{swap_comm::SwapComm, swap_config::SwapConfig, swap_load::SwapLoad, swap_mng::SwapMng};
+use utils::logger;
+
+// the structure of the swap unit type
+struct SwapUnit {
+    comm: Rc<SwapComm>,
+    config: Rc<SwapConfig>,
+    mng: Rc<SwapMng>,
+    load: SwapLoad,
+}
+
+impl UnitObj for SwapUnit {
+    fn load(&self, paths: &Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        log::debug!("swap begin to load conf file");
+        self.config.load(paths)?;
+
+        self.load.parse()?;
+
+        self.load.swap_add_extras()?;
+
+        self.load.swap_verify()?;
+
+        self.mng.coldplug();
+        Ok(())
+    }
+
+    // the function entrance to start the unit
+    fn start(&self) -> Result<(), UnitActionError> {
+        self.mng.start_check()?;
+
+        self.mng.start_action();
+        Ok(())
+    }
+
+    fn stop(&self) -> Result<(), UnitActionError> {
+        self.mng.stop_check()?;
+
+        self.mng.stop_action();
+        Ok(())
+    }
+
+    fn current_active_state(&self) -> UnitActiveState {
+        self.mng.current_active_state()
+    }
+
+    fn attach_unit(&self, unit: Rc<Unit>) {
+        self.comm.attach_unit(unit);
+    }
+}
+
+// attach the UnitManager for weak reference
+impl UnitMngUtil for SwapUnit {
+    fn attach(&self, um: Rc<UnitManager>) {
+        self.comm.attach_um(um);
+    }
+}
+
+impl UnitSubClass for SwapUnit {
+    fn into_unitobj(self: Box<Self>) -> Box<dyn UnitObj> {
+        Box::new(*self)
+    }
+}
+
+impl SwapUnit {
+    fn new() -> SwapUnit {
+        let comm = Rc::new(SwapComm::new());
+        let config = Rc::new(SwapConfig::new());
+        let mng = SwapMng::new(&comm);
+        SwapUnit {
+            comm: Rc::clone(&comm),
+            config: Rc::clone(&config),
+            mng: Rc::clone(&mng),
+            load: SwapLoad::new(&config, &comm, &mng),
+        }
+    }
+}
+
+impl Default for SwapUnit {
+    fn default() -> Self {
+        SwapUnit::new()
+    }
+}
+
+const LOG_LEVEL: u32 = 4;
+const PLUGIN_NAME: &str = "SwapUnit";
+
+use process1::declure_unitobj_plugin;
+
+// define the method to create the instance of the unit
+declure_unitobj_plugin!(SwapUnit, SwapUnit::default, PLUGIN_NAME, LOG_LEVEL);
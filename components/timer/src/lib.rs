@@ -0,0 +1,70 @@
+//! timer是process1的定时任务类型，timer到期时拉起对应的unit(默认为同名的service)，用于替代cron。
+//! timer配置文件包含Unit、Timer、Install三个Section。
+//!
+//! # Example:
+//! ```toml
+//! [Unit]
+//! Description="daily backup"
+//!
+//! [Timer]
+//! OnBootSec="15min"
+//! OnUnitActiveSec="1d"
+//! OnCalendar="Mon..Fri *-*-* 02:00:00 Asia/Shanghai"
+//! Persistent=true
+//! RandomizedDelaySec="10min"
+//! Unit="backup.service"
+//!
+//! [Install]
+//! WantedBy="timers.target"
+//! ```
+
+//! [Timer] section相关的配置
+//!
+//! OnActiveSec、OnBootSec、OnStartupSec、OnUnitActiveSec、OnUnitInactiveSec
+//!
+//! 单调时钟定时器，分别相对于timer自身启动、系统启动、process1启动、被触发unit最近一次启动、被触发unit最近一次停止的时间，
+//! 格式为时间段，如"90"、"1min 30s"、"1.5h"，不带单位时为秒。
+//! OnBootSec、OnStartupSec使用CLOCK_BOOTTIME时钟，其余使用CLOCK_MONOTONIC时钟。
+//!
+//! OnCalendar
+//!
+//! 日历定时器，使用CLOCK_REALTIME时钟，多个表达式以";"分隔，格式为"[星期] [[年-]月-日] [时:分[:秒]] [时区]"，
+//! 如"Mon..Fri *-*-* 08:00"、"*-*~01 UTC"、"daily"，详见utils::calendar_spec。
+//!
+//! AccuracySec
+//!
+//! 定时器的精度，默认为"1min"，到期时间会在[到期时间, 到期时间 + AccuracySec]内对齐到统一的边界，以合并唤醒。
+//!
+//! RandomizedDelaySec
+//!
+//! 到期时间额外增加[0, RandomizedDelaySec)内的随机延迟，默认为0。
+//!
+//! Persistent
+//!
+//! 可设为true或false(默认)，为true时在/var/lib/process1/timers下记录最近一次触发的时间，
+//! timer启动时若错过了OnCalendar的到期时间，则立即触发一次。
+//!
+//! Unit
+//!
+//! timer到期时拉起的unit，默认为同名的service，不能是timer。
+//!
+//! ## 自动依赖
+//!
+//! ### 隐含依赖
+//! timer对Unit=指定的unit添加Before=和Triggers=依赖。
+
+// dependency:
+// timer_base -> {timer_comm | timer_config}
+// {timer_mng | timer_load} -> timer_unit
+
+mod timer_base;
+
+mod timer_comm;
+
+mod timer_config;
+
+mod timer_mng;
+
+mod timer_load;
+
+mod timer_unit;
@@ -0,0 +1,202 @@
+//! timer_base模块定义单调时钟定时器的基准，以及时钟、随机延迟、精度对齐和触发记录等辅助函数。
+//!
+
+use event::EventType;
+use nix::time::{clock_gettime, ClockId};
+use nix::unistd::{sysconf, SysconfVar};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::PathBuf;
+use utils::time_util;
+
+pub(super) const USEC_PER_SEC: u64 = 1_000_000;
+
+// the default of AccuracySec=
+pub(super) const DEFAULT_ACCURACY_USEC: u64 = 60 * USEC_PER_SEC;
+
+// the directory of the last trigger stamps of Persistent= timers
+const TIMER_STAMP_DIR: &str = "/var/lib/process1/timers";
+
+// the boundaries where the elapses of the timers are coalesced, from the coarsest one
+const COALESCE_BOUNDARIES: [u64; 4] = [
+    60 * USEC_PER_SEC,
+    10 * USEC_PER_SEC,
+    USEC_PER_SEC,
+    USEC_PER_SEC / 4,
+];
+
+/// the point which a monotonic timer is relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TimerBase {
+    // OnActiveSec=, the activation of the timer itself
+    Active,
+    // OnBootSec=, the boot of the system
+    Boot,
+    // OnStartupSec=, the startup of process1
+    Startup,
+    // OnUnitActiveSec=, the last activation of the triggered unit
+    UnitActive,
+    // OnUnitInactiveSec=, the last deactivation of the triggered unit
+    UnitInactive,
+}
+
+impl TimerBase {
+    // the clock where the base and the elapse are measured
+    pub(super) fn clock(&self) -> TimerClock {
+        match self {
+            TimerBase::Boot | TimerBase::Startup => TimerClock::Boottime,
+            _ => TimerClock::Monotonic,
+        }
+    }
+}
+
+/// the clocks which the timers are based on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TimerClock {
+    Monotonic,
+    Boottime,
+    Realtime,
+}
+
+impl TimerClock {
+    pub(super) fn event_type(self) -> EventType {
+        match self {
+            TimerClock::Monotonic => EventType::TimerMonotonic,
+            TimerClock::Boottime => EventType::TimerBoottime,
+            TimerClock::Realtime => EventType::TimerRealtime,
+        }
+    }
+
+    /// the current time of the clock in useconds
+    pub(super) fn now(self) -> u64 {
+        let id = match self {
+            TimerClock::Monotonic => ClockId::CLOCK_MONOTONIC,
+            TimerClock::Boottime => ClockId::CLOCK_BOOTTIME,
+            TimerClock::Realtime => ClockId::CLOCK_REALTIME,
+        };
+        match clock_gettime(id) {
+            Ok(ts) => ts.tv_sec() as u64 * USEC_PER_SEC + ts.tv_nsec() as u64 / 1_000,
+            Err(_) => 0,
+        }
+    }
+}
+
+/// the boot time when process1 started, which OnStartupSec= is relative to
+pub(super) fn startup_usec() -> u64 {
+    // the 22nd field of /proc/self/stat is the start time in clock ticks after the boot
+    let stat = match fs::read_to_string("/proc/self/stat") {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    let ticks = stat
+        .rsplit_once(')')
+        .and_then(|(_, fields)| fields.split_whitespace().nth(19))
+        .and_then(|t| t.parse::<u64>().ok());
+    let hz = match sysconf(SysconfVar::CLK_TCK) {
+        Ok(Some(hz)) if hz > 0 => hz as u64,
+        _ => 100,
+    };
+
+    ticks.map_or(0, |t| t * USEC_PER_SEC / hz)
+}
+
+/// the delay of RandomizedDelaySec=, which is uniformly distributed in [0, {max})
+pub(super) fn random_usec(max: u64) -> u64 {
+    if max == 0 {
+        return 0;
+    }
+
+    let mut buf = [0u8; 8];
+    match File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut buf)) {
+        Ok(_) => u64::from_ne_bytes(buf) % max,
+        Err(e) => {
+            log::warn!("failed to read /dev/urandom: {}", e);
+            0
+        }
+    }
+}
+
+/// pick the elapse in [{next}, {next} + {accuracy}], so that the timers of
+/// the system wake up at the same boundaries.
+pub(super) fn coalesce_usec(next: u64, accuracy: u64) -> u64 {
+    coalesce(next, next.saturating_add(accuracy), boot_perturb())
+}
+
+// the latest boundary in [{a}, {b}], the boundaries are shifted by {perturb}
+// to avoid all the machines waking up at the same time.
+fn coalesce(a: u64, b: u64, perturb: u64) -> u64 {
+    if b <= a {
+        return a;
+    }
+
+    for boundary in COALESCE_BOUNDARIES {
+        let mut c = (b / boundary) * boundary + perturb % boundary;
+        if c > b {
+            match c.checked_sub(boundary) {
+                Some(v) => c = v,
+                None => continue,
+            }
+        }
+        if c >= a {
+            return c;
+        }
+    }
+
+    b
+}
+
+// a value fixed during one boot, which is derived from the boot id
+fn boot_perturb() -> u64 {
+    match fs::read_to_string("/proc/sys/kernel/random/boot_id") {
+        Ok(id) => id
+            .trim()
+            .bytes()
+            .fold(0u64, |h, b| h.wrapping_mul(31).wrapping_add(b as u64)),
+        Err(_) => 0,
+    }
+}
+
+fn stamp_path(unit: &str) -> PathBuf {
+    PathBuf::from(TIMER_STAMP_DIR).join(format!("stamp-{}", unit))
+}
+
+/// the realtime when the Persistent= timer {unit} was triggered last time
+pub(super) fn read_stamp(unit: &str) -> Option<u64> {
+    let modified = fs::metadata(stamp_path(unit)).ok()?.modified().ok()?;
+    Some(time_util::timespec_load(modified) as u64)
+}
+
+/// record the trigger of the Persistent= timer {unit} as the modified time of its stamp file
+pub(super) fn touch_stamp(unit: &str) {
+    let path = stamp_path(unit);
+    if let Err(e) = fs::create_dir_all(TIMER_STAMP_DIR).and_then(|_| fs::write(&path, "")) {
+        log::warn!("failed to update the timer stamp {:?}: {}", path, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{coalesce, random_usec, USEC_PER_SEC};
+
+    #[test]
+    fn test_coalesce() {
+        let min = 60 * USEC_PER_SEC;
+        // the minute boundary in the window wins
+        assert_eq!(coalesce(50 * USEC_PER_SEC, 110 * USEC_PER_SEC, 0), min);
+        // the boundaries are shifted by the perturbation
+        assert_eq!(
+            coalesce(50 * USEC_PER_SEC, 110 * USEC_PER_SEC, 5 * USEC_PER_SEC),
+            65 * USEC_PER_SEC
+        );
+        // fall back to the finer boundaries in a small window
+        assert_eq!(
+            coalesce(61 * USEC_PER_SEC + 1, 63 * USEC_PER_SEC, 0),
+            63 * USEC_PER_SEC
+        );
+        assert_eq!(coalesce(min + 1, min + 2, 0), min + 2);
+        assert_eq!(coalesce(min, min, 0), min);
+
+        assert_eq!(random_usec(0), 0);
+        assert!(random_usec(10) < 10);
+    }
+}
@@ -0,0 +1,81 @@
+//! timer_config模块timer类型配置文件的定义，以及保存配置文件解析之后的内容
+//!
+#![allow(non_snake_case)]
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use confique::{Config, Error};
+use process1::manager::DeserializeWith;
+
+pub(super) struct TimerConfig {
+    data: Rc<RefCell<TimerConfigData>>,
+}
+
+impl TimerConfig {
+    pub(super) fn new() -> Self {
+        TimerConfig {
+            data: Rc::new(RefCell::new(TimerConfigData::default())),
+        }
+    }
+
+    pub(super) fn load(&self, paths: &[PathBuf]) -> Result<(), Error> {
+        let mut builder = TimerConfigData::builder().env();
+
+        // fragment
+        for v in paths {
+            builder = builder.file(&v);
+        }
+
+        *self.data.borrow_mut() = builder.load()?;
+        Ok(())
+    }
+
+    pub(super) fn config_data(&self) -> Rc<RefCell<TimerConfigData>> {
+        self.data.clone()
+    }
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct TimerConfigData {
+    #[config(nested)]
+    pub Timer: SectionTimer,
+}
+
+#[derive(Config, Default, Debug)]
+pub(super) struct SectionTimer {
+    pub OnActiveSec: Option<String>,
+    pub OnBootSec: Option<String>,
+    pub OnStartupSec: Option<String>,
+    pub OnUnitActiveSec: Option<String>,
+    pub OnUnitInactiveSec: Option<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    pub OnCalendar: Option<Vec<String>>,
+    pub AccuracySec: Option<String>,
+    pub RandomizedDelaySec: Option<String>,
+    pub Persistent: Option<bool>,
+    pub Unit: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::timer_config::TimerConfig;
+    use utils::test_util::get_project_root;
+
+    #[test]
+    fn test_timer_parse() {
+        let mut file_path = get_project_root().unwrap();
+        file_path.push("libutils/examples/test.timer.toml");
+        let paths = vec![file_path];
+
+        let config = TimerConfig::new();
+        assert!(config.load(&paths).is_ok());
+
+        let data = config.config_data();
+        let timer = &data.borrow().Timer;
+        assert_eq!(timer.OnBootSec, Some("15min".to_string()));
+        assert_eq!(timer.OnCalendar.as_ref().unwrap().len(), 2);
+        assert_eq!(timer.Persistent, Some(true));
+        assert_eq!(timer.Unit, Some("test.service".to_string()));
+    }
+}
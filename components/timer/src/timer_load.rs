@@ -0,0 +1,114 @@
+//! timer_load模块实现timer配置文件的解析，以及对被触发unit的依赖。
+//!
+
+use std::{error::Error, path::Path, rc::Rc};
+
+use process1::manager::{UnitRelations, UnitType};
+use utils::{calendar_spec::CalendarSpec, time_util};
+
+use crate::timer_base::TimerBase;
+use crate::timer_comm::TimerComm;
+use crate::timer_config::TimerConfig;
+use crate::timer_mng::TimerMng;
+
+pub(super) struct TimerLoad {
+    config: Rc<TimerConfig>,
+    comm: Rc<TimerComm>,
+    mng: Rc<TimerMng>,
+}
+
+impl TimerLoad {
+    pub(super) fn new(
+        configr: &Rc<TimerConfig>,
+        commr: &Rc<TimerComm>,
+        mngr: &Rc<TimerMng>,
+    ) -> Self {
+        TimerLoad {
+            config: configr.clone(),
+            comm: commr.clone(),
+            mng: mngr.clone(),
+        }
+    }
+
+    pub(super) fn parse(&self) -> Result<(), Box<dyn Error>> {
+        log::debug!("begin to parse timer section");
+        let data = self.config.config_data();
+        let timer = &data.borrow().Timer;
+
+        for (base, value) in [
+            (TimerBase::Active, &timer.OnActiveSec),
+            (TimerBase::Boot, &timer.OnBootSec),
+            (TimerBase::Startup, &timer.OnStartupSec),
+            (TimerBase::UnitActive, &timer.OnUnitActiveSec),
+            (TimerBase::UnitInactive, &timer.OnUnitInactiveSec),
+        ] {
+            if let Some(v) = value {
+                self.mng.add_monotonic(base, time_util::parse_timespan(v)?);
+            }
+        }
+
+        for calendar in timer.OnCalendar.iter().flatten() {
+            if calendar.is_empty() {
+                continue;
+            }
+            self.mng.add_calendar(calendar.parse::<CalendarSpec>()?);
+        }
+
+        if let Some(v) = &timer.AccuracySec {
+            self.mng.set_accuracy(time_util::parse_timespan(v)?);
+        }
+
+        if let Some(v) = &timer.RandomizedDelaySec {
+            self.mng.set_randomized_delay(time_util::parse_timespan(v)?);
+        }
+
+        if let Some(persistent) = timer.Persistent {
+            self.mng.set_persistent(persistent);
+        }
+
+        let unit = match &timer.Unit {
+            Some(u) => u.clone(),
+            None => {
+                // the service with the same name as the timer by default
+                let id = self.comm.unit().get_id().to_string();
+                let stem = Path::new(&id)
+                    .file_stem()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string();
+                format!("{}.{}", stem, String::from(UnitType::UnitService))
+            }
+        };
+        self.mng.set_unit(unit);
+
+        Ok(())
+    }
+
+    pub(super) fn timer_add_extras(&self) -> Result<(), Box<dyn Error>> {
+        let unit = self.mng.unit();
+        if !self.comm.um().load_unit_success(&unit) {
+            return Err(format!("failed to load unit {}", unit).into());
+        }
+
+        self.comm.unit().insert_two_deps(
+            UnitRelations::UnitBefore,
+            UnitRelations::UnitTriggers,
+            unit,
+        );
+        Ok(())
+    }
+
+    pub(super) fn timer_verify(&self) -> Result<(), Box<dyn Error>> {
+        let id = self.comm.unit().get_id().to_string();
+        if !self.mng.has_triggers() {
+            return Err(format!("timer {} has no trigger configured", id).into());
+        }
+
+        let unit = self.mng.unit();
+        if unit.ends_with(".timer") {
+            return Err(format!("timer {} can not trigger the timer {}", id, unit).into());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,636 @@
+//! timer_mng模块是timer类型的核心逻辑，维护timer的状态机，计算下一次到期时间，到期时拉起对应的unit。
+//!
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use event::{EventState, EventType, Events, Source};
+use nix::libc;
+use process1::manager::{UnitActionError, UnitActiveState, UnitNotifyFlags};
+use utils::{calendar_spec::CalendarSpec, Error, IN_SET};
+
+use crate::timer_base::{self, TimerBase, TimerClock, DEFAULT_ACCURACY_USEC};
+use crate::timer_comm::TimerComm;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum TimerState {
+    Dead,
+    Waiting,
+    Running,
+    Elapsed,
+    Failed,
+}
+
+impl TimerState {
+    fn to_unit_active_state(self) -> UnitActiveState {
+        match self {
+            TimerState::Dead => UnitActiveState::UnitInActive,
+            TimerState::Waiting | TimerState::Running | TimerState::Elapsed => {
+                UnitActiveState::UnitActive
+            }
+            TimerState::Failed => UnitActiveState::UnitFailed,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum TimerResult {
+    Success,
+    FailureResources,
+}
+
+// the time of an event in the clocks which the timers are based on
+#[derive(Debug, Default, Copy, Clone)]
+struct Timestamp {
+    realtime: u64,
+    monotonic: u64,
+    boottime: u64,
+}
+
+impl Timestamp {
+    fn now() -> Self {
+        Timestamp {
+            realtime: TimerClock::Realtime.now(),
+            monotonic: TimerClock::Monotonic.now(),
+            boottime: TimerClock::Boottime.now(),
+        }
+    }
+
+    fn get(&self, clock: TimerClock) -> u64 {
+        match clock {
+            TimerClock::Monotonic => self.monotonic,
+            TimerClock::Boottime => self.boottime,
+            TimerClock::Realtime => self.realtime,
+        }
+    }
+}
+
+// the settings of the [Timer] section
+struct TimerSettings {
+    monotonic: Vec<(TimerBase, u64)>,
+    calendar: Vec<CalendarSpec>,
+    unit: String,
+    accuracy: u64,
+    randomized_delay: u64,
+    persistent: bool,
+}
+
+pub(super) struct TimerMng {
+    comm: Rc<TimerComm>,
+    state: RefCell<TimerState>,
+    result: RefCell<TimerResult>,
+    settings: RefCell<TimerSettings>,
+
+    // when the timer is activated and triggered last time
+    activated: RefCell<Timestamp>,
+    last_trigger: RefCell<Timestamp>,
+
+    // the state of the triggered unit, and when it is activated and deactivated last time
+    unit_state: RefCell<UnitActiveState>,
+    unit_active: RefCell<Option<u64>>,
+    unit_inactive: RefCell<Option<u64>>,
+
+    sources: Vec<Rc<TimerSource>>,
+}
+
+impl TimerMng {
+    pub(super) fn new(commr: &Rc<TimerComm>) -> Rc<TimerMng> {
+        let clocks = [
+            TimerClock::Monotonic,
+            TimerClock::Boottime,
+            TimerClock::Realtime,
+        ];
+        let mng = Rc::new(TimerMng {
+            comm: Rc::clone(commr),
+            state: RefCell::new(TimerState::Dead),
+            result: RefCell::new(TimerResult::Success),
+            settings: RefCell::new(TimerSettings {
+                monotonic: Vec::new(),
+                calendar: Vec::new(),
+                unit: String::new(),
+                accuracy: DEFAULT_ACCURACY_USEC,
+                randomized_delay: 0,
+                persistent: false,
+            }),
+            activated: RefCell::new(Timestamp::default()),
+            last_trigger: RefCell::new(Timestamp::default()),
+            unit_state: RefCell::new(UnitActiveState::UnitInActive),
+            unit_active: RefCell::new(None),
+            unit_inactive: RefCell::new(None),
+            sources: clocks
+                .iter()
+                .map(|c| Rc::new(TimerSource::new(*c)))
+                .collect(),
+        });
+
+        for source in &mng.sources {
+            source.attach(Rc::downgrade(&mng));
+        }
+        mng
+    }
+
+    pub(super) fn add_monotonic(&self, base: TimerBase, usec: u64) {
+        self.settings.borrow_mut().monotonic.push((base, usec));
+    }
+
+    pub(super) fn add_calendar(&self, spec: CalendarSpec) {
+        self.settings.borrow_mut().calendar.push(spec);
+    }
+
+    pub(super) fn has_triggers(&self) -> bool {
+        let settings = self.settings.borrow();
+        !settings.monotonic.is_empty() || !settings.calendar.is_empty()
+    }
+
+    pub(super) fn set_unit(&self, unit: String) {
+        self.settings.borrow_mut().unit = unit;
+    }
+
+    pub(super) fn unit(&self) -> String {
+        self.settings.borrow().unit.clone()
+    }
+
+    pub(super) fn set_accuracy(&self, usec: u64) {
+        self.settings.borrow_mut().accuracy = usec;
+    }
+
+    pub(super) fn set_randomized_delay(&self, usec: u64) {
+        self.settings.borrow_mut().randomized_delay = usec;
+    }
+
+    pub(super) fn set_persistent(&self, persistent: bool) {
+        self.settings.borrow_mut().persistent = persistent;
+    }
+
+    pub(super) fn start_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(
+            self.state(),
+            TimerState::Waiting,
+            TimerState::Running,
+            TimerState::Elapsed
+        ) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+
+        if self.unit().is_empty() {
+            return Err(UnitActionError::UnitActionENoent);
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn start_action(&self) {
+        let id = self.comm.unit().get_id().to_string();
+        *self.result.borrow_mut() = TimerResult::Success;
+        *self.activated.borrow_mut() = Timestamp::now();
+
+        // the last trigger is kept when the timer is restarted, so that OnBootSec= is
+        // not triggered twice in one boot, and Persistent= restores it across boots.
+        self.restore_last_trigger(&id, timer_base::read_stamp(&id));
+
+        for source in &self.sources {
+            if !source.registered() {
+                self.comm.um().register(source.clone());
+                source.set_registered();
+            }
+        }
+
+        self.enter_waiting();
+    }
+
+    // the calendar timers with Persistent= catch up with the trigger {stamp} of the last boot
+    fn restore_last_trigger(&self, id: &str, stamp: Option<u64>) {
+        if !self.settings.borrow().persistent {
+            return;
+        }
+        if let Some(stamp) = stamp {
+            log::debug!("timer {} was triggered at {} last time", id, stamp);
+            self.last_trigger.borrow_mut().realtime = stamp;
+        }
+    }
+
+    pub(super) fn stop_check(&self) -> Result<(), UnitActionError> {
+        if IN_SET!(self.state(), TimerState::Dead, TimerState::Failed) {
+            return Err(UnitActionError::UnitActionEAlready);
+        }
+        Ok(())
+    }
+
+    pub(super) fn stop_action(&self) {
+        self.enter_dead(TimerResult::Success);
+    }
+
+    pub(super) fn current_active_state(&self) -> UnitActiveState {
+        self.state().to_unit_active_state()
+    }
+
+    // the triggered unit changes its state
+    pub(super) fn trigger_notify(&self, new_state: UnitActiveState) {
+        if !self.record_unit_state(new_state) {
+            return;
+        }
+
+        let inactive = IN_SET!(
+            new_state,
+            UnitActiveState::UnitInActive,
+            UnitActiveState::UnitFailed
+        );
+        match self.state() {
+            TimerState::Waiting | TimerState::Elapsed => self.enter_waiting(),
+            TimerState::Running if inactive => self.enter_waiting(),
+            _ => {}
+        }
+    }
+
+    // record when the triggered unit is activated and deactivated, false if the state is the same
+    fn record_unit_state(&self, new_state: UnitActiveState) -> bool {
+        let old_state = self.unit_state.replace(new_state);
+        if old_state == new_state {
+            return false;
+        }
+
+        let inactive = |s: UnitActiveState| {
+            IN_SET!(
+                s,
+                UnitActiveState::UnitInActive,
+                UnitActiveState::UnitFailed
+            )
+        };
+        let now = TimerClock::Monotonic.now();
+        if inactive(old_state) && !inactive(new_state) {
+            *self.unit_active.borrow_mut() = Some(now);
+        } else if !inactive(old_state) && inactive(new_state) {
+            *self.unit_inactive.borrow_mut() = Some(now);
+        }
+        true
+    }
+
+    // compute the next elapses of the clocks, and arm the event sources
+    fn enter_waiting(&self) {
+        let id = self.comm.unit().get_id().to_string();
+        let elapses = self.next_elapses();
+        if elapses.iter().all(|e| e.is_none()) {
+            log::info!("timer {} is elapsed", id);
+            self.disarm_all();
+            self.set_state(TimerState::Elapsed);
+            return;
+        }
+
+        let (delay, accuracy) = {
+            let settings = self.settings.borrow();
+            (
+                timer_base::random_usec(settings.randomized_delay),
+                settings.accuracy,
+            )
+        };
+        for (source, elapse) in self.sources.iter().zip(elapses.iter()) {
+            match elapse {
+                Some(next) => {
+                    let next = timer_base::coalesce_usec(next.saturating_add(delay), accuracy);
+                    log::debug!("timer {} elapses at {} of {:?}", id, next, source.clock);
+                    source.arm(next);
+                    self.comm.um().enable(source.clone(), EventState::On);
+                }
+                None => self.disarm(source),
+            }
+        }
+
+        self.set_state(TimerState::Waiting);
+    }
+
+    // the next elapses in the monotonic, boottime and realtime clocks, None if nothing is left
+    fn next_elapses(&self) -> [Option<u64>; 3] {
+        let settings = self.settings.borrow();
+        let activated = *self.activated.borrow();
+        let last_trigger = *self.last_trigger.borrow();
+
+        let mut monotonic: Option<u64> = None;
+        let mut boottime: Option<u64> = None;
+        for (base, usec) in &settings.monotonic {
+            let start = match base {
+                TimerBase::Active => activated.monotonic,
+                TimerBase::Boot => 0,
+                TimerBase::Startup => timer_base::startup_usec(),
+                TimerBase::UnitActive => match *self.unit_active.borrow() {
+                    Some(t) => t,
+                    None => continue,
+                },
+                TimerBase::UnitInactive => match *self.unit_inactive.borrow() {
+                    Some(t) => t,
+                    None => continue,
+                },
+            };
+
+            let clock = base.clock();
+            let next = start.saturating_add(*usec);
+            // the elapse is triggered already
+            if next <= last_trigger.get(clock) {
+                continue;
+            }
+
+            let elapse = match clock {
+                TimerClock::Boottime => &mut boottime,
+                _ => &mut monotonic,
+            };
+            match elapse {
+                Some(e) if *e <= next => {}
+                _ => *elapse = Some(next),
+            }
+        }
+
+        // the calendar timers elapse after the last trigger, or the activation if never triggered.
+        let base = match last_trigger.realtime {
+            0 => activated.realtime,
+            t => t,
+        };
+        let realtime = settings
+            .calendar
+            .iter()
+            .filter_map(|spec| spec.next_elapse(base))
+            .min();
+
+        [monotonic, boottime, realtime]
+    }
+
+    // one of the clocks elapses, start the triggered unit
+    fn enter_running(&self) {
+        let id = self.comm.unit().get_id().to_string();
+        if self.comm.um().has_stop_job(&id) {
+            return;
+        }
+
+        *self.last_trigger.borrow_mut() = Timestamp::now();
+        if self.settings.borrow().persistent {
+            timer_base::touch_stamp(&id);
+        }
+
+        self.disarm_all();
+        self.set_state(TimerState::Running);
+
+        let unit = self.unit();
+        log::info!("timer {} is elapsed, start {}", id, unit);
+        if let Err(e) = self.comm.um().start_unit(&unit) {
+            log::error!("failed to start unit {} triggered by {}: {:?}", unit, id, e);
+            self.enter_dead(TimerResult::FailureResources);
+        }
+    }
+
+    fn enter_dead(&self, result: TimerResult) {
+        if *self.result.borrow() == TimerResult::Success {
+            *self.result.borrow_mut() = result;
+        }
+
+        self.disarm_all();
+        if *self.result.borrow() == TimerResult::Success {
+            self.set_state(TimerState::Dead);
+        } else {
+            self.set_state(TimerState::Failed);
+        }
+    }
+
+    fn dispatch_timer(&self) {
+        if self.state() != TimerState::Waiting {
+            return;
+        }
+        self.enter_running();
+    }
+
+    fn disarm(&self, source: &Rc<TimerSource>) {
+        if source.armed() {
+            source.disarm();
+            self.comm.um().enable(source.clone(), EventState::Off);
+        }
+    }
+
+    fn disarm_all(&self) {
+        for source in &self.sources {
+            self.disarm(source);
+        }
+    }
+
+    fn set_state(&self, state: TimerState) {
+        let original_state = self.state.replace(state);
+        if original_state != state {
+            log::debug!(
+                "timer {} original state[{:?}] -> new state[{:?}]",
+                self.comm.unit().get_id(),
+                original_state,
+                state
+            );
+        }
+
+        self.comm.unit().notify(
+            original_state.to_unit_active_state(),
+            state.to_unit_active_state(),
+            UnitNotifyFlags::UNIT_NOTIFY_RELOAD_FAILURE,
+        );
+    }
+
+    fn state(&self) -> TimerState {
+        *self.state.borrow()
+    }
+}
+
+// the event source of one clock of the timer
+struct TimerSource {
+    clock: TimerClock,
+    // the absolute time in the clock, u64::MAX means the source is disarmed.
+    deadline: RefCell<u64>,
+    registered: RefCell<bool>,
+    mng: RefCell<Weak<TimerMng>>,
+}
+
+impl TimerSource {
+    fn new(clock: TimerClock) -> Self {
+        TimerSource {
+            clock,
+            deadline: RefCell::new(u64::MAX),
+            registered: RefCell::new(false),
+            mng: RefCell::new(Weak::new()),
+        }
+    }
+
+    fn attach(&self, mng: Weak<TimerMng>) {
+        *self.mng.borrow_mut() = mng;
+    }
+
+    fn arm(&self, usec: u64) {
+        *self.deadline.borrow_mut() = usec;
+    }
+
+    fn disarm(&self) {
+        *self.deadline.borrow_mut() = u64::MAX;
+    }
+
+    fn armed(&self) -> bool {
+        *self.deadline.borrow() != u64::MAX
+    }
+
+    fn expired(&self) -> bool {
+        self.armed() && self.clock.now() >= *self.deadline.borrow()
+    }
+
+    fn registered(&self) -> bool {
+        *self.registered.borrow()
+    }
+
+    fn set_registered(&self) {
+        *self.registered.borrow_mut() = true;
+    }
+
+    fn mng(&self) -> Option<Rc<TimerMng>> {
+        self.mng.borrow().upgrade()
+    }
+}
+
+impl Source for TimerSource {
+    fn event_type(&self) -> EventType {
+        self.clock.event_type()
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn time(&self) -> u64 {
+        *self.deadline.borrow()
+    }
+
+    fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+        // the expiration queued before re-arming is earlier, ignore it.
+        if !self.expired() {
+            return Ok(0);
+        }
+
+        log::debug!("dispatch timer of {:?}", self.clock);
+        if let Some(mng) = self.mng() {
+            mng.dispatch_timer();
+        }
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timer_base::USEC_PER_SEC;
+
+    // 2023-03-15 10:15:00 UTC
+    const NOW: u64 = (1_678_838_400 + 36_900) * USEC_PER_SEC;
+    const DAY: u64 = 86_400 * USEC_PER_SEC;
+
+    fn mng_for_test() -> Rc<TimerMng> {
+        let mng = TimerMng::new(&Rc::new(TimerComm::new()));
+        *mng.activated.borrow_mut() = Timestamp {
+            realtime: NOW,
+            monotonic: 100 * USEC_PER_SEC,
+            boottime: 100 * USEC_PER_SEC,
+        };
+        mng
+    }
+
+    #[test]
+    fn test_timer_elapse() {
+        let mng = mng_for_test();
+        mng.add_monotonic(TimerBase::Active, 10 * USEC_PER_SEC);
+        mng.add_monotonic(TimerBase::Active, 60 * USEC_PER_SEC);
+        mng.add_monotonic(TimerBase::Boot, 3600 * USEC_PER_SEC);
+        mng.add_calendar("daily UTC".parse().unwrap());
+
+        // the earliest elapse of each clock
+        let midnight = NOW - 36_900 * USEC_PER_SEC + DAY;
+        assert_eq!(
+            mng.next_elapses(),
+            [
+                Some(110 * USEC_PER_SEC),
+                Some(3600 * USEC_PER_SEC),
+                Some(midnight)
+            ]
+        );
+
+        // the triggered elapses are skipped, and the calendar follows the last trigger
+        *mng.last_trigger.borrow_mut() = Timestamp {
+            realtime: midnight,
+            monotonic: 120 * USEC_PER_SEC,
+            boottime: 120 * USEC_PER_SEC,
+        };
+        assert_eq!(
+            mng.next_elapses(),
+            [
+                Some(160 * USEC_PER_SEC),
+                Some(3600 * USEC_PER_SEC),
+                Some(midnight + DAY)
+            ]
+        );
+
+        // nothing is left without the calendar
+        mng.settings.borrow_mut().calendar.clear();
+        *mng.last_trigger.borrow_mut() = Timestamp {
+            realtime: midnight,
+            monotonic: 3600 * USEC_PER_SEC,
+            boottime: 3600 * USEC_PER_SEC,
+        };
+        assert_eq!(mng.next_elapses(), [None, None, None]);
+    }
+
+    #[test]
+    fn test_timer_persistent() {
+        let mng = mng_for_test();
+        mng.add_calendar("daily UTC".parse().unwrap());
+        let yesterday = NOW - DAY;
+        let midnight = NOW - 36_900 * USEC_PER_SEC;
+
+        // the trigger of the last boot is ignored without Persistent=
+        mng.restore_last_trigger("test.timer", Some(yesterday));
+        assert_eq!(mng.next_elapses(), [None, None, Some(midnight + DAY)]);
+
+        // the missed elapse of today is caught up at once
+        mng.set_persistent(true);
+        mng.restore_last_trigger("test.timer", None);
+        assert_eq!(mng.last_trigger.borrow().realtime, 0);
+        mng.restore_last_trigger("test.timer", Some(yesterday));
+        assert_eq!(mng.next_elapses(), [None, None, Some(midnight)]);
+    }
+
+    #[test]
+    fn test_timer_rearm() {
+        let mng = mng_for_test();
+        mng.add_monotonic(TimerBase::UnitActive, 60 * USEC_PER_SEC);
+
+        // OnUnitActiveSec= waits for the unit to be activated
+        assert_eq!(mng.next_elapses(), [None, None, None]);
+
+        assert!(mng.record_unit_state(UnitActiveState::UnitActivating));
+        assert!(!mng.record_unit_state(UnitActiveState::UnitActivating));
+        assert!(mng.record_unit_state(UnitActiveState::UnitActive));
+        let active = mng.unit_active.borrow().unwrap();
+        assert_eq!(
+            mng.next_elapses(),
+            [Some(active + 60 * USEC_PER_SEC), None, None]
+        );
+
+        // it is triggered, and rearmed by the next activation only
+        mng.last_trigger.borrow_mut().monotonic = active + 60 * USEC_PER_SEC;
+        assert_eq!(mng.next_elapses(), [None, None, None]);
+        assert!(mng.record_unit_state(UnitActiveState::UnitInActive));
+        assert!(mng.unit_inactive.borrow().is_some());
+        assert_eq!(mng.next_elapses(), [None, None, None]);
+
+        *mng.unit_active.borrow_mut() = Some(active + 90 * USEC_PER_SEC);
+        assert_eq!(
+            mng.next_elapses(),
+            [Some(active + 150 * USEC_PER_SEC), None, None]
+        );
+    }
+}
@@ -24,6 +24,9 @@ const CG_BASE_DIR: &str = "/sys/fs/cgroup";
 const CG_UNIFIED_DIR: &str = "/sys/fs/cgroup/unified";
 const CG_V1_DIR: &str = "/sys/fs/cgroup/process1";
 
+// the period of cpu.max, in useconds
+const CPU_QUOTA_PERIOD_USEC: u64 = 100_000;
+
 pub fn cg_type() -> Result<CgType, CgroupErr> {
     // 查询cgroup的版本类型
     let stat = if let Ok(s) = statfs(CG_BASE_DIR) {
@@ -110,6 +113,114 @@ pub fn cg_escape(id: &str) -> &str {
     return id;
 }
 
+/// the cgroup path of the slice, which is nested in its parent slices, such as
+/// a-b.slice => a.slice/a-b.slice, and the root slice -.slice is the root cgroup.
+pub fn cg_slice_to_path(slice: &str) -> PathBuf {
+    let mut path = PathBuf::new();
+    let stem = match slice.strip_suffix(".slice") {
+        Some(s) if s != "-" => s,
+        _ => return path,
+    };
+
+    let mut end = 0;
+    for part in stem.split('-') {
+        end += part.len();
+        path.push(format!("{}.slice", cg_escape(&stem[..end])));
+        end += 1;
+    }
+
+    path
+}
+
+/// write the attribute {attr} of the cgroup, such as memory.max
+pub fn cg_set_attr(cg_path: &PathBuf, attr: &str, value: &str) -> Result<(), CgroupErr> {
+    log::debug!("set cgroup {:?} attribute {} to {}", cg_path, attr, value);
+    let path = cg_abs_path(cg_path, &PathBuf::from(attr))?;
+    fs::write(path, value).map_err(|e| CgroupErr::IoError(e))?;
+
+    Ok(())
+}
+
+/// enable the {controllers} for the cgroup in all its ancestors, so that the
+/// attributes of the controllers are available in the cgroup.
+pub fn cg_enable_controllers(cg_path: &PathBuf, controllers: &[&str]) -> Result<(), CgroupErr> {
+    if cg_type()? != CgType::UnifiedV2 {
+        return Err(CgroupErr::NotSupported);
+    }
+
+    let mut ancestors: Vec<&std::path::Path> = cg_path.ancestors().skip(1).collect();
+    ancestors.reverse();
+    for ancestor in ancestors {
+        for controller in controllers {
+            if let Err(e) = cg_set_attr(
+                &ancestor.to_path_buf(),
+                "cgroup.subtree_control",
+                &format!("+{}", controller),
+            ) {
+                log::debug!(
+                    "failed to enable controller {} in {:?}: {}",
+                    controller,
+                    ancestor,
+                    e
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// apply the resource settings of {resources}, each one is the attribute and its value,
+/// the controllers of the attributes are enabled first.
+pub fn cg_set_resources(cg_path: &PathBuf, resources: &[(&str, String)]) -> Result<(), CgroupErr> {
+    let mut controllers: Vec<&str> = resources
+        .iter()
+        .filter_map(|(attr, _)| attr.split('.').next())
+        .collect();
+    controllers.sort_unstable();
+    controllers.dedup();
+    cg_enable_controllers(cg_path, &controllers)?;
+
+    for (attr, value) in resources {
+        if let Err(e) = cg_set_attr(cg_path, attr, value) {
+            log::warn!(
+                "failed to set {} of {:?} to {}: {}",
+                attr,
+                cg_path,
+                value,
+                e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// the content of cpu.max for the percentage of one CPU, such as "150%"
+pub fn cg_cpu_quota(quota: &str) -> Option<String> {
+    let percent = quota
+        .trim()
+        .strip_suffix('%')
+        .and_then(|p| p.trim().parse::<u64>().ok())
+        .filter(|p| *p > 0)?;
+
+    Some(format!(
+        "{} {}",
+        percent * CPU_QUOTA_PERIOD_USEC / 100,
+        CPU_QUOTA_PERIOD_USEC
+    ))
+}
+
+/// the file cgroup.events of the cgroup, which is modified when the cgroup becomes
+/// populated or empty, it exists in the unified hierarchy only.
+pub fn cg_events_path(cg_path: &PathBuf) -> Result<PathBuf, CgroupErr> {
+    if cg_type()? < CgType::UnifiedV1 {
+        return Err(CgroupErr::NotSupported);
+    }
+
+    cg_abs_path(cg_path, &PathBuf::from("cgroup.events"))
+}
+
 fn get_pids(cg_path: &PathBuf, item: &str) -> Result<Vec<Pid>, CgroupErr> {
     let path = cg_abs_path(cg_path, &PathBuf::from(item))?;
     let file = fs::OpenOptions::new()
@@ -430,6 +541,32 @@ mod tests {
         assert_eq!(pids.len(), 0);
     }
 
+    #[test]
+    fn test_cg_slice_to_path() {
+        use std::path::PathBuf;
+
+        assert_eq!(super::cg_slice_to_path("-.slice"), PathBuf::from(""));
+        assert_eq!(
+            super::cg_slice_to_path("system.slice"),
+            PathBuf::from("system.slice")
+        );
+        assert_eq!(
+            super::cg_slice_to_path("a-b-c.slice"),
+            PathBuf::from("a.slice/a-b.slice/a-b-c.slice")
+        );
+    }
+
+    #[test]
+    fn test_cg_cpu_quota() {
+        assert_eq!(super::cg_cpu_quota("20%"), Some("20000 100000".to_string()));
+        assert_eq!(
+            super::cg_cpu_quota("150%"),
+            Some("150000 100000".to_string())
+        );
+        assert_eq!(super::cg_cpu_quota("0%"), None);
+        assert_eq!(super::cg_cpu_quota("20"), None);
+    }
+
     #[test]
     fn test_cg_file_type() {
         println!("file type is {:?}", super::cg_type());
@@ -40,9 +40,15 @@ impl std::fmt::Display for CgroupErr {
 
 pub use crate::cgroup::cg_attach;
 pub use crate::cgroup::cg_controllers;
+pub use crate::cgroup::cg_cpu_quota;
 pub use crate::cgroup::cg_create;
+pub use crate::cgroup::cg_enable_controllers;
 pub use crate::cgroup::cg_escape;
+pub use crate::cgroup::cg_events_path;
 pub use crate::cgroup::cg_get_pids;
 pub use crate::cgroup::cg_is_empty_recursive;
 pub use crate::cgroup::cg_kill_recursive;
+pub use crate::cgroup::cg_set_attr;
+pub use crate::cgroup::cg_set_resources;
+pub use crate::cgroup::cg_slice_to_path;
 pub use crate::cgroup::cg_type;
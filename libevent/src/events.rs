@@ -9,7 +9,7 @@ use utils::Error;
 use utils::Result;
 
 use std::cell::RefCell;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::mem::MaybeUninit;
 use std::os::unix::prelude::{AsRawFd, RawFd};
@@ -146,6 +146,7 @@ pub(crate) struct EventsData {
     children: HashMap<i64, i64>,
     pidfd: RawFd,
     timerfd: HashMap<EventType, RawFd>,
+    timerfd_polled: HashSet<RawFd>,
     signal: Signals,
     timer: Timer,
     inotify: Inotify,
@@ -166,6 +167,7 @@ impl EventsData {
             children: HashMap::new(),
             pidfd: 0,
             timerfd: HashMap::new(),
+            timerfd_polled: HashSet::new(),
             signal: Signals::new(),
             timer: Timer::new(),
             inotify: Inotify::init(InitFlags::IN_CLOEXEC | InitFlags::IN_NONBLOCK).unwrap(),
@@ -293,20 +295,21 @@ impl EventsData {
             | EventType::TimerBoottime
             | EventType::TimerMonotonic
             | EventType::TimerRealtimeAlarm
-            | EventType::TimerBoottimeAlarm => match self.timerfd.get(&et) {
-                None => {
+            | EventType::TimerBoottimeAlarm => {
+                if !self.timerfd.contains_key(&et) {
                     let fd = unsafe {
                         libc::timerfd_create(
                             self.timer.clockid(&et),
                             libc::TFD_NONBLOCK | libc::TFD_CLOEXEC,
                         )
                     };
-                    self.timerfd.insert(et, fd);
-                    self.poller.register(fd, &mut event)?;
-                    self.timer.push(source.clone());
+                    self.timerfd.insert(source.event_type(), fd);
                 }
-                Some(_) => self.timer.push(source.clone()),
-            },
+                // enabling an armed timer again re-arms it with the new time
+                self.timer.remove(source);
+                self.timer.push(source.clone());
+                self.timerfd_update(&et)?;
+            }
             EventType::Defer => {
                 self.pending.push(source.clone());
             }
@@ -348,12 +351,8 @@ impl EventsData {
             | EventType::TimerMonotonic
             | EventType::TimerRealtimeAlarm
             | EventType::TimerBoottimeAlarm => {
-                if self.timer.is_empty(&et) {
-                    let fd = self.timerfd.get(&et);
-                    if let Some(fd) = fd {
-                        self.poller.unregister(fd.as_raw_fd())?
-                    }
-                }
+                self.timer.remove(source);
+                self.timerfd_update(&et)?;
             }
             EventType::Inotify => {
                 self.poller.unregister(self.inotify.as_raw_fd())?;
@@ -367,6 +366,33 @@ impl EventsData {
         Ok(0)
     }
 
+    /// the timerfd shared by all the sources of the clock is polled while any of them is armed,
+    /// its token is the fd itself rather than any of the sources.
+    pub(self) fn timerfd_update(&mut self, et: &EventType) -> Result<i32> {
+        let fd = match self.timerfd.get(et) {
+            Some(fd) => *fd,
+            None => return Ok(0),
+        };
+
+        let polled = self.timerfd_polled.contains(&fd);
+        match (polled, self.timer.is_empty(et)) {
+            (false, false) => {
+                let mut event = libc::epoll_event {
+                    events: libc::EPOLLIN as u32,
+                    u64: fd as u64,
+                };
+                self.poller.register(fd, &mut event)?;
+                self.timerfd_polled.insert(fd);
+            }
+            (true, true) => {
+                self.poller.unregister(fd)?;
+                self.timerfd_polled.remove(&fd);
+            }
+            _ => (),
+        }
+        Ok(0)
+    }
+
     pub(self) fn add_child(&mut self, event: &mut libc::epoll_event, pid: libc::pid_t) {
         let pidfd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
         self.pidfd = pidfd.try_into().unwrap();
@@ -415,6 +441,10 @@ impl EventsData {
             let token = event.u64;
             if let Some(s) = self.sources.get(&token) {
                 self.pending.push(s.clone());
+            } else if let Some(fd) = self.timerfd.values().find(|fd| **fd as u64 == token) {
+                // drain the expiration, the due sources are collected below
+                let mut buf = [0u8; 8];
+                let _ = nix::unistd::read(*fd, &mut buf);
             }
         }
 
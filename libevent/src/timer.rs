@@ -109,7 +109,7 @@ impl Timer {
             },
             it_value: libc::timespec {
                 tv_sec: (next / USEC_PER_SEC) as i64,
-                tv_nsec: (next % USEC_PER_SEC) as i64,
+                tv_nsec: ((next % USEC_PER_SEC) * NSEC_PER_USEC) as i64,
             },
         }
     }
@@ -150,6 +150,14 @@ impl Timer {
         };
     }
 
+    /// drop the pending expirations of the source, so it can be armed again with a new time
+    pub fn remove(&mut self, source: &Rc<dyn Source>) {
+        let token = source.token();
+        if let Some(timer) = self.timer_set.get_mut(&source.event_type()) {
+            timer.data.retain(|cd| cd.source.token() != token);
+        }
+    }
+
     pub fn pop(&mut self, et: &EventType) -> Option<Rc<dyn Source>> {
         let next = self.timerid(et);
         // self.now();
@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod test {
+    use std::cell::Cell;
     use std::os::unix::prelude::RawFd;
     use std::rc::Rc;
     use utils::Error;
@@ -62,4 +63,75 @@ mod test {
 
         e.del_source(s.clone()).unwrap();
     }
+
+    #[derive(Debug)]
+    struct Rearm {
+        fired: Cell<u32>,
+    }
+
+    impl Source for Rearm {
+        fn event_type(&self) -> EventType {
+            EventType::TimerMonotonic
+        }
+
+        fn epoll_event(&self) -> u32 {
+            (libc::EPOLLIN) as u32
+        }
+
+        fn priority(&self) -> i8 {
+            0i8
+        }
+
+        fn time_relative(&self) -> u64 {
+            10000
+        }
+
+        fn dispatch(&self, _: &Events) -> Result<i32, Error> {
+            self.fired.set(self.fired.get() + 1);
+            Ok(0)
+        }
+
+        fn token(&self) -> u64 {
+            let data: u64 = unsafe { std::mem::transmute(self) };
+            data
+        }
+    }
+
+    #[test]
+    fn test_timer_rearm() {
+        let e = Events::new().unwrap();
+        let rearm = Rc::new(Rearm {
+            fired: Cell::new(0),
+        });
+        let s: Rc<dyn Source> = rearm.clone();
+        e.add_source(s.clone()).unwrap();
+
+        for i in 1..=3 {
+            e.set_enabled(s.clone(), EventState::OneShot).unwrap();
+            while rearm.fired.get() < i {
+                e.run(-1).unwrap();
+            }
+        }
+
+        // a timer enabled twice only fires once
+        e.set_enabled(s.clone(), EventState::OneShot).unwrap();
+        e.set_enabled(s.clone(), EventState::OneShot).unwrap();
+        while rearm.fired.get() < 4 {
+            e.run(-1).unwrap();
+        }
+        e.run(50).unwrap();
+        assert_eq!(rearm.fired.get(), 4);
+
+        // a disabled timer does not fire, and is polled again once enabled
+        e.set_enabled(s.clone(), EventState::OneShot).unwrap();
+        e.set_enabled(s.clone(), EventState::Off).unwrap();
+        e.run(50).unwrap();
+        assert_eq!(rearm.fired.get(), 4);
+        e.set_enabled(s.clone(), EventState::OneShot).unwrap();
+        while rearm.fired.get() < 5 {
+            e.run(-1).unwrap();
+        }
+
+        e.del_source(s.clone()).unwrap();
+    }
 }
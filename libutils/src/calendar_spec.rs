@@ -0,0 +1,887 @@
+//! calendar event expressions of OnCalendar=, such as "Mon..Fri *-*-* 08:00:00 Asia/Shanghai"
+//!
+//! The grammar is "[WEEKDAYS] [[YEAR-]MONTH-DAY] [HOUR:MINUTE[:SECOND]] [TIMEZONE]", every
+//! component is "*" or a comma-separated list of "VALUE", "START..STOP" and "START[..STOP]/REPEAT".
+//! "MONTH~DAY" counts the day from the end of the month, and the shorthands "minutely", "hourly",
+//! "daily", "weekly", "monthly", "quarterly", "semiannually" and "yearly" are accepted.
+use nix::libc;
+use std::env;
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+const USEC_PER_SEC: i64 = 1_000_000;
+const SEC_PER_DAY: i64 = 86_400;
+const ZONEINFO_PATH: &str = "/usr/share/zoneinfo";
+
+// the years after this one are never elapsed
+const YEAR_MAX: i32 = 2199;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const WEEKDAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+extern "C" {
+    fn tzset();
+}
+
+// TZ is process-wide, the local time conversions are serialised while it is switched
+static TZ_LOCK: Mutex<()> = Mutex::new(());
+
+// one element of a component list: START[..STOP][/REPEAT]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CalendarComponent {
+    start: i64,
+    stop: Option<i64>,
+    repeat: i64,
+}
+
+// the broken-down time where the search of the next elapse happens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DateTime {
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: i32,
+    minute: i32,
+    second: i32,
+    usec: i64,
+}
+
+/// a parsed calendar event expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarSpec {
+    // bit 0 is Monday, 0 means any weekday
+    weekdays: u8,
+    // an empty list matches every value
+    year: Vec<CalendarComponent>,
+    month: Vec<CalendarComponent>,
+    day: Vec<CalendarComponent>,
+    hour: Vec<CalendarComponent>,
+    minute: Vec<CalendarComponent>,
+    // in useconds
+    second: Vec<CalendarComponent>,
+    end_of_month: bool,
+    utc: bool,
+    timezone: Option<String>,
+}
+
+impl FromStr for CalendarSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut spec = CalendarSpec {
+            weekdays: 0,
+            year: Vec::new(),
+            month: Vec::new(),
+            day: Vec::new(),
+            hour: Vec::new(),
+            minute: Vec::new(),
+            second: Vec::new(),
+            end_of_month: false,
+            utc: false,
+            timezone: None,
+        };
+
+        let mut words: Vec<&str> = s.split_whitespace().collect();
+        if words.len() > 1 {
+            let last = words[words.len() - 1];
+            if last == "UTC" || last == "Z" {
+                spec.utc = true;
+                words.pop();
+            } else if timezone_is_valid(last) {
+                spec.timezone = Some(last.to_string());
+                words.pop();
+            }
+        }
+
+        if words.is_empty() {
+            return Err(format!("empty calendar expression: {}", s));
+        }
+
+        if words.len() == 1 {
+            if let Some(expanded) = shorthand(words[0]) {
+                words = expanded.split_whitespace().collect();
+            }
+        }
+
+        let mut words = words.into_iter().peekable();
+        if let Some(w) = words.peek() {
+            if w.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                spec.weekdays = parse_weekdays(w)?;
+                words.next();
+            }
+        }
+
+        let mut date = None;
+        let mut time = None;
+        for word in words {
+            if time.is_some() {
+                return Err(format!("invalid calendar expression: {}", s));
+            }
+            if word.contains(':') {
+                time = Some(word);
+            } else if date.is_none() {
+                date = Some(word);
+            } else {
+                return Err(format!("invalid calendar expression: {}", s));
+            }
+        }
+
+        if let Some(date) = date {
+            spec.parse_date(date)?;
+        }
+
+        match time {
+            Some(time) => spec.parse_time(time)?,
+            None => {
+                spec.hour = vec![CalendarComponent::value(0)];
+                spec.minute = vec![CalendarComponent::value(0)];
+                spec.second = vec![CalendarComponent::value(0)];
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+impl CalendarSpec {
+    /// the realtime in useconds when the expression elapses next after {usec}, None if never
+    pub fn next_elapse(&self, usec: u64) -> Option<u64> {
+        let usec = usec.checked_add(1)?;
+        let secs = (usec / USEC_PER_SEC as u64) as i64;
+        let start = match self.utc {
+            true => DateTime::from_utc(secs),
+            false => with_timezone(self.timezone.as_deref(), || DateTime::from_local(secs))?,
+        };
+        let start = DateTime {
+            usec: (usec % USEC_PER_SEC as u64) as i64,
+            ..start
+        };
+
+        let next = self.find_next(start)?;
+        let secs = match self.utc {
+            true => next.to_utc(),
+            false => with_timezone(self.timezone.as_deref(), || next.to_local())?,
+        };
+
+        if secs < 0 {
+            return None;
+        }
+        Some(secs as u64 * USEC_PER_SEC as u64 + next.usec as u64)
+    }
+
+    fn find_next(&self, mut c: DateTime) -> Option<DateTime> {
+        loop {
+            c.normalize();
+            if c.year > YEAR_MAX {
+                return None;
+            }
+
+            let year = find_matching(&self.year, c.year as i64)?;
+            if year != c.year as i64 {
+                if year > YEAR_MAX as i64 {
+                    return None;
+                }
+                c.year = year as i32;
+                c.month = 1;
+                c.day = 1;
+                c.reset_time();
+            }
+
+            match find_matching(&self.month, c.month as i64) {
+                Some(month) if month <= 12 => {
+                    if month != c.month as i64 {
+                        c.month = month as i32;
+                        c.day = 1;
+                        c.reset_time();
+                    }
+                }
+                _ => {
+                    c.year += 1;
+                    c.month = 1;
+                    c.day = 1;
+                    c.reset_time();
+                    continue;
+                }
+            }
+
+            let days = days_in_month(c.year, c.month);
+            let day = match self.end_of_month {
+                true => find_matching(&from_month_end(&self.day, days), c.day as i64),
+                false => find_matching(&self.day, c.day as i64),
+            };
+            match day {
+                Some(day) if day >= 1 && day <= days as i64 => {
+                    if day != c.day as i64 {
+                        c.day = day as i32;
+                        c.reset_time();
+                    }
+                }
+                _ => {
+                    c.month += 1;
+                    c.day = 1;
+                    c.reset_time();
+                    continue;
+                }
+            }
+
+            if self.weekdays != 0 && self.weekdays & (1 << c.weekday()) == 0 {
+                c.day += 1;
+                c.reset_time();
+                continue;
+            }
+
+            match find_matching(&self.hour, c.hour as i64) {
+                Some(hour) if hour <= 23 => {
+                    if hour != c.hour as i64 {
+                        c.hour = hour as i32;
+                        c.minute = 0;
+                        c.second = 0;
+                        c.usec = 0;
+                    }
+                }
+                _ => {
+                    c.day += 1;
+                    c.reset_time();
+                    continue;
+                }
+            }
+
+            match find_matching(&self.minute, c.minute as i64) {
+                Some(minute) if minute <= 59 => {
+                    if minute != c.minute as i64 {
+                        c.minute = minute as i32;
+                        c.second = 0;
+                        c.usec = 0;
+                    }
+                }
+                _ => {
+                    c.hour += 1;
+                    c.minute = 0;
+                    c.second = 0;
+                    c.usec = 0;
+                    continue;
+                }
+            }
+
+            let usec = c.second as i64 * USEC_PER_SEC + c.usec;
+            match find_matching(&self.second, usec) {
+                Some(usec) if usec < 60 * USEC_PER_SEC => {
+                    c.second = (usec / USEC_PER_SEC) as i32;
+                    c.usec = usec % USEC_PER_SEC;
+                }
+                _ => {
+                    c.minute += 1;
+                    c.second = 0;
+                    c.usec = 0;
+                    continue;
+                }
+            }
+
+            return Some(c);
+        }
+    }
+
+    fn parse_date(&mut self, date: &str) -> Result<(), String> {
+        let (year_month, day) = match date.split_once('~') {
+            Some((ym, d)) => {
+                self.end_of_month = true;
+                (ym.split('-').collect::<Vec<&str>>(), d)
+            }
+            None => match date.rsplit_once('-') {
+                Some((ym, d)) => (ym.split('-').collect(), d),
+                None => return Err(format!("invalid date: {}", date)),
+            },
+        };
+
+        match year_month.as_slice() {
+            [month] => self.month = parse_component(month, 1, 12, 1)?,
+            [year, month] => {
+                self.year = parse_component(year, 0, YEAR_MAX as i64, 1)?
+                    .into_iter()
+                    .map(|c| c.full_year())
+                    .collect();
+                self.month = parse_component(month, 1, 12, 1)?;
+            }
+            _ => return Err(format!("invalid date: {}", date)),
+        }
+        self.day = parse_component(day, 1, 31, 1)?;
+
+        Ok(())
+    }
+
+    fn parse_time(&mut self, time: &str) -> Result<(), String> {
+        let fields: Vec<&str> = time.split(':').collect();
+        let (hour, minute, second) = match fields.as_slice() {
+            [h, m] => (*h, *m, None),
+            [h, m, s] => (*h, *m, Some(*s)),
+            _ => return Err(format!("invalid time: {}", time)),
+        };
+
+        self.hour = parse_component(hour, 0, 23, 1)?;
+        self.minute = parse_component(minute, 0, 59, 1)?;
+        self.second = match second {
+            Some(s) => parse_component(s, 0, 59, USEC_PER_SEC)?,
+            None => vec![CalendarComponent::value(0)],
+        };
+
+        Ok(())
+    }
+}
+
+impl Display for CalendarSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.weekdays != 0 {
+            let days: Vec<&str> = (0..7)
+                .filter(|i| self.weekdays & (1 << i) != 0)
+                .map(|i| WEEKDAYS[i])
+                .collect();
+            write!(f, "{} ", days.join(","))?;
+        }
+
+        write!(
+            f,
+            "{}-{}{}{} {}:{}:{}",
+            format_component(&self.year, 4, 1),
+            format_component(&self.month, 2, 1),
+            if self.end_of_month { "~" } else { "-" },
+            format_component(&self.day, 2, 1),
+            format_component(&self.hour, 2, 1),
+            format_component(&self.minute, 2, 1),
+            format_component(&self.second, 2, USEC_PER_SEC),
+        )?;
+
+        if self.utc {
+            write!(f, " UTC")?;
+        } else if let Some(tz) = &self.timezone {
+            write!(f, " {}", tz)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CalendarComponent {
+    fn value(v: i64) -> Self {
+        CalendarComponent {
+            start: v,
+            stop: None,
+            repeat: 0,
+        }
+    }
+
+    // two-digit years are in 1970..2069
+    fn full_year(self) -> Self {
+        let full = |y: i64| match y {
+            0..=69 => y + 2000,
+            70..=99 => y + 1900,
+            _ => y,
+        };
+        CalendarComponent {
+            start: full(self.start),
+            stop: self.stop.map(full),
+            repeat: self.repeat,
+        }
+    }
+}
+
+impl DateTime {
+    fn from_utc(secs: i64) -> Self {
+        let days = secs.div_euclid(SEC_PER_DAY);
+        let rem = secs.rem_euclid(SEC_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        DateTime {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as i32,
+            minute: (rem % 3600 / 60) as i32,
+            second: (rem % 60) as i32,
+            usec: 0,
+        }
+    }
+
+    fn to_utc(self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * SEC_PER_DAY
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    fn from_local(secs: i64) -> Option<Self> {
+        let t = secs as libc::time_t;
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+            return None;
+        }
+        Some(DateTime {
+            year: tm.tm_year + 1900,
+            month: tm.tm_mon + 1,
+            day: tm.tm_mday,
+            hour: tm.tm_hour,
+            minute: tm.tm_min,
+            second: tm.tm_sec,
+            usec: 0,
+        })
+    }
+
+    fn to_local(self) -> Option<libc::time_t> {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        tm.tm_year = self.year - 1900;
+        tm.tm_mon = self.month - 1;
+        tm.tm_mday = self.day;
+        tm.tm_hour = self.hour;
+        tm.tm_min = self.minute;
+        tm.tm_sec = self.second;
+        tm.tm_isdst = -1;
+        match unsafe { libc::mktime(&mut tm) } {
+            -1 => None,
+            t => Some(t),
+        }
+    }
+
+    fn reset_time(&mut self) {
+        self.hour = 0;
+        self.minute = 0;
+        self.second = 0;
+        self.usec = 0;
+    }
+
+    // carry the overflowed fields into the higher ones
+    fn normalize(&mut self) {
+        if self.usec >= USEC_PER_SEC {
+            self.second += (self.usec / USEC_PER_SEC) as i32;
+            self.usec %= USEC_PER_SEC;
+        }
+        if self.second >= 60 {
+            self.minute += self.second / 60;
+            self.second %= 60;
+        }
+        if self.minute >= 60 {
+            self.hour += self.minute / 60;
+            self.minute %= 60;
+        }
+        if self.hour >= 24 {
+            self.day += self.hour / 24;
+            self.hour %= 24;
+        }
+        loop {
+            if self.month > 12 {
+                self.year += (self.month - 1) / 12;
+                self.month = (self.month - 1) % 12 + 1;
+            }
+            let days = days_in_month(self.year, self.month);
+            if self.day <= days {
+                break;
+            }
+            self.day -= days;
+            self.month += 1;
+        }
+    }
+
+    // 0 is Monday
+    fn weekday(&self) -> u32 {
+        // 1970-01-01 is a Thursday
+        (days_from_civil(self.year, self.month, self.day) + 3).rem_euclid(7) as u32
+    }
+}
+
+// the smallest value not less than {val} matched by {components}
+fn find_matching(components: &[CalendarComponent], val: i64) -> Option<i64> {
+    if components.is_empty() {
+        return Some(val);
+    }
+
+    let mut next: Option<i64> = None;
+    for c in components {
+        let candidate = if c.start >= val {
+            c.start
+        } else if c.repeat > 0 {
+            c.start + c.repeat * ((val - c.start + c.repeat - 1) / c.repeat)
+        } else {
+            continue;
+        };
+
+        if matches!(c.stop, Some(stop) if candidate > stop) {
+            continue;
+        }
+        match next {
+            Some(n) if n <= candidate => {}
+            _ => next = Some(candidate),
+        }
+    }
+
+    next
+}
+
+// turn the days counted from the end of a month into the days of the month
+fn from_month_end(components: &[CalendarComponent], days: i32) -> Vec<CalendarComponent> {
+    let day = |d: i64| days as i64 + 1 - d;
+    components
+        .iter()
+        .map(|c| match c.stop {
+            Some(stop) => CalendarComponent {
+                start: day(stop).min(day(c.start)),
+                stop: Some(day(stop).max(day(c.start))),
+                repeat: c.repeat,
+            },
+            None if c.repeat > 0 => CalendarComponent {
+                start: day(c.start),
+                stop: Some(days as i64),
+                repeat: c.repeat,
+            },
+            None => CalendarComponent::value(day(c.start)),
+        })
+        .collect()
+}
+
+fn parse_component(
+    s: &str,
+    min: i64,
+    max: i64,
+    scale: i64,
+) -> Result<Vec<CalendarComponent>, String> {
+    if s == "*" {
+        return Ok(Vec::new());
+    }
+
+    let mut components = Vec::new();
+    for item in s.split(',') {
+        let (range, repeat) = match item.split_once('/') {
+            Some((r, rep)) => (r, Some(parse_value(rep, scale)?)),
+            None => (item, None),
+        };
+
+        let (start, stop) = match range.split_once("..") {
+            Some((a, b)) => (parse_value(a, scale)?, Some(parse_value(b, scale)?)),
+            None if range == "*" => (min * scale, None),
+            None => (parse_value(range, scale)?, None),
+        };
+
+        if start < min * scale || matches!(stop, Some(s) if s < start) {
+            return Err(format!("invalid calendar component: {}", item));
+        }
+        let limit = max * scale + (scale - 1);
+        if start > limit || matches!(stop, Some(s) if s > limit) {
+            return Err(format!("calendar component out of range: {}", item));
+        }
+
+        let repeat = match (repeat, stop) {
+            (Some(0), _) => return Err(format!("invalid calendar repetition: {}", item)),
+            (Some(r), _) => r,
+            (None, Some(_)) => scale,
+            (None, None) => 0,
+        };
+
+        components.push(CalendarComponent {
+            start,
+            stop,
+            repeat,
+        });
+    }
+
+    Ok(components)
+}
+
+// parse a decimal value, the fraction is allowed when {scale} is not 1
+fn parse_value(s: &str, scale: i64) -> Result<i64, String> {
+    let err = || format!("invalid calendar value: {}", s);
+    let (int, frac) = match s.split_once('.') {
+        Some((i, f)) if scale > 1 => (i, f),
+        _ => (s, ""),
+    };
+
+    if int.is_empty() || !int.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(err());
+    }
+    if !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(err());
+    }
+
+    let mut value = int.parse::<i64>().map_err(|_| err())? * scale;
+    let mut unit = scale;
+    for b in frac.bytes().take(6) {
+        unit /= 10;
+        value += (b - b'0') as i64 * unit;
+    }
+    Ok(value)
+}
+
+fn parse_weekdays(s: &str) -> Result<u8, String> {
+    let weekday = |w: &str| -> Result<u32, String> {
+        let lower = w.to_ascii_lowercase();
+        WEEKDAY_NAMES
+            .iter()
+            .position(|n| lower.len() >= 3 && n.starts_with(&lower))
+            .map(|p| p as u32)
+            .ok_or(format!("invalid weekday: {}", w))
+    };
+
+    let mut bits = 0u8;
+    for item in s.split(',') {
+        let range = item.split_once("..").or_else(|| item.split_once('-'));
+        match range {
+            Some((a, b)) => {
+                let (a, b) = (weekday(a)?, weekday(b)?);
+                // a range such as Sat..Mon wraps around the end of the week
+                let mut d = a;
+                loop {
+                    bits |= 1 << d;
+                    if d == b {
+                        break;
+                    }
+                    d = (d + 1) % 7;
+                }
+            }
+            None => bits |= 1 << weekday(item)?,
+        }
+    }
+
+    Ok(bits)
+}
+
+fn format_component(components: &[CalendarComponent], width: usize, scale: i64) -> String {
+    if components.is_empty() {
+        return "*".to_string();
+    }
+
+    let value = |v: i64, width: usize| match v % scale {
+        0 => format!("{:0w$}", v / scale, w = width),
+        frac => format!("{:0w$}.{:06}", v / scale, frac, w = width),
+    };
+    components
+        .iter()
+        .map(|c| {
+            let mut s = value(c.start, width);
+            if let Some(stop) = c.stop {
+                s = format!("{}..{}", s, value(stop, width));
+            }
+            if c.repeat > 0 && !(c.stop.is_some() && c.repeat == scale) {
+                s = format!("{}/{}", s, value(c.repeat, 1));
+            }
+            s
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn shorthand(s: &str) -> Option<&'static str> {
+    let expanded = match s {
+        "minutely" => "*-*-* *:*:00",
+        "hourly" => "*-*-* *:00:00",
+        "daily" => "*-*-* 00:00:00",
+        "weekly" => "Mon *-*-* 00:00:00",
+        "monthly" => "*-*-01 00:00:00",
+        "quarterly" => "*-01,04,07,10-01 00:00:00",
+        "semiannually" => "*-01,07-01 00:00:00",
+        "yearly" | "annually" => "*-01-01 00:00:00",
+        _ => return None,
+    };
+    Some(expanded)
+}
+
+/// whether {tz} names a time zone in the tz database, such as "Asia/Shanghai"
+pub fn timezone_is_valid(tz: &str) -> bool {
+    if tz.is_empty() || tz.starts_with('/') || tz.split('/').any(|p| p == "..") {
+        return false;
+    }
+
+    Path::new(ZONEINFO_PATH).join(tz).is_file()
+}
+
+// run {f} with the local time zone switched to {tz}, TZ is restored even if {f} panics
+fn with_timezone<T>(tz: Option<&str>, f: impl FnOnce() -> T) -> T {
+    let _lock = TZ_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let tz = match tz {
+        Some(tz) => tz,
+        None => return f(),
+    };
+
+    let _restore = TzRestore(env::var_os("TZ"));
+    env::set_var("TZ", format!(":{}", tz));
+    unsafe { tzset() };
+    f()
+}
+
+// put the saved TZ back when the switched time zone is out of scope
+struct TzRestore(Option<OsString>);
+
+impl Drop for TzRestore {
+    fn drop(&mut self) {
+        match self.0.take() {
+            Some(v) => env::set_var("TZ", v),
+            None => env::remove_var("TZ"),
+        }
+        unsafe { tzset() };
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: i32) -> i32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// the days since 1970-01-01 of the date
+fn days_from_civil(year: i32, month: i32, day: i32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year } as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+// the date of the days since 1970-01-01
+fn civil_from_days(days: i64) -> (i32, i32, i32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year as i32, month as i32, day as i32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 2023-03-15 10:20:30 UTC, a Wednesday
+    const NOW: u64 = 1_678_875_630_000_000;
+
+    fn next(spec: &str, usec: u64) -> Option<u64> {
+        spec.parse::<CalendarSpec>().unwrap().next_elapse(usec)
+    }
+
+    fn utc(year: i32, month: i32, day: i32, hour: i32, minute: i32, second: i32) -> u64 {
+        let t = DateTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            usec: 0,
+        };
+        t.to_utc() as u64 * USEC_PER_SEC as u64
+    }
+
+    #[test]
+    fn test_calendar_parse() {
+        let normalized = |s: &str| s.parse::<CalendarSpec>().unwrap().to_string();
+        assert_eq!(normalized("daily UTC"), "*-*-* 00:00:00 UTC");
+        assert_eq!(normalized("weekly UTC"), "Mon *-*-* 00:00:00 UTC");
+        assert_eq!(
+            normalized("Mon..Wed,Sat 2023-*-1,15 8:30 UTC"),
+            "Mon,Tue,Wed,Sat 2023-*-01,15 08:30:00 UTC"
+        );
+        assert_eq!(normalized("*:0/15 UTC"), "*-*-* *:00/15:00 UTC");
+        assert_eq!(normalized("*-02~03 UTC"), "*-02~03 00:00:00 UTC");
+        assert_eq!(normalized("12-25 UTC"), "*-12-25 00:00:00 UTC");
+        assert_eq!(normalized("23-1..3-1 UTC"), "2023-01..03-01 00:00:00 UTC");
+        assert_eq!(normalized("*:*:1.5 UTC"), "*-*-* *:*:01.500000 UTC");
+
+        assert!("".parse::<CalendarSpec>().is_err());
+        assert!("Funday *-*-*".parse::<CalendarSpec>().is_err());
+        assert!("*-13-01".parse::<CalendarSpec>().is_err());
+        assert!("24:00".parse::<CalendarSpec>().is_err());
+        assert!("*:0/0".parse::<CalendarSpec>().is_err());
+        assert!("*-*-* 00:00 00:00".parse::<CalendarSpec>().is_err());
+    }
+
+    #[test]
+    fn test_calendar_next_elapse() {
+        assert_eq!(next("daily UTC", NOW), Some(utc(2023, 3, 16, 0, 0, 0)));
+        assert_eq!(next("hourly UTC", NOW), Some(utc(2023, 3, 15, 11, 0, 0)));
+        assert_eq!(next("*:0/15 UTC", NOW), Some(utc(2023, 3, 15, 10, 30, 0)));
+        assert_eq!(next("weekly UTC", NOW), Some(utc(2023, 3, 20, 0, 0, 0)));
+        assert_eq!(next("monthly UTC", NOW), Some(utc(2023, 4, 1, 0, 0, 0)));
+        assert_eq!(next("quarterly UTC", NOW), Some(utc(2023, 4, 1, 0, 0, 0)));
+        assert_eq!(next("yearly UTC", NOW), Some(utc(2024, 1, 1, 0, 0, 0)));
+        assert_eq!(
+            next("Sat,Sun 09:00 UTC", NOW),
+            Some(utc(2023, 3, 18, 9, 0, 0))
+        );
+        assert_eq!(
+            next("Fri *-*-13 UTC", NOW),
+            Some(utc(2023, 10, 13, 0, 0, 0))
+        );
+        assert_eq!(next("*-02-29 UTC", NOW), Some(utc(2024, 2, 29, 0, 0, 0)));
+        assert_eq!(next("*-*-31 UTC", NOW), Some(utc(2023, 3, 31, 0, 0, 0)));
+        assert_eq!(next("*-04-31 UTC", NOW), None);
+        assert_eq!(next("*-02~01 UTC", NOW), Some(utc(2024, 2, 29, 0, 0, 0)));
+        assert_eq!(next("*-*~03 UTC", NOW), Some(utc(2023, 3, 29, 0, 0, 0)));
+        assert_eq!(next("2020-01-01 UTC", NOW), None);
+        assert_eq!(
+            next("10:20:30 UTC", NOW),
+            Some(utc(2023, 3, 16, 10, 20, 30))
+        );
+        assert_eq!(
+            next("*:*:*/0.5 UTC", NOW),
+            Some(utc(2023, 3, 15, 10, 20, 30) + 500_000)
+        );
+    }
+
+    #[test]
+    fn test_calendar_timezone() {
+        if !timezone_is_valid("Asia/Shanghai") {
+            return;
+        }
+
+        let spec = "*-*-* 08:00:00 Asia/Shanghai"
+            .parse::<CalendarSpec>()
+            .unwrap();
+        assert_eq!(spec.next_elapse(NOW), Some(utc(2023, 3, 16, 0, 0, 0)));
+        assert!(!timezone_is_valid("../etc/passwd"));
+
+        // the switched time zone does not leak into the other threads, nor stays afterwards
+        let saved = env::var_os("TZ");
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(|| {
+                    let spec = "*-*-* 08:00:00 Asia/Shanghai"
+                        .parse::<CalendarSpec>()
+                        .unwrap();
+                    for _ in 0..50 {
+                        assert_eq!(spec.next_elapse(NOW), Some(utc(2023, 3, 16, 0, 0, 0)));
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(env::var_os("TZ"), saved);
+    }
+
+    #[test]
+    fn test_civil_days() {
+        for days in [-1, 0, 59, 365, 11_016, 19_431, 47_541] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+        assert_eq!(civil_from_days(19_431), (2023, 3, 15));
+    }
+}
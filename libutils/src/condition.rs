@@ -1,10 +1,94 @@
+use crate::path_util::{fnmatch, fnmatch_casefold};
+use crate::{conf_parser, mount_util, proc_cmdline, virt};
+use nix::libc;
+use nix::sys::statvfs::{statvfs, FsFlags};
+use nix::sys::utsname::uname;
+use nix::unistd::{self, AccessFlags, Gid, Group, Uid, User};
+use std::cmp::Ordering;
+use std::env;
+use std::ffi::CString;
+use std::fs;
+use std::io::Read;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
 use std::path::Path;
 
-#[derive(Eq, PartialEq)]
+// the max uid of the system users
+const SYSTEM_UID_MAX: u32 = 999;
+
+const MACHINE_ID_PATH: &str = "/etc/machine-id";
+
+const EFI_SECURE_BOOT_VAR: &str =
+    "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+const CAPABILITY_NAMES: [&str; 41] = [
+    "cap_chown",
+    "cap_dac_override",
+    "cap_dac_read_search",
+    "cap_fowner",
+    "cap_fsetid",
+    "cap_kill",
+    "cap_setgid",
+    "cap_setuid",
+    "cap_setpcap",
+    "cap_linux_immutable",
+    "cap_net_bind_service",
+    "cap_net_broadcast",
+    "cap_net_admin",
+    "cap_net_raw",
+    "cap_ipc_lock",
+    "cap_ipc_owner",
+    "cap_sys_module",
+    "cap_sys_rawio",
+    "cap_sys_chroot",
+    "cap_sys_ptrace",
+    "cap_sys_pacct",
+    "cap_sys_admin",
+    "cap_sys_boot",
+    "cap_sys_nice",
+    "cap_sys_resource",
+    "cap_sys_time",
+    "cap_sys_tty_config",
+    "cap_mknod",
+    "cap_lease",
+    "cap_audit_write",
+    "cap_audit_control",
+    "cap_setfcap",
+    "cap_mac_override",
+    "cap_mac_admin",
+    "cap_syslog",
+    "cap_wake_alarm",
+    "cap_block_suspend",
+    "cap_audit_read",
+    "cap_perfmon",
+    "cap_bpf",
+    "cap_checkpoint_restore",
+];
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub enum ConditionType {
     PathExists,
+    PathExistsGlob,
+    PathIsDirectory,
+    PathIsSymbolicLink,
+    PathIsMountPoint,
+    PathIsReadWrite,
+    DirectoryNotEmpty,
     FileNotEmpty,
+    FileIsExecutable,
     NeedsUpdate,
+    KernelCommandLine,
+    KernelVersion,
+    Virtualization,
+    Architecture,
+    Host,
+    FirstBoot,
+    Security,
+    Capability,
+    ACPower,
+    User,
+    Group,
+    ControlGroupController,
+    Environment,
     _MAX,
 }
 
@@ -35,67 +119,577 @@ impl Condition {
         self.revert
     }
 
+    pub fn c_type(&self) -> ConditionType {
+        self.c_type
+    }
+
+    pub fn params(&self) -> &str {
+        &self.params
+    }
+
     pub fn test(&self) -> bool {
         if self.params.is_empty() {
             return false;
         }
-        let mut result = match self.c_type {
+        let result = match self.c_type {
             ConditionType::PathExists => self.test_path_exists(),
+            ConditionType::PathExistsGlob => self.test_path_exists_glob(),
+            ConditionType::PathIsDirectory => self.test_path_is_directory(),
+            ConditionType::PathIsSymbolicLink => self.test_path_is_symbolic_link(),
+            ConditionType::PathIsMountPoint => self.test_path_is_mount_point(),
+            ConditionType::PathIsReadWrite => self.test_path_is_read_write(),
+            ConditionType::DirectoryNotEmpty => self.test_directory_not_empty(),
             ConditionType::FileNotEmpty => self.test_file_not_empty(),
+            ConditionType::FileIsExecutable => self.test_file_is_executable(),
             ConditionType::NeedsUpdate => self.test_needs_update(),
-            ConditionType::_MAX => todo!(),
+            ConditionType::KernelCommandLine => self.test_kernel_command_line(),
+            ConditionType::KernelVersion => self.test_kernel_version(),
+            ConditionType::Virtualization => self.test_virtualization(),
+            ConditionType::Architecture => self.test_architecture(),
+            ConditionType::Host => self.test_host(),
+            ConditionType::FirstBoot => self.test_first_boot(),
+            ConditionType::Security => self.test_security(),
+            ConditionType::Capability => self.test_capability(),
+            ConditionType::ACPower => self.test_ac_power(),
+            ConditionType::User => self.test_user(),
+            ConditionType::Group => self.test_group(),
+            ConditionType::ControlGroupController => self.test_control_group_controller(),
+            ConditionType::Environment => self.test_environment(),
+            ConditionType::_MAX => false,
         };
-        if self.revert() >= 1 {
-            result = !result;
-        }
 
-        if result > 0 {
-            true
+        if self.revert() >= 1 {
+            !result
         } else {
-            false
+            result
         }
     }
 
-    fn test_path_exists(&self) -> i8 {
-        let tmp_path = Path::new(&self.params);
-        let result = tmp_path.exists();
-        if result {
-            return 1;
-        } else {
-            return 0;
-        }
+    fn test_path_exists(&self) -> bool {
+        Path::new(&self.params).exists()
+    }
+
+    fn test_path_exists_glob(&self) -> bool {
+        let pattern = match CString::new(self.params.as_str()) {
+            Ok(p) => p,
+            Err(_) => return false,
+        };
+
+        let mut glob: libc::glob_t = unsafe { std::mem::zeroed() };
+        let ret = unsafe {
+            libc::glob(
+                pattern.as_ptr(),
+                libc::GLOB_NOSORT | libc::GLOB_BRACE,
+                None,
+                &mut glob,
+            )
+        };
+        let result = ret == 0 && glob.gl_pathc > 0;
+        unsafe { libc::globfree(&mut glob) };
+        result
+    }
+
+    fn test_path_is_directory(&self) -> bool {
+        Path::new(&self.params).is_dir()
+    }
+
+    fn test_path_is_symbolic_link(&self) -> bool {
+        Path::new(&self.params)
+            .symlink_metadata()
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false)
+    }
+
+    fn test_path_is_mount_point(&self) -> bool {
+        let path = match Path::new(&self.params).canonicalize() {
+            Ok(p) => p,
+            Err(_) => return false,
+        };
+
+        mount_util::path_is_mount_point(&path)
+    }
+
+    fn test_path_is_read_write(&self) -> bool {
+        statvfs(self.params.as_str())
+            .map(|s| !s.flags().contains(FsFlags::ST_RDONLY))
+            .unwrap_or(false)
     }
 
-    fn test_file_not_empty(&self) -> i8 {
+    fn test_directory_not_empty(&self) -> bool {
+        let entries = match fs::read_dir(&self.params) {
+            Ok(e) => e,
+            Err(_) => return false,
+        };
+
+        // hidden files and backup files do not count
+        entries.filter_map(|e| e.ok()).any(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !name.ends_with('~')
+        })
+    }
+
+    fn test_file_not_empty(&self) -> bool {
         let tmp_path = Path::new(&self.params);
-        let result = tmp_path
+        tmp_path
             .metadata()
-            .map(|m| {
-                if m.is_file() {
-                    m.len() > 0
-                } else {
-                    return false;
+            .map(|m| m.is_file() && m.len() > 0)
+            .unwrap_or(false)
+    }
+
+    fn test_file_is_executable(&self) -> bool {
+        let tmp_path = Path::new(&self.params);
+        if !tmp_path.metadata().map(|m| m.is_file()).unwrap_or(false) {
+            return false;
+        }
+
+        unistd::access(tmp_path, AccessFlags::X_OK).is_ok()
+    }
+
+    // the condition is true if /usr is modified after the last update of
+    // the directory in the parameter, which is usually /etc or /var
+    fn test_needs_update(&self) -> bool {
+        let usr = match fs::metadata("/usr") {
+            Ok(m) => m,
+            Err(_) => return false,
+        };
+
+        let stamp = Path::new(&self.params).join(".updated");
+        let updated = match fs::metadata(&stamp) {
+            Ok(m) => m,
+            Err(_) => return true,
+        };
+
+        (usr.mtime(), usr.mtime_nsec()) > (updated.mtime(), updated.mtime_nsec())
+    }
+
+    fn test_kernel_command_line(&self) -> bool {
+        let cmdline = match proc_cmdline::cmdline_content() {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+
+        let equal = self.params.contains('=');
+        cmdline.split_whitespace().any(|word| {
+            if equal {
+                word == self.params
+            } else {
+                word == self.params
+                    || word
+                        .strip_prefix(self.params.as_str())
+                        .map(|s| s.starts_with('='))
+                        .unwrap_or(false)
+            }
+        })
+    }
+
+    fn test_kernel_version(&self) -> bool {
+        let uts = match uname() {
+            Ok(u) => u,
+            Err(_) => return false,
+        };
+        let release = uts.release().to_string_lossy().to_string();
+
+        let mut words = self.params.split_whitespace();
+        while let Some(word) = words.next() {
+            let (op, version) = parse_compare_operator(word);
+            // the operator could be separated from the version by spaces, such as ">= 5.0"
+            let version = match (op, version.is_empty()) {
+                (Some(_), true) => match words.next() {
+                    Some(v) => v,
+                    None => return false,
+                },
+                _ => version,
+            };
+            let result = match op {
+                None => fnmatch(version, &release),
+                Some(op) => op.test(version_compare(&release, version)),
+            };
+            if !result {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn test_virtualization(&self) -> bool {
+        let container = virt::detect_container();
+
+        if let Ok(b) = conf_parser::parse_boolen(&self.params) {
+            return b == (container.is_some() || virt::detect_vm().is_some());
+        }
+
+        match self.params.as_str() {
+            "container" => container.is_some(),
+            "vm" => container.is_none() && virt::detect_vm().is_some(),
+            "private-users" => running_in_userns(),
+            id => virt::detect_virtualization()
+                .map(|v| v == id)
+                .unwrap_or(false),
+        }
+    }
+
+    fn test_architecture(&self) -> bool {
+        let arch = match uname_architecture() {
+            Some(a) => a,
+            None => return false,
+        };
+
+        if self.params == "native" {
+            return true;
+        }
+
+        self.params == arch
+    }
+
+    fn test_host(&self) -> bool {
+        if let Ok(machine_id) = fs::read_to_string(MACHINE_ID_PATH) {
+            if machine_id.trim().eq_ignore_ascii_case(&self.params) {
+                return true;
+            }
+        }
+
+        match fs::read_to_string("/proc/sys/kernel/hostname") {
+            Ok(hostname) => fnmatch_casefold(&self.params, hostname.trim()),
+            Err(_) => false,
+        }
+    }
+
+    fn test_first_boot(&self) -> bool {
+        let expected = match conf_parser::parse_boolen(&self.params) {
+            Ok(b) => b,
+            Err(_) => return false,
+        };
+
+        first_boot() == expected
+    }
+
+    fn test_security(&self) -> bool {
+        match self.params.as_str() {
+            "selinux" => Path::new("/sys/fs/selinux/enforce").exists(),
+            "apparmor" => fs::read_to_string("/sys/module/apparmor/parameters/enabled")
+                .map(|v| v.trim().starts_with('Y'))
+                .unwrap_or(false),
+            "tomoyo" => Path::new("/sys/kernel/security/tomoyo/version").exists(),
+            "ima" => Path::new("/sys/kernel/security/ima/").exists(),
+            "smack" => Path::new("/sys/fs/smackfs/").exists(),
+            "audit" => Path::new("/proc/self/loginuid").exists(),
+            "uefi-secureboot" => fs::read(EFI_SECURE_BOOT_VAR)
+                .map(|v| v.last() == Some(&1))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    fn test_capability(&self) -> bool {
+        let name = self.params.to_lowercase();
+        let cap = match CAPABILITY_NAMES.iter().position(|c| *c == name) {
+            Some(c) => c,
+            None => return false,
+        };
+
+        let status = match fs::read_to_string("/proc/self/status") {
+            Ok(s) => s,
+            Err(_) => return false,
+        };
+
+        for line in status.lines() {
+            if let Some(v) = line.strip_prefix("CapBnd:") {
+                return u64::from_str_radix(v.trim(), 16)
+                    .map(|mask| mask & (1 << cap) != 0)
+                    .unwrap_or(false);
+            }
+        }
+
+        false
+    }
+
+    fn test_ac_power(&self) -> bool {
+        match conf_parser::parse_boolen(&self.params) {
+            Ok(b) => on_ac_power() == b,
+            Err(_) => false,
+        }
+    }
+
+    fn test_user(&self) -> bool {
+        let uid = Uid::current();
+        let euid = Uid::effective();
+
+        if let Ok(id) = self.params.parse::<u32>() {
+            return Uid::from_raw(id) == uid || Uid::from_raw(id) == euid;
+        }
+
+        if self.params == "@system" {
+            return uid.as_raw() <= SYSTEM_UID_MAX || euid.as_raw() <= SYSTEM_UID_MAX;
+        }
+
+        match User::from_name(&self.params) {
+            Ok(Some(user)) => user.uid == uid || user.uid == euid,
+            _ => false,
+        }
+    }
+
+    fn test_group(&self) -> bool {
+        let gid = match self.params.parse::<u32>() {
+            Ok(id) => Gid::from_raw(id),
+            Err(_) => match Group::from_name(&self.params) {
+                Ok(Some(group)) => group.gid,
+                _ => return false,
+            },
+        };
+
+        if gid == Gid::current() || gid == Gid::effective() {
+            return true;
+        }
+
+        unistd::getgroups()
+            .map(|groups| groups.contains(&gid))
+            .unwrap_or(false)
+    }
+
+    fn test_control_group_controller(&self) -> bool {
+        let available = cgroup_controllers();
+
+        self.params
+            .split_whitespace()
+            .all(|c| available.iter().any(|a| a == c))
+    }
+
+    fn test_environment(&self) -> bool {
+        match self.params.split_once('=') {
+            Some((key, value)) => env::var(key).map(|v| v == value).unwrap_or(false),
+            None => env::var_os(&self.params).is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+enum CompareOperator {
+    Lower,
+    LowerOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl CompareOperator {
+    fn test(&self, order: Ordering) -> bool {
+        match self {
+            CompareOperator::Lower => order == Ordering::Less,
+            CompareOperator::LowerOrEqual => order != Ordering::Greater,
+            CompareOperator::Equal => order == Ordering::Equal,
+            CompareOperator::NotEqual => order != Ordering::Equal,
+            CompareOperator::GreaterOrEqual => order != Ordering::Less,
+            CompareOperator::Greater => order == Ordering::Greater,
+        }
+    }
+}
+
+fn parse_compare_operator(s: &str) -> (Option<CompareOperator>, &str) {
+    let table = [
+        ("<=", CompareOperator::LowerOrEqual),
+        (">=", CompareOperator::GreaterOrEqual),
+        ("!=", CompareOperator::NotEqual),
+        ("<", CompareOperator::Lower),
+        (">", CompareOperator::Greater),
+        ("=", CompareOperator::Equal),
+    ];
+
+    for (prefix, op) in table.iter() {
+        if let Some(rest) = s.strip_prefix(prefix) {
+            return (Some(*op), rest);
+        }
+    }
+
+    (None, s)
+}
+
+// compare two version strings, the numeric parts are compared by value and
+// the other parts are compared by character
+fn version_compare(a: &str, b: &str) -> Ordering {
+    fn segments(s: &str) -> Vec<&str> {
+        let mut ret = Vec::new();
+        let mut start = None;
+        let mut numeric = false;
+        for (i, c) in s.char_indices() {
+            let alnum = c.is_ascii_alphanumeric();
+            match start {
+                Some(_) if alnum && c.is_ascii_digit() == numeric => continue,
+                Some(st) => {
+                    ret.push(&s[st..i]);
+                    start = None;
                 }
-            })
-            .unwrap_or(false);
-        if result {
-            return 1;
-        } else {
-            return 0;
+                None => {}
+            }
+            if alnum {
+                start = Some(i);
+                numeric = c.is_ascii_digit();
+            }
+        }
+        if let Some(st) = start {
+            ret.push(&s[st..]);
+        }
+        ret
+    }
+
+    let sa = segments(a);
+    let sb = segments(b);
+    for (x, y) in sa.iter().zip(sb.iter()) {
+        let x_num = x.chars().all(|c| c.is_ascii_digit());
+        let y_num = y.chars().all(|c| c.is_ascii_digit());
+        let order = match (x_num, y_num) {
+            (true, true) => {
+                let x = x.trim_start_matches('0');
+                let y = y.trim_start_matches('0');
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => x.cmp(y),
+        };
+        if order != Ordering::Equal {
+            return order;
         }
     }
 
-    fn test_needs_update(&self) -> i8 {
-        0
+    sa.len().cmp(&sb.len())
+}
+
+// map the machine name of the kernel to the architecture name used in unit files
+fn uname_architecture() -> Option<&'static str> {
+    let uts = uname().ok()?;
+    let machine = uts.machine().to_string_lossy().to_string();
+
+    let arch = match machine.as_str() {
+        "x86_64" => "x86-64",
+        "i386" | "i486" | "i586" | "i686" => "x86",
+        "aarch64" => "arm64",
+        "aarch64_be" => "arm64-be",
+        m if m.starts_with("armv") && m.ends_with('b') => "arm-be",
+        m if m.starts_with("arm") => "arm",
+        "ppc64le" => "ppc64-le",
+        "ppc64" => "ppc64",
+        "ppcle" => "ppc-le",
+        "ppc" => "ppc",
+        "s390x" => "s390x",
+        "s390" => "s390",
+        "riscv64" => "riscv64",
+        "riscv32" => "riscv32",
+        "loongarch64" => "loongarch64",
+        "mips64" => "mips64",
+        "mips" => "mips",
+        "sparc64" => "sparc64",
+        "alpha" => "alpha",
+        _ => return None,
+    };
+
+    Some(arch)
+}
+
+fn running_in_userns() -> bool {
+    match fs::read_to_string("/proc/self/uid_map") {
+        Ok(m) => m.split_whitespace().collect::<Vec<_>>() != ["0", "0", "4294967295"],
+        Err(_) => false,
     }
 }
 
+// an empty or uninitialized machine-id means the system is not set up yet
+pub fn first_boot() -> bool {
+    machine_id_unset(Path::new(MACHINE_ID_PATH))
+}
+
+/// write a random machine-id if it is not set up yet, the following boots are not
+/// the first boot any more.
+pub fn machine_id_commit() -> std::io::Result<()> {
+    write_machine_id(Path::new(MACHINE_ID_PATH))
+}
+
+fn machine_id_unset(path: &Path) -> bool {
+    match fs::read_to_string(path) {
+        Ok(id) => {
+            let id = id.trim();
+            id.is_empty() || id == "uninitialized"
+        }
+        Err(_) => true,
+    }
+}
+
+fn write_machine_id(path: &Path) -> std::io::Result<()> {
+    if !machine_id_unset(path) {
+        return Ok(());
+    }
+
+    let mut id = [0u8; 16];
+    fs::File::open("/dev/urandom")?.read_exact(&mut id)?;
+    // the random id is formatted as the v4 uuid
+    id[6] = (id[6] & 0x0f) | 0x40;
+    id[8] = (id[8] & 0x3f) | 0x80;
+
+    let hex: String = id.iter().map(|b| format!("{:02x}", b)).collect();
+    fs::write(path, format!("{}\n", hex))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o444))
+}
+
+// we are on AC power if any mains power supply is online, or if there is no
+// mains power supply at all
+fn on_ac_power() -> bool {
+    let entries = match fs::read_dir("/sys/class/power_supply") {
+        Ok(e) => e,
+        Err(_) => return true,
+    };
+
+    let mut found_mains = false;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let ty = fs::read_to_string(path.join("type")).unwrap_or_default();
+        if ty.trim() != "Mains" {
+            continue;
+        }
+
+        found_mains = true;
+        let online = fs::read_to_string(path.join("online")).unwrap_or_default();
+        if online.trim() == "1" {
+            return true;
+        }
+    }
+
+    !found_mains
+}
+
+fn cgroup_controllers() -> Vec<String> {
+    if let Ok(c) = fs::read_to_string("/sys/fs/cgroup/cgroup.controllers") {
+        return c.split_whitespace().map(|s| s.to_string()).collect();
+    }
+
+    // cgroup v1, the fourth column of /proc/cgroups tells whether it is enabled
+    let cgroups = match fs::read_to_string("/proc/cgroups") {
+        Ok(c) => c,
+        Err(_) => return Vec::new(),
+    };
+
+    cgroups
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| {
+            let fields: Vec<&str> = l.split_whitespace().collect();
+            if fields.len() >= 4 && fields[3] == "1" {
+                Some(fields[0].to_string())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use crate::logger;
 
-    use super::{Condition, ConditionType};
+    use super::{machine_id_unset, version_compare, write_machine_id, Condition, ConditionType};
     use crate::test_util::get_project_root;
+    use std::cmp::Ordering;
 
     #[test]
     fn test_condition_test() {
@@ -137,5 +731,123 @@ mod test {
             project_root.to_str().unwrap().to_string(),
         );
         assert!(!cond_file_empty.test(), "cond test file empty");
+
+        let cond_path_not_exists_revert =
+            Condition::new(ConditionType::PathExists, 0, 1, "/home/test".to_string());
+        assert!(cond_path_not_exists_revert.test());
+    }
+
+    #[test]
+    fn test_condition_path() {
+        let project_root = get_project_root().unwrap();
+        let root = project_root.to_str().unwrap().to_string();
+
+        let cond = Condition::new(ConditionType::PathIsDirectory, 0, 0, root.clone());
+        assert!(cond.test());
+        let cond = Condition::new(
+            ConditionType::PathIsDirectory,
+            0,
+            0,
+            root.clone() + "/Cargo.lock",
+        );
+        assert!(!cond.test());
+        let cond = Condition::new(
+            ConditionType::PathExistsGlob,
+            0,
+            0,
+            root.clone() + "/Cargo.*",
+        );
+        assert!(cond.test());
+        let cond = Condition::new(
+            ConditionType::PathExistsGlob,
+            0,
+            0,
+            root.clone() + "/*.none",
+        );
+        assert!(!cond.test());
+        let cond = Condition::new(ConditionType::DirectoryNotEmpty, 0, 0, root.clone());
+        assert!(cond.test());
+        let cond = Condition::new(ConditionType::PathIsMountPoint, 0, 0, "/".to_string());
+        assert!(cond.test());
+        let cond = Condition::new(ConditionType::PathIsSymbolicLink, 0, 0, root);
+        assert!(!cond.test());
+        let cond = Condition::new(ConditionType::FileIsExecutable, 0, 0, "/bin/sh".to_string());
+        assert!(cond.test());
+    }
+
+    #[test]
+    fn test_condition_environment() {
+        // the environment is shared by the tests running in parallel
+        let name = format!("CONDITION_TEST_ENV_{}", std::process::id());
+        std::env::set_var(&name, "yes");
+        let cond = Condition::new(ConditionType::Environment, 0, 0, name.clone());
+        assert!(cond.test());
+        let cond = Condition::new(ConditionType::Environment, 0, 0, format!("{}=no", name));
+        assert!(!cond.test());
+        let cond = Condition::new(ConditionType::Environment, 0, 1, format!("{}=no", name));
+        assert!(cond.test());
+        std::env::remove_var(&name);
+        let cond = Condition::new(ConditionType::Environment, 0, 0, name);
+        assert!(!cond.test());
+    }
+
+    #[test]
+    fn test_condition_kernel_version() {
+        let cond = Condition::new(ConditionType::KernelVersion, 0, 0, ">=2.6".to_string());
+        assert!(cond.test());
+        let cond = Condition::new(ConditionType::KernelVersion, 0, 0, "<2.6".to_string());
+        assert!(!cond.test());
+        let cond = Condition::new(ConditionType::KernelVersion, 0, 0, "*".to_string());
+        assert!(cond.test());
+        let cond = Condition::new(ConditionType::KernelVersion, 0, 0, ">= 2.6".to_string());
+        assert!(cond.test());
+        let cond = Condition::new(
+            ConditionType::KernelVersion,
+            0,
+            0,
+            "> 2.6 < 2.7".to_string(),
+        );
+        assert!(!cond.test());
+        let cond = Condition::new(ConditionType::KernelVersion, 0, 0, ">=".to_string());
+        assert!(!cond.test());
+
+        assert_eq!(version_compare("5.10.0-60", "5.9"), Ordering::Greater);
+        assert_eq!(version_compare("5.10", "5.10.0"), Ordering::Less);
+        assert_eq!(version_compare("4.19.90", "4.19.90"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_condition_user() {
+        let uid = nix::unistd::getuid().to_string();
+        let cond = Condition::new(ConditionType::User, 0, 0, uid);
+        assert!(cond.test());
+        let gid = nix::unistd::getgid().to_string();
+        let cond = Condition::new(ConditionType::Group, 0, 0, gid);
+        assert!(cond.test());
+        let cond = Condition::new(ConditionType::Architecture, 0, 0, "native".to_string());
+        assert!(cond.test());
+    }
+
+    #[test]
+    fn test_machine_id_commit() {
+        let dir = std::env::temp_dir().join(format!("machine_id_commit_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("machine-id");
+
+        std::fs::write(&path, "uninitialized\n").unwrap();
+        assert!(machine_id_unset(&path));
+
+        // the first boot sets up the machine-id
+        write_machine_id(&path).unwrap();
+        assert!(!machine_id_unset(&path));
+        let id = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(id.len(), 33);
+        assert!(id.trim().chars().all(|c| c.is_ascii_hexdigit()));
+
+        // and it is kept by the following boots
+        write_machine_id(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), id);
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
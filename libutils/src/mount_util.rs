@@ -3,6 +3,18 @@ use nix::{
     fcntl::AtFlags,
     sys::stat::{fstatat, SFlag},
 };
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+
+// the tags of What= and the directories of the device links udev creates for them
+const WHAT_TAGS: [(&str, &str); 4] = [
+    ("UUID=", "/dev/disk/by-uuid"),
+    ("LABEL=", "/dev/disk/by-label"),
+    ("PARTUUID=", "/dev/disk/by-partuuid"),
+    ("PARTLABEL=", "/dev/disk/by-partlabel"),
+];
 
 pub fn mount_point_fd_valid(fd: i32, file_name: &str, flags: AtFlags) -> Result<bool, Errno> {
     assert!(fd >= 0);
@@ -26,3 +38,104 @@ pub fn mount_point_fd_valid(fd: i32, file_name: &str, flags: AtFlags) -> Result<
 
     Ok(f_stat.st_dev != d_stat.st_dev)
 }
+
+/// the mount points in /proc/self/mountinfo, which are the fifth field of each line,
+/// with the white spaces escaped as octal
+pub fn mount_points() -> Vec<PathBuf> {
+    match fs::read_to_string(MOUNTINFO_PATH) {
+        Ok(m) => mountinfo_mount_points(&m),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// check whether the path is a mount point listed in /proc/self/mountinfo
+pub fn path_is_mount_point(path: &Path) -> bool {
+    mount_points().iter().any(|m| m == path)
+}
+
+fn mountinfo_mount_points(mountinfo: &str) -> Vec<PathBuf> {
+    mountinfo
+        .lines()
+        .filter_map(|l| l.split_whitespace().nth(4))
+        .map(|m| PathBuf::from(unescape_octal(m)))
+        .collect()
+}
+
+fn unescape_octal(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let oct: String = chars.clone().take(3).collect();
+            if oct.len() == 3 {
+                if let Ok(v) = u8::from_str_radix(&oct, 8) {
+                    ret.push(v as char);
+                    chars.nth(2);
+                    continue;
+                }
+            }
+        }
+        ret.push(c);
+    }
+    ret
+}
+
+/// turn the tags of What=, such as "UUID=xxx", into the device links udev creates for them,
+/// the characters not allowed in the link names are escaped as "\\xNN" like udev does.
+pub fn what_to_node(what: &str) -> String {
+    for (tag, dir) in WHAT_TAGS.iter() {
+        if let Some(value) = what.strip_prefix(tag) {
+            let mut name = String::with_capacity(value.len());
+            for c in value.chars() {
+                if c.is_ascii_alphanumeric() || "#+-.:=@_".contains(c) || !c.is_ascii() {
+                    name.push(c);
+                } else {
+                    name.push_str(&format!("\\x{:02x}", c as u32));
+                }
+            }
+            return format!("{}/{}", dir, name);
+        }
+    }
+    what.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mountinfo_mount_points, what_to_node};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_mountinfo_mount_points() {
+        let mountinfo = "22 1 253:0 / / rw,relatime shared:1 - ext4 /dev/vda1 rw
+23 22 0:21 / /proc rw,nosuid,nodev,noexec,relatime shared:2 - proc proc rw
+24 22 0:45 / /mnt/my\\040disk rw,relatime shared:3 - tmpfs tmpfs rw
+";
+        assert_eq!(
+            mountinfo_mount_points(mountinfo),
+            vec![
+                PathBuf::from("/"),
+                PathBuf::from("/proc"),
+                PathBuf::from("/mnt/my disk")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_what_to_node() {
+        assert_eq!(what_to_node("/dev/sda1"), "/dev/sda1");
+        assert_eq!(what_to_node("tmpfs"), "tmpfs");
+        assert_eq!(
+            what_to_node("UUID=452b7bd2-c3ba-45d6"),
+            "/dev/disk/by-uuid/452b7bd2-c3ba-45d6"
+        );
+        assert_eq!(
+            what_to_node("LABEL=my data/1"),
+            "/dev/disk/by-label/my\\x20data\\x2f1"
+        );
+        assert_eq!(
+            what_to_node("PARTUUID=0a1b-01"),
+            "/dev/disk/by-partuuid/0a1b-01"
+        );
+        assert_eq!(what_to_node("PARTLABEL=EFI"), "/dev/disk/by-partlabel/EFI");
+    }
+}
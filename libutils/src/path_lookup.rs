@@ -1,12 +1,19 @@
 use std::env;
 
-const ETC_SYSTEM_PATH: &str = "/etc/process1";
-const RUN_SYSTEM_PATH: &str = "/run/process1";
-const LIB_SYSTEM_PATH: &str = "/usr/lib/process1";
+pub const ETC_SYSTEM_PATH: &str = "/etc/process1";
+pub const RUN_SYSTEM_PATH: &str = "/run/process1";
+pub const LIB_SYSTEM_PATH: &str = "/usr/lib/process1";
+// the unit files of the units created at runtime, such as scopes
+pub const TRANSIENT_SYSTEM_PATH: &str = "/run/process1/transient";
+
+pub const ETC_PRESET_PATH: &str = "/etc/process1-preset";
+pub const RUN_PRESET_PATH: &str = "/run/process1-preset";
+pub const LIB_PRESET_PATH: &str = "/usr/lib/process1-preset";
 
 #[derive(Debug, Clone)]
 pub struct LookupPaths {
     pub search_path: Vec<String>,
+    pub preset_path: Vec<String>,
     pub generator: String,
     pub generator_early: String,
     pub generator_late: String,
@@ -21,6 +28,7 @@ impl LookupPaths {
             generator_late: String::from(""),
             transient: String::from(""),
             search_path: Vec::new(),
+            preset_path: Vec::new(),
         }
     }
 
@@ -45,6 +53,13 @@ impl LookupPaths {
         self.search_path.push(LIB_SYSTEM_PATH.to_string());
         self.search_path.push(RUN_SYSTEM_PATH.to_string());
         self.search_path.push(ETC_SYSTEM_PATH.to_string());
+        // the transient units take precedence over all the others
+        self.transient = TRANSIENT_SYSTEM_PATH.to_string();
+        self.search_path.push(self.transient.clone());
+
+        self.preset_path.push(LIB_PRESET_PATH.to_string());
+        self.preset_path.push(RUN_PRESET_PATH.to_string());
+        self.preset_path.push(ETC_PRESET_PATH.to_string());
     }
 }
 
@@ -1,3 +1,5 @@
+use nix::libc;
+use std::ffi::CString;
 use std::path::Path;
 
 pub fn path_equal(a: &str, b: &str) -> bool {
@@ -6,9 +8,44 @@ pub fn path_equal(a: &str, b: &str) -> bool {
     return p_a == p_b;
 }
 
+fn fnmatch_flags(pattern: &str, s: &str, flags: libc::c_int) -> bool {
+    let (pattern, s) = match (CString::new(pattern), CString::new(s)) {
+        (Ok(p), Ok(s)) => (p, s),
+        _ => return false,
+    };
+
+    unsafe { libc::fnmatch(pattern.as_ptr(), s.as_ptr(), flags) == 0 }
+}
+
+// match the string with the shell wildcard pattern
+pub fn fnmatch(pattern: &str, s: &str) -> bool {
+    fnmatch_flags(pattern, s, 0)
+}
+
+// match the string with the shell wildcard pattern, ignoring the case
+pub fn fnmatch_casefold(pattern: &str, s: &str) -> bool {
+    fnmatch_flags(pattern, s, libc::FNM_CASEFOLD)
+}
+
+// whether any path in the file system matches the shell wildcard pattern
+pub fn glob_exists(pattern: &str) -> bool {
+    let pattern = match CString::new(pattern) {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+
+    unsafe {
+        let mut g: libc::glob_t = std::mem::zeroed();
+        let r = libc::glob(pattern.as_ptr(), libc::GLOB_NOSORT, None, &mut g);
+        let exist = r == 0 && g.gl_pathc > 0;
+        libc::globfree(&mut g);
+        exist
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::path_util::path_equal;
+    use crate::path_util::{fnmatch, glob_exists, path_equal};
 
     #[test]
     fn test_path_equal() {
@@ -20,4 +57,18 @@ mod tests {
         assert_eq!(path_equal("/x/././y", "/x/y/./."), true);
         assert_eq!(path_equal("/etc", "/var"), false);
     }
+
+    #[test]
+    fn test_fnmatch() {
+        assert!(fnmatch("*.service", "foo.service"));
+        assert!(fnmatch("getty@*.service", "getty@tty1.service"));
+        assert!(!fnmatch("*.socket", "foo.service"));
+    }
+
+    #[test]
+    fn test_glob_exists() {
+        assert!(glob_exists("/proc/self/st*"));
+        assert!(glob_exists("/proc"));
+        assert!(!glob_exists("/proc/self/nonexistent*"));
+    }
 }
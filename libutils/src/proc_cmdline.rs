@@ -4,7 +4,7 @@ use std::io::{Error as IOError, ErrorKind, Read};
 use crate::conf_parser;
 use crate::Error;
 
-fn cmdline_content() -> Result<String, IOError> {
+pub(crate) fn cmdline_content() -> Result<String, IOError> {
     let mut file = match File::open("/proc/cmdline") {
         Err(why) => {
             return Err(IOError::new(
@@ -58,3 +58,10 @@ pub fn proc_cmdline_get_bool(key: &str) -> Result<bool, Error> {
 
     Ok(r)
 }
+
+// read the content from /proc/cmdline and return whether the bare word is in it, such as "emergency"
+pub fn proc_cmdline_has(word: &str) -> Result<bool, Error> {
+    let buf = cmdline_content()?;
+
+    Ok(buf.split_whitespace().any(|cmd| cmd == word))
+}
@@ -1,11 +1,14 @@
 use nix::errno::errno;
 use nix::libc::{kill, ESRCH};
+use nix::sys::signal::Signal;
 use nix::sys::wait::{waitpid, WaitPidFlag};
 use nix::unistd::Pid;
 use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::fs::read_dir;
 use std::io::{Error, ErrorKind};
 use std::path::Path;
+use std::str::FromStr;
 use std::thread::sleep;
 use std::time::{Duration, SystemTime};
 
@@ -16,30 +19,53 @@ pub fn process_state(pid: Pid) -> Result<char, Error> {
         return Ok('R');
     }
 
-    let proc_file = format!("/proc/{:?}/stat", pid.as_raw());
-    let stat_path = Path::new(&proc_file);
-    let first_line = file_util::read_first_line(stat_path)?;
-    let stat: Vec<String> = first_line
-        .split_whitespace()
-        .map(|s| s.to_string())
-        .collect();
+    let stat = process_stat(pid)?;
+    let p_stat: Vec<char> = stat[0].trim().chars().collect();
 
-    if stat.len() < 3 {
+    if p_stat.len() == 0 {
         return Err(Error::new(
             ErrorKind::Other,
-            format!("invalid process stat format"),
+            format!("invalid process state"),
         ));
     }
+    return Ok(p_stat[0]);
+}
+
+pub fn get_parent_pid(pid: Pid) -> Result<Pid, Error> {
+    if pid == Pid::from_raw(0) || pid == nix::unistd::getpid() {
+        return Ok(nix::unistd::getppid());
+    }
 
-    let p_stat: Vec<char> = stat[3].trim().chars().collect();
+    let stat = process_stat(pid)?;
+    let ppid = stat[1]
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| Error::new(ErrorKind::Other, format!("invalid parent pid")))?;
 
-    if p_stat.len() == 0 {
+    Ok(Pid::from_raw(ppid))
+}
+
+// the fields of /proc/pid/stat after the command, which may contain whitespaces.
+fn process_stat(pid: Pid) -> Result<Vec<String>, Error> {
+    let proc_file = format!("/proc/{:?}/stat", pid.as_raw());
+    let stat_path = Path::new(&proc_file);
+    let first_line = file_util::read_first_line(stat_path)?;
+    let stat: Vec<String> = match first_line.rfind(')') {
+        Some(pos) => first_line[pos + 1..]
+            .split_whitespace()
+            .map(|s| s.to_string())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    if stat.len() < 2 {
         return Err(Error::new(
             ErrorKind::Other,
-            format!("invalid process state"),
+            format!("invalid process stat format"),
         ));
     }
-    return Ok(p_stat[0]);
+
+    Ok(stat)
 }
 
 pub fn alive(pid: Pid) -> bool {
@@ -151,3 +177,49 @@ pub fn wait_pids(mut pids: HashSet<i32>, timeout: u64) -> HashSet<i32> {
     }
     pids
 }
+
+/// parse the signal configured, such as "SIGHUP", "HUP" or "1".
+pub fn parse_signal(s: &str) -> Option<Signal> {
+    let s = s.trim();
+    if let Ok(num) = s.parse::<i32>() {
+        return Signal::try_from(num).ok();
+    }
+
+    if s.starts_with("SIG") {
+        return Signal::from_str(s).ok();
+    }
+
+    Signal::from_str(&format!("SIG{}", s)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_signal;
+    use nix::sys::signal::Signal;
+    use nix::unistd::Pid;
+
+    #[test]
+    fn test_get_parent_pid() {
+        let pid = nix::unistd::getpid();
+        assert!(super::alive(pid));
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("1")
+            .spawn()
+            .unwrap();
+        let cpid = Pid::from_raw(child.id() as i32);
+        assert_eq!(super::get_parent_pid(cpid).unwrap(), pid);
+        assert_eq!(super::process_state(cpid).is_ok(), true);
+
+        // reap the child, not leaving a zombie behind
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!(parse_signal("SIGHUP"), Some(Signal::SIGHUP));
+        assert_eq!(parse_signal("USR1"), Some(Signal::SIGUSR1));
+        assert_eq!(parse_signal("15"), Some(Signal::SIGTERM));
+        assert_eq!(parse_signal("SIGFOO"), None);
+    }
+}
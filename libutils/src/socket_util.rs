@@ -2,9 +2,12 @@ use std::{os::unix::prelude::RawFd, path::Path};
 
 use nix::{
     errno::Errno,
-    sys::socket::{self, sockopt, AddressFamily},
+    libc,
+    sys::socket::{self, sockopt, AddressFamily, SockFlag, SockType},
 };
 
+use crate::fd_util;
+
 pub fn ipv6_is_supported() -> bool {
     let inet6 = Path::new("/proc/net/if_inet6");
 
@@ -36,3 +39,41 @@ pub fn set_receive_buffer(fd: RawFd, v: usize) -> Result<(), Errno> {
 pub fn set_send_buffer(fd: RawFd, v: usize) -> Result<(), Errno> {
     socket::setsockopt(fd, sockopt::SndBuf, &v)
 }
+
+/// bring up the loopback interface of the current network namespace
+pub fn loopback_setup() -> Result<(), Errno> {
+    let fd = socket::socket(
+        AddressFamily::Inet,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        None,
+    )?;
+
+    let ret = loopback_set_up(fd);
+    fd_util::close(fd);
+    ret
+}
+
+fn loopback_set_up(fd: RawFd) -> Result<(), Errno> {
+    let mut req: libc::ifreq = unsafe { std::mem::zeroed() };
+    for (i, c) in b"lo".iter().enumerate() {
+        req.ifr_name[i] = *c as libc::c_char;
+    }
+
+    if unsafe { libc::ioctl(fd, libc::SIOCGIFFLAGS, &mut req) } < 0 {
+        return Err(Errno::last());
+    }
+
+    let flags = unsafe { req.ifr_ifru.ifru_flags };
+    let up = (libc::IFF_UP | libc::IFF_RUNNING) as libc::c_short;
+    if flags & up == up {
+        return Ok(());
+    }
+
+    req.ifr_ifru.ifru_flags = flags | up;
+    if unsafe { libc::ioctl(fd, libc::SIOCSIFFLAGS, &req) } < 0 {
+        return Err(Errno::last());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+//! the helpers shared by the tests of the crates in the workspace.
+
+use std::{
+    env,
+    io::{self, ErrorKind},
+    path::PathBuf,
+};
+
+/// the root of the workspace, the nearest ancestor of the current directory containing Cargo.lock.
+pub fn get_project_root() -> io::Result<PathBuf> {
+    let path = env::current_dir()?;
+
+    for p in path.ancestors() {
+        if p.join("Cargo.lock").exists() {
+            return Ok(PathBuf::from(p));
+        }
+    }
+    Err(io::Error::new(
+        ErrorKind::NotFound,
+        "Ran out of places to find Cargo.lock",
+    ))
+}
@@ -8,3 +8,63 @@ pub fn timespec_load(systime: SystemTime) -> u128 {
         Err(_) => USEC_INFINITY,
     }
 }
+
+/// parse a time span such as "90", "1min 30s" or "1.5h" into useconds,
+/// a value without unit is in seconds.
+pub fn parse_timespan(s: &str) -> Result<u64, String> {
+    let err = || format!("invalid time span: {}", s);
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return Err(err());
+    }
+
+    let mut usec: f64 = 0.0;
+    while !rest.is_empty() {
+        let num_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if num_len == 0 {
+            return Err(err());
+        }
+        let value: f64 = rest[..num_len].parse().map_err(|_| err())?;
+        rest = rest[num_len..].trim_start();
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_len] {
+            "us" | "usec" | "µs" => 1.0,
+            "ms" | "msec" => 1_000.0,
+            "" | "s" | "sec" | "second" | "seconds" => 1_000_000.0,
+            "m" | "min" | "minute" | "minutes" => 60_000_000.0,
+            "h" | "hr" | "hour" | "hours" => 3_600_000_000.0,
+            "d" | "day" | "days" => 86_400_000_000.0,
+            "w" | "week" | "weeks" => 604_800_000_000.0,
+            "M" | "month" | "months" => 2_629_800_000_000.0,
+            "y" | "year" | "years" => 31_557_600_000_000.0,
+            _ => return Err(err()),
+        };
+        usec += value * unit;
+        rest = rest[unit_len..].trim_start();
+    }
+
+    Ok(usec as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_timespan;
+
+    #[test]
+    fn test_parse_timespan() {
+        assert_eq!(parse_timespan("90"), Ok(90_000_000));
+        assert_eq!(parse_timespan("1min 30s"), Ok(90_000_000));
+        assert_eq!(parse_timespan("1h30min"), Ok(5_400_000_000));
+        assert_eq!(parse_timespan("1.5h"), Ok(5_400_000_000));
+        assert_eq!(parse_timespan("500ms"), Ok(500_000));
+        assert_eq!(parse_timespan("2 weeks"), Ok(1_209_600_000_000));
+        assert!(parse_timespan("").is_err());
+        assert!(parse_timespan("5 fortnights").is_err());
+        assert!(parse_timespan("min").is_err());
+    }
+}
@@ -0,0 +1,246 @@
+// the max length of a unit name, including the suffix
+const UNIT_NAME_MAX: usize = 256;
+
+fn valid_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || ":-_.\\".contains(c)
+}
+
+// split the unit name into (prefix, instance, suffix), the instance is None
+// if the name is a plain unit name, and is empty for a template name
+fn unit_name_split(name: &str) -> Option<(&str, Option<&str>, &str)> {
+    if name.is_empty() || name.len() > UNIT_NAME_MAX {
+        return None;
+    }
+
+    let dot = name.rfind('.')?;
+    let (stem, suffix) = (&name[..dot], &name[dot + 1..]);
+    if stem.is_empty() || suffix.is_empty() || !suffix.chars().all(|c| c.is_ascii_lowercase()) {
+        return None;
+    }
+
+    let (prefix, instance) = match stem.find('@') {
+        Some(at) => (&stem[..at], Some(&stem[at + 1..])),
+        None => (stem, None),
+    };
+
+    if prefix.is_empty() || !prefix.chars().all(valid_char) {
+        return None;
+    }
+
+    if let Some(i) = instance {
+        if !i.chars().all(|c| valid_char(c) || c == '@') {
+            return None;
+        }
+    }
+
+    Some((prefix, instance, suffix))
+}
+
+// check whether the name is a valid plain, template or instance unit name
+pub fn unit_name_is_valid(name: &str) -> bool {
+    unit_name_split(name).is_some()
+}
+
+// check whether the name is a template unit name, such as foo@.service
+pub fn unit_name_is_template(name: &str) -> bool {
+    matches!(unit_name_split(name), Some((_, Some(""), _)))
+}
+
+// check whether the name is an instance unit name, such as foo@bar.service
+pub fn unit_name_is_instance(name: &str) -> bool {
+    matches!(unit_name_split(name), Some((_, Some(i), _)) if !i.is_empty())
+}
+
+// return the part before the '@' or the suffix, foo@bar.service => foo
+pub fn unit_name_to_prefix(name: &str) -> Option<String> {
+    unit_name_split(name).map(|(p, _, _)| p.to_string())
+}
+
+// return the instance of the unit name, foo@bar.service => bar
+pub fn unit_name_to_instance(name: &str) -> Option<String> {
+    match unit_name_split(name) {
+        Some((_, Some(i), _)) if !i.is_empty() => Some(i.to_string()),
+        _ => None,
+    }
+}
+
+// return the unit name without the suffix, foo@bar.service => foo@bar
+pub fn unit_name_without_suffix(name: &str) -> Option<String> {
+    unit_name_split(name)?;
+    name.rfind('.').map(|dot| name[..dot].to_string())
+}
+
+// return the template of an instance unit name, foo@bar.service => foo@.service
+pub fn unit_name_template(name: &str) -> Option<String> {
+    match unit_name_split(name) {
+        Some((p, Some(i), s)) if !i.is_empty() => Some(format!("{}@.{}", p, s)),
+        _ => None,
+    }
+}
+
+// fill the instance into a template or instance unit name, (foo@.service, bar) => foo@bar.service
+pub fn unit_name_replace_instance(name: &str, instance: &str) -> Option<String> {
+    let (p, i, s) = unit_name_split(name)?;
+    i?;
+
+    let ret = format!("{}@{}.{}", p, instance, s);
+    if unit_name_is_instance(&ret) {
+        Some(ret)
+    } else {
+        None
+    }
+}
+
+// undo the unit name escaping, "-" is turned into "/" and "\xNN" into the character
+pub fn unit_name_unescape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '-' => ret.push('/'),
+            '\\' => {
+                let rest = chars.as_str();
+                let hex = rest.strip_prefix('x').and_then(|h| h.get(..2));
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(v) => {
+                        ret.push(v as char);
+                        chars.nth(2);
+                    }
+                    None => ret.push(c),
+                }
+            }
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+// escape the path into the unit name with the suffix, the slashes are turned into "-",
+// and the characters not allowed in the unit name into "\xNN", "/" itself is "-"
+pub fn unit_name_from_path(path: &str, suffix: &str) -> String {
+    let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    if parts.is_empty() {
+        return format!("-.{}", suffix);
+    }
+
+    let mut ret = String::with_capacity(path.len());
+    for (i, c) in parts.join("/").chars().enumerate() {
+        match c {
+            '/' => ret.push('-'),
+            '.' if i == 0 => ret.push_str("\\x2e"),
+            c if c.is_ascii_alphanumeric() || ":_.".contains(c) => ret.push(c),
+            c => {
+                let mut buf = [0u8; 4];
+                for b in c.encode_utf8(&mut buf).bytes() {
+                    ret.push_str(&format!("\\x{:02x}", b));
+                }
+            }
+        }
+    }
+    format!("{}.{}", ret, suffix)
+}
+
+// check whether the name is a valid slice name, the dashes separate the parent slices
+pub fn slice_name_is_valid(name: &str) -> bool {
+    if !unit_name_is_valid(name) || unit_name_is_template(name) || unit_name_is_instance(name) {
+        return false;
+    }
+
+    match name.strip_suffix(".slice") {
+        Some("-") => true,
+        Some(stem) => !stem.starts_with('-') && !stem.ends_with('-') && !stem.contains("--"),
+        None => false,
+    }
+}
+
+// return the parent of the slice, a-b.slice => a.slice, a.slice => -.slice, and the
+// root slice -.slice has no parent
+pub fn slice_name_parent(name: &str) -> Option<String> {
+    let stem = name.strip_suffix(".slice")?;
+    if stem == "-" {
+        return None;
+    }
+
+    match stem.rfind('-') {
+        Some(dash) => Some(format!("{}.slice", &stem[..dash])),
+        None => Some("-.slice".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unit_name_valid() {
+        assert!(unit_name_is_valid("foo.service"));
+        assert!(unit_name_is_valid("foo@.service"));
+        assert!(unit_name_is_valid("getty@tty1.service"));
+        assert!(unit_name_is_valid("dev-sda1.device"));
+        assert!(!unit_name_is_valid("foo"));
+        assert!(!unit_name_is_valid(".service"));
+        assert!(!unit_name_is_valid("@bar.service"));
+        assert!(!unit_name_is_valid("foo bar.service"));
+
+        assert!(unit_name_is_template("foo@.service"));
+        assert!(!unit_name_is_template("foo@bar.service"));
+        assert!(unit_name_is_instance("foo@bar.service"));
+        assert!(!unit_name_is_instance("foo.service"));
+    }
+
+    #[test]
+    fn test_unit_name_instance() {
+        assert_eq!(
+            unit_name_template("getty@tty1.service"),
+            Some("getty@.service".to_string())
+        );
+        assert_eq!(unit_name_template("getty.service"), None);
+        assert_eq!(
+            unit_name_to_instance("worker@3.service"),
+            Some("3".to_string())
+        );
+        assert_eq!(
+            unit_name_to_prefix("worker@3.service"),
+            Some("worker".to_string())
+        );
+        assert_eq!(
+            unit_name_without_suffix("worker@3.service"),
+            Some("worker@3".to_string())
+        );
+        assert_eq!(
+            unit_name_replace_instance("worker@.service", "4"),
+            Some("worker@4.service".to_string())
+        );
+        assert_eq!(unit_name_replace_instance("worker.service", "4"), None);
+        assert_eq!(unit_name_unescape("dev-sda\\x2d1"), "dev/sda-1");
+        assert_eq!(
+            unit_name_from_path("/dev/mapper/vg-swap", "swap"),
+            "dev-mapper-vg\\x2dswap.swap"
+        );
+        assert_eq!(unit_name_from_path("/", "mount"), "-.mount");
+        assert_eq!(
+            unit_name_from_path("/home//user/", "mount"),
+            "home-user.mount"
+        );
+    }
+
+    #[test]
+    fn test_slice_name() {
+        assert!(slice_name_is_valid("-.slice"));
+        assert!(slice_name_is_valid("system.slice"));
+        assert!(slice_name_is_valid("system-getty.slice"));
+        assert!(!slice_name_is_valid("system-.slice"));
+        assert!(!slice_name_is_valid("a--b.slice"));
+        assert!(!slice_name_is_valid("foo.service"));
+
+        assert_eq!(slice_name_parent("-.slice"), None);
+        assert_eq!(
+            slice_name_parent("system.slice"),
+            Some("-.slice".to_string())
+        );
+        assert_eq!(
+            slice_name_parent("system-getty.slice"),
+            Some("system.slice".to_string())
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// DMI vendor strings and the virtualization id they stand for
+const DMI_VENDOR_TABLE: [(&str, &str); 10] = [
+    ("KVM", "kvm"),
+    ("OpenStack", "kvm"),
+    ("KubeVirt", "kvm"),
+    ("Amazon EC2", "amazon"),
+    ("QEMU", "qemu"),
+    ("VMware", "vmware"),
+    ("VMW", "vmware"),
+    ("innotek GmbH", "oracle"),
+    ("Xen", "xen"),
+    ("Microsoft Corporation", "microsoft"),
+];
+
+const DMI_VENDOR_FILES: [&str; 3] = [
+    "/sys/class/dmi/id/product_name",
+    "/sys/class/dmi/id/sys_vendor",
+    "/sys/class/dmi/id/board_vendor",
+];
+
+// return the container id if we are running in a container, such as docker or lxc
+pub fn detect_container() -> Option<String> {
+    if let Ok(v) = env::var("container") {
+        if !v.is_empty() {
+            return Some(v);
+        }
+    }
+
+    if let Ok(v) = fs::read_to_string("/run/systemd/container") {
+        let v = v.trim();
+        if !v.is_empty() {
+            return Some(v.to_string());
+        }
+    }
+
+    if Path::new("/.dockerenv").exists() {
+        return Some("docker".to_string());
+    }
+
+    if Path::new("/run/.containerenv").exists() {
+        return Some("podman".to_string());
+    }
+
+    if Path::new("/proc/vz").exists() && !Path::new("/proc/bc").exists() {
+        return Some("openvz".to_string());
+    }
+
+    if let Ok(v) = fs::read_to_string("/proc/sys/kernel/osrelease") {
+        if v.contains("Microsoft") || v.contains("WSL") {
+            return Some("wsl".to_string());
+        }
+    }
+
+    None
+}
+
+// return the hypervisor id if we are running in a virtual machine
+pub fn detect_vm() -> Option<String> {
+    for file in DMI_VENDOR_FILES.iter() {
+        let vendor = match fs::read_to_string(file) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+
+        for (prefix, id) in DMI_VENDOR_TABLE.iter() {
+            if vendor.starts_with(prefix) {
+                return Some(id.to_string());
+            }
+        }
+    }
+
+    if let Ok(v) = fs::read_to_string("/sys/hypervisor/type") {
+        if v.trim() == "xen" {
+            return Some("xen".to_string());
+        }
+    }
+
+    if let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") {
+        let hypervisor = cpuinfo
+            .lines()
+            .filter(|l| l.starts_with("flags"))
+            .any(|l| l.split_whitespace().any(|f| f == "hypervisor"));
+        if hypervisor {
+            return Some("vm-other".to_string());
+        }
+    }
+
+    None
+}
+
+// return the container id first, and then the hypervisor id
+pub fn detect_virtualization() -> Option<String> {
+    detect_container().or_else(detect_vm)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{detect_container, detect_virtualization, detect_vm};
+
+    #[test]
+    fn test_detect_virtualization() {
+        let virt = detect_virtualization();
+        if detect_container().is_none() && detect_vm().is_none() {
+            assert!(virt.is_none());
+        } else {
+            assert!(virt.is_some());
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::emergency_action::EmergencyAction;
 use super::unit_dep_conf::UnitDepConf;
 use super::unit_state::UnitState;
 use crate::manager::table::{Table, TableSubscribe};
@@ -6,15 +7,20 @@ use std::rc::Rc;
 
 pub struct DataManager {
     tables: (
-        RefCell<Table<String, UnitDepConf>>, // unit-dep-config
-        RefCell<Table<String, UnitState>>,   // unit-state
+        RefCell<Table<String, UnitDepConf>>,     // unit-dep-config
+        RefCell<Table<String, UnitState>>,       // unit-state
+        RefCell<Table<String, EmergencyAction>>, // unit-emergency-action
     ),
 }
 
 impl DataManager {
     pub fn new() -> DataManager {
         DataManager {
-            tables: (RefCell::new(Table::new()), RefCell::new(Table::new())),
+            tables: (
+                RefCell::new(Table::new()),
+                RefCell::new(Table::new()),
+                RefCell::new(Table::new()),
+            ),
         }
     }
 
@@ -55,6 +61,24 @@ impl DataManager {
         let mut table = self.tables.1.borrow_mut();
         table.subscribe(name.to_string(), subscriber)
     }
+
+    pub(in crate::manager) fn insert_emergency_action(
+        &self,
+        u_name: String,
+        action: EmergencyAction,
+    ) -> Option<EmergencyAction> {
+        let mut table = self.tables.2.borrow_mut();
+        table.insert(u_name, action)
+    }
+
+    pub(in crate::manager) fn register_emergency_action(
+        &self,
+        name: &str,
+        subscriber: Rc<dyn TableSubscribe<String, EmergencyAction>>,
+    ) -> Option<Rc<dyn TableSubscribe<String, EmergencyAction>>> {
+        let mut table = self.tables.2.borrow_mut();
+        table.subscribe(name.to_string(), subscriber)
+    }
 }
 
 #[cfg(test)]
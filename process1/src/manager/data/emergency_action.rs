@@ -0,0 +1,171 @@
+use nix::sys::reboot::RebootMode;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+use utils::path_lookup::RUN_SYSTEM_PATH;
+
+// RebootArgument= of the unit requesting the reboot, which is passed to the reboot syscall
+const REBOOT_PARAM_FILE: &str = "reboot-param";
+
+/// the action taken on the whole system when the unit fails, succeeds, hits the
+/// start limit or its job times out, such as FailureAction=reboot.
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Default)]
+pub(crate) enum UnitEmergencyAction {
+    #[default]
+    None,
+    Reboot,
+    RebootForce,
+    RebootImmediate,
+    Poweroff,
+    PoweroffForce,
+    PoweroffImmediate,
+    Exit,
+    Halt,
+    HaltForce,
+    HaltImmediate,
+    // start emergency.target, which starts a shell on the console
+    Emergency,
+}
+
+impl FromStr for UnitEmergencyAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let action = match s {
+            "none" => UnitEmergencyAction::None,
+            "reboot" => UnitEmergencyAction::Reboot,
+            "reboot-force" => UnitEmergencyAction::RebootForce,
+            "reboot-immediate" => UnitEmergencyAction::RebootImmediate,
+            "poweroff" => UnitEmergencyAction::Poweroff,
+            "poweroff-force" => UnitEmergencyAction::PoweroffForce,
+            "poweroff-immediate" => UnitEmergencyAction::PoweroffImmediate,
+            "exit" => UnitEmergencyAction::Exit,
+            "halt" => UnitEmergencyAction::Halt,
+            "halt-force" => UnitEmergencyAction::HaltForce,
+            "halt-immediate" => UnitEmergencyAction::HaltImmediate,
+            "emergency" => UnitEmergencyAction::Emergency,
+            _ => return Err(format!("unsupported emergency action {}", s)),
+        };
+        Ok(action)
+    }
+}
+
+impl UnitEmergencyAction {
+    /// the graceful actions start the target, which stops the units in order by the shutdown jobs.
+    pub(crate) fn target(self) -> Option<&'static str> {
+        match self {
+            UnitEmergencyAction::Reboot => Some("reboot.target"),
+            UnitEmergencyAction::Poweroff => Some("poweroff.target"),
+            UnitEmergencyAction::Halt => Some("halt.target"),
+            UnitEmergencyAction::Exit => Some("exit.target"),
+            UnitEmergencyAction::Emergency => Some("emergency.target"),
+            _ => None,
+        }
+    }
+
+    /// the forced and immediate actions stop the system at once, the same as `pctrl reboot`.
+    pub(crate) fn reboot_mode(self) -> Option<RebootMode> {
+        match self {
+            UnitEmergencyAction::RebootForce | UnitEmergencyAction::RebootImmediate => {
+                Some(RebootMode::RB_AUTOBOOT)
+            }
+            UnitEmergencyAction::PoweroffForce | UnitEmergencyAction::PoweroffImmediate => {
+                Some(RebootMode::RB_POWER_OFF)
+            }
+            UnitEmergencyAction::HaltForce | UnitEmergencyAction::HaltImmediate => {
+                Some(RebootMode::RB_HALT_SYSTEM)
+            }
+            _ => None,
+        }
+    }
+
+    /// the immediate actions call reboot(2) at once, without stopping or killing anything.
+    pub(crate) fn is_immediate(self) -> bool {
+        matches!(
+            self,
+            UnitEmergencyAction::RebootImmediate
+                | UnitEmergencyAction::PoweroffImmediate
+                | UnitEmergencyAction::HaltImmediate
+        )
+    }
+
+    pub(crate) fn is_reboot(self) -> bool {
+        matches!(
+            self,
+            UnitEmergencyAction::Reboot
+                | UnitEmergencyAction::RebootForce
+                | UnitEmergencyAction::RebootImmediate
+        )
+    }
+}
+
+/// the emergency action requested by the unit, with its RebootArgument=
+#[derive(Debug)]
+pub(crate) struct EmergencyAction {
+    pub action: UnitEmergencyAction,
+    pub reboot_argument: String,
+}
+
+impl EmergencyAction {
+    pub(crate) fn new(action: UnitEmergencyAction, reboot_argument: String) -> EmergencyAction {
+        EmergencyAction {
+            action,
+            reboot_argument,
+        }
+    }
+}
+
+pub(crate) fn reboot_argument_path() -> PathBuf {
+    PathBuf::from(RUN_SYSTEM_PATH).join(REBOOT_PARAM_FILE)
+}
+
+/// save RebootArgument= for the reboot syscall, an empty argument drops the stale one.
+pub(crate) fn write_reboot_argument(argument: &str) {
+    if argument.is_empty() {
+        remove_reboot_argument();
+        return;
+    }
+    let path = reboot_argument_path();
+    if let Err(e) = fs::write(&path, argument) {
+        log::warn!("failed to write the reboot argument to {:?}: {}", path, e);
+    }
+}
+
+/// drop the saved RebootArgument= once the reboot is cancelled, or the next reboot would use it.
+pub(crate) fn remove_reboot_argument() {
+    let path = reboot_argument_path();
+    match fs::remove_file(&path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            log::warn!("failed to remove the reboot argument {:?}: {}", path, e)
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emergency_action() {
+        let action: UnitEmergencyAction = "reboot".parse().unwrap();
+        assert_eq!(action.target(), Some("reboot.target"));
+        assert!(action.reboot_mode().is_none());
+        assert!(action.is_reboot());
+        assert!(!action.is_immediate());
+
+        let action: UnitEmergencyAction = "poweroff-immediate".parse().unwrap();
+        assert!(action.target().is_none());
+        assert!(action.is_immediate());
+        assert_eq!(action.reboot_mode(), Some(RebootMode::RB_POWER_OFF));
+
+        let action: UnitEmergencyAction = "halt-force".parse().unwrap();
+        assert_eq!(action.reboot_mode(), Some(RebootMode::RB_HALT_SYSTEM));
+        assert!(!action.is_reboot());
+        assert!(!action.is_immediate());
+
+        assert_eq!("none".parse(), Ok(UnitEmergencyAction::None));
+        assert!("kexec".parse::<UnitEmergencyAction>().is_err());
+    }
+}
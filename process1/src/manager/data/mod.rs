@@ -1,9 +1,14 @@
 pub(super) use d_table::DataManager;
+pub(super) use emergency_action::{
+    reboot_argument_path, remove_reboot_argument, write_reboot_argument, EmergencyAction,
+    UnitEmergencyAction,
+};
 pub(super) use unit_dep_conf::UnitDepConf;
 pub use unit_dep_conf::UnitRelations;
 pub(super) use unit_state::UnitState;
 pub use unit_state::{UnitActiveState, UnitNotifyFlags};
 
 mod d_table;
+mod emergency_action;
 mod unit_dep_conf;
 mod unit_state;
@@ -0,0 +1,267 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    os::unix::prelude::RawFd,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use event::{EventType, Events, Source};
+use nix::{
+    errno::Errno,
+    sys::socket::{self, AddressFamily, NetlinkAddr, SockFlag, SockProtocol, SockType},
+};
+use utils::Error;
+
+use super::Manager;
+
+// the multicast group of the uevents broadcasted by udev, the raw kernel uevents are in group 1
+const UDEV_MONITOR_GROUP: u32 = 2;
+
+// the header of the uevents broadcasted by udev, defined in libudev-monitor
+const UDEV_MONITOR_PREFIX: &[u8] = b"libudev\0";
+const UDEV_MONITOR_MAGIC: u32 = 0xfeedcafe;
+const UDEV_MONITOR_HEADER_SIZE: usize = 40;
+
+// the devices tagged by udev are recorded in the tags directory, the symlinks in the data
+const UDEV_TAGS_DIR: &str = "/run/udev/tags/process1";
+const UDEV_DATA_DIR: &str = "/run/udev/data";
+
+pub(super) struct DeviceMonitor {
+    fd: RefCell<i32>,
+    manager: Rc<Manager>,
+}
+
+impl DeviceMonitor {
+    pub(super) fn new(mr: &Rc<Manager>) -> DeviceMonitor {
+        DeviceMonitor {
+            fd: RefCell::new(-1),
+            manager: mr.clone(),
+        }
+    }
+
+    pub(super) fn fd(&self) -> RawFd {
+        *self.fd.borrow() as RawFd
+    }
+
+    pub(super) fn open_socket(&self) -> Result<(), Errno> {
+        let fd = socket::socket(
+            AddressFamily::Netlink,
+            SockType::Raw,
+            SockFlag::SOCK_CLOEXEC | SockFlag::SOCK_NONBLOCK,
+            SockProtocol::NetlinkKObjectUEvent,
+        )?;
+
+        socket::bind(fd, &NetlinkAddr::new(0, UDEV_MONITOR_GROUP))?;
+
+        log::debug!("uevent listened fd is: {}", fd);
+        *self.fd.borrow_mut() = fd;
+        Ok(())
+    }
+
+    /// the devices processed by udev before process1 starts listening are not announced
+    /// again, they are found from the udev database.
+    pub(super) fn coldplug(&self) {
+        let entries = match fs::read_dir(UDEV_TAGS_DIR) {
+            Ok(entries) => entries,
+            Err(_) => {
+                log::debug!("no device is tagged in {}", UDEV_TAGS_DIR);
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let id = entry.file_name().to_string_lossy().to_string();
+            match udev_db_device(&id) {
+                Some(props) => self.manager.dispatch_uevent("add", &props),
+                None => log::debug!("failed to find the device of {}", id),
+            }
+        }
+    }
+}
+
+impl Source for DeviceMonitor {
+    fn fd(&self) -> RawFd {
+        self.fd()
+    }
+
+    fn event_type(&self) -> EventType {
+        EventType::Io
+    }
+
+    fn epoll_event(&self) -> u32 {
+        (libc::EPOLLIN) as u32
+    }
+
+    fn priority(&self) -> i8 {
+        0i8
+    }
+
+    fn dispatch(&self, _e: &Events) -> Result<i32, Error> {
+        let mut buffer = [0u8; 8192];
+        loop {
+            let (len, addr) = match socket::recvfrom::<NetlinkAddr>(self.fd(), &mut buffer) {
+                Ok(ret) => ret,
+                Err(Errno::EAGAIN) | Err(Errno::EINTR) => break,
+                Err(_) => {
+                    return Err(Error::Other {
+                        msg: "failed to receive uevent",
+                    })
+                }
+            };
+
+            // only udev multicasts in the group, the messages sent to us directly are ignored
+            if !matches!(addr, Some(a) if a.groups() == UDEV_MONITOR_GROUP && a.pid() != 0) {
+                log::debug!("ignore the uevent not from udev");
+                continue;
+            }
+
+            match uevent_parse(&buffer[..len]) {
+                Some(props) => {
+                    let action = props.get("ACTION").cloned().unwrap_or_default();
+                    log::debug!("received uevent {} {:?}", action, props.get("DEVPATH"));
+                    self.manager.dispatch_uevent(&action, &props);
+                }
+                None => log::debug!("ignore the invalid uevent"),
+            }
+        }
+
+        Ok(0)
+    }
+
+    fn token(&self) -> u64 {
+        let data: u64 = unsafe { std::mem::transmute(self) };
+        data
+    }
+}
+
+/// parse the uevent broadcasted by udev, which is the header followed by the
+/// properties of KEY=VALUE separated by '\0'.
+fn uevent_parse(buf: &[u8]) -> Option<HashMap<String, String>> {
+    if buf.len() < UDEV_MONITOR_HEADER_SIZE || !buf.starts_with(UDEV_MONITOR_PREFIX) {
+        return None;
+    }
+
+    let field = |i: usize| [buf[i], buf[i + 1], buf[i + 2], buf[i + 3]];
+    // the magic is in the network byte order, the others are native
+    if u32::from_be_bytes(field(8)) != UDEV_MONITOR_MAGIC {
+        return None;
+    }
+    let off = u32::from_ne_bytes(field(16)) as usize;
+    let len = u32::from_ne_bytes(field(20)) as usize;
+    let properties = buf.get(off..off.checked_add(len)?)?;
+
+    let mut props = HashMap::new();
+    for prop in properties.split(|c| *c == 0) {
+        let prop = String::from_utf8_lossy(prop);
+        if let Some((key, value)) = prop.split_once('=') {
+            props.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    if !props.contains_key("ACTION") || !props.contains_key("DEVPATH") {
+        return None;
+    }
+    Some(props)
+}
+
+/// the device recorded in the udev database, whose id is the name of the entries in
+/// /run/udev/tags, such as b8:1 for the block device, c189:1 for the char device and
+/// n2 for the network interface.
+fn udev_db_device(id: &str) -> Option<HashMap<String, String>> {
+    if !id.is_char_boundary(1) {
+        return None;
+    }
+
+    let syspath = match id.split_at(1) {
+        ("b", devnum) => fs::canonicalize(Path::new("/sys/dev/block").join(devnum)).ok()?,
+        ("c", devnum) => fs::canonicalize(Path::new("/sys/dev/char").join(devnum)).ok()?,
+        ("n", ifindex) => net_syspath(ifindex)?,
+        _ => return None,
+    };
+
+    let devpath = syspath.strip_prefix("/sys").ok()?;
+    let mut props = HashMap::new();
+    props.insert(
+        "DEVPATH".to_string(),
+        format!("/{}", devpath.to_string_lossy()),
+    );
+    props.insert("TAGS".to_string(), ":process1:".to_string());
+
+    // DEVNAME and the others exported by the kernel
+    let uevent = fs::read_to_string(syspath.join("uevent")).unwrap_or_default();
+    for line in uevent.lines() {
+        if let Some((key, value)) = line.split_once('=') {
+            props.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    // the symlinks created by udev are recorded as S:disk/by-uuid/...
+    let data = fs::read_to_string(Path::new(UDEV_DATA_DIR).join(id)).unwrap_or_default();
+    let devlinks: Vec<String> = data
+        .lines()
+        .filter_map(|l| l.strip_prefix("S:"))
+        .map(|l| format!("/dev/{}", l))
+        .collect();
+    if !devlinks.is_empty() {
+        props.insert("DEVLINKS".to_string(), devlinks.join(" "));
+    }
+
+    Some(props)
+}
+
+fn net_syspath(ifindex: &str) -> Option<PathBuf> {
+    for entry in fs::read_dir("/sys/class/net").ok()?.flatten() {
+        let path = entry.path();
+        let index = fs::read_to_string(path.join("ifindex")).unwrap_or_default();
+        if index.trim() == ifindex {
+            return fs::canonicalize(path).ok();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn udev_message(props: &[&str]) -> Vec<u8> {
+        let properties: Vec<u8> = props
+            .iter()
+            .flat_map(|p| p.bytes().chain(std::iter::once(0)))
+            .collect();
+
+        let mut buf = UDEV_MONITOR_PREFIX.to_vec();
+        buf.extend_from_slice(&UDEV_MONITOR_MAGIC.to_be_bytes());
+        buf.extend_from_slice(&(UDEV_MONITOR_HEADER_SIZE as u32).to_ne_bytes());
+        buf.extend_from_slice(&(UDEV_MONITOR_HEADER_SIZE as u32).to_ne_bytes());
+        buf.extend_from_slice(&(properties.len() as u32).to_ne_bytes());
+        buf.resize(UDEV_MONITOR_HEADER_SIZE, 0);
+        buf.extend_from_slice(&properties);
+        buf
+    }
+
+    #[test]
+    fn test_uevent_parse() {
+        let buf = udev_message(&[
+            "ACTION=add",
+            "DEVPATH=/devices/virtual/block/loop0",
+            "DEVNAME=/dev/loop0",
+            "TAGS=:process1:",
+        ]);
+        let props = uevent_parse(&buf).unwrap();
+        assert_eq!(props.get("ACTION").unwrap(), "add");
+        assert_eq!(props.get("DEVNAME").unwrap(), "/dev/loop0");
+        assert_eq!(props.get("TAGS").unwrap(), ":process1:");
+
+        // the raw kernel uevent has no header
+        let kernel = b"add@/devices/virtual/block/loop0\0ACTION=add\0";
+        assert!(uevent_parse(kernel).is_none());
+
+        // the properties out of the message
+        let mut buf = udev_message(&["ACTION=add", "DEVPATH=/devices/virtual/block/loop0"]);
+        buf.truncate(buf.len() - 4);
+        assert!(uevent_parse(&buf).is_none());
+    }
+}
@@ -1,9 +1,11 @@
 use super::commands::Commands;
-use super::data::DataManager;
+use super::data::{reboot_argument_path, write_reboot_argument, DataManager, EmergencyAction};
+use super::device_monitor::DeviceMonitor;
 use super::manager_config::ManagerConfig;
 use super::mount_monitor::MountMonitor;
 use super::notify::NotifyEvent;
 use super::signals::Signals;
+use super::table::{TableOp, TableSubscribe};
 use super::unit::UnitManagerX;
 use super::MngErrno;
 use event::{EventState, Events};
@@ -11,10 +13,21 @@ use nix::sys::reboot::{reboot, RebootMode};
 use nix::sys::socket::UnixCredentials;
 use std::collections::HashMap;
 use std::error::Error as Err;
+use std::ffi::CString;
+use std::fs;
 use std::io::Error;
 use std::rc::Rc;
 use utils::error::Error as ServiceError;
-use utils::{process_util, Result};
+use utils::{condition, process_util, unit_name, Result};
+
+// the target started on boot if there is no default.target
+const BASIC_TARGET: &str = "basic.target";
+const EMERGENCY_TARGET: &str = "emergency.target";
+const RESCUE_TARGET: &str = "rescue.target";
+
+// the words on the kernel command line selecting the rescue mode, the same as sysvinit
+const RESCUE_WORDS: [&str; 5] = ["rescue", "single", "s", "S", "1"];
+
 pub enum Mode {
     SYSTEM,
     USER,
@@ -45,6 +58,7 @@ pub struct ManagerX {
     data: Rc<Manager>,
     signal: Rc<Signals>,
     mount_monitor: Rc<MountMonitor>,
+    device_monitor: Rc<DeviceMonitor>,
     config: Rc<ManagerConfig>,
     notify: Rc<NotifyEvent>,
 }
@@ -61,15 +75,39 @@ impl ManagerX {
             data: Rc::clone(&_data),
             signal: Rc::new(Signals::new(&_data)),
             mount_monitor: Rc::new(MountMonitor::new(&_data)),
+            device_monitor: Rc::new(DeviceMonitor::new(&_data)),
             config: configm.clone(),
             notify: Rc::new(NotifyEvent::new(&_data, &configm)),
         };
         m.register(&_event);
         m.enable(&_event);
+
+        // the forced and immediate emergency actions requested by the units
+        let subscriber = Rc::clone(&_data);
+        _data.dm.register_emergency_action("Manager", subscriber);
         m
     }
 
     pub fn startup(&self) -> Result<i32> {
+        if condition::first_boot() {
+            log::info!("First boot, applying the unit presets.");
+            self.data.preset_all_units();
+            // the presets are applied only once, the machine-id marks the system set up.
+            if let Err(e) = condition::machine_id_commit() {
+                log::warn!("Failed to commit the machine-id: {}", e);
+            }
+        }
+        self.data.write_builtin_units();
+
+        // the daemons forked by the services are reparented to us instead of pid 1, the
+        // main pid of the service without PIDFile= is guessed from them.
+        if unsafe { libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0) } < 0 {
+            log::warn!(
+                "Failed to make process1 a subreaper: {}",
+                Error::last_os_error()
+            );
+        }
+
         log::debug!("Adding signals source to event loop.");
         let signal_source = Rc::clone(&self.signal);
         self.event.add_source(signal_source.clone())?;
@@ -82,6 +120,19 @@ impl ManagerX {
         self.event
             .set_enabled(mount_source.clone(), EventState::On)?;
 
+        // the devices are not monitored in the environment without udev, such as the container.
+        log::debug!("Adding uevent source to event loop.");
+        let device_monitor = Rc::clone(&self.device_monitor);
+        match device_monitor.open_socket() {
+            Ok(_) => {
+                self.event.add_source(device_monitor.clone())?;
+                self.event
+                    .set_enabled(device_monitor.clone(), EventState::On)?;
+                device_monitor.coldplug();
+            }
+            Err(e) => log::warn!("Failed to listen on the uevents: {}", e),
+        }
+
         log::debug!("Setup notify socket event.");
         let notify = Rc::clone(&self.notify);
         notify.open_socket().map_err(|e| Error::from(e))?;
@@ -99,6 +150,63 @@ impl ManagerX {
         self.data.start_unit(name)
     }
 
+    pub fn cat_unit(&self, name: &str) -> Result<String, MngErrno> {
+        self.data.cat_unit(name)
+    }
+
+    pub fn enable_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.data.enable_unit(name, runtime)
+    }
+
+    pub fn disable_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.data.disable_unit(name, runtime)
+    }
+
+    pub fn mask_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.data.mask_unit(name, runtime)
+    }
+
+    pub fn unmask_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.data.unmask_unit(name, runtime)
+    }
+
+    pub fn preset_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.data.preset_unit(name, runtime)
+    }
+
+    pub fn preset_all_units(&self) -> String {
+        self.data.preset_all_units()
+    }
+
+    pub fn get_default_target(&self) -> Result<String, MngErrno> {
+        self.data.get_default_target()
+    }
+
+    pub fn set_default_target(&self, name: &str) -> Result<String, MngErrno> {
+        self.data.set_default_target(name)
+    }
+
+    pub fn isolate_unit(&self, name: &str) -> Result<(), MngErrno> {
+        self.data.isolate_unit(name)
+    }
+
+    pub fn start_scope(
+        &self,
+        name: &str,
+        pids: &[i32],
+        properties: &[String],
+    ) -> Result<(), MngErrno> {
+        self.data.start_scope(name, pids, properties)
+    }
+
+    pub fn boot_target(&self) -> String {
+        self.data.boot_target()
+    }
+
+    pub fn unit_status(&self, name: &str) -> Result<String, MngErrno> {
+        self.data.unit_status(name)
+    }
+
     pub fn stop_unit(&self, name: &str) -> Result<(), MngErrno> {
         self.data.stop_unit(name)
     }
@@ -168,6 +276,82 @@ impl Manager {
         self.um.start_unit(name)
     }
 
+    pub(crate) fn cat_unit(&self, name: &str) -> Result<String, MngErrno> {
+        self.um.cat_unit(name)
+    }
+
+    pub(crate) fn enable_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.um.enable_unit(name, runtime)
+    }
+
+    pub(crate) fn disable_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.um.disable_unit(name, runtime)
+    }
+
+    pub(crate) fn mask_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.um.mask_unit(name, runtime)
+    }
+
+    pub(crate) fn unmask_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.um.unmask_unit(name, runtime)
+    }
+
+    pub(crate) fn preset_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        self.um.preset_unit(name, runtime)
+    }
+
+    pub(crate) fn preset_all_units(&self) -> String {
+        self.um.preset_all_units()
+    }
+
+    pub(crate) fn write_builtin_units(&self) {
+        self.um.write_builtin_units()
+    }
+
+    pub(crate) fn get_default_target(&self) -> Result<String, MngErrno> {
+        self.um.get_default_target()
+    }
+
+    pub(crate) fn set_default_target(&self, name: &str) -> Result<String, MngErrno> {
+        self.um.set_default_target(name)
+    }
+
+    pub(crate) fn isolate_unit(&self, name: &str) -> Result<(), MngErrno> {
+        self.um.isolate_unit(name)
+    }
+
+    pub(crate) fn start_scope(
+        &self,
+        name: &str,
+        pids: &[i32],
+        properties: &[String],
+    ) -> Result<(), MngErrno> {
+        self.um.start_scope(name, pids, properties)
+    }
+
+    // the unit started on boot, process1.unit= on the kernel command line overrides default.target
+    pub(crate) fn boot_target(&self) -> String {
+        let cmdline = fs::read_to_string("/proc/cmdline").unwrap_or_default();
+        if let Some(name) = cmdline_boot_target(&cmdline) {
+            return name;
+        }
+
+        match self.um.get_default_target() {
+            Ok(name) => name,
+            Err(_) => {
+                log::warn!(
+                    "default.target is not found, falling back to {}",
+                    BASIC_TARGET
+                );
+                BASIC_TARGET.to_string()
+            }
+        }
+    }
+
+    pub(crate) fn unit_status(&self, name: &str) -> Result<String, MngErrno> {
+        self.um.unit_status(name)
+    }
+
     pub(crate) fn stop_unit(&self, name: &str) -> Result<(), MngErrno> {
         self.um.stop_unit(name)
     }
@@ -193,16 +377,47 @@ impl Manager {
         // self.start_unit("shutdown.target");
         let mut pids = process_util::kill_all_pids(15);
         pids = process_util::wait_pids(pids, 10000000);
-        if pids.is_empty() {
-            return Ok(());
+        if !pids.is_empty() {
+            pids = process_util::kill_all_pids(9);
+            process_util::wait_pids(pids, 10000000);
         }
-        pids = process_util::kill_all_pids(9);
-        pids = process_util::wait_pids(pids, 10000000);
         log::info!("Rebooting...");
+        if reboot_mode == RebootMode::RB_AUTOBOOT {
+            reboot_with_argument();
+        }
         reboot(reboot_mode);
         Ok(())
     }
 
+    // the forced actions kill everything and stop the system, the same as SysComm, and the
+    // immediate actions call reboot(2) at once. The graceful ones are left to the UnitManager.
+    fn emergency_action(&self, source: &str, action: &EmergencyAction) {
+        let mode = match action.action.reboot_mode() {
+            Some(mode) => mode,
+            None => return,
+        };
+
+        log::info!("{:?} is requested by {}", action.action, source);
+        if action.action.is_reboot() {
+            write_reboot_argument(&action.reboot_argument);
+        }
+
+        if action.action.is_immediate() {
+            nix::unistd::sync();
+            if mode == RebootMode::RB_AUTOBOOT {
+                reboot_with_argument();
+            }
+            // reboot(2) returns only on failure
+            let Err(e) = reboot(mode);
+            log::error!("failed to execute {:?}: {}", action.action, e);
+            return;
+        }
+
+        if let Err(e) = self.reboot(mode) {
+            log::error!("failed to execute {:?}: {:?}", action.action, e);
+        }
+    }
+
     pub(crate) fn reexec(&self) -> Result<(), Error> {
         todo!()
     }
@@ -235,6 +450,10 @@ impl Manager {
         self.um.dispatch_mountinfo()
     }
 
+    pub(crate) fn dispatch_uevent(&self, action: &str, props: &HashMap<String, String>) {
+        self.um.dispatch_uevent(action, props)
+    }
+
     pub(crate) fn notify_message(
         &self,
         ucred: &UnixCredentials,
@@ -245,6 +464,56 @@ impl Manager {
     }
 }
 
+impl TableSubscribe<String, EmergencyAction> for Manager {
+    fn notify(&self, op: &TableOp<String, EmergencyAction>) {
+        if let TableOp::TableInsert(name, action) = op {
+            self.emergency_action(name, action);
+        }
+    }
+}
+
+// reboot with RebootArgument=, which is consumed by the firmware or the boot loader
+fn reboot_with_argument() {
+    let arg = match fs::read_to_string(reboot_argument_path()) {
+        Ok(arg) if !arg.trim().is_empty() => arg.trim().to_string(),
+        _ => return,
+    };
+    let arg = match CString::new(arg) {
+        Ok(arg) => arg,
+        Err(_) => return,
+    };
+
+    log::info!("Rebooting with argument {:?}", arg);
+    unsafe {
+        libc::syscall(
+            libc::SYS_reboot,
+            libc::LINUX_REBOOT_MAGIC1,
+            libc::LINUX_REBOOT_MAGIC2,
+            libc::LINUX_REBOOT_CMD_RESTART2,
+            arg.as_ptr(),
+        );
+    }
+}
+
+// the target selected by the kernel command line {cmdline}, None to boot default.target
+fn cmdline_boot_target(cmdline: &str) -> Option<String> {
+    let words: Vec<&str> = cmdline.split_whitespace().collect();
+    if let Some(name) = words.iter().find_map(|w| w.strip_prefix("process1.unit=")) {
+        if unit_name::unit_name_is_valid(name) {
+            return Some(name.to_string());
+        }
+        log::warn!("invalid unit name process1.unit={}, ignore it", name);
+    }
+
+    if words.iter().any(|w| *w == "emergency" || *w == "-b") {
+        return Some(EMERGENCY_TARGET.to_string());
+    }
+    if words.iter().any(|w| RESCUE_WORDS.contains(w)) {
+        return Some(RESCUE_TARGET.to_string());
+    }
+    None
+}
+
 impl Drop for Manager {
     fn drop(&mut self) {}
 }
@@ -253,8 +522,33 @@ impl Drop for Manager {
 mod tests {
     // use crate::manager::service::ServiceUnit;
 
-    // use super::*;
+    use super::cmdline_boot_target;
 
     #[test]
     fn test_mangerplugin() {}
+
+    #[test]
+    fn test_cmdline_boot_target() {
+        assert_eq!(cmdline_boot_target("ro quiet\n"), None);
+        assert_eq!(
+            cmdline_boot_target("ro process1.unit=multi-user.target quiet"),
+            Some("multi-user.target".to_string())
+        );
+        assert_eq!(
+            cmdline_boot_target("process1.unit=rescue.target emergency"),
+            Some("rescue.target".to_string())
+        );
+        assert_eq!(
+            cmdline_boot_target("process1.unit=invalid emergency"),
+            Some("emergency.target".to_string())
+        );
+        assert_eq!(
+            cmdline_boot_target("ro -b"),
+            Some("emergency.target".to_string())
+        );
+        assert_eq!(
+            cmdline_boot_target("ro single"),
+            Some("rescue.target".to_string())
+        );
+    }
 }
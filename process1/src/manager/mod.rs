@@ -2,7 +2,8 @@ pub use data::{UnitActiveState, UnitNotifyFlags, UnitRelations};
 pub(super) use manager::Manager;
 pub use manager::{Action, ManagerX, Mode, Stats};
 pub use unit::{
-    DeserializeWith, ExecCmdError, ExecCommand, ExecContext, ExecFlags, ExecParameters,
+    DeserializeWith, ExecCmdError, ExecCommand, ExecContext, ExecDirectory, ExecDirectoryType,
+    ExecFlags, ExecNamespace, ExecParameters, ExecPreserveMode, KillContext, KillMode,
     KillOperation, Unit, UnitActionError, UnitDependencyMask, UnitManager, UnitMngUtil, UnitObj,
     UnitRef, UnitRelationAtom, UnitSubClass, UnitType,
 };
@@ -18,6 +19,7 @@ pub enum MngErrno {
 mod commands;
 #[allow(dead_code)]
 mod data;
+mod device_monitor;
 #[allow(dead_code)]
 mod manager;
 mod mount_monitor;
@@ -1,4 +1,7 @@
 use bitflags::bitflags;
+use nix::unistd::{Gid, Group, Uid, User};
+use std::path::{Component, Path};
+use std::str::FromStr;
 use std::{cell::RefCell, collections::HashMap, ffi::CString, path::PathBuf, rc::Rc};
 
 #[derive(PartialEq, Clone, Eq, Debug)]
@@ -31,15 +34,222 @@ pub enum ExecCmdError {
 
 pub struct ExecContext {
     envs: RefCell<HashMap<String, String>>,
+    ns: RefCell<ExecNamespace>,
+    user: RefCell<Option<String>>,
+    group: RefCell<Option<String>>,
+    directories: RefCell<HashMap<ExecDirectoryType, ExecDirectory>>,
+    runtime_directory_preserve: RefCell<ExecPreserveMode>,
+}
+
+/// the directories managed for the executed processes: RuntimeDirectory=, StateDirectory=, ...
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ExecDirectoryType {
+    Runtime,
+    State,
+    Cache,
+    Logs,
+    Configuration,
+}
+
+impl ExecDirectoryType {
+    pub fn iter() -> impl Iterator<Item = ExecDirectoryType> {
+        vec![
+            ExecDirectoryType::Runtime,
+            ExecDirectoryType::State,
+            ExecDirectoryType::Cache,
+            ExecDirectoryType::Logs,
+            ExecDirectoryType::Configuration,
+        ]
+        .into_iter()
+    }
+
+    fn prefix(&self) -> &'static str {
+        match self {
+            ExecDirectoryType::Runtime => "/run",
+            ExecDirectoryType::State => "/var/lib",
+            ExecDirectoryType::Cache => "/var/cache",
+            ExecDirectoryType::Logs => "/var/log",
+            ExecDirectoryType::Configuration => "/etc",
+        }
+    }
+
+    fn env_name(&self) -> &'static str {
+        match self {
+            ExecDirectoryType::Runtime => "RUNTIME_DIRECTORY",
+            ExecDirectoryType::State => "STATE_DIRECTORY",
+            ExecDirectoryType::Cache => "CACHE_DIRECTORY",
+            ExecDirectoryType::Logs => "LOGS_DIRECTORY",
+            ExecDirectoryType::Configuration => "CONFIGURATION_DIRECTORY",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExecDirectory {
+    paths: Vec<String>,
+    mode: u32,
+}
+
+impl ExecDirectory {
+    /// the paths are relative to the prefix of the directory type, such as "/run" for RuntimeDirectory=
+    pub fn new(paths: Vec<String>, mode: u32) -> Result<ExecDirectory, String> {
+        for path in paths.iter() {
+            let p = Path::new(path);
+            if path.is_empty()
+                || p.is_absolute()
+                || p.components().any(|c| c == Component::ParentDir)
+            {
+                return Err(format!("invalid exec directory: {}", path));
+            }
+        }
+
+        Ok(ExecDirectory { paths, mode })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExecPreserveMode {
+    No,
+    Yes,
+    Restart,
+}
+
+impl FromStr for ExecPreserveMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "no" | "false" => Ok(ExecPreserveMode::No),
+            "yes" | "true" => Ok(ExecPreserveMode::Yes),
+            "restart" => Ok(ExecPreserveMode::Restart),
+            _ => Err(format!("invalid preserve mode: {}", s)),
+        }
+    }
+}
+
+/// namespace isolation of the executed processes
+#[derive(Default, Clone, Debug)]
+pub struct ExecNamespace {
+    pub private_network: bool,
+    pub private_users: bool,
+    pub private_ipc: bool,
+    pub protect_hostname: bool,
+    pub network_namespace_path: Option<PathBuf>,
+}
+
+impl ExecNamespace {
+    pub(super) fn need_netns(&self) -> bool {
+        self.private_network || self.network_namespace_path.is_some()
+    }
 }
 
 impl ExecContext {
     pub fn new() -> ExecContext {
         ExecContext {
             envs: RefCell::new(HashMap::new()),
+            ns: RefCell::new(ExecNamespace::default()),
+            user: RefCell::new(None),
+            group: RefCell::new(None),
+            directories: RefCell::new(HashMap::new()),
+            runtime_directory_preserve: RefCell::new(ExecPreserveMode::No),
         }
     }
 
+    pub fn set_user(&self, user: Option<String>) {
+        *self.user.borrow_mut() = user;
+    }
+
+    pub fn set_group(&self, group: Option<String>) {
+        *self.group.borrow_mut() = group;
+    }
+
+    pub fn user(&self) -> Option<String> {
+        self.user.borrow().clone()
+    }
+
+    /// resolve User= and Group=, the primary group of the user is used if Group= is not set.
+    pub fn user_and_group(&self) -> utils::Result<(Option<Uid>, Option<Gid>)> {
+        let mut uid = None;
+        let mut gid = None;
+
+        if let Some(name) = &*self.user.borrow() {
+            let user = User::from_name(name)
+                .ok()
+                .flatten()
+                .ok_or(utils::Error::Other {
+                    msg: "the user configured is not found",
+                })?;
+            uid = Some(user.uid);
+            gid = Some(user.gid);
+        }
+
+        if let Some(name) = &*self.group.borrow() {
+            let group = Group::from_name(name)
+                .ok()
+                .flatten()
+                .ok_or(utils::Error::Other {
+                    msg: "the group configured is not found",
+                })?;
+            gid = Some(group.gid);
+        }
+
+        Ok((uid, gid))
+    }
+
+    pub fn insert_directory(&self, dt: ExecDirectoryType, directory: ExecDirectory) {
+        self.directories.borrow_mut().insert(dt, directory);
+    }
+
+    pub fn set_runtime_directory_preserve(&self, mode: ExecPreserveMode) {
+        *self.runtime_directory_preserve.borrow_mut() = mode;
+    }
+
+    pub fn runtime_directory_preserve(&self) -> ExecPreserveMode {
+        *self.runtime_directory_preserve.borrow()
+    }
+
+    pub fn directory_paths(&self, dt: ExecDirectoryType) -> Vec<PathBuf> {
+        match self.directories.borrow().get(&dt) {
+            Some(d) => d
+                .paths
+                .iter()
+                .map(|p| Path::new(dt.prefix()).join(p))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn directory_mode(&self, dt: ExecDirectoryType) -> u32 {
+        match self.directories.borrow().get(&dt) {
+            Some(d) => d.mode,
+            None => 0o755,
+        }
+    }
+
+    /// $RUNTIME_DIRECTORY, $STATE_DIRECTORY ... exported to the executed processes
+    pub fn directory_envs(&self) -> HashMap<String, String> {
+        let mut envs = HashMap::new();
+        for dt in ExecDirectoryType::iter() {
+            let paths = self
+                .directory_paths(dt)
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            if !paths.is_empty() {
+                envs.insert(dt.env_name().to_string(), paths.join(":"));
+            }
+        }
+        envs
+    }
+
+    pub fn set_namespace(&self, ns: ExecNamespace) {
+        *self.ns.borrow_mut() = ns;
+    }
+
+    pub fn namespace(&self) -> ExecNamespace {
+        self.ns.borrow().clone()
+    }
+
     pub fn insert_env(&self, key: String, value: String) {
         self.envs.borrow_mut().insert(key, value);
     }
@@ -58,6 +268,8 @@ pub struct ExecParameters {
     environment: Rc<EnvData>,
     fds: Vec<i32>,
     notify_sock: Option<PathBuf>,
+    exec_fd: Option<i32>,
+    idle: bool,
 }
 
 struct EnvData {
@@ -96,6 +308,8 @@ impl ExecParameters {
             environment: Rc::new(EnvData::new()),
             fds: Vec::new(),
             notify_sock: None,
+            exec_fd: None,
+            idle: false,
         }
     }
 
@@ -122,6 +336,24 @@ impl ExecParameters {
     pub fn set_notify_sock(&mut self, notify_sock: PathBuf) {
         self.notify_sock = Some(notify_sock)
     }
+
+    /// the write end of a CLOEXEC pipe, which is closed when execve succeeded.
+    pub fn set_exec_fd(&mut self, fd: i32) {
+        self.exec_fd = Some(fd)
+    }
+
+    pub fn exec_fd(&self) -> Option<i32> {
+        self.exec_fd
+    }
+
+    /// delay the execution until the job queue is empty, or the idle timeout is reached.
+    pub fn set_idle(&mut self, idle: bool) {
+        self.idle = idle
+    }
+
+    pub fn idle(&self) -> bool {
+        self.idle
+    }
 }
 
 bitflags! {
@@ -0,0 +1,118 @@
+use super::exec_base::{ExecContext, ExecDirectoryType, ExecPreserveMode};
+use nix::sys::stat::{self, Mode};
+use nix::unistd::{self, Gid, Uid};
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use utils::{Error, Result};
+
+/// create the directories configured in the context, and change the owner to the user executed.
+/// ConfigurationDirectory= is not owned by the user, the configuration is only read by the service.
+pub(in crate::manager::unit) fn setup_exec_directories(ctx: &ExecContext) -> Result<()> {
+    let (uid, gid) = ctx.user_and_group()?;
+
+    for dt in ExecDirectoryType::iter() {
+        let mode = ctx.directory_mode(dt);
+        let (uid, gid) = match dt {
+            ExecDirectoryType::Configuration => (None, None),
+            _ => (uid, gid),
+        };
+        for path in ctx.directory_paths(dt) {
+            create_directory(&path, mode, uid, gid)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// remove RuntimeDirectory= when the unit is stopped, unless RuntimeDirectoryPreserve= is set.
+pub(in crate::manager::unit) fn destroy_runtime_directory(ctx: &ExecContext, restarting: bool) {
+    match ctx.runtime_directory_preserve() {
+        ExecPreserveMode::Yes => return,
+        ExecPreserveMode::Restart if restarting => return,
+        _ => {}
+    }
+
+    for path in ctx.directory_paths(ExecDirectoryType::Runtime) {
+        log::debug!("remove runtime directory: {:?}", path);
+        if let Err(e) = fs::remove_dir_all(&path) {
+            if e.kind() != ErrorKind::NotFound {
+                log::warn!("failed to remove runtime directory {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+fn create_directory(path: &PathBuf, mode: u32, uid: Option<Uid>, gid: Option<Gid>) -> Result<()> {
+    log::debug!("create exec directory: {:?}, mode: {:o}", path, mode);
+    // the parent directories are created with the default mode, only the last one is changed.
+    let old_mask = stat::umask(Mode::from_bits_truncate(0o022));
+    let ret = fs::create_dir_all(path);
+    stat::umask(old_mask);
+    ret?;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+
+    if uid.is_some() || gid.is_some() {
+        unistd::chown(path, uid, gid).map_err(|e| Error::Syscall {
+            syscall: "chown",
+            ret: -1,
+            errno: e as i32,
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::unit::execute::exec_base::ExecDirectory;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn test_create_directory() {
+        let dir = std::env::temp_dir().join(format!("exec_dir_{}", std::process::id()));
+        let path = dir.join("foo/bar");
+
+        create_directory(&path, 0o750, None, None).unwrap();
+        let meta = fs::metadata(&path).unwrap();
+        assert!(meta.is_dir());
+        assert_eq!(meta.mode() & 0o7777, 0o750);
+        // the parent directories are created with the default mode
+        let meta = fs::metadata(dir.join("foo")).unwrap();
+        assert_eq!(meta.mode() & 0o7777, 0o755);
+
+        // the mode of the directory existing is changed
+        create_directory(&path, 0o700, None, None).unwrap();
+        let meta = fs::metadata(&path).unwrap();
+        assert_eq!(meta.mode() & 0o7777, 0o700);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_exec_directory() {
+        let ctx = ExecContext::new();
+        ctx.insert_directory(
+            ExecDirectoryType::State,
+            ExecDirectory::new(vec!["foo".to_string(), "bar/baz".to_string()], 0o700).unwrap(),
+        );
+        assert_eq!(
+            ctx.directory_paths(ExecDirectoryType::State),
+            vec![
+                PathBuf::from("/var/lib/foo"),
+                PathBuf::from("/var/lib/bar/baz")
+            ]
+        );
+        assert_eq!(ctx.directory_mode(ExecDirectoryType::State), 0o700);
+        assert_eq!(ctx.directory_mode(ExecDirectoryType::Runtime), 0o755);
+        assert!(ctx.directory_paths(ExecDirectoryType::Runtime).is_empty());
+
+        // the paths must be relative and stay inside the prefix
+        assert!(ExecDirectory::new(vec!["/foo".to_string()], 0o755).is_err());
+        assert!(ExecDirectory::new(vec!["foo/../..".to_string()], 0o755).is_err());
+        assert!(ExecDirectory::new(vec!["".to_string()], 0o755).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use nix::errno::Errno;
+use nix::fcntl::{self, OFlag};
+use nix::libc;
+use nix::sched::{self, CloneFlags};
+use nix::sys::socket::{
+    self, AddressFamily, ControlMessage, ControlMessageOwned, MsgFlags, SockFlag, SockType,
+};
+use nix::sys::stat::Mode;
+use std::io::{IoSlice, IoSliceMut};
+use std::os::unix::prelude::RawFd;
+use std::path::Path;
+use utils::{fd_util, socket_util};
+
+/// the namespace storage is a datagram socket pair, the namespace fd is kept in the socket queue,
+/// so the namespace is kept alive by process1 even if there is no process running in it.
+/// units joined with JoinsNamespaceOf= share the same storage.
+pub struct ExecNsStorage {
+    fds: (RawFd, RawFd),
+}
+
+impl Drop for ExecNsStorage {
+    fn drop(&mut self) {
+        fd_util::close(self.fds.0);
+        fd_util::close(self.fds.1);
+    }
+}
+
+impl ExecNsStorage {
+    pub(in crate::manager::unit) fn new() -> Result<ExecNsStorage, Errno> {
+        let fds = socket::socketpair(
+            AddressFamily::Unix,
+            SockType::Datagram,
+            None,
+            SockFlag::SOCK_CLOEXEC,
+        )?;
+
+        Ok(ExecNsStorage { fds })
+    }
+
+    fn lock(&self) -> Result<(), Errno> {
+        // posix lock is owned by process, so the children forked from process1 exclude each other.
+        Errno::result(unsafe { libc::lockf(self.fds.0, libc::F_LOCK, 0) }).map(drop)
+    }
+
+    fn unlock(&self) {
+        unsafe { libc::lockf(self.fds.0, libc::F_ULOCK, 0) };
+    }
+
+    fn take(&self) -> Result<Option<RawFd>, Errno> {
+        let mut buffer = [0u8; 1];
+        let mut iov = [IoSliceMut::new(&mut buffer)];
+        let mut space = nix::cmsg_space!(RawFd);
+
+        let msg = match socket::recvmsg::<()>(
+            self.fds.0,
+            &mut iov,
+            Some(&mut space),
+            MsgFlags::MSG_DONTWAIT | MsgFlags::MSG_CMSG_CLOEXEC,
+        ) {
+            Ok(msg) => msg,
+            Err(Errno::EAGAIN) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        for cmsg in msg.cmsgs() {
+            if let ControlMessageOwned::ScmRights(fds) = cmsg {
+                if let Some(fd) = fds.first() {
+                    return Ok(Some(*fd));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn store(&self, fd: RawFd) -> Result<(), Errno> {
+        let buffer = [b'x'];
+        let iov = [IoSlice::new(&buffer)];
+        let fds = [fd];
+        let cmsg = [ControlMessage::ScmRights(&fds)];
+
+        socket::sendmsg::<()>(self.fds.1, &iov, &cmsg, MsgFlags::MSG_DONTWAIT, None)?;
+        Ok(())
+    }
+}
+
+/// join the namespace kept in the storage, or create(join the path) a new one and keep it in the storage.
+pub(super) fn setup_shareable_ns(
+    storage: &ExecNsStorage,
+    nstype: CloneFlags,
+    path: Option<&Path>,
+) -> Result<(), Errno> {
+    storage.lock()?;
+    let ret = shareable_ns_join_or_new(storage, nstype, path);
+    storage.unlock();
+    ret
+}
+
+fn shareable_ns_join_or_new(
+    storage: &ExecNsStorage,
+    nstype: CloneFlags,
+    path: Option<&Path>,
+) -> Result<(), Errno> {
+    if let Some(fd) = storage.take()? {
+        // the namespace exists already, join it and put it back.
+        let ret = sched::setns(fd, nstype).and_then(|_| storage.store(fd));
+        fd_util::close(fd);
+        return ret;
+    }
+
+    let fd = match path {
+        Some(p) => {
+            let fd = fcntl::open(p, OFlag::O_RDONLY | OFlag::O_CLOEXEC, Mode::empty())?;
+            if let Err(e) = sched::setns(fd, nstype) {
+                fd_util::close(fd);
+                return Err(e);
+            }
+            fd
+        }
+        None => {
+            sched::unshare(nstype)?;
+            if nstype == CloneFlags::CLONE_NEWNET {
+                if let Err(e) = socket_util::loopback_setup() {
+                    log::warn!(
+                        "failed to set up the loopback in the network namespace: {}",
+                        e
+                    );
+                }
+            }
+
+            let ns_path = match nstype {
+                CloneFlags::CLONE_NEWNET => "/proc/self/ns/net",
+                CloneFlags::CLONE_NEWIPC => "/proc/self/ns/ipc",
+                _ => return Err(Errno::EINVAL),
+            };
+            fcntl::open(ns_path, OFlag::O_RDONLY | OFlag::O_CLOEXEC, Mode::empty())?
+        }
+    };
+
+    let ret = storage.store(fd);
+    fd_util::close(fd);
+    ret
+}
+
+/// new user namespace, only the current user and group are mapped into it.
+pub(super) fn setup_private_users() -> Result<(), Errno> {
+    let uid = nix::unistd::getuid();
+    let gid = nix::unistd::getgid();
+
+    sched::unshare(CloneFlags::CLONE_NEWUSER)?;
+
+    let write = |path: &str, content: String| -> Result<(), Errno> {
+        std::fs::write(path, content).map_err(|e| Errno::from_i32(e.raw_os_error().unwrap_or(0)))
+    };
+
+    write("/proc/self/setgroups", "deny".to_string())?;
+    write("/proc/self/uid_map", format!("{} {} 1\n", uid, uid))?;
+    write("/proc/self/gid_map", format!("{} {} 1\n", gid, gid))?;
+
+    Ok(())
+}
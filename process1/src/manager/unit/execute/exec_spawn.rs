@@ -1,12 +1,17 @@
-use super::exec_base::{ExecCmdError, ExecCommand, ExecParameters};
+use super::exec_base::{ExecCmdError, ExecCommand, ExecNamespace, ExecParameters};
+use super::exec_ns::{self, ExecNsStorage};
 use super::ExecContext;
 use crate::manager::unit::Unit;
 use cgroup;
 use log;
-use nix::fcntl::FcntlArg;
+use nix::fcntl::{FcntlArg, OFlag};
+use nix::poll::{self, PollFd, PollFlags};
+use nix::sched::{self, CloneFlags};
 use nix::unistd::{self, ForkResult, Pid};
 use regex::Regex;
+use std::cell::RefCell;
 use std::convert::TryInto;
+use std::os::unix::prelude::RawFd;
 use std::path::PathBuf;
 use std::process;
 use std::rc::Rc;
@@ -17,11 +22,51 @@ use walkdir::WalkDir;
 
 use utils::fd_util;
 
-pub(in crate::manager::unit) struct ExecSpawn;
+// the processes of Type=idle wait for the job queue being empty at most 5s.
+const IDLE_TIMEOUT_MSEC: i32 = 5000;
+
+/// the idle pipe is kept open while there are jobs in the queue, the processes of Type=idle wait for
+/// its write end being closed.
+pub(in crate::manager::unit) struct ExecIdlePipe {
+    fds: RefCell<Option<(RawFd, RawFd)>>,
+}
+
+impl ExecIdlePipe {
+    pub(in crate::manager::unit) fn new() -> ExecIdlePipe {
+        ExecIdlePipe {
+            fds: RefCell::new(None),
+        }
+    }
+
+    fn open(&self) -> Option<(RawFd, RawFd)> {
+        if self.fds.borrow().is_none() {
+            match unistd::pipe2(OFlag::O_CLOEXEC) {
+                Ok(fds) => *self.fds.borrow_mut() = Some(fds),
+                Err(e) => log::warn!("failed to create the idle pipe: {}", e),
+            }
+        }
+
+        *self.fds.borrow()
+    }
+
+    pub(in crate::manager::unit) fn close(&self) {
+        if let Some((r, w)) = self.fds.take() {
+            log::debug!("the job queue is empty, close the idle pipe");
+            fd_util::close(r);
+            fd_util::close(w);
+        }
+    }
+}
+
+pub(in crate::manager::unit) struct ExecSpawn {
+    idle: Rc<ExecIdlePipe>,
+}
 
 impl ExecSpawn {
-    pub(in crate::manager::unit) fn new() -> ExecSpawn {
-        ExecSpawn
+    pub(in crate::manager::unit) fn new(idle: &Rc<ExecIdlePipe>) -> ExecSpawn {
+        ExecSpawn {
+            idle: Rc::clone(idle),
+        }
     }
 
     pub(in crate::manager::unit) fn spawn(
@@ -31,6 +76,31 @@ impl ExecSpawn {
         params: &ExecParameters,
         ctx: Rc<ExecContext>,
     ) -> Result<Pid, ExecCmdError> {
+        // the namespace storages must be created before forking, which are kept by process1.
+        let ns = ctx.namespace();
+        let netns = if ns.need_netns() {
+            Some(
+                unit.ns_storage(CloneFlags::CLONE_NEWNET)
+                    .map_err(|_e| ExecCmdError::SpawnError)?,
+            )
+        } else {
+            None
+        };
+        let ipcns = if ns.private_ipc {
+            Some(
+                unit.ns_storage(CloneFlags::CLONE_NEWIPC)
+                    .map_err(|_e| ExecCmdError::SpawnError)?,
+            )
+        } else {
+            None
+        };
+
+        let idle_pipe = if params.idle() {
+            self.idle.open()
+        } else {
+            None
+        };
+
         unsafe {
             match unistd::fork() {
                 Ok(ForkResult::Parent { child }) => {
@@ -41,6 +111,24 @@ impl ExecSpawn {
                 }
                 Ok(ForkResult::Child) => {
                     thread::sleep(Duration::from_secs(2));
+                    if let Some((r, w)) = idle_pipe {
+                        wait_idle(r, w);
+                    }
+                    if let Err(e) = setup_namespaces(&ns, netns.as_deref(), ipcns.as_deref()) {
+                        log::error!("failed to set up namespaces: {}", e);
+                        process::exit(1);
+                    }
+                    if let Err(e) = setup_credentials(&ctx) {
+                        log::error!("failed to change the user and group: {}", e);
+                        process::exit(1);
+                    }
+                    // the user namespace is the last one, otherwise joining the namespaces owned by the host fails.
+                    if ns.private_users {
+                        if let Err(e) = exec_ns::setup_private_users() {
+                            log::error!("failed to set up the user namespace: {}", e);
+                            process::exit(1);
+                        }
+                    }
                     exec_child(unit, cmdline, params, ctx.clone());
                     process::exit(0);
                 }
@@ -57,6 +145,15 @@ fn exec_child(unit: &Unit, cmdline: &ExecCommand, params: &ExecParameters, ctx:
         params.add_env(&key, value.to_string());
     }
 
+    for (key, value) in ctx.directory_envs() {
+        params.add_env(&key, value);
+    }
+
+    // $MONITOR_* of the unit triggering this one through OnFailure=/OnSuccess=
+    for (key, value) in unit.monitor_envs() {
+        params.add_env(&key, value);
+    }
+
     let (cmd, args) = build_run_args(unit, cmdline, params);
     let cstr_args = args
         .iter()
@@ -77,7 +174,12 @@ fn exec_child(unit: &Unit, cmdline: &ExecCommand, params: &ExecParameters, ctx:
     let envs_cstr = envs.iter().map(|v| v.as_c_str()).collect::<Vec<_>>();
     let mut keep_fds = params.fds();
 
-    let ret = close_all_fds(params.fds());
+    let mut reserve_fds = params.fds();
+    if let Some(fd) = params.exec_fd() {
+        reserve_fds.push(fd);
+    }
+
+    let ret = close_all_fds(reserve_fds);
     if !ret {
         log::error!("close all needless fds failed");
         return;
@@ -93,6 +195,11 @@ fn exec_child(unit: &Unit, cmdline: &ExecCommand, params: &ExecParameters, ctx:
         return;
     }
 
+    // tell the parent that we are about to execve, the pipe is closed on success.
+    if let Some(fd) = params.exec_fd() {
+        let _ = unistd::write(fd, &[1u8]);
+    }
+
     log::debug!("exec child envs to execve is: {:?}", envs_cstr);
     match unistd::execve(&cmd, &cstr_args, &envs_cstr) {
         Ok(_) => {
@@ -100,11 +207,69 @@ fn exec_child(unit: &Unit, cmdline: &ExecCommand, params: &ExecParameters, ctx:
         }
         Err(e) => {
             log::error!("exec child failed: {:?}", e);
+            if let Some(fd) = params.exec_fd() {
+                let _ = unistd::write(fd, &[0u8]);
+            }
             std::process::exit(1);
         }
     }
 }
 
+fn wait_idle(r: RawFd, w: RawFd) {
+    // the write end must be closed in the child, otherwise the hang-up is never seen.
+    fd_util::close(w);
+
+    let mut fds = [PollFd::new(r, PollFlags::POLLHUP)];
+    if let Err(e) = poll::poll(&mut fds, IDLE_TIMEOUT_MSEC) {
+        log::debug!("failed to wait for the idle pipe: {}", e);
+    }
+
+    fd_util::close(r);
+}
+
+fn setup_namespaces(
+    ns: &ExecNamespace,
+    netns: Option<&ExecNsStorage>,
+    ipcns: Option<&ExecNsStorage>,
+) -> Result<(), nix::Error> {
+    if let Some(storage) = netns {
+        exec_ns::setup_shareable_ns(
+            storage,
+            CloneFlags::CLONE_NEWNET,
+            ns.network_namespace_path.as_deref(),
+        )?;
+    }
+
+    if let Some(storage) = ipcns {
+        exec_ns::setup_shareable_ns(storage, CloneFlags::CLONE_NEWIPC, None)?;
+    }
+
+    if ns.protect_hostname {
+        sched::unshare(CloneFlags::CLONE_NEWUTS)?;
+    }
+
+    Ok(())
+}
+
+fn setup_credentials(ctx: &ExecContext) -> Result<(), Box<dyn std::error::Error>> {
+    let (uid, gid) = ctx.user_and_group()?;
+
+    if let Some(gid) = gid {
+        // the supplementary groups of the user are kept, as what login does
+        match ctx.user() {
+            Some(user) => unistd::initgroups(&std::ffi::CString::new(user)?, gid)?,
+            None => unistd::setgroups(&[gid])?,
+        }
+        unistd::setgid(gid)?;
+    }
+
+    if let Some(uid) = uid {
+        unistd::setuid(uid)?;
+    }
+
+    Ok(())
+}
+
 // contrast: build_environment
 fn build_run_args(
     _unit: &Unit,
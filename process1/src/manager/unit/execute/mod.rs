@@ -1,6 +1,13 @@
-pub use exec_base::{ExecCmdError, ExecCommand, ExecContext, ExecFlags, ExecParameters};
-pub(super) use exec_spawn::ExecSpawn;
+pub use exec_base::{
+    ExecCmdError, ExecCommand, ExecContext, ExecDirectory, ExecDirectoryType, ExecFlags,
+    ExecNamespace, ExecParameters, ExecPreserveMode,
+};
+pub(super) use exec_dir::{destroy_runtime_directory, setup_exec_directories};
+pub(super) use exec_ns::ExecNsStorage;
+pub(super) use exec_spawn::{ExecIdlePipe, ExecSpawn};
 
 #[allow(dead_code)]
 mod exec_base;
+mod exec_dir;
+mod exec_ns;
 mod exec_spawn;
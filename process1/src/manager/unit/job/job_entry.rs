@@ -344,6 +344,7 @@ pub(super) fn job_process_unit(
         JobKind::JobStart => job_process_unit_start(ns),
         JobKind::JobStop => job_process_unit_stop(ns),
         JobKind::JobReload => job_process_unit_reload(ns, flags),
+        JobKind::JobVerify => job_process_unit_verify(ns),
         _ => unreachable!("Invalid job run-kind."),
     }
 }
@@ -409,6 +410,8 @@ fn job_trigger_err_to_result(err: UnitActionError) -> Option<JobResult> {
         UnitActionError::UnitActionEInval => Some(JobResult::JobFailed),
         UnitActionError::UnitActionEBusy => Some(JobResult::JobFailed),
         UnitActionError::UnitActionENoent => Some(JobResult::JobFailed),
+        UnitActionError::UnitActionETimedOut => Some(JobResult::JobTimeOut),
+        UnitActionError::UnitActionECanceled => Some(JobResult::JobFailed),
     }
 }
 
@@ -468,6 +471,20 @@ fn job_process_unit_reload(
     }
 }
 
+fn job_process_unit_verify(ns: UnitActiveState) -> (Option<JobResult>, bool) {
+    match ns {
+        // nothing is generated from the verification
+        UnitActiveState::UnitActive | UnitActiveState::UnitReloading => {
+            (Some(JobResult::JobDone), false)
+        }
+        UnitActiveState::UnitActivating => (None, false),
+        UnitActiveState::UnitInActive
+        | UnitActiveState::UnitFailed
+        | UnitActiveState::UnitDeActivating
+        | UnitActiveState::UnitMaintenance => (Some(JobResult::JobSkipped), false),
+    }
+}
+
 fn job_merge_unit(kind: JobKind, unit: &UnitX) -> JobKind {
     let us_is_active_or_reloading = match unit.active_state() {
         UnitActiveState::UnitActive | UnitActiveState::UnitReloading => true,
@@ -6,15 +6,19 @@ use super::job_stat::JobStat;
 use super::job_table::JobTable;
 use super::job_transaction::{self};
 use super::JobErrno;
-use crate::manager::data::{UnitActiveState, UnitNotifyFlags};
+use crate::manager::data::{
+    remove_reboot_argument, UnitActiveState, UnitEmergencyAction, UnitNotifyFlags,
+};
 use crate::manager::table::{TableOp, TableSubscribe};
+use crate::manager::unit::execute::ExecIdlePipe;
 use crate::manager::unit::unit_base::{JobMode, UnitRelationAtom};
 use crate::manager::unit::unit_datastore::UnitDb;
 use crate::manager::unit::unit_entry::UnitX;
 use event::{EventState, EventType, Events, Source};
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
-use utils::{Error, Result};
+use utils::{Error, Result, IN_SET};
 
 #[derive(Debug)]
 pub(in crate::manager::unit) struct JobAffect {
@@ -111,6 +115,10 @@ impl JobManager {
         self.data.get_jobinfo(id)
     }
 
+    pub(in crate::manager::unit) fn attach_idle_pipe(&self, idle: &Rc<ExecIdlePipe>) {
+        self.data.attach_idle_pipe(idle);
+    }
+
     pub(in crate::manager::unit) fn has_stop_job(&self, unit: &Rc<UnitX>) -> bool {
         match self.data.get_suspends(unit) {
             Some(_) => true,
@@ -118,6 +126,10 @@ impl JobManager {
         }
     }
 
+    pub(in crate::manager::unit) fn has_restart_job(&self, unit: &Rc<UnitX>) -> bool {
+        self.data.has_restart_job(unit)
+    }
+
     fn try_enable(&self) {
         // prepare for async-running
         if self.data.is_jobs_ready() {
@@ -195,6 +207,12 @@ struct JobManagerData {
 
     // statistics
     stat: JobStat,
+
+    // the processes of Type=idle are waiting for the job queue being empty
+    idle_pipe: RefCell<Option<Rc<ExecIdlePipe>>>,
+
+    // the start jobs triggered by OnFailure=/OnSuccess=, whose units export $MONITOR_*
+    monitors: RefCell<HashSet<u32>>,
 }
 
 // the declaration "pub(self)" is for identification only.
@@ -212,9 +230,17 @@ impl JobManagerData {
             text: RefCell::new(None),
 
             stat: JobStat::new(),
+
+            idle_pipe: RefCell::new(None),
+
+            monitors: RefCell::new(HashSet::new()),
         }
     }
 
+    pub(self) fn attach_idle_pipe(&self, idle: &Rc<ExecIdlePipe>) {
+        *self.idle_pipe.borrow_mut() = Some(Rc::clone(idle));
+    }
+
     pub(self) fn exec(
         &self,
         config: &JobConf,
@@ -235,6 +261,7 @@ impl JobManagerData {
 
         // clear stage
         self.stage.clear();
+        self.end_monitors(&del_jobs);
 
         // update statistics
         self.stat
@@ -293,6 +320,8 @@ impl JobManagerData {
                 break;
             }
         }
+
+        self.try_close_idle_pipe();
     }
 
     pub(self) fn try_finish(
@@ -315,6 +344,7 @@ impl JobManagerData {
         } else {
             // (asynchronous)finish not in context
             self.do_try_finish(unit, os, ns, flags); // do it
+            self.try_close_idle_pipe();
         }
 
         Ok(())
@@ -337,6 +367,7 @@ impl JobManagerData {
         }
 
         self.do_remove(&job_info.unwrap(), JobResult::JobCancelled, true);
+        self.try_close_idle_pipe();
         Ok(())
     }
 
@@ -352,9 +383,29 @@ impl JobManagerData {
         self.jobs.get_suspend(unit, JobKind::JobStop)
     }
 
+    pub(self) fn has_restart_job(&self, unit: &Rc<UnitX>) -> bool {
+        // the restart job runs as a stop job first, and then as a start job.
+        match self.jobs.get_trigger_info(unit) {
+            Some((trigger, _)) => trigger.kind == JobKind::JobRestart,
+            None => false,
+        }
+    }
+
+    fn try_close_idle_pipe(&self) {
+        if !self.jobs.is_empty() {
+            return;
+        }
+
+        if let Some(idle) = &*self.idle_pipe.borrow() {
+            idle.close();
+        }
+    }
+
     fn remove_unit(&self, unit: &UnitX) {
         // delete related jobs
         let (del_trigger, del_suspends) = self.jobs.remove_unit(unit);
+        self.end_monitors(&del_suspends);
+        self.end_monitors(&del_trigger.iter().cloned().collect::<Vec<_>>());
 
         // update statistics
         self.stat.update_change(&(&None, &del_trigger, &None));
@@ -398,6 +449,12 @@ impl JobManagerData {
 
         // start on previous result
         self.unit_start_on(unit, os, ns, flags);
+
+        // Upholds=
+        self.unit_start_upheld(unit, ns, flags);
+
+        // StopWhenUnneeded=
+        self.unit_stop_unneeded(unit, ns);
     }
 
     fn do_remove(&self, job_info: &JobInfo, result: JobResult, force: bool) {
@@ -408,6 +465,14 @@ impl JobManagerData {
             self.del_suspends(job_info, result);
         }
 
+        // the reboot is cancelled, RebootArgument= must not be passed to the next reboot.
+        if job_info.kind == JobKind::JobStart
+            && result != JobResult::JobDone
+            && UnitEmergencyAction::Reboot.target() == Some(job_info.unit.get_id())
+        {
+            remove_reboot_argument();
+        }
+
         // simulate and notify unit events, which are not generated by the unit.
         self.simulate_unit_notify(&job_info.unit, result, force);
     }
@@ -426,6 +491,8 @@ impl JobManagerData {
                 job_info.run_kind,
             ),
         };
+        self.end_monitors(&del_trigger.iter().cloned().collect::<Vec<_>>());
+        self.end_monitors(&remove_jobs);
 
         // update statistics
         self.stat.update_change(&(&None, &del_trigger, &None));
@@ -458,6 +525,7 @@ impl JobManagerData {
                 job_info.run_kind,
             ));
         }
+        self.end_monitors(&del_jobs);
 
         // update statistics
         self.stat
@@ -473,7 +541,9 @@ impl JobManagerData {
             ) => self.do_notify(&JobConf::new(Rc::clone(unit), JobKind::JobStart), None),
             (
                 UnitActiveState::UnitActive | UnitActiveState::UnitActivating,
-                UnitActiveState::UnitInActive | UnitActiveState::UnitDeActivating,
+                UnitActiveState::UnitInActive
+                | UnitActiveState::UnitFailed
+                | UnitActiveState::UnitDeActivating,
             ) => self.do_notify(&JobConf::new(Rc::clone(unit), JobKind::JobStop), None),
             _ => {} // do nothing
         }
@@ -484,22 +554,36 @@ impl JobManagerData {
         if !force {
             // is forced removement a failure?
             if result != JobResult::JobDone {
-                if let JobMode::JobFail = unit
+                let mode = unit
                     .get_config()
                     .config_data()
                     .borrow()
                     .Unit
-                    .OnFailureJobMode
-                {
-                    self.exec_on(
-                        Rc::clone(unit),
-                        UnitRelationAtom::UnitAtomOnFailure,
-                        JobMode::JobFail,
-                    );
-                }
+                    .OnFailureJobMode;
+                self.exec_on(Rc::clone(unit), UnitRelationAtom::UnitAtomOnFailure, mode);
             }
         }
 
+        // StartLimitAction= and JobTimeoutAction=
+        if unit.start_limit_hit() {
+            let action = unit
+                .get_config()
+                .config_data()
+                .borrow()
+                .Unit
+                .StartLimitAction;
+            unit.emergency_action(action, "hit the start limit");
+        }
+        if result == JobResult::JobTimeOut {
+            let action = unit
+                .get_config()
+                .config_data()
+                .borrow()
+                .Unit
+                .JobTimeoutAction;
+            unit.emergency_action(action, "timed out");
+        }
+
         // trigger-notify
         for other in self
             .db
@@ -520,27 +604,23 @@ impl JobManagerData {
         if ns != os && !flags.intersects(UnitNotifyFlags::UNIT_NOTIFY_WILL_AUTO_RESTART) {
             match ns {
                 UnitActiveState::UnitFailed => {
-                    if let JobMode::JobFail = unit
+                    let mode = unit
                         .get_config()
                         .config_data()
                         .borrow()
                         .Unit
-                        .OnFailureJobMode
-                    {
-                        self.exec_on(
-                            Rc::clone(unit),
-                            UnitRelationAtom::UnitAtomOnFailure,
-                            JobMode::JobFail,
-                        );
-                    }
+                        .OnFailureJobMode;
+                    self.exec_on(Rc::clone(unit), UnitRelationAtom::UnitAtomOnFailure, mode);
+
+                    // FailureAction=
+                    let action = unit.get_config().config_data().borrow().Unit.FailureAction;
+                    unit.emergency_action(action, "failed");
                 }
                 _ => {}
             };
         }
 
         // OnSuccess=
-        // if ns == UnitActiveState::UnitInActive
-        //     && flags & UnitNotifyFlags::UNIT_NOTIFY_WILL_AUTO_RESTART == 0
         if ns == UnitActiveState::UnitInActive
             && !flags.intersects(UnitNotifyFlags::UNIT_NOTIFY_WILL_AUTO_RESTART)
         {
@@ -549,29 +629,140 @@ impl JobManagerData {
                 | UnitActiveState::UnitInActive
                 | UnitActiveState::UnitMaintenance => {}
                 _ => {
-                    if let JobMode::JobFail = unit
+                    let mode = unit
                         .get_config()
                         .config_data()
                         .borrow()
                         .Unit
-                        .OnFailureJobMode
-                    {
-                        self.exec_on(
-                            Rc::clone(unit),
-                            UnitRelationAtom::UnitAtomOnSuccess,
-                            JobMode::JobFail,
-                        );
-                    }
+                        .OnSuccessJobMode;
+                    self.exec_on(Rc::clone(unit), UnitRelationAtom::UnitAtomOnSuccess, mode);
+
+                    // SuccessAction=
+                    let action = unit.get_config().config_data().borrow().Unit.SuccessAction;
+                    unit.emergency_action(action, "succeeded");
                 }
             };
         }
     }
 
+    fn unit_start_upheld(&self, unit: &Rc<UnitX>, ns: UnitActiveState, flags: UnitNotifyFlags) {
+        match ns {
+            // the unit is gone, start it again if it is still upheld by some active unit.
+            UnitActiveState::UnitInActive | UnitActiveState::UnitFailed => {
+                if flags.intersects(UnitNotifyFlags::UNIT_NOTIFY_WILL_AUTO_RESTART)
+                    || !self.jobs.is_unit_empty(unit)
+                {
+                    return;
+                }
+
+                let upheld = self
+                    .db
+                    .dep_gets_atom(unit, UnitRelationAtom::UnitAtomStartSteadily)
+                    .iter()
+                    .any(|other| unit_is_active_or_activating(other));
+                if upheld {
+                    self.exec_upheld(unit);
+                }
+            }
+            // the unit is up, start the units upheld by it which are not running.
+            UnitActiveState::UnitActive => {
+                for other in self
+                    .db
+                    .dep_gets_atom(unit, UnitRelationAtom::UnitAtomAddStartWhenUpheldQueue)
+                    .iter()
+                {
+                    if unit_is_inactive_or_failed(other) && self.jobs.is_unit_empty(other) {
+                        self.exec_upheld(other);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn exec_upheld(&self, unit: &Rc<UnitX>) {
+        log::debug!("starting unit {} which is upheld", unit.get_id());
+        let config = JobConf::new(Rc::clone(unit), JobKind::JobStart);
+        if let Err(e) = self.exec(&config, JobMode::JobReplace, &mut JobAffect::new(false)) {
+            log::debug!("failed to start upheld unit {}: {:?}", unit.get_id(), e);
+        }
+    }
+
+    fn unit_stop_unneeded(&self, unit: &Rc<UnitX>, ns: UnitActiveState) {
+        match ns {
+            // the units needed by this one may be unneeded now.
+            UnitActiveState::UnitInActive | UnitActiveState::UnitFailed => {
+                for other in self
+                    .db
+                    .dep_gets_atom(unit, UnitRelationAtom::UnitAtomAddStopWhenUnneededQueue)
+                    .iter()
+                {
+                    self.try_stop_unneeded(other);
+                }
+            }
+            UnitActiveState::UnitActive => self.try_stop_unneeded(unit),
+            _ => {}
+        }
+    }
+
+    fn try_stop_unneeded(&self, unit: &Rc<UnitX>) {
+        if !unit
+            .get_config()
+            .config_data()
+            .borrow()
+            .Unit
+            .StopWhenUnneeded
+        {
+            return;
+        }
+
+        if unit_is_inactive_or_failed(unit) || !self.jobs.is_unit_empty(unit) {
+            return;
+        }
+
+        // the unit is still needed by someone which is active or going to be.
+        for other in self
+            .db
+            .dep_gets_atom(unit, UnitRelationAtom::UnitAtomPinsStopWhenUnneeded)
+            .iter()
+        {
+            if !unit_is_inactive_or_failed(other) || !self.jobs.is_unit_empty(other) {
+                return;
+            }
+        }
+
+        log::debug!("stopping unit {} which is unneeded", unit.get_id());
+        let config = JobConf::new(Rc::clone(unit), JobKind::JobStop);
+        if let Err(e) = self.exec(&config, JobMode::JobFail, &mut JobAffect::new(false)) {
+            log::debug!("failed to stop unneeded unit {}: {:?}", unit.get_id(), e);
+        }
+    }
+
     fn exec_on(&self, unit: Rc<UnitX>, atom: UnitRelationAtom, mode: JobMode) {
-        let (configs, mode) = job_notify::job_notify_result(&self.db, unit, atom, mode);
+        let (configs, mode) = job_notify::job_notify_result(&self.db, Rc::clone(&unit), atom, mode);
         for config in configs.iter() {
-            if let Err(_e) = self.exec(config, mode, &mut JobAffect::new(false)) {
-                // debug
+            if let Err(e) = self.exec(config, mode, &mut JobAffect::new(false)) {
+                log::debug!(
+                    "failed to start {} triggered by {}: {:?}",
+                    config.get_unit().get_id(),
+                    unit.get_id(),
+                    e
+                );
+                continue;
+            }
+
+            // $MONITOR_* are exported to the processes of the triggered start job only.
+            if let Some(job) = self.jobs.get_suspend(config.get_unit(), JobKind::JobStart) {
+                config.get_unit().set_monitor(&unit);
+                self.monitors.borrow_mut().insert(job.id);
+            }
+        }
+    }
+
+    fn end_monitors(&self, jobs: &[Rc<Job>]) {
+        for job in jobs.iter() {
+            if self.monitors.borrow_mut().remove(&job.get_id()) {
+                job.get_unit().clear_monitor();
             }
         }
     }
@@ -586,6 +777,23 @@ impl JobManagerData {
     }
 }
 
+fn unit_is_active_or_activating(unit: &UnitX) -> bool {
+    IN_SET!(
+        unit.active_state(),
+        UnitActiveState::UnitActive,
+        UnitActiveState::UnitActivating,
+        UnitActiveState::UnitReloading
+    )
+}
+
+fn unit_is_inactive_or_failed(unit: &UnitX) -> bool {
+    IN_SET!(
+        unit.active_state(),
+        UnitActiveState::UnitInActive,
+        UnitActiveState::UnitFailed
+    )
+}
+
 fn jobs_2_jobinfo(jobs: &Vec<Rc<Job>>) -> Vec<JobInfo> {
     jobs.iter().map(|jr| JobInfo::map(jr)).collect::<Vec<_>>()
 }
@@ -759,12 +967,345 @@ mod tests {
         assert_eq!(jm.data.jobs.ready_len(), 0);
     }
 
+    #[test]
+    fn job_exec_on_monitor() {
+        let event = Rc::new(Events::new().unwrap());
+        let db = Rc::new(UnitDb::new());
+        let name_test1 = String::from("test1.service");
+        let unit_test1 = create_unit(&name_test1);
+        let name_test2 = String::from("test2.service");
+        let unit_test2 = create_unit(&name_test2);
+        db.units_insert(name_test1.clone(), Rc::clone(&unit_test1));
+        db.units_insert(name_test2.clone(), Rc::clone(&unit_test2));
+        db.dep_insert(
+            Rc::clone(&unit_test1),
+            UnitRelations::UnitOnFailure,
+            Rc::clone(&unit_test2),
+            true,
+            0,
+        )
+        .unwrap();
+        let jm = JobManager::new(&db, &event);
+
+        // the handler gets $MONITOR_* with the start job triggered
+        jm.data.exec_on(
+            Rc::clone(&unit_test1),
+            UnitRelationAtom::UnitAtomOnFailure,
+            JobMode::JobReplace,
+        );
+        let job_info = jm
+            .data
+            .jobs
+            .get_suspend(&unit_test2, JobKind::JobStart)
+            .unwrap();
+        assert_eq!(
+            unit_test2.monitor_envs().get("MONITOR_UNIT"),
+            Some(&name_test1)
+        );
+
+        // and loses them once the job is over
+        jm.remove(job_info.id).unwrap();
+        assert_eq!(jm.data.jobs.len(), 0);
+        assert!(unit_test2.monitor_envs().is_empty());
+        assert!(jm.data.monitors.borrow().is_empty());
+    }
+
+    #[test]
+    fn job_upholds_restart() {
+        let event = Rc::new(Events::new().unwrap());
+        let db = Rc::new(UnitDb::new());
+        let name_test1 = String::from("base.target");
+        let unit_test1 = create_unit_with_type(&name_test1, UnitType::UnitTarget);
+        let name_test2 = String::from("testsunit.target");
+        let unit_test2 = create_unit_with_type(&name_test2, UnitType::UnitTarget);
+        db.units_insert(name_test1.clone(), Rc::clone(&unit_test1));
+        db.units_insert(name_test2.clone(), Rc::clone(&unit_test2));
+        db.dep_insert(
+            Rc::clone(&unit_test1),
+            UnitRelations::UnitUpHolds,
+            Rc::clone(&unit_test2),
+            true,
+            0,
+        )
+        .unwrap();
+        let jm = JobManager::new(&db, &event);
+        unit_test1.load().unwrap();
+        unit_test2.load().unwrap();
+
+        // nobody upholds the unit: it stays down
+        jm.data.do_try_finish(
+            &unit_test2,
+            UnitActiveState::UnitActive,
+            UnitActiveState::UnitInActive,
+            UnitNotifyFlags::empty(),
+        );
+        assert_eq!(jm.data.jobs.len(), 0);
+
+        // the active unit upholds it: it is started again
+        unit_test1.start().unwrap();
+        assert_eq!(unit_test1.active_state(), UnitActiveState::UnitActive);
+        jm.data.do_try_finish(
+            &unit_test2,
+            UnitActiveState::UnitActive,
+            UnitActiveState::UnitInActive,
+            UnitNotifyFlags::empty(),
+        );
+        assert!(jm
+            .data
+            .jobs
+            .get_suspend(&unit_test2, JobKind::JobStart)
+            .is_some());
+    }
+
+    #[test]
+    fn job_stop_when_unneeded() {
+        let event = Rc::new(Events::new().unwrap());
+        let db = Rc::new(UnitDb::new());
+        let name_test1 = String::from("base.target");
+        let unit_test1 = create_unit_with_type(&name_test1, UnitType::UnitTarget);
+        let name_test2 = String::from("testsunit.target");
+        let unit_test2 = create_unit_with_type(&name_test2, UnitType::UnitTarget);
+        db.units_insert(name_test1.clone(), Rc::clone(&unit_test1));
+        db.units_insert(name_test2.clone(), Rc::clone(&unit_test2));
+        db.dep_insert(
+            Rc::clone(&unit_test1),
+            UnitRelations::UnitRequires,
+            Rc::clone(&unit_test2),
+            true,
+            0,
+        )
+        .unwrap();
+        let jm = JobManager::new(&db, &event);
+        unit_test1.load().unwrap();
+        unit_test2.load().unwrap();
+        unit_test2
+            .get_config()
+            .config_data()
+            .borrow_mut()
+            .Unit
+            .StopWhenUnneeded = true;
+
+        // the unit is still needed by the active one
+        unit_test1.start().unwrap();
+        unit_test2.start().unwrap();
+        jm.data.do_try_finish(
+            &unit_test2,
+            UnitActiveState::UnitActivating,
+            UnitActiveState::UnitActive,
+            UnitNotifyFlags::empty(),
+        );
+        assert_eq!(jm.data.jobs.len(), 0);
+
+        // the only dependent is gone, the unit is stopped
+        unit_test1.stop().unwrap();
+        jm.data.do_try_finish(
+            &unit_test1,
+            UnitActiveState::UnitActive,
+            UnitActiveState::UnitInActive,
+            UnitNotifyFlags::empty(),
+        );
+        assert!(jm
+            .data
+            .jobs
+            .get_suspend(&unit_test2, JobKind::JobStop)
+            .is_some());
+    }
+
+    #[test]
+    fn job_requisite_inactive() {
+        let event = Rc::new(Events::new().unwrap());
+        let db = Rc::new(UnitDb::new());
+        let name_test1 = String::from("base.target");
+        let unit_test1 = create_unit_with_type(&name_test1, UnitType::UnitTarget);
+        let name_test2 = String::from("testsunit.target");
+        let unit_test2 = create_unit_with_type(&name_test2, UnitType::UnitTarget);
+        db.units_insert(name_test1.clone(), Rc::clone(&unit_test1));
+        db.units_insert(name_test2.clone(), Rc::clone(&unit_test2));
+        for relation in [UnitRelations::UnitRequisite, UnitRelations::UnitAfter] {
+            db.dep_insert(
+                Rc::clone(&unit_test1),
+                relation,
+                Rc::clone(&unit_test2),
+                true,
+                0,
+            )
+            .unwrap();
+        }
+        let jm = JobManager::new(&db, &event);
+        unit_test1.load().unwrap();
+        unit_test2.load().unwrap();
+
+        let mut affect = JobAffect::new(true);
+        jm.exec(
+            &JobConf::new(Rc::clone(&unit_test1), JobKind::JobStart),
+            JobMode::JobReplace,
+            &mut affect,
+        )
+        .unwrap();
+        assert_eq!(affect.adds.len(), 2);
+        let verify = jm
+            .data
+            .jobs
+            .get_suspend(&unit_test2, JobKind::JobVerify)
+            .unwrap();
+        assert_eq!(verify.run_kind, JobKind::JobVerify);
+
+        // the verification of the inactive unit fails, and so does the start job requiring it
+        assert_eq!(
+            job_entry::job_process_unit(
+                JobKind::JobVerify,
+                UnitActiveState::UnitInActive,
+                UnitNotifyFlags::empty()
+            ),
+            (Some(JobResult::JobSkipped), false)
+        );
+        jm.data.run();
+        assert_eq!(jm.data.jobs.len(), 0);
+        assert_eq!(unit_test1.active_state(), UnitActiveState::UnitInActive);
+    }
+
+    #[test]
+    fn job_notify_propagate_reload() {
+        let event = Rc::new(Events::new().unwrap());
+        let db = Rc::new(UnitDb::new());
+        let name_test1 = String::from("test1.service");
+        let unit_test1 = create_unit(&name_test1);
+        let name_test2 = String::from("test2.service");
+        let unit_test2 = create_unit(&name_test2);
+        db.units_insert(name_test1.clone(), Rc::clone(&unit_test1));
+        db.units_insert(name_test2.clone(), Rc::clone(&unit_test2));
+        db.dep_insert(
+            Rc::clone(&unit_test1),
+            UnitRelations::UnitPropagatesReloadTo,
+            Rc::clone(&unit_test2),
+            true,
+            0,
+        )
+        .unwrap();
+        let jm = JobManager::new(&db, &event);
+
+        // only the reload can be propagated
+        assert!(jm
+            .notify(
+                &JobConf::new(Rc::clone(&unit_test1), JobKind::JobStart),
+                JobMode::JobFail
+            )
+            .is_err());
+        assert_eq!(jm.data.jobs.len(), 0);
+
+        // the reload of the unit queues a try-reload job of the unit it propagates to,
+        // which collapses to a nop job as the unit is inactive
+        jm.notify(
+            &JobConf::new(Rc::clone(&unit_test1), JobKind::JobReload),
+            JobMode::JobFail,
+        )
+        .unwrap();
+        assert_eq!(jm.data.jobs.len(), 1);
+        assert!(jm
+            .data
+            .jobs
+            .get_suspend(&unit_test2, JobKind::JobNop)
+            .is_some());
+        assert!(jm
+            .data
+            .jobs
+            .get_suspend(&unit_test1, JobKind::JobReload)
+            .is_none());
+    }
+
+    #[test]
+    fn job_isolate_affect() {
+        let event = Rc::new(Events::new().unwrap());
+        let db = Rc::new(UnitDb::new());
+        let name_test1 = String::from("base.target");
+        let unit_test1 = create_unit_with_type(&name_test1, UnitType::UnitTarget);
+        let name_test2 = String::from("testsunit.target");
+        let unit_test2 = create_unit_with_type(&name_test2, UnitType::UnitTarget);
+        let name_test3 = String::from("test1.service");
+        let unit_test3 = create_unit(&name_test3);
+        db.units_insert(name_test1.clone(), Rc::clone(&unit_test1));
+        db.units_insert(name_test2.clone(), Rc::clone(&unit_test2));
+        db.units_insert(name_test3.clone(), Rc::clone(&unit_test3));
+        let jm = JobManager::new(&db, &event);
+        unit_test1.load().unwrap();
+        unit_test2.load().unwrap();
+        unit_test2.start().unwrap();
+        unit_test2
+            .get_config()
+            .config_data()
+            .borrow_mut()
+            .Unit
+            .IgnoreOnIsolate = true;
+
+        // a unit without AllowIsolate= can not be isolated
+        assert_eq!(
+            jm.exec(
+                &JobConf::new(Rc::clone(&unit_test1), JobKind::JobStart),
+                JobMode::JobIsolate,
+                &mut JobAffect::new(false),
+            ),
+            Err(JobErrno::JobErrInput)
+        );
+        unit_test1
+            .get_config()
+            .config_data()
+            .borrow_mut()
+            .Unit
+            .AllowIsolate = true;
+
+        // the active unit with IgnoreOnIsolate= is left running
+        jm.exec(
+            &JobConf::new(Rc::clone(&unit_test1), JobKind::JobStart),
+            JobMode::JobIsolate,
+            &mut JobAffect::new(false),
+        )
+        .unwrap();
+        assert!(jm
+            .data
+            .jobs
+            .get_suspend(&unit_test2, JobKind::JobStop)
+            .is_none());
+        jm.data.run();
+        assert_eq!(unit_test1.active_state(), UnitActiveState::UnitActive);
+        assert_eq!(unit_test2.active_state(), UnitActiveState::UnitActive);
+
+        // the other active units are stopped, and the inactive ones are not touched
+        unit_test2
+            .get_config()
+            .config_data()
+            .borrow_mut()
+            .Unit
+            .IgnoreOnIsolate = false;
+        jm.exec(
+            &JobConf::new(Rc::clone(&unit_test1), JobKind::JobStart),
+            JobMode::JobIsolate,
+            &mut JobAffect::new(false),
+        )
+        .unwrap();
+        assert!(jm
+            .data
+            .jobs
+            .get_suspend(&unit_test2, JobKind::JobStop)
+            .is_some());
+        assert!(jm
+            .data
+            .jobs
+            .get_suspend(&unit_test3, JobKind::JobStop)
+            .is_none());
+        jm.data.run();
+        assert_eq!(unit_test2.active_state(), UnitActiveState::UnitInActive);
+    }
+
     fn create_unit(name: &str) -> Rc<UnitX> {
+        create_unit_with_type(name, UnitType::UnitService)
+    }
+
+    fn create_unit_with_type(name: &str, unit_type: UnitType) -> Rc<UnitX> {
         logger::init_log_with_console("test_unit_load", 4);
         log::info!("test");
         let dm = Rc::new(DataManager::new());
         let file = Rc::new(UnitFile::new());
-        let unit_type = UnitType::UnitService;
+        file.build_name_map(name.to_string(), false);
         let plugins = Plugin::get_instance();
         let subclass = plugins.create_unit_obj(unit_type).unwrap();
         Rc::new(UnitX::new(
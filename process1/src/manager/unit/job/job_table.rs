@@ -693,7 +693,7 @@ impl JobUnitTableData {
     }
 
     pub(self) fn is_unit_empty(&self, unit: &UnitX) -> bool {
-        self.t_data.contains_key(unit)
+        !self.t_data.contains_key(unit)
     }
 
     pub(self) fn is_suspends_conflict(&self) -> bool {
@@ -917,6 +917,9 @@ impl JobUnitTableData {
     fn try_gc_empty_unit(&mut self, entry: &(&Rc<UnitX>, &Rc<JobUnit>)) {
         let (u, uv) = entry;
         if uv.is_empty() {
+            self.readys_backfill(); // the entry could be waiting in 'readys' to be triggered
+
+            // data
             self.t_data.remove(*u);
             self.t_ready.remove(*u);
         }
@@ -3,11 +3,13 @@ use super::job_alloc::JobAlloc;
 use super::job_entry::{self, Job, JobConf, JobKind, JobResult};
 use super::job_table::JobTable;
 use super::JobErrno;
+use crate::manager::data::UnitActiveState;
 use crate::manager::unit::unit_base::UnitActionError;
 use crate::manager::unit::unit_base::{JobMode, UnitRelationAtom};
 use crate::manager::unit::unit_datastore::UnitDb;
 use crate::manager::unit::unit_entry::UnitX;
 use std::rc::Rc;
+use utils::IN_SET;
 
 pub(super) fn job_trans_expand(
     stage: &JobTable,
@@ -308,6 +310,15 @@ fn trans_affect_isolate(
             continue;
         }
 
+        // there is nothing to stop
+        if IN_SET!(
+            other.active_state(),
+            UnitActiveState::UnitInActive,
+            UnitActiveState::UnitFailed
+        ) {
+            continue;
+        }
+
         // isolate(stop)
         if let Err(_err) = job_trans_expand(
             stage,
@@ -1,8 +1,12 @@
 use std::{collections::HashMap, path::Path};
 
-pub use execute::{ExecCmdError, ExecCommand, ExecContext, ExecFlags, ExecParameters};
+pub use execute::{
+    ExecCmdError, ExecCommand, ExecContext, ExecDirectory, ExecDirectoryType, ExecFlags,
+    ExecNamespace, ExecParameters, ExecPreserveMode,
+};
 pub use unit_base::{
-    KillOperation, UnitActionError, UnitDependencyMask, UnitRelationAtom, UnitType,
+    KillContext, KillMode, KillOperation, UnitActionError, UnitDependencyMask, UnitRelationAtom,
+    UnitType,
 };
 pub use unit_entry::{Unit, UnitObj, UnitRef};
 pub(super) use unit_manager::UnitManagerX;
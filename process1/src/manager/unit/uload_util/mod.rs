@@ -1,4 +1,8 @@
-pub(super) use unit_file::UnitFile;
+pub(super) use unit_file::{UnitFile, DEFAULT_TARGET};
 
-// dependency: {unit_file | unit_parser_mgr}
+// dependency: unit_specifier -> unit_merge -> {unit_install | unit_preset} -> {unit_file | unit_parser_mgr}
 mod unit_file;
+mod unit_install;
+mod unit_merge;
+mod unit_preset;
+mod unit_specifier;
@@ -1,11 +1,50 @@
+use super::unit_install::{self, InstallInfo};
+use super::unit_merge::unit_file_merge;
+use super::unit_preset::{PresetAction, UnitPresets};
+use crate::manager::unit::unit_base;
 use siphasher::sip::SipHasher24;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::hash::Hasher;
 use std::path::{Path, PathBuf};
-use utils::path_lookup::LookupPaths;
-use utils::{path_lookup, time_util};
+use utils::path_lookup::{LookupPaths, ETC_SYSTEM_PATH, RUN_SYSTEM_PATH};
+use utils::{path_lookup, time_util, unit_name};
+
+// the max length of an alias symlink chain
+const UNIT_ALIAS_MAX_DEPTH: usize = 8;
+
+// the target started on boot, which is an alias of the real one
+pub(in crate::manager::unit) const DEFAULT_TARGET: &str = "default.target";
+
+// the units to land on when booting fails, written to /run unless they are provided
+const BUILTIN_UNITS: [(&str, &str); 4] = [
+    (
+        "emergency.target",
+        "[Unit]\nDescription=\"Emergency Mode\"\nRequires=\"emergency.service\"\n\
+         After=\"emergency.service\"\nAllowIsolate=true\n",
+    ),
+    (
+        "emergency.service",
+        "[Unit]\nDescription=\"Emergency Shell\"\nDefaultDependencies=false\n\
+         Conflicts=\"shutdown.target rescue.service\"\nBefore=\"shutdown.target\"\n\n\
+         [Service]\nType=\"idle\"\nExecStart=\"/usr/lib/process1/sulogin emergency\"\n\
+         ExecStopPost=\"/usr/bin/pctrl start default.target\"\n",
+    ),
+    (
+        "rescue.target",
+        "[Unit]\nDescription=\"Rescue Mode\"\nRequires=\"rescue.service\"\n\
+         After=\"rescue.service\"\nAllowIsolate=true\n",
+    ),
+    (
+        "rescue.service",
+        "[Unit]\nDescription=\"Rescue Shell\"\nDefaultDependencies=false\n\
+         Conflicts=\"shutdown.target emergency.service\"\nBefore=\"shutdown.target\"\n\n\
+         [Service]\nType=\"idle\"\nExecStart=\"/usr/lib/process1/sulogin rescue\"\n\
+         ExecStopPost=\"/usr/bin/pctrl start default.target\"\n",
+    ),
+];
 
 pub struct UnitFile {
     data: RefCell<UnitFileData>,
@@ -29,23 +68,198 @@ impl UnitFile {
             .clone()
     }
 
+    // the fragment and drop-ins the effective config of the unit comes from
+    pub fn get_unit_id_source_pathbuf(&self, name: &String) -> Vec<PathBuf> {
+        self.data.borrow().get_unit_id_source_pathbuf(name)
+    }
+
     pub fn get_unit_id_dropin_wants(&self, name: &String) -> Vec<PathBuf> {
         self.data.borrow().get_unit_id_dropin_wants(name)
     }
 
+    // return the name of the unit file that {name} is an alias of, or {name} itself
+    pub fn unit_name_resolve(&self, name: &str) -> String {
+        self.data.borrow().resolve_name(name)
+    }
+
+    // create the install symlinks of the unit {name} and its Also= units, return the created ones
+    pub fn enable_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+        let data = self.data.borrow();
+        let infos = data.install_infos(name)?;
+        if infos.iter().all(|i| i.is_empty()) {
+            log::warn!("unit {} has no [Install] settings, nothing to enable", name);
+        }
+
+        let mut changes = Vec::new();
+        for info in infos {
+            changes.append(&mut info.enable(config_path(runtime))?);
+        }
+        Ok(changes)
+    }
+
+    // remove the install symlinks of the unit {name} and its Also= units, return the removed ones
+    pub fn disable_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+        let data = self.data.borrow();
+        let mut changes = Vec::new();
+        for info in data.install_infos(name)? {
+            changes.append(&mut info.disable(config_path(runtime))?);
+        }
+        Ok(changes)
+    }
+
+    // the unit {name} and the units pulled in by its Also= recursively
+    pub fn install_units(&self, name: &str) -> Vec<String> {
+        match self.data.borrow().install_infos(name) {
+            Ok(infos) => infos.iter().map(|i| i.name().to_string()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    // link the unit {name} to /dev/null, which makes it unloadable
+    pub fn mask_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+        if !unit_name::unit_name_is_valid(name) {
+            return Err(format!("invalid unit name {}", name));
+        }
+        unit_install::mask_unit(config_path(runtime), name)
+    }
+
+    pub fn unmask_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+        unit_install::unmask_unit(config_path(runtime), name)
+    }
+
+    pub fn unit_masked(&self, name: &str) -> bool {
+        self.data.borrow().unit_masked(name)
+    }
+
+    // enable or disable the unit {name} according to the preset files
+    pub fn preset_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+        let presets = self.data.borrow().presets();
+        match presets.query(name) {
+            PresetAction::Enable => self.enable_unit(name, runtime),
+            PresetAction::Disable => self.disable_unit(name, runtime),
+        }
+    }
+
+    // apply the preset files to all the unit files, a failed unit does not stop the others
+    pub fn preset_all_units(&self, runtime: bool) -> Vec<PathBuf> {
+        let (presets, names) = {
+            let data = self.data.borrow();
+            (data.presets(), data.unit_file_names())
+        };
+
+        let mut changes = Vec::new();
+        for name in names {
+            if self.unit_masked(&name) {
+                continue;
+            }
+            let ret = match presets.query(&name) {
+                PresetAction::Enable => self.enable_unit(&name, runtime),
+                PresetAction::Disable => self.disable_unit(&name, runtime),
+            };
+            match ret {
+                Ok(mut c) => changes.append(&mut c),
+                Err(e) => log::warn!("failed to preset unit {}: {}", name, e),
+            }
+        }
+        changes
+    }
+
+    // the target that default.target is an alias of, None if there is no unit file for it
+    pub fn get_default_target(&self) -> Option<String> {
+        self.data.borrow().get_default_target()
+    }
+
+    // make default.target an alias of the target {name}, return the changed symlink
+    pub fn set_default_target(&self, name: &str) -> Result<Vec<PathBuf>, String> {
+        self.data
+            .borrow()
+            .set_default_target(name, Path::new(ETC_SYSTEM_PATH))
+    }
+
+    // the Alias= names of the unit {name}
+    pub fn unit_aliases(&self, name: &str) -> Vec<String> {
+        match self.data.borrow().install_info(name) {
+            Ok(info) => info.aliases().clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+
     pub fn get_unit_id_dropin_requires(&self, name: &String) -> Vec<PathBuf> {
         self.data.borrow().get_unit_id_dropin_requires(name)
     }
+
+    // check whether there is a unit file for the unit {name} itself
+    pub fn unit_file_exists(&self, name: &str) -> bool {
+        self.data.borrow().search_fragment_file(name).is_some()
+    }
+
+    // write the unit file of the unit {name} created at runtime, such as a scope
+    pub fn write_transient_unit(&self, name: &str, content: &str) -> Result<PathBuf, String> {
+        let dir = PathBuf::from(&self.data.borrow().lookup_path.transient);
+        fs::create_dir_all(&dir).map_err(|e| format!("failed to create {:?}: {}", dir, e))?;
+
+        let path = dir.join(name);
+        fs::write(&path, content).map_err(|e| format!("failed to write {:?}: {}", path, e))?;
+        self.data
+            .borrow_mut()
+            .transient_units
+            .insert(name.to_string());
+        Ok(path)
+    }
+
+    // write the built-in units to /run, the ones in the unit paths take precedence
+    pub fn write_builtin_units(&self) {
+        for (name, content) in BUILTIN_UNITS.iter() {
+            if self.unit_file_exists(name) {
+                continue;
+            }
+
+            let path = Path::new(RUN_SYSTEM_PATH).join(name);
+            match fs::create_dir_all(RUN_SYSTEM_PATH).and_then(|_| fs::write(&path, content)) {
+                Ok(_) => log::debug!("write built-in unit {:?}", path),
+                Err(e) => log::warn!("failed to write built-in unit {:?}: {}", path, e),
+            }
+        }
+    }
+
+    // remove the unit file of the transient unit {name}, and the one merged from it. the
+    // transient path is searched for the units too, only the files written above are removed.
+    pub fn remove_transient_unit(&self, name: &str) {
+        if !self.data.borrow_mut().transient_units.remove(name) {
+            return;
+        }
+
+        let dir = PathBuf::from(&self.data.borrow().lookup_path.transient);
+        for path in [dir.join(name), dir.join(format!("{}.toml", name))] {
+            match fs::remove_file(&path) {
+                Ok(_) => log::debug!("remove transient unit file {:?}", path),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => log::warn!("failed to remove {:?}: {}", path, e),
+            }
+        }
+    }
+}
+
+// the install symlinks are created in /run with --runtime, and gone after reboot
+fn config_path(runtime: bool) -> &'static Path {
+    if runtime {
+        Path::new(RUN_SYSTEM_PATH)
+    } else {
+        Path::new(ETC_SYSTEM_PATH)
+    }
 }
 
 #[derive(Debug)]
 struct UnitFileData {
     pub unit_id_fragment: HashMap<String, Vec<PathBuf>>,
+    pub unit_id_sources: HashMap<String, Vec<PathBuf>>,
     pub unit_id_dropin_wants: HashMap<String, Vec<PathBuf>>,
     pub unit_id_dropin_requires: HashMap<String, Vec<PathBuf>>,
     unit_name_map: HashMap<String, String>,
     lookup_path: LookupPaths,
     last_updated_timestamp_hash: u64,
+    // the units created by write_transient_unit
+    transient_units: HashSet<String>,
 }
 
 // the declaration "pub(self)" is for identification only.
@@ -55,11 +269,13 @@ impl UnitFileData {
         lookup_path.init_lookup_paths();
         UnitFileData {
             unit_id_fragment: HashMap::new(),
+            unit_id_sources: HashMap::new(),
             unit_id_dropin_wants: HashMap::new(),
             unit_id_dropin_requires: HashMap::new(),
             unit_name_map: HashMap::new(),
             lookup_path,
             last_updated_timestamp_hash: 0,
+            transient_units: HashSet::new(),
         }
     }
 
@@ -70,6 +286,13 @@ impl UnitFileData {
         }
     }
 
+    pub(self) fn get_unit_id_source_pathbuf(&self, name: &String) -> Vec<PathBuf> {
+        match self.unit_id_sources.get(name) {
+            Some(v) => v.to_vec(),
+            None => Vec::new(),
+        }
+    }
+
     pub(self) fn get_unit_id_dropin_wants(&self, name: &String) -> Vec<PathBuf> {
         match self.unit_id_dropin_wants.get(name) {
             Some(v) => v.to_vec(),
@@ -95,39 +318,241 @@ impl UnitFileData {
 
     pub fn build_id_fragment(&mut self, name: &String) {
         let mut pathbuf_fragment = Vec::new();
+        let mut sources = Vec::new();
+        if self.unit_masked(name) {
+            log::debug!("unit {} is masked", name);
+        } else if let Some(fragment) = self.search_unit_file(name) {
+            sources = self.unit_sources(name, &fragment);
+
+            // the fragment and drop-ins are merged into one file for the config loader
+            let to = fragment.with_file_name(format!("{}.toml", name));
+            match unit_file_merge(&sources, name) {
+                Ok(content) => match fs::write(&to, content) {
+                    Ok(_) => pathbuf_fragment.push(to),
+                    Err(e) => log::error!("failed to write unit file {:?}: {}", to, e),
+                },
+                Err(e) => log::error!("failed to merge unit file of {}: {}", name, e),
+            }
+        }
+
+        self.unit_id_fragment
+            .insert(name.to_string(), pathbuf_fragment);
+        self.unit_id_sources.insert(name.to_string(), sources);
+    }
+
+    // an instance falls back to its template if there is no unit file for itself
+    fn search_unit_file(&self, name: &str) -> Option<PathBuf> {
+        if let Some(fragment) = self.search_fragment_file(name) {
+            return Some(fragment);
+        }
+
+        let template = unit_name::unit_name_template(name)?;
+        self.search_fragment_file(&template)
+    }
+
+    // the fragment and drop-ins of the unit {name}, in merge order
+    fn unit_sources(&self, name: &str, fragment: &Path) -> Vec<PathBuf> {
+        let template = unit_name::unit_name_template(name);
+        let mut sources = vec![fragment.to_path_buf()];
+        sources.append(&mut self.search_dropin_files(name, template.as_deref()));
+        sources
+    }
+
+    // follow the alias symlinks of {name} to the name of the unit file
+    pub(self) fn resolve_name(&self, name: &str) -> String {
+        let mut id = name.to_string();
+        // the depth is limited to break symlink loops
+        for _ in 0..UNIT_ALIAS_MAX_DEPTH {
+            let link = match self.search_alias_link(&id) {
+                Some(l) => l,
+                None => break,
+            };
+            let target = match fs::read_link(&link) {
+                Ok(t) => t,
+                Err(_) => break,
+            };
+            let target_name = match target.file_name() {
+                Some(n) => n.to_string_lossy().to_string(),
+                None => break,
+            };
+
+            if target_name == id
+                || !unit_name::unit_name_is_valid(&target_name)
+                || unit_base::unit_name_to_type(&target_name) != unit_base::unit_name_to_type(&id)
+            {
+                break;
+            }
+            log::debug!("unit {} is an alias of {}", id, target_name);
+            id = target_name;
+        }
+        id
+    }
+
+    // the symlink in the directory with the highest priority, a regular unit file hides it
+    fn search_alias_link(&self, name: &str) -> Option<PathBuf> {
+        for v in self.lookup_path.search_path.iter().rev() {
+            let path = Path::new(v).join(name);
+            match fs::symlink_metadata(&path) {
+                Ok(m) if m.file_type().is_symlink() => return Some(path),
+                Ok(_) => return None,
+                Err(_) => continue,
+            }
+        }
+        None
+    }
+
+    // the unit {name} is masked if the file with the highest priority is a
+    // symlink to /dev/null, an instance is masked with its template as well.
+    pub(self) fn unit_masked(&self, name: &str) -> bool {
+        let mut names = vec![name.to_string()];
+        if let Some(template) = unit_name::unit_name_template(name) {
+            names.push(template);
+        }
+
+        names.iter().any(|n| {
+            for v in self.lookup_path.search_path.iter().rev() {
+                let path = Path::new(v).join(n);
+                if fs::symlink_metadata(&path).is_ok() {
+                    return unit_install::is_mask_link(&path);
+                }
+            }
+            false
+        })
+    }
+
+    pub(self) fn get_default_target(&self) -> Option<String> {
+        let name = self.resolve_name(DEFAULT_TARGET);
+        self.search_unit_file(&name)?;
+        Some(name)
+    }
+
+    // the default.target symlink is created in the directory {dir}
+    pub(self) fn set_default_target(&self, name: &str, dir: &Path) -> Result<Vec<PathBuf>, String> {
+        if unit_base::unit_name_to_type(name) != unit_base::UnitType::UnitTarget {
+            return Err(format!("{} is not a target", name));
+        }
+        let fragment = match self.search_unit_file(name) {
+            Some(f) => f,
+            None => return Err(format!("unit file of {} is not found", name)),
+        };
+
+        let link = dir.join(DEFAULT_TARGET);
+        if let Ok(dest) = fs::read_link(&link) {
+            if dest == fragment {
+                return Ok(Vec::new());
+            }
+            fs::remove_file(&link).map_err(|e| format!("failed to remove {:?}: {}", link, e))?;
+        }
+
+        unit_install::install_symlink(&fragment, &link)?;
+        Ok(vec![link])
+    }
+
+    pub(self) fn install_info(&self, name: &str) -> Result<InstallInfo, String> {
+        if self.unit_masked(name) {
+            return Err(format!("unit {} is masked", name));
+        }
+
+        let fragment = match self.search_unit_file(name) {
+            Some(f) => f,
+            None => return Err(format!("unit file of {} is not found", name)),
+        };
+
+        let content = unit_file_merge(&self.unit_sources(name, &fragment), name)?;
+        InstallInfo::new(name, &fragment, &content)
+    }
+
+    // the install info of the unit {name}, followed by the ones of its Also= units
+    pub(self) fn install_infos(&self, name: &str) -> Result<Vec<InstallInfo>, String> {
+        let mut infos = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = vec![name.to_string()];
+        while let Some(n) = queue.pop() {
+            if !visited.insert(n.clone()) {
+                continue;
+            }
+            let info = self.install_info(&n)?;
+            queue.extend(info.also().iter().rev().cloned());
+            infos.push(info);
+        }
+        Ok(infos)
+    }
+
+    pub(self) fn presets(&self) -> UnitPresets {
+        UnitPresets::load(&self.lookup_path.preset_path)
+    }
+
+    // the names of all the regular unit files in the search paths
+    pub(self) fn unit_file_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
         for v in &self.lookup_path.search_path {
+            let entries = match Path::new(v).read_dir() {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.path().is_file()
+                    && !entry.path().is_symlink()
+                    && unit_base::unit_name_to_type(&name) != unit_base::UnitType::UnitTypeInvalid
+                {
+                    names.insert(name);
+                }
+            }
+        }
+        names
+    }
+
+    // the fragment in the directory with the highest priority wins
+    fn search_fragment_file(&self, name: &str) -> Option<PathBuf> {
+        for v in self.lookup_path.search_path.iter().rev() {
             if let Err(_e) = fs::metadata(v) {
                 continue;
             }
-            let path = if v.ends_with("/") {
-                format!("{}{}", v, name)
-            } else {
-                format!("{}/{}", v, name)
-            };
-            let tmp = Path::new(&path);
+            let tmp = Path::new(v).join(name);
             if tmp.exists() && !tmp.is_symlink() {
-                let path = format!("{}.toml", tmp.to_string_lossy().to_string());
-                std::fs::copy(tmp, &path);
-                let to = Path::new(&path);
-                pathbuf_fragment.push(to.to_path_buf());
+                return Some(tmp);
             }
-            let pathd = format!("{}/{}.d", v, name);
-            let dir = Path::new(&pathd);
-            if dir.is_dir() {
-                for entry in dir.read_dir().unwrap() {
-                    let fragment = entry.unwrap().path();
-                    if fragment.is_file() {
-                        let path = format!("{}.toml", fragment.to_string_lossy().to_string());
-                        std::fs::copy(fragment, &path);
-                        let to = Path::new(&path);
-                        pathbuf_fragment.push(to.to_path_buf());
+        }
+        None
+    }
+
+    // collect the *.conf drop-ins of the unit type, the template and the unit
+    // itself, a drop-in masks the ones with the same file name in the
+    // directories of lower priority, and all of them are sorted by file name.
+    fn search_dropin_files(&self, name: &str, template: Option<&str>) -> Vec<PathBuf> {
+        let mut dirs = vec![format!("{}.d", name)];
+        if let Some(t) = template {
+            dirs.push(format!("{}.d", t));
+        }
+        if let Some((_, suffix)) = name.rsplit_once('.') {
+            dirs.push(format!("{}.d", suffix));
+        }
+
+        let mut dropins: HashMap<OsString, PathBuf> = HashMap::new();
+        for v in self.lookup_path.search_path.iter().rev() {
+            for d in &dirs {
+                let dir = Path::new(v).join(d);
+                let entries = match dir.read_dir() {
+                    Ok(e) => e,
+                    Err(_) => continue,
+                };
+                for entry in entries.filter_map(|e| e.ok()) {
+                    let path = entry.path();
+                    if !path.is_file() || path.extension() != Some(OsStr::new("conf")) {
+                        continue;
                     }
+                    dropins.entry(entry.file_name()).or_insert(path);
                 }
             }
         }
 
-        self.unit_id_fragment
-            .insert(name.to_string(), pathbuf_fragment);
+        let mut names: Vec<OsString> = dropins.keys().cloned().collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|n| dropins.remove(&n))
+            .collect()
     }
 
     pub fn build_id_dropin(&mut self, name: &String, suffix: String) {
@@ -185,3 +610,100 @@ impl UnitFileData {
         return path_updated;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{UnitFile, UnitFileData};
+    use std::fs;
+
+    #[test]
+    fn test_instance_falls_back_to_template() {
+        let dir = std::env::temp_dir().join(format!("unit_file_template_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("foo@.service"),
+            "[Unit]\nDescription = \"foo %i\"\n\n[Service]\nExecStart = \"/bin/echo %I\"\n",
+        )
+        .unwrap();
+
+        let mut data = UnitFileData::new();
+        data.lookup_path.search_path = vec![dir.to_string_lossy().to_string()];
+        data.build_id_fragment(&"foo@a\\x22b.service".to_string());
+
+        let fragment = data.get_unit_id_fragment_pathbuf(&"foo@a\\x22b.service".to_string());
+        assert_eq!(fragment, vec![dir.join("foo@a\\x22b.service.toml")]);
+        assert_eq!(
+            data.get_unit_id_source_pathbuf(&"foo@a\\x22b.service".to_string()),
+            vec![dir.join("foo@.service")]
+        );
+
+        let value: toml::Value =
+            toml::from_str(&fs::read_to_string(&fragment[0]).unwrap()).unwrap();
+        assert_eq!(value["Unit"]["Description"].as_str(), Some("foo a\\x22b"));
+        assert_eq!(
+            value["Service"]["ExecStart"].as_str(),
+            Some("/bin/echo a\"b")
+        );
+
+        data.build_id_fragment(&"bar@baz.service".to_string());
+        assert!(data
+            .get_unit_id_fragment_pathbuf(&"bar@baz.service".to_string())
+            .is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_default_target() {
+        let dir = std::env::temp_dir().join(format!("unit_file_default_{}", std::process::id()));
+        let lib = dir.join("lib");
+        let etc = dir.join("etc");
+        fs::create_dir_all(&lib).unwrap();
+        fs::create_dir_all(&etc).unwrap();
+        fs::write(lib.join("multi-user.target"), "[Unit]\n").unwrap();
+        fs::write(lib.join("foo.service"), "[Service]\n").unwrap();
+
+        let mut data = UnitFileData::new();
+        data.lookup_path.search_path = vec![
+            lib.to_string_lossy().to_string(),
+            etc.to_string_lossy().to_string(),
+        ];
+        assert_eq!(data.get_default_target(), None);
+
+        assert!(data.set_default_target("foo.service", &etc).is_err());
+        assert!(data.set_default_target("graphical.target", &etc).is_err());
+
+        let changes = data.set_default_target("multi-user.target", &etc).unwrap();
+        assert_eq!(changes, vec![etc.join("default.target")]);
+        assert_eq!(
+            data.get_default_target(),
+            Some("multi-user.target".to_string())
+        );
+        assert!(data
+            .set_default_target("multi-user.target", &etc)
+            .unwrap()
+            .is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_transient_unit() {
+        let dir = std::env::temp_dir().join(format!("unit_file_transient_{}", std::process::id()));
+        let file = UnitFile::new();
+        file.data.borrow_mut().lookup_path.transient = dir.to_string_lossy().to_string();
+
+        // the unit placed in the transient path by others is left alone
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("foo.service"), "[Service]\n").unwrap();
+        file.remove_transient_unit("foo.service");
+        assert!(dir.join("foo.service").exists());
+
+        let path = file.write_transient_unit("foo.scope", "[Scope]\n").unwrap();
+        assert!(path.exists());
+        file.remove_transient_unit("foo.scope");
+        assert!(!path.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
+use utils::{fs_util, unit_name};
+
+// a unit is masked by linking its name to this file
+const DEV_NULL: &str = "/dev/null";
+
+// the [Install] section of a unit file
+pub(super) struct InstallInfo {
+    name: String,
+    path: PathBuf,
+    aliases: Vec<String>,
+    wanted_by: Vec<String>,
+    required_by: Vec<String>,
+    also: Vec<String>,
+    default_instance: Option<String>,
+}
+
+impl InstallInfo {
+    // {content} is the merged unit file of the unit {name}, whose fragment is {path}
+    pub(super) fn new(name: &str, path: &Path, content: &str) -> Result<InstallInfo, String> {
+        let value: Value = toml::from_str(content).map_err(|e| e.to_string())?;
+        let install = value.get("Install");
+        let list = |key: &str| -> Vec<String> {
+            install
+                .and_then(|i| i.get(key))
+                .and_then(|v| v.as_str())
+                .map(|s| {
+                    s.split_terminator(';')
+                        .map(|i| i.trim().to_string())
+                        .filter(|i| !i.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let default_instance = install
+            .and_then(|i| i.get("DefaultInstance"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        Ok(InstallInfo {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            aliases: list("Alias"),
+            wanted_by: list("WantedBy"),
+            required_by: list("RequiredBy"),
+            also: list("Also"),
+            default_instance,
+        })
+    }
+
+    // the name of the symlinks in .wants/.requires, a template is installed as its DefaultInstance=
+    fn install_name(&self) -> Option<String> {
+        if !unit_name::unit_name_is_template(&self.name) {
+            return Some(self.name.clone());
+        }
+
+        let instance = self.default_instance.as_ref()?;
+        unit_name::unit_name_replace_instance(&self.name, instance)
+    }
+
+    fn alias_paths(&self, config_path: &Path) -> Result<Vec<PathBuf>, String> {
+        let suffix = self.name.rsplit_once('.').map(|(_, s)| s);
+        let mut paths = Vec::new();
+        for alias in &self.aliases {
+            if !unit_name::unit_name_is_valid(alias)
+                || alias.rsplit_once('.').map(|(_, s)| s) != suffix
+            {
+                return Err(format!("invalid alias {} of unit {}", alias, self.name));
+            }
+            paths.push(config_path.join(alias));
+        }
+        Ok(paths)
+    }
+
+    // the symlinks of Alias=, WantedBy= and RequiredBy= in {config_path}
+    fn link_paths(&self, config_path: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut paths = self.alias_paths(config_path)?;
+        if self.wanted_by.is_empty() && self.required_by.is_empty() {
+            return Ok(paths);
+        }
+
+        let install_name = match self.install_name() {
+            Some(n) => n,
+            None => {
+                log::warn!(
+                    "template unit {} has no DefaultInstance=, ignore WantedBy= and RequiredBy=",
+                    self.name
+                );
+                return Ok(paths);
+            }
+        };
+
+        for (targets, suffix) in [(&self.wanted_by, "wants"), (&self.required_by, "requires")] {
+            for target in targets {
+                if !unit_name::unit_name_is_valid(target) {
+                    return Err(format!("invalid unit name {} in [Install]", target));
+                }
+                paths.push(
+                    config_path
+                        .join(format!("{}.{}", target, suffix))
+                        .join(&install_name),
+                );
+            }
+        }
+        Ok(paths)
+    }
+
+    // create the install symlinks in {config_path}, return the created ones
+    pub(super) fn enable(&self, config_path: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut changes = Vec::new();
+        for link in self.link_paths(config_path)? {
+            if install_symlink(&self.path, &link)? {
+                changes.push(link);
+            }
+        }
+        Ok(changes)
+    }
+
+    // remove the install symlinks in {config_path}, return the removed ones
+    pub(super) fn disable(&self, config_path: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut changes = Vec::new();
+        for link in self.link_paths(config_path)? {
+            if remove_symlink(&self.path, &link)? {
+                changes.push(link);
+            }
+        }
+        Ok(changes)
+    }
+
+    pub(super) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(super) fn aliases(&self) -> &Vec<String> {
+        &self.aliases
+    }
+
+    pub(super) fn also(&self) -> &Vec<String> {
+        &self.also
+    }
+
+    // a unit without any [Install] settings can not be enabled
+    pub(super) fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+            && self.wanted_by.is_empty()
+            && self.required_by.is_empty()
+            && self.also.is_empty()
+    }
+}
+
+// mask the unit {name} by linking it to /dev/null in {config_path}
+pub(super) fn mask_unit(config_path: &Path, name: &str) -> Result<Vec<PathBuf>, String> {
+    let link = config_path.join(name);
+    match install_symlink(Path::new(DEV_NULL), &link)? {
+        true => Ok(vec![link]),
+        false => Ok(Vec::new()),
+    }
+}
+
+// remove the mask symlink of the unit {name} in {config_path}
+pub(super) fn unmask_unit(config_path: &Path, name: &str) -> Result<Vec<PathBuf>, String> {
+    let link = config_path.join(name);
+    match remove_symlink(Path::new(DEV_NULL), &link)? {
+        true => Ok(vec![link]),
+        false => Ok(Vec::new()),
+    }
+}
+
+// whether {path} is a symlink to /dev/null
+pub(super) fn is_mask_link(path: &Path) -> bool {
+    match fs::read_link(path) {
+        Ok(dest) => dest == Path::new(DEV_NULL),
+        Err(_) => false,
+    }
+}
+
+// create the symlink {link} pointing to {target}, return false if it is there already
+pub(super) fn install_symlink(target: &Path, link: &Path) -> Result<bool, String> {
+    if let Ok(dest) = fs::read_link(link) {
+        if dest == target {
+            return Ok(false);
+        }
+        return Err(format!("{:?} exists and points to {:?}", link, dest));
+    }
+
+    if link.exists() {
+        return Err(format!("{:?} exists and is not a symlink", link));
+    }
+
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("failed to create {:?}: {}", parent, e))?;
+    }
+
+    fs_util::symlink(&target.to_string_lossy(), &link.to_string_lossy(), false)
+        .map_err(|e| format!("failed to create symlink {:?}: {}", link, e))?;
+    log::info!("created symlink {:?} -> {:?}", link, target);
+    Ok(true)
+}
+
+// remove the symlink {link} if it points to {target}, return false if there is nothing to remove
+pub(super) fn remove_symlink(target: &Path, link: &Path) -> Result<bool, String> {
+    match fs::read_link(link) {
+        Ok(dest) if dest == target => {
+            fs::remove_file(link).map_err(|e| format!("failed to remove {:?}: {}", link, e))?;
+            log::info!("removed symlink {:?}", link);
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_mask_link, mask_unit, unmask_unit, InstallInfo};
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_install_alias() {
+        let dir = std::env::temp_dir().join(format!("unit_install_{}", std::process::id()));
+        let target = Path::new("/usr/lib/process1/foo.service");
+        let content = "[Install]\nAlias = \"bar.service;baz.service\"\n";
+        let info = InstallInfo::new("foo.service", target, content).unwrap();
+        assert_eq!(info.aliases().len(), 2);
+
+        let changes = info.enable(&dir).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(fs::read_link(dir.join("bar.service")).unwrap(), target);
+        assert!(info.enable(&dir).unwrap().is_empty());
+
+        let changes = info.disable(&dir).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert!(fs::symlink_metadata(dir.join("baz.service")).is_err());
+
+        let content = "[Install]\nAlias = \"bar.socket\"\n";
+        let info = InstallInfo::new("foo.service", target, content).unwrap();
+        assert!(info.enable(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_wanted_by() {
+        let dir = std::env::temp_dir().join(format!("unit_install_wants_{}", std::process::id()));
+        let target = Path::new("/usr/lib/process1/getty@.service");
+        let content = "[Install]\nWantedBy = \"multi-user.target\"\nRequiredBy = \"a.target\"\nAlso = \"b.service\"\nDefaultInstance = \"tty1\"\n";
+        let info = InstallInfo::new("getty@.service", target, content).unwrap();
+        assert_eq!(info.also(), &vec!["b.service".to_string()]);
+
+        let changes = info.enable(&dir).unwrap();
+        assert_eq!(changes.len(), 2);
+        let wants = dir.join("multi-user.target.wants/getty@tty1.service");
+        assert_eq!(fs::read_link(&wants).unwrap(), target);
+        assert!(dir
+            .join("a.target.requires/getty@tty1.service")
+            .is_symlink());
+
+        assert_eq!(info.disable(&dir).unwrap().len(), 2);
+        assert!(!wants.is_symlink());
+
+        let changes = mask_unit(&dir, "foo.service").unwrap();
+        assert_eq!(changes.len(), 1);
+        assert!(is_mask_link(&dir.join("foo.service")));
+        assert_eq!(unmask_unit(&dir, "foo.service").unwrap().len(), 1);
+        assert!(!is_mask_link(&dir.join("foo.service")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+use super::unit_specifier::unit_specifier_expand;
+use std::fs;
+use std::path::PathBuf;
+use toml::value::{Table, Value};
+
+// the list settings whose items are separated by ';'. the settings are parsed by the unit
+// plugins, every key deserialized with Vec::<String>::deserialize_with, by any unit type,
+// must be registered here, or a drop-in overrides it instead of appending to it.
+const LIST_SETTINGS: &[&str] = &[
+    "Wants",
+    "Requires",
+    "Requisite",
+    "BindsTo",
+    "PartOf",
+    "Upholds",
+    "Before",
+    "After",
+    "Conflicts",
+    "OnFailure",
+    "OnSuccess",
+    "JoinsNamespaceOf",
+    "PropagatesReloadTo",
+    "ReloadPropagatedFrom",
+    "RequiresMountsFor",
+    "WantedBy",
+    "RequiredBy",
+    "Alias",
+    "Also",
+    "Environment",
+    "EnvironmentFile",
+    "Sockets",
+    "RuntimeDirectory",
+    "StateDirectory",
+    "CacheDirectory",
+    "LogsDirectory",
+    "ConfigurationDirectory",
+    // socket
+    "Symlinks",
+    // timer
+    "OnCalendar",
+    // path
+    "PathExists",
+    "PathExistsGlob",
+    "PathChanged",
+    "PathModified",
+    "DirectoryNotEmpty",
+];
+
+const LIST_SETTING_PREFIXES: [&str; 3] = ["Exec", "Condition", "Assert"];
+
+// the list settings whose items are separated by white spaces
+const SPACE_LIST_SETTINGS: [&str; 3] = [
+    "SuccessExitStatus",
+    "RestartPreventExitStatus",
+    "RestartForceExitStatus",
+];
+
+fn list_separator(key: &str) -> Option<&'static str> {
+    if LIST_SETTINGS.contains(&key) || LIST_SETTING_PREFIXES.iter().any(|p| key.starts_with(p)) {
+        return Some(";");
+    }
+
+    if SPACE_LIST_SETTINGS.contains(&key) {
+        return Some(" ");
+    }
+
+    None
+}
+
+// merge the unit files in order, the later file overrides the single value
+// settings of the former ones, and appends to the list settings, an empty
+// list item resets the list. the specifiers are expanded for the unit {name}.
+pub(super) fn unit_file_merge(files: &[PathBuf], name: &str) -> Result<String, String> {
+    let mut merged = Table::new();
+    for file in files {
+        let content = fs::read_to_string(file)
+            .map_err(|e| format!("failed to read unit file {:?}: {}", file, e))?;
+        let table: Table = toml::from_str(&unit_specifier_expand(&content, name))
+            .map_err(|e| format!("failed to parse unit file {:?}: {}", file, e))?;
+
+        for (section, values) in table {
+            let values = match values {
+                Value::Table(t) => t,
+                _ => {
+                    log::warn!(
+                        "ignore setting {} outside of a section in {:?}",
+                        section,
+                        file
+                    );
+                    continue;
+                }
+            };
+
+            let entry = merged
+                .entry(section)
+                .or_insert_with(|| Value::Table(Table::new()));
+            if let Value::Table(section) = entry {
+                for (key, value) in values {
+                    merge_setting(section, key, value);
+                }
+            }
+        }
+    }
+
+    toml::to_string(&Value::Table(merged)).map_err(|e| e.to_string())
+}
+
+fn merge_setting(section: &mut Table, key: String, value: Value) {
+    let sep = match list_separator(&key) {
+        Some(s) => s,
+        None => {
+            section.insert(key, value);
+            return;
+        }
+    };
+
+    // a list setting could be assigned with a string or an array of strings
+    let items: Vec<String> = match value {
+        Value::String(s) => vec![s],
+        Value::Array(a) => a
+            .into_iter()
+            .map(|v| match v {
+                Value::String(s) => s,
+                v => v.to_string(),
+            })
+            .collect(),
+        v => {
+            section.insert(key, v);
+            return;
+        }
+    };
+
+    let mut list = match section.get(&key) {
+        Some(Value::String(s)) => s.clone(),
+        _ => String::new(),
+    };
+    for item in items {
+        if item.trim().is_empty() {
+            list.clear();
+        } else if list.is_empty() {
+            list = item;
+        } else {
+            list = format!("{}{}{}", list, sep, item);
+        }
+    }
+
+    section.insert(key, Value::String(list));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unit_file_merge;
+    use std::fs;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_unit_file_merge() {
+        let dir = std::env::temp_dir().join(format!("unit_merge_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fragment = dir.join("foo@.service");
+        let dropin1 = dir.join("10-a.conf");
+        let dropin2 = dir.join("20-b.conf");
+        fs::write(
+            &fragment,
+            "[Unit]\nDescription = \"%i\"\nAfter = \"a.service\"\n\n[Service]\nExecStart = \"/bin/a\"\nRestart = \"no\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &dropin1,
+            "[Unit]\nAfter = \"b.service\"\n\n[Service]\nExecStart = [\"\", \"/bin/b\"]\n",
+        )
+        .unwrap();
+        fs::write(&dropin2, "[Service]\nRestart = \"always\"\n").unwrap();
+
+        let files: Vec<PathBuf> = vec![fragment, dropin1, dropin2];
+        let merged = unit_file_merge(&files, "foo@bar.service").unwrap();
+        let value: toml::Value = toml::from_str(&merged).unwrap();
+        assert_eq!(value["Unit"]["Description"].as_str(), Some("bar"));
+        assert_eq!(value["Unit"]["After"].as_str(), Some("a.service;b.service"));
+        assert_eq!(value["Service"]["ExecStart"].as_str(), Some("/bin/b"));
+        assert_eq!(value["Service"]["Restart"].as_str(), Some("always"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unit_file_merge_socket() {
+        let dir = std::env::temp_dir().join(format!("unit_merge_socket_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fragment = dir.join("foo.socket");
+        let dropin = dir.join("10-a.conf");
+        fs::write(&fragment, "[Socket]\nSymlinks = \"/run/a\"\n").unwrap();
+        fs::write(&dropin, "[Socket]\nSymlinks = \"/run/b\"\n").unwrap();
+
+        let files: Vec<PathBuf> = vec![fragment, dropin];
+        let merged = unit_file_merge(&files, "foo.socket").unwrap();
+        let value: toml::Value = toml::from_str(&merged).unwrap();
+        assert_eq!(value["Socket"]["Symlinks"].as_str(), Some("/run/a;/run/b"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unit_file_merge_timer() {
+        let dir = std::env::temp_dir().join(format!("unit_merge_timer_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fragment = dir.join("foo.timer");
+        let dropin1 = dir.join("10-a.conf");
+        let dropin2 = dir.join("20-b.conf");
+        fs::write(
+            &fragment,
+            "[Timer]\nOnCalendar = \"daily\"\nPersistent = false\n",
+        )
+        .unwrap();
+        fs::write(&dropin1, "[Timer]\nOnCalendar = \"weekly\"\n").unwrap();
+
+        // the drop-in appends to the schedules of the fragment
+        let files: Vec<PathBuf> = vec![fragment.clone(), dropin1.clone()];
+        let merged = unit_file_merge(&files, "foo.timer").unwrap();
+        let value: toml::Value = toml::from_str(&merged).unwrap();
+        assert_eq!(value["Timer"]["OnCalendar"].as_str(), Some("daily;weekly"));
+        assert_eq!(value["Timer"]["Persistent"].as_bool(), Some(false));
+
+        // an empty OnCalendar= resets them
+        fs::write(
+            &dropin2,
+            "[Timer]\nOnCalendar = [\"\", \"*-*-* 04:00:00\"]\n",
+        )
+        .unwrap();
+        let files: Vec<PathBuf> = vec![fragment, dropin1, dropin2];
+        let merged = unit_file_merge(&files, "foo.timer").unwrap();
+        let value: toml::Value = toml::from_str(&merged).unwrap();
+        assert_eq!(
+            value["Timer"]["OnCalendar"].as_str(),
+            Some("*-*-* 04:00:00")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unit_file_merge_path() {
+        let dir = std::env::temp_dir().join(format!("unit_merge_path_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fragment = dir.join("foo.path");
+        let dropin1 = dir.join("10-a.conf");
+        let dropin2 = dir.join("20-b.conf");
+        fs::write(
+            &fragment,
+            "[Path]\nPathExists = \"/run/a\"\nPathChanged = \"/etc/a\"\nPathExistsGlob = \"/run/*.a\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &dropin1,
+            "[Path]\nPathExists = \"/run/b\"\nPathChanged = \"/etc/b\"\nPathModified = \"/etc/c\"\nDirectoryNotEmpty = \"/var/d\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &dropin2,
+            "[Path]\nPathExists = \"\"\nPathExistsGlob = [\"\", \"/run/*.b\"]\n",
+        )
+        .unwrap();
+
+        let files: Vec<PathBuf> = vec![fragment, dropin1, dropin2];
+        let merged = unit_file_merge(&files, "foo.path").unwrap();
+        let value: toml::Value = toml::from_str(&merged).unwrap();
+        // the drop-ins append to the watched paths
+        assert_eq!(value["Path"]["PathChanged"].as_str(), Some("/etc/a;/etc/b"));
+        assert_eq!(value["Path"]["PathModified"].as_str(), Some("/etc/c"));
+        assert_eq!(value["Path"]["DirectoryNotEmpty"].as_str(), Some("/var/d"));
+        // and an empty value resets them
+        assert_eq!(value["Path"]["PathExists"].as_str(), Some(""));
+        assert_eq!(value["Path"]["PathExistsGlob"].as_str(), Some("/run/*.b"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use utils::path_util;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PresetAction {
+    Enable,
+    Disable,
+}
+
+struct PresetRule {
+    action: PresetAction,
+    pattern: String,
+}
+
+// the rules of the *.preset files, the first matched one wins
+pub(super) struct UnitPresets {
+    rules: Vec<PresetRule>,
+}
+
+impl UnitPresets {
+    // read the *.preset files in {dirs}, which are ordered from low to high priority
+    pub(super) fn load(dirs: &[String]) -> UnitPresets {
+        let mut rules = Vec::new();
+        for file in preset_files(dirs) {
+            let content = match fs::read_to_string(&file) {
+                Ok(c) => c,
+                Err(e) => {
+                    log::warn!("failed to read preset file {:?}: {}", file, e);
+                    continue;
+                }
+            };
+
+            for (n, line) in content.lines().enumerate() {
+                match parse_rule(line) {
+                    Ok(Some(rule)) => rules.push(rule),
+                    Ok(None) => {}
+                    Err(e) => log::warn!("{:?}:{}: {}", file, n + 1, e),
+                }
+            }
+        }
+
+        UnitPresets { rules }
+    }
+
+    // the preset of the unit {name}, a unit is enabled if no rule matches it
+    pub(super) fn query(&self, name: &str) -> PresetAction {
+        for rule in &self.rules {
+            if path_util::fnmatch(&rule.pattern, name) {
+                log::debug!(
+                    "preset of {} is {:?} by {}",
+                    name,
+                    rule.action,
+                    rule.pattern
+                );
+                return rule.action;
+            }
+        }
+
+        PresetAction::Enable
+    }
+}
+
+fn parse_rule(line: &str) -> Result<Option<PresetRule>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+        return Ok(None);
+    }
+
+    let mut words = line.split_whitespace();
+    let action = match words.next() {
+        Some("enable") => PresetAction::Enable,
+        Some("disable") => PresetAction::Disable,
+        _ => return Err(format!("unknown preset rule: {}", line)),
+    };
+    let pattern = match words.next() {
+        Some(p) => p.to_string(),
+        None => return Err(format!("preset rule without a unit: {}", line)),
+    };
+
+    Ok(Some(PresetRule { action, pattern }))
+}
+
+// a preset file masks the ones with the same file name in the directories
+// of lower priority, and all of them are sorted by file name.
+fn preset_files(dirs: &[String]) -> Vec<PathBuf> {
+    let mut presets: HashMap<OsString, PathBuf> = HashMap::new();
+    for dir in dirs.iter().rev() {
+        let entries = match Path::new(dir).read_dir() {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() || path.extension() != Some(OsStr::new("preset")) {
+                continue;
+            }
+            presets.entry(entry.file_name()).or_insert(path);
+        }
+    }
+
+    let mut names: Vec<OsString> = presets.keys().cloned().collect();
+    names.sort();
+    names
+        .into_iter()
+        .filter_map(|n| presets.remove(&n))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PresetAction, UnitPresets};
+    use std::fs;
+
+    #[test]
+    fn test_unit_presets() {
+        let dir = std::env::temp_dir().join(format!("unit_preset_{}", std::process::id()));
+        let lib = dir.join("lib");
+        let etc = dir.join("etc");
+        fs::create_dir_all(&lib).unwrap();
+        fs::create_dir_all(&etc).unwrap();
+        fs::write(
+            lib.join("90-default.preset"),
+            "# default\nenable foo.service\ndisable *\n",
+        )
+        .unwrap();
+        fs::write(lib.join("10-a.preset"), "enable bar.service\n").unwrap();
+        fs::write(etc.join("10-a.preset"), "disable bar.service\n").unwrap();
+
+        let dirs = vec![
+            lib.to_string_lossy().to_string(),
+            etc.to_string_lossy().to_string(),
+        ];
+        let presets = UnitPresets::load(&dirs);
+        assert_eq!(presets.query("foo.service"), PresetAction::Enable);
+        assert_eq!(presets.query("bar.service"), PresetAction::Disable);
+        assert_eq!(presets.query("baz.service"), PresetAction::Disable);
+        assert_eq!(
+            UnitPresets::load(&[]).query("baz.service"),
+            PresetAction::Enable
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
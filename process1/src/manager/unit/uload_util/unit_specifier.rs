@@ -0,0 +1,104 @@
+use nix::unistd::{self, User};
+use std::fs;
+use utils::unit_name;
+
+// the runtime directory of the system manager
+const RUNTIME_DIR: &str = "/run";
+
+// expand the specifiers in the unit file content loaded as the unit {name},
+// the unknown specifiers are left untouched
+pub(super) fn unit_specifier_expand(content: &str, name: &str) -> String {
+    let mut ret = String::with_capacity(content.len());
+    let mut chars = content.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            ret.push(c);
+            continue;
+        }
+
+        let spec = match chars.next() {
+            Some(s) => s,
+            None => {
+                ret.push(c);
+                break;
+            }
+        };
+
+        match specifier_value(spec, name) {
+            Some(v) => ret.push_str(&toml_escape(&v)),
+            None => {
+                ret.push(c);
+                ret.push(spec);
+            }
+        }
+    }
+    ret
+}
+
+fn specifier_value(spec: char, name: &str) -> Option<String> {
+    let value = match spec {
+        '%' => "%".to_string(),
+        'n' => name.to_string(),
+        'N' => unit_name::unit_name_without_suffix(name).unwrap_or_default(),
+        'p' => unit_name::unit_name_to_prefix(name).unwrap_or_default(),
+        'i' => unit_name::unit_name_to_instance(name).unwrap_or_default(),
+        'I' => unit_name::unit_name_unescape(
+            &unit_name::unit_name_to_instance(name).unwrap_or_default(),
+        ),
+        'u' => manager_user().map(|u| u.name).unwrap_or_default(),
+        'h' => manager_user()
+            .map(|u| u.dir.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        't' => RUNTIME_DIR.to_string(),
+        'H' => fs::read_to_string("/proc/sys/kernel/hostname")
+            .map(|h| h.trim().to_string())
+            .unwrap_or_default(),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+// escape the specifier value for the toml basic string it is expanded into,
+// so a value with quotes or backslashes can not break the unit file syntax
+fn toml_escape(value: &str) -> String {
+    let mut ret = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\t' => ret.push_str("\\t"),
+            c if c.is_control() => ret.push_str(&format!("\\u{:04X}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+fn manager_user() -> Option<User> {
+    User::from_uid(unistd::getuid()).ok().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unit_specifier_expand;
+
+    #[test]
+    fn test_unit_specifier_expand() {
+        let name = "getty@tty1.service";
+        assert_eq!(
+            unit_specifier_expand("Description=\"%p on %i (%n, %N)\"", name),
+            "Description=\"getty on tty1 (getty@tty1.service, getty@tty1)\""
+        );
+        assert_eq!(
+            unit_specifier_expand("%t/%I %% %s", "foo@a-b.service"),
+            "/run/a/b % %s"
+        );
+        assert_eq!(unit_specifier_expand("100%", name), "100%");
+        assert_eq!(
+            unit_specifier_expand("ExecStart=\"/bin/echo %I\"", "foo@a\\x22b\\x5cc.service"),
+            "ExecStart=\"/bin/echo a\\\"b\\\\c\""
+        );
+    }
+}
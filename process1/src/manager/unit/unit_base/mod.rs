@@ -1,14 +1,17 @@
 #![allow(unused_imports)]
 pub(super) use ub_basic::unit_name_to_type;
-pub use ub_basic::{KillOperation, UnitActionError, UnitDependencyMask, UnitType};
+pub use ub_basic::{
+    KillContext, KillMode, KillOperation, UnitActionError, UnitDependencyMask, UnitType,
+};
 pub(super) use ub_job::JobMode;
 pub(super) use ub_load::UnitLoadState;
 pub(super) use ub_relation::unit_relation_to_inverse;
 pub use ub_relation_atom::UnitRelationAtom;
 pub(super) use ub_relation_atom::{unit_relation_from_unique_atom, unit_relation_to_atom};
 
-// dependency: ub_basic -> {ub_relation | ub_relation_atom} -> {ub_load | ub_job}
+// dependency: ub_basic -> {ub_relation | ub_relation_atom} -> {ub_load | ub_job | ub_emergency_action}
 mod ub_basic;
+mod ub_emergency_action;
 mod ub_job;
 mod ub_load;
 mod ub_relation;
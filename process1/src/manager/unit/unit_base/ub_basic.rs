@@ -2,7 +2,9 @@
 use crate::null_str;
 use core::fmt::{Display, Formatter, Result as FmtResult};
 use nix::sys::signal::Signal;
+use std::cell::RefCell;
 use std::{num::ParseIntError, str::FromStr};
+use utils::unit_name;
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone)]
 pub enum UnitType {
@@ -10,6 +12,13 @@ pub enum UnitType {
     UnitTarget,
     UnitSocket,
     UnitMount,
+    UnitTimer,
+    UnitPath,
+    UnitSlice,
+    UnitScope,
+    UnitSwap,
+    UnitAutomount,
+    UnitDevice,
     UnitTypeMax,
     UnitTypeInvalid,
     UnitTypeErrnoMax,
@@ -24,6 +33,13 @@ impl FromStr for UnitType {
             "Target" => UnitType::UnitTarget,
             "Socket" => UnitType::UnitSocket,
             "Mount" => UnitType::UnitMount,
+            "Timer" => UnitType::UnitTimer,
+            "Path" => UnitType::UnitPath,
+            "Slice" => UnitType::UnitSlice,
+            "Scope" => UnitType::UnitScope,
+            "Swap" => UnitType::UnitSwap,
+            "Automount" => UnitType::UnitAutomount,
+            "Device" => UnitType::UnitDevice,
             _ => UnitType::UnitTypeInvalid,
         };
         Ok(ret)
@@ -36,6 +52,13 @@ impl From<UnitType> for String {
             UnitType::UnitTarget => "target".into(),
             UnitType::UnitSocket => "socket".into(),
             UnitType::UnitMount => "mount".into(),
+            UnitType::UnitTimer => "timer".into(),
+            UnitType::UnitPath => "path".into(),
+            UnitType::UnitSlice => "slice".into(),
+            UnitType::UnitScope => "scope".into(),
+            UnitType::UnitSwap => "swap".into(),
+            UnitType::UnitAutomount => "automount".into(),
+            UnitType::UnitDevice => "device".into(),
             UnitType::UnitTypeMax => null_str!("").into(),
             UnitType::UnitTypeInvalid => null_str!("").into(),
             UnitType::UnitTypeErrnoMax => null_str!("").into(),
@@ -49,6 +72,13 @@ impl Display for UnitType {
             UnitType::UnitTarget => write!(f, "Target"),
             UnitType::UnitSocket => write!(f, "Socket"),
             UnitType::UnitMount => write!(f, "Mount"),
+            UnitType::UnitTimer => write!(f, "Timer"),
+            UnitType::UnitPath => write!(f, "Path"),
+            UnitType::UnitSlice => write!(f, "Slice"),
+            UnitType::UnitScope => write!(f, "Scope"),
+            UnitType::UnitSwap => write!(f, "Swap"),
+            UnitType::UnitAutomount => write!(f, "Automount"),
+            UnitType::UnitDevice => write!(f, "Device"),
             UnitType::UnitTypeMax => write!(f, "Max"),
             UnitType::UnitTypeInvalid => write!(f, ""),
             UnitType::UnitTypeErrnoMax => write!(f, ""),
@@ -73,12 +103,23 @@ pub enum UnitDependencyMask {
 // }
 
 pub(in crate::manager::unit) fn unit_name_to_type(unit_name: &str) -> UnitType {
+    if !unit_name::unit_name_is_valid(unit_name) {
+        return UnitType::UnitTypeInvalid;
+    }
+
     let words: Vec<&str> = unit_name.split(".").collect();
     match words[words.len() - 1] {
         "service" => UnitType::UnitService,
         "target" => UnitType::UnitTarget,
         "socket" => UnitType::UnitSocket,
         "mount" => UnitType::UnitMount,
+        "timer" => UnitType::UnitTimer,
+        "path" => UnitType::UnitPath,
+        "slice" => UnitType::UnitSlice,
+        "scope" => UnitType::UnitScope,
+        "swap" => UnitType::UnitSwap,
+        "automount" => UnitType::UnitAutomount,
+        "device" => UnitType::UnitDevice,
         _ => UnitType::UnitTypeInvalid,
     }
 }
@@ -105,8 +146,11 @@ pub enum UnitActionError {
     UnitActionEInval,
     UnitActionEBusy,
     UnitActionENoent,
+    UnitActionETimedOut,
+    UnitActionECanceled,
 }
 
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum KillOperation {
     KillTerminate,
     KillTerminateAndLog,
@@ -116,6 +160,115 @@ pub enum KillOperation {
     KillInvalid,
 }
 
+/// which processes of the unit are killed when stopping the unit.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum KillMode {
+    ControlGroup,
+    Process,
+    Mixed,
+    None,
+}
+
+impl Default for KillMode {
+    fn default() -> Self {
+        KillMode::ControlGroup
+    }
+}
+
+impl FromStr for KillMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "control-group" => Ok(KillMode::ControlGroup),
+            "process" => Ok(KillMode::Process),
+            "mixed" => Ok(KillMode::Mixed),
+            "none" => Ok(KillMode::None),
+            _ => Err(format!("invalid kill mode: {}", s)),
+        }
+    }
+}
+
+/// the kill settings of the unit: KillMode=, KillSignal=, SendSIGKILL=, ...
+pub struct KillContext {
+    kill_mode: RefCell<KillMode>,
+    kill_signal: RefCell<Signal>,
+    restart_kill_signal: RefCell<Option<Signal>>,
+    final_kill_signal: RefCell<Signal>,
+    send_sigkill: RefCell<bool>,
+    send_sighup: RefCell<bool>,
+}
+
+impl Default for KillContext {
+    fn default() -> Self {
+        KillContext::new()
+    }
+}
+
+impl KillContext {
+    pub fn new() -> KillContext {
+        KillContext {
+            kill_mode: RefCell::new(KillMode::ControlGroup),
+            kill_signal: RefCell::new(Signal::SIGTERM),
+            restart_kill_signal: RefCell::new(None),
+            final_kill_signal: RefCell::new(Signal::SIGKILL),
+            send_sigkill: RefCell::new(true),
+            send_sighup: RefCell::new(false),
+        }
+    }
+
+    pub fn set_kill_mode(&self, mode: KillMode) {
+        *self.kill_mode.borrow_mut() = mode;
+    }
+
+    pub fn kill_mode(&self) -> KillMode {
+        *self.kill_mode.borrow()
+    }
+
+    pub fn set_kill_signal(&self, sig: Signal) {
+        *self.kill_signal.borrow_mut() = sig;
+    }
+
+    pub fn set_restart_kill_signal(&self, sig: Option<Signal>) {
+        *self.restart_kill_signal.borrow_mut() = sig;
+    }
+
+    pub fn set_final_kill_signal(&self, sig: Signal) {
+        *self.final_kill_signal.borrow_mut() = sig;
+    }
+
+    pub fn set_send_sigkill(&self, send: bool) {
+        *self.send_sigkill.borrow_mut() = send;
+    }
+
+    pub fn send_sigkill(&self) -> bool {
+        *self.send_sigkill.borrow()
+    }
+
+    pub fn set_send_sighup(&self, send: bool) {
+        *self.send_sighup.borrow_mut() = send;
+    }
+
+    pub fn send_sighup(&self) -> bool {
+        *self.send_sighup.borrow()
+    }
+
+    /// the signal sent for the kill operation
+    pub fn signal(&self, ko: KillOperation) -> Signal {
+        match ko {
+            KillOperation::KillTerminate | KillOperation::KillTerminateAndLog => {
+                *self.kill_signal.borrow()
+            }
+            KillOperation::KillRestart => self
+                .restart_kill_signal
+                .borrow()
+                .unwrap_or(*self.kill_signal.borrow()),
+            KillOperation::KillKill => *self.final_kill_signal.borrow(),
+            _ => ko.to_signal(),
+        }
+    }
+}
+
 impl KillOperation {
     pub fn to_signal(&self) -> Signal {
         match *self {
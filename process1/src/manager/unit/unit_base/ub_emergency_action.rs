@@ -0,0 +1,23 @@
+#![warn(unused_imports)]
+
+use serde::{Deserialize, Deserializer};
+
+use crate::manager::data::UnitEmergencyAction;
+use crate::manager::unit::DeserializeWith;
+
+impl DeserializeWith for UnitEmergencyAction {
+    fn deserialize_with<'de, D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(de)?;
+
+        match s.parse() {
+            Ok(action) => Ok(action),
+            Err(e) => {
+                log::warn!("{}, ignore it", e);
+                Ok(UnitEmergencyAction::None)
+            }
+        }
+    }
+}
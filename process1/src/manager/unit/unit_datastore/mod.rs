@@ -41,6 +41,18 @@ impl UnitDb {
         self.units.get(name)
     }
 
+    pub(super) fn units_insert_alias(&self, alias: String, id: String) {
+        self.units.insert_alias(alias, id)
+    }
+
+    pub(super) fn units_remove_alias(&self, alias: &str) {
+        self.units.remove_alias(alias)
+    }
+
+    pub(super) fn units_get_aliases(&self, id: &str) -> Vec<String> {
+        self.units.get_aliases(id)
+    }
+
     pub(super) fn units_get_all(&self) -> Vec<Rc<UnitX>> {
         self.units.get_all()
     }
@@ -131,8 +131,11 @@ impl UnitChildData {
         self.watch_pids.borrow().get(&pid).map(|u| u.clone())
     }
 
-    fn remove_unit(&self, _unit: &UnitX) {
-        todo!();
+    // the pids of the unit removed are not watched any more
+    fn remove_unit(&self, unit: &UnitX) {
+        self.watch_pids
+            .borrow_mut()
+            .retain(|_, u| u.get_id() != unit.get_id());
     }
 }
 
@@ -461,6 +461,31 @@ mod tests {
         assert_eq!(value, true);
     }
 
+    #[test]
+    fn dep_joins_namespace_of() {
+        let sets = UnitSets::new();
+        let dep = UnitDep::new(&Rc::new(sets));
+        let unit_test1 = create_unit("test1.service");
+        let unit_test2 = create_unit("test2.service");
+        let atom = UnitRelationAtom::UnitAtomJoinsNameSpaceOf;
+
+        dep.insert(
+            Rc::clone(&unit_test1),
+            UnitRelations::UnitJoinsNameSpaceOf,
+            Rc::clone(&unit_test2),
+            true,
+            0,
+        )
+        .unwrap();
+
+        // JoinsNamespaceOf= is symmetric, either unit finds the other one
+        assert!(dep.is_dep_atom_with(&unit_test1, atom, &unit_test2));
+        assert!(dep.is_dep_atom_with(&unit_test2, atom, &unit_test1));
+        let units = dep.gets_atom(&unit_test2, atom);
+        assert_eq!(units.len(), 1);
+        assert!(contain_unit(&units, &unit_test1));
+    }
+
     fn create_unit(name: &str) -> Rc<UnitX> {
         logger::init_log_with_console("test_unit_load", 4);
         log::info!("test");
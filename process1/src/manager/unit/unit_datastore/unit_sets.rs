@@ -1,16 +1,19 @@
 use crate::manager::table::{Table, TableSubscribe};
 use crate::manager::unit::unit_entry::UnitX;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 pub(super) struct UnitSets {
     t: RefCell<Table<String, Rc<UnitX>>>,
+    aliases: RefCell<HashMap<String, String>>, // key: alias, value: id of the unit
 }
 
 impl UnitSets {
     pub(super) fn new() -> UnitSets {
         UnitSets {
             t: RefCell::new(Table::new()),
+            aliases: RefCell::new(HashMap::new()),
         }
     }
 
@@ -19,11 +22,43 @@ impl UnitSets {
     }
 
     pub(super) fn remove(&self, name: &str) -> Option<Rc<UnitX>> {
+        self.aliases.borrow_mut().retain(|_, id| id != name);
         self.t.borrow_mut().remove(&name.to_string())
     }
 
     pub(super) fn get(&self, name: &str) -> Option<Rc<UnitX>> {
-        self.t.borrow().get(&name.to_string()).cloned()
+        let t = self.t.borrow();
+        if let Some(unit) = t.get(&name.to_string()) {
+            return Some(Rc::clone(unit));
+        }
+
+        let aliases = self.aliases.borrow();
+        aliases.get(name).and_then(|id| t.get(id)).cloned()
+    }
+
+    // make {alias} another name of the unit {id}, the alias is not a key of the table,
+    // so the subscribers see one unit only.
+    pub(super) fn insert_alias(&self, alias: String, id: String) {
+        if alias == id {
+            return;
+        }
+        self.aliases.borrow_mut().insert(alias, id);
+    }
+
+    pub(super) fn remove_alias(&self, alias: &str) {
+        self.aliases.borrow_mut().remove(alias);
+    }
+
+    pub(super) fn get_aliases(&self, id: &str) -> Vec<String> {
+        let mut ret: Vec<String> = self
+            .aliases
+            .borrow()
+            .iter()
+            .filter(|(_, v)| *v == id)
+            .map(|(k, _)| k.clone())
+            .collect();
+        ret.sort();
+        ret
     }
 
     pub(super) fn get_all(&self) -> Vec<Rc<UnitX>> {
@@ -119,6 +154,25 @@ mod tests {
         assert!(Rc::ptr_eq(&value.unwrap(), &unit_test2));
     }
 
+    #[test]
+    fn sets_alias() {
+        let sets = UnitSets::new();
+        let name_test1 = String::from("test1.service");
+        let unit_test1 = create_unit(&name_test1);
+        let alias_test1 = String::from("alias1.service");
+
+        sets.insert(name_test1.clone(), Rc::clone(&unit_test1));
+        sets.insert_alias(alias_test1.clone(), name_test1.clone());
+        let value = sets.get(&alias_test1);
+        assert!(Rc::ptr_eq(&value.unwrap(), &unit_test1));
+        assert_eq!(sets.get_aliases(&name_test1), vec![alias_test1.clone()]);
+        assert_eq!(sets.get_all().len(), 1);
+
+        sets.remove(&name_test1);
+        assert!(sets.get(&alias_test1).is_none());
+        assert!(sets.get_aliases(&name_test1).is_empty());
+    }
+
     #[test]
     fn sets_getall() {
         let sets = UnitSets::new();
@@ -1,8 +1,9 @@
 pub use u_entry::{Unit, UnitObj, UnitRef};
 pub(in crate::manager) use uf_interface::UnitX;
+pub(super) use uu_config::unit_default_instance;
 // pub(super) use uu_config::UnitConfigItem;
 
-// dependency: {uu_config | uu_cgroup} -> {uu_load | uu_child} -> u_entry -> uf_interface
+// dependency: {uu_config | uu_cgroup | uu_ns | uu_start_limit} -> {uu_load | uu_child} -> u_entry -> uf_interface
 mod u_entry;
 mod uf_interface;
 mod uu_cgroup;
@@ -10,3 +11,5 @@ mod uu_child;
 mod uu_condition;
 mod uu_config;
 mod uu_load;
+mod uu_ns;
+mod uu_start_limit;
@@ -1,28 +1,37 @@
 use super::uu_cgroup::UeCgroup;
 use super::uu_child::UeChild;
-use super::uu_condition::{
-    UeCondition, ASSERT_PATH_EXISTS, CONDITION_FILE_NOT_EMPTY, CONDITION_NEEDS_UPDATE,
-    CONDITION_PATH_EXISTS,
-};
+use super::uu_condition::UeCondition;
 use super::uu_config::UeConfig;
 use super::uu_load::UeLoad;
-use crate::manager::data::{DataManager, UnitActiveState, UnitDepConf, UnitState};
+use super::uu_ns::UeNamespace;
+use super::uu_start_limit::UeStartLimit;
+use crate::manager::data::{
+    DataManager, EmergencyAction, UnitActiveState, UnitDepConf, UnitEmergencyAction, UnitState,
+};
+use crate::manager::unit::execute::{self, ExecContext, ExecNsStorage};
 use crate::manager::unit::uload_util::UnitFile;
-use crate::manager::unit::unit_base::{KillOperation, UnitActionError, UnitLoadState, UnitType};
+use crate::manager::unit::unit_base::{
+    KillContext, KillMode, KillOperation, UnitActionError, UnitLoadState, UnitType,
+};
 use crate::manager::{UnitNotifyFlags, UnitRelations};
+use crate::plugin::Plugin;
 use cgroup::{self, CgFlags};
 use log;
+use nix::errno::Errno;
+use nix::sched::CloneFlags;
 use nix::sys::signal::Signal;
 use nix::sys::socket::UnixCredentials;
 use nix::unistd::Pid;
 use nix::NixPath;
+use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use utils::Result;
+use std::time::{Duration, Instant};
+use utils::{mount_util, process_util, unit_name, Result, IN_SET};
 
 use utils::error::Error as ServiceError;
 
@@ -66,7 +75,10 @@ pub struct Unit {
     load: UeLoad,
     child: UeChild,
     cgroup: UeCgroup,
+    ns: UeNamespace,
     conditions: Rc<UeCondition>,
+    monitor: RefCell<HashMap<String, String>>,
+    start_limit: UeStartLimit,
     sub: Box<dyn UnitObj>,
 }
 
@@ -119,9 +131,27 @@ pub trait UnitObj {
         Vec::new()
     }
 
+    /// the details of the result, which are exported to the OnFailure=/OnSuccess= handlers,
+    /// such as $MONITOR_SERVICE_RESULT.
+    fn monitor_envs(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
     fn current_active_state(&self) -> UnitActiveState;
     fn attach_unit(&self, unit: Rc<Unit>);
 
+    /// the unit {other}, which is triggered by this one, has changed its state,
+    /// such as the service of a timer.
+    fn trigger(&self, _other: Rc<Unit>) {}
+
+    /// the mount point of the mount unit is found in or gone from /proc/self/mountinfo,
+    /// which may be mounted or unmounted by the unit itself or by others.
+    fn mountinfo_update(&self, _mounted: bool) {}
+
+    /// the device of the device unit is plugged or unplugged, which is announced by
+    /// the uevents tagged with process1.
+    fn device_update(&self, _found: bool) {}
+
     fn notify_message(
         &self,
         _ucred: &UnixCredentials,
@@ -149,7 +179,10 @@ impl Unit {
             load: UeLoad::new(dmr, filer, &_config, String::from(name)),
             child: UeChild::new(),
             cgroup: UeCgroup::new(),
+            ns: UeNamespace::new(),
             conditions: Rc::new(UeCondition::new()),
+            monitor: RefCell::new(HashMap::new()),
+            start_limit: UeStartLimit::new(),
             sub,
         }
     }
@@ -158,61 +191,22 @@ impl Unit {
         let flag = self.conditions.init_flag();
         if flag != 0 {
             return Rc::clone(&self.conditions);
-        } else {
-            //need to reconstruct the code, expose the config detail out is wrong
-            let add_condition = |condop: &str, _params: &str| {
-                if _params.is_empty() {
-                    return;
-                }
-                self.conditions.add_condition(condop, String::from(_params));
-            };
-
-            let add_assert = |assert_op: &str, _params: &str| {
-                if _params.is_empty() {
-                    return;
-                }
-                self.conditions.add_assert(assert_op, String::from(_params));
-            };
-            add_condition(
-                CONDITION_FILE_NOT_EMPTY,
-                self.get_config()
-                    .config_data()
-                    .borrow()
-                    .Unit
-                    .ConditionFileNotEmpty
-                    .as_str(),
-            );
-
-            add_condition(
-                CONDITION_NEEDS_UPDATE,
-                self.get_config()
-                    .config_data()
-                    .borrow()
-                    .Unit
-                    .ConditionNeedsUpdate
-                    .as_str(),
-            );
-
-            add_condition(
-                CONDITION_PATH_EXISTS,
-                self.get_config()
-                    .config_data()
-                    .borrow()
-                    .Unit
-                    .ConditionPathExists
-                    .as_str(),
-            );
+        }
 
-            add_assert(
-                ASSERT_PATH_EXISTS,
-                self.get_config()
-                    .config_data()
-                    .borrow()
-                    .Unit
-                    .AssertPathExists
-                    .as_str(),
-            );
+        let config = self.get_config().config_data();
+        let unit = &config.borrow().Unit;
+        for (c_type, params) in unit.conditions() {
+            for param in params {
+                self.conditions.add_condition(c_type, param.clone());
+            }
         }
+        for (c_type, params) in unit.asserts() {
+            for param in params {
+                self.conditions.add_assert(c_type, param.clone());
+            }
+        }
+        self.conditions.set_init_flag(1);
+
         Rc::clone(&self.conditions)
     }
 
@@ -233,79 +227,250 @@ impl Unit {
         self.dm.insert_unit_state(self.id.clone(), u_state);
     }
 
+    /// request the emergency action on the system, which is taken by the manager,
+    /// the graceful ones start the shutdown jobs and the others reboot at once.
+    pub(super) fn emergency_action(&self, action: UnitEmergencyAction, reason: &str) {
+        if action == UnitEmergencyAction::None {
+            return;
+        }
+
+        log::warn!(
+            "unit {} {}, executing the emergency action {:?}",
+            self.id,
+            reason,
+            action
+        );
+        let reboot_argument = self
+            .get_config()
+            .config_data()
+            .borrow()
+            .Unit
+            .RebootArgument
+            .clone();
+        self.dm.insert_emergency_action(
+            self.id.clone(),
+            EmergencyAction::new(action, reboot_argument),
+        );
+    }
+
+    // StartLimitIntervalSec= and StartLimitBurst=, StartLimitAction= is taken when the failed job is finished
+    fn start_limit_test(&self) -> bool {
+        let config = self.get_config().config_data();
+        let (interval, burst) = {
+            let unit = &config.borrow().Unit;
+            (
+                Duration::from_secs(unit.StartLimitIntervalSec),
+                unit.StartLimitBurst,
+            )
+        };
+
+        if self.start_limit.test(interval, burst, Instant::now()) {
+            return true;
+        }
+        log::error!(
+            "unit {} is started too quickly, refuse to start it",
+            self.id
+        );
+        false
+    }
+
+    /// the last start is refused by the start limit, which is reported once
+    pub(super) fn start_limit_hit(&self) -> bool {
+        self.start_limit.take_hit()
+    }
+
     pub fn get_id(&self) -> &str {
         &self.id
     }
 
-    pub fn prepare_exec(&self) -> Result<()> {
+    pub fn prepare_exec(&self, ctx: &ExecContext) -> Result<()> {
         log::debug!("prepare exec cgroup");
+        self.cg_realize()?;
+
+        log::debug!("prepare exec directories");
+        execute::setup_exec_directories(ctx)
+    }
+
+    /// create the cgroup of the unit, which is nested in the cgroups of its slices.
+    pub fn cg_realize(&self) -> Result<()> {
         self.cgroup.setup_cg_path(&self.id);
 
         self.cgroup.prepare_cg_exec()
     }
 
+    /// put the unit into the slice, the unit requires the slice and is ordered after it.
+    pub fn set_slice(&self, slice: &str) -> Result<(), Box<dyn Error>> {
+        if !unit_name::slice_name_is_valid(slice) {
+            return Err(format!("invalid slice {} of unit {}", slice, self.id).into());
+        }
+        if slice == self.id {
+            return Err(format!("the slice {} can not be put into itself", slice).into());
+        }
+
+        log::debug!("put unit {} into slice {}", self.id, slice);
+        self.cgroup.set_slice(slice);
+        self.insert_dep(UnitRelations::UnitInSlice, slice.to_string());
+        self.insert_two_deps(
+            UnitRelations::UnitAfter,
+            UnitRelations::UnitRequires,
+            slice.to_string(),
+        );
+        Ok(())
+    }
+
+    pub fn slice(&self) -> Option<String> {
+        self.cgroup.slice()
+    }
+
+    /// the slice which the unit is put into without Slice=, the instances of a template
+    /// share a slice named after the template, such as system-getty.slice.
+    pub fn default_slice(&self) -> String {
+        match unit_name::unit_name_to_prefix(&self.id) {
+            Some(prefix) if unit_name::unit_name_is_instance(&self.id) => {
+                format!("system-{}.slice", prefix.replace('-', "\\x2d"))
+            }
+            _ => "system.slice".to_string(),
+        }
+    }
+
+    /// clean up the runtime resources of the executed processes when the unit is stopped.
+    pub fn destroy_runtime_data(&self, ctx: &ExecContext, restarting: bool) {
+        execute::destroy_runtime_directory(ctx, restarting);
+    }
+
     pub fn cg_path(&self) -> PathBuf {
         self.cgroup.cg_path()
     }
 
+    /// check whether the process is in the cgroup of the unit.
+    pub fn pid_in_cgroup(&self, pid: Pid) -> bool {
+        let cg_path = self.cg_path();
+        if cg_path.is_empty() {
+            return false;
+        }
+
+        cgroup::cg_get_pids(&cg_path).contains(&pid)
+    }
+
+    /// guess the main pid from the processes in the cgroup of the unit, the only process whose
+    /// parent is process1 is considered as the main pid.
+    pub fn guess_main_pid(&self) -> Option<Pid> {
+        let cg_path = self.cg_path();
+        if cg_path.is_empty() {
+            return None;
+        }
+
+        let mypid = nix::unistd::getpid();
+        let mut main_pid = None;
+        for pid in cgroup::cg_get_pids(&cg_path) {
+            if pid == mypid {
+                continue;
+            }
+
+            match process_util::get_parent_pid(pid) {
+                Ok(ppid) if ppid == mypid => {}
+                _ => continue,
+            }
+
+            // more than one daemon, can not decide which one is the main pid.
+            if main_pid.is_some() {
+                return None;
+            }
+            main_pid = Some(pid);
+        }
+
+        main_pid
+    }
+
+    pub(in crate::manager::unit) fn ns_storage(
+        &self,
+        nstype: CloneFlags,
+    ) -> Result<Rc<ExecNsStorage>, Errno> {
+        self.ns.storage(nstype)
+    }
+
+    /// share the namespaces with the unit configured in JoinsNamespaceOf=
+    pub(in crate::manager::unit) fn join_namespace_of(&self, other: &Unit) {
+        self.ns.join(&other.ns);
+    }
+
+    /// kill the processes of the unit according to the kill context, return whether there is
+    /// any process killed which should be waited for.
     pub fn kill_context(
         &self,
+        k_context: &KillContext,
         m_pid: Option<Pid>,
         c_pid: Option<Pid>,
         ko: KillOperation,
-    ) -> Result<(), Box<dyn Error>> {
-        let sig = ko.to_signal();
-        if m_pid.is_some() {
-            match nix::sys::signal::kill(m_pid.unwrap(), sig) {
-                Ok(_) => {
-                    if sig != Signal::SIGCONT && sig != Signal::SIGKILL {
-                        match nix::sys::signal::kill(m_pid.unwrap(), Signal::SIGCONT) {
-                            Ok(_) => {}
-                            Err(e) => {
-                                log::debug!("kill pid {} errno: {}", m_pid.unwrap(), e)
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::warn!("Failed to kill main service: error: {}", e);
-                }
+    ) -> Result<bool, Box<dyn Error>> {
+        let mode = k_context.kill_mode();
+        if mode == KillMode::None {
+            return Ok(false);
+        }
+
+        let sig = k_context.signal(ko);
+        let sighup = k_context.send_sighup() && !IN_SET!(sig, Signal::SIGHUP, Signal::SIGKILL);
+        let mut wait = false;
+
+        if let Some(pid) = m_pid {
+            match self.kill_pid(pid, sig, sighup) {
+                Ok(_) => wait = true,
+                Err(e) => log::warn!("Failed to kill main service: error: {}", e),
             }
         }
-        if c_pid.is_some() {
-            match nix::sys::signal::kill(c_pid.unwrap(), sig) {
-                Ok(_) => {
-                    if sig != Signal::SIGCONT && sig != Signal::SIGKILL {
-                        match nix::sys::signal::kill(c_pid.unwrap(), Signal::SIGCONT) {
-                            Ok(_) => {}
-                            Err(e) => {
-                                log::debug!("kill pid {} errno: {}", c_pid.unwrap(), e)
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    log::warn!("Failed to kill control service: error: {}", e);
-                }
+
+        if let Some(pid) = c_pid {
+            match self.kill_pid(pid, sig, sighup) {
+                Ok(_) => wait = true,
+                Err(e) => log::warn!("Failed to kill control service: error: {}", e),
             }
         }
 
-        if !self.cgroup.cg_path().is_empty() {
+        // the main process only for KillMode=process, and also the rest for KillMode=mixed on SIGKILL.
+        let kill_cgroup = mode == KillMode::ControlGroup
+            || (mode == KillMode::Mixed && ko == KillOperation::KillKill);
+        if kill_cgroup && !self.cgroup.cg_path().is_empty() {
             let pids = self.pids_set(m_pid, c_pid);
+            let flags = if sig != Signal::SIGCONT && sig != Signal::SIGKILL {
+                CgFlags::IGNORE_SELF | CgFlags::SIGCONT
+            } else {
+                CgFlags::IGNORE_SELF
+            };
+
+            if let Err(_) = cgroup::cg_kill_recursive(&self.cg_path(), sig, flags, pids.clone()) {
+                log::debug!("failed to kill cgroup context, {:?}", self.cg_path());
+            }
 
-            match cgroup::cg_kill_recursive(
-                &self.cg_path(),
-                sig,
-                CgFlags::IGNORE_SELF | CgFlags::SIGCONT,
-                pids,
-            ) {
-                Ok(_) => {}
-                Err(_) => {
-                    log::debug!("failed to kill cgroup context, {:?}", self.cg_path());
+            if sighup {
+                if let Err(_) = cgroup::cg_kill_recursive(
+                    &self.cg_path(),
+                    Signal::SIGHUP,
+                    CgFlags::IGNORE_SELF,
+                    pids,
+                ) {
+                    log::debug!("failed to send SIGHUP to cgroup, {:?}", self.cg_path());
                 }
             }
         }
 
+        Ok(wait)
+    }
+
+    fn kill_pid(&self, pid: Pid, sig: Signal, sighup: bool) -> nix::Result<()> {
+        nix::sys::signal::kill(pid, sig)?;
+
+        if sig != Signal::SIGCONT && sig != Signal::SIGKILL {
+            if let Err(e) = nix::sys::signal::kill(pid, Signal::SIGCONT) {
+                log::debug!("kill pid {} errno: {}", pid, e)
+            }
+        }
+
+        if sighup {
+            if let Err(e) = nix::sys::signal::kill(pid, Signal::SIGHUP) {
+                log::debug!("send SIGHUP to pid {} errno: {}", pid, e)
+            }
+        }
+
         Ok(())
     }
 
@@ -325,6 +490,15 @@ impl Unit {
             .IgnoreOnIsolate
     }
 
+    // RebootArgument= configured in the section of the unit type, such as [Service]
+    pub fn set_reboot_argument(&self, reboot_argument: &str) {
+        self.get_config()
+            .config_data()
+            .borrow_mut()
+            .Unit
+            .RebootArgument = reboot_argument.to_string();
+    }
+
     pub fn set_ignore_on_isolate(&self, ignore_on_isolate: bool) {
         self.get_config()
             .config_data()
@@ -376,6 +550,36 @@ impl Unit {
         self.dm.insert_ud_config(self.get_id().to_string(), ud_conf)
     }
 
+    /// require and start after the mount units of the path and all its parents,
+    /// like RequiresMountsFor=
+    pub fn requires_mounts_for(&self, path: &str) {
+        for name in self.load.mounts_for(Path::new(path)) {
+            self.insert_two_deps(UnitRelations::UnitRequires, UnitRelations::UnitAfter, name);
+        }
+    }
+
+    /// bind to and start after the device unit of What=, the tags such as "UUID=" are resolved
+    /// to the device links first. nothing is added without the device units, which could not
+    /// be loaded then, or if What= is not a device node.
+    pub fn bind_to_device(&self, what: &str) {
+        let node = mount_util::what_to_node(what);
+        if !node.starts_with("/dev/")
+            || !Plugin::get_instance().unit_type_supported(UnitType::UnitDevice)
+        {
+            return;
+        }
+        self.insert_two_deps(
+            UnitRelations::UnitBindsTo,
+            UnitRelations::UnitAfter,
+            unit_name::unit_name_from_path(&node, "device"),
+        );
+    }
+
+    // the unit files the effective config comes from, the fragment comes first
+    pub(super) fn source_paths(&self) -> Vec<PathBuf> {
+        self.load.get_unit_id_source_pathbuf()
+    }
+
     pub(super) fn get_config(&self) -> Rc<UeConfig> {
         self.config.clone()
     }
@@ -398,14 +602,13 @@ impl Unit {
 
     pub(super) fn load_unit(&self) -> Result<(), Box<dyn Error>> {
         self.set_in_load_queue(false);
-        // Mount unit doesn't have config file, set its loadstate to
-        // UnitLoaded directly.
-        if self.unit_type() == UnitType::UnitMount {
-            self.load.set_load_state(UnitLoadState::UnitLoaded);
-            return Ok(());
+        if self.load.masked() {
+            self.load.set_load_state(UnitLoadState::UnitMasked);
+            return Err(format!("unit {} is masked", self.id).into());
         }
         match self.load.load_unit_confs() {
             Ok(_) => Ok({
+                self.conditions.reset();
                 let paths = self.load.get_unit_id_fragment_pathbuf();
                 log::debug!("begin exec sub class load");
                 let ret = self.sub.load(&paths);
@@ -416,6 +619,25 @@ impl Unit {
 
                 self.load.set_load_state(UnitLoadState::UnitLoaded);
             }),
+            // the slices are defined by their names, the unit files are optional for them,
+            // such as the built-in -.slice and system.slice, and so are the mount points
+            // mounted by others and the devices announced by udev.
+            Err(_)
+                if IN_SET!(
+                    self.unit_type(),
+                    UnitType::UnitSlice,
+                    UnitType::UnitMount,
+                    UnitType::UnitDevice
+                ) && self.load.get_unit_id_fragment_pathbuf().is_empty() =>
+            {
+                log::debug!("load {} without unit file", self.id);
+                if let Err(e) = self.sub.load(&Vec::new()) {
+                    return Err(format!("load Unit {} failed, error: {}", self.id, e).into());
+                }
+
+                self.load.set_load_state(UnitLoadState::UnitLoaded);
+                Ok(())
+            }
             Err(e) => {
                 self.load.set_load_state(UnitLoadState::UnitNotFound);
                 return Err(e);
@@ -423,10 +645,14 @@ impl Unit {
         }
     }
 
-    pub(super) fn current_active_state(&self) -> UnitActiveState {
+    pub fn current_active_state(&self) -> UnitActiveState {
         self.sub.current_active_state()
     }
 
+    pub(super) fn trigger(&self, other: &Rc<Unit>) {
+        self.sub.trigger(Rc::clone(other))
+    }
+
     pub(super) fn start(&self) -> Result<(), UnitActionError> {
         let active_state = self.current_active_state();
         let us_is_active_or_reloading = match active_state {
@@ -446,12 +672,19 @@ impl Unit {
             return Err(UnitActionError::UnitActionEInval);
         }
         if active_state != UnitActiveState::UnitActivating && !self.conditions().condtions_test() {
-            log::debug!("Starting failed because condtion test failed");
-            return Err(UnitActionError::UnitActionEInval);
+            log::debug!("Starting skipped because condtion test failed");
+            return Err(UnitActionError::UnitActionEComm);
         }
         if active_state != UnitActiveState::UnitActivating && !self.conditions().asserts_test() {
             log::error!("Starting failed because assert test failed");
-            return Err(UnitActionError::UnitActionEInval);
+            return Err(UnitActionError::UnitActionEProto);
+        }
+        // the device is not started by process1, the retries of the job waiting for it are not counted
+        if active_state != UnitActiveState::UnitActivating
+            && self.unit_type != UnitType::UnitDevice
+            && !self.start_limit_test()
+        {
+            return Err(UnitActionError::UnitActionECanceled);
         }
 
         self.sub.start()
@@ -475,6 +708,14 @@ impl Unit {
         self.sub.sigchld_events(pid, code, signal)
     }
 
+    pub(super) fn mountinfo_update(&self, mounted: bool) {
+        self.sub.mountinfo_update(mounted)
+    }
+
+    pub(super) fn device_update(&self, found: bool) {
+        self.sub.device_update(found)
+    }
+
     pub(super) fn load_state(&self) -> UnitLoadState {
         self.load.load_state()
     }
@@ -491,6 +732,26 @@ impl Unit {
         self.sub.collect_fds()
     }
 
+    /// record the unit which triggers this one through OnFailure=/OnSuccess=, the details of it
+    /// are exported to the executed processes as $MONITOR_*.
+    pub(super) fn set_monitor(&self, trigger: &Unit) {
+        let mut envs = HashMap::new();
+        envs.insert("MONITOR_UNIT".to_string(), trigger.get_id().to_string());
+        for (key, value) in trigger.sub.monitor_envs() {
+            envs.insert(key, value);
+        }
+
+        *self.monitor.borrow_mut() = envs;
+    }
+
+    pub(super) fn clear_monitor(&self) {
+        self.monitor.borrow_mut().clear();
+    }
+
+    pub(in crate::manager::unit) fn monitor_envs(&self) -> HashMap<String, String> {
+        self.monitor.borrow().clone()
+    }
+
     pub(in crate::manager) fn notify_message(
         &self,
         ucred: &UnixCredentials,
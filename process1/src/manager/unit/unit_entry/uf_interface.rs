@@ -1,6 +1,6 @@
 use super::u_entry::{Unit, UnitObj};
 use super::uu_config::UeConfig;
-use crate::manager::data::{DataManager, UnitActiveState, UnitRelations};
+use crate::manager::data::{DataManager, UnitActiveState, UnitEmergencyAction, UnitRelations};
 use crate::manager::unit::uload_util::UnitFile;
 use crate::manager::unit::unit_base::{UnitActionError, UnitLoadState, UnitType};
 use crate::manager::unit::UnitErrno;
@@ -69,7 +69,25 @@ impl UnitX {
         self.0.sigchld_events(pid, code, signal)
     }
     pub(in crate::manager::unit) fn reset_failed(&self) {}
-    pub(in crate::manager::unit) fn trigger(&self, _other: &Self) {}
+    pub(in crate::manager::unit) fn mountinfo_update(&self, mounted: bool) {
+        self.0.mountinfo_update(mounted)
+    }
+    pub(in crate::manager::unit) fn device_update(&self, found: bool) {
+        self.0.device_update(found)
+    }
+    pub(in crate::manager::unit) fn emergency_action(
+        &self,
+        action: UnitEmergencyAction,
+        reason: &str,
+    ) {
+        self.0.emergency_action(action, reason)
+    }
+    pub(in crate::manager::unit) fn start_limit_hit(&self) -> bool {
+        self.0.start_limit_hit()
+    }
+    pub(in crate::manager::unit) fn trigger(&self, other: &Self) {
+        self.0.trigger(&other.0)
+    }
     pub(in crate::manager::unit) fn in_load_queue(&self) -> bool {
         self.0.in_load_queue()
     }
@@ -96,6 +114,10 @@ impl UnitX {
         Ok(())
     }
 
+    pub(in crate::manager::unit) fn source_paths(&self) -> Vec<PathBuf> {
+        self.0.source_paths()
+    }
+
     pub(in crate::manager::unit) fn get_id(&self) -> &str {
         self.0.get_id()
     }
@@ -164,6 +186,14 @@ impl UnitX {
         self.0.collect_fds()
     }
 
+    pub(in crate::manager::unit) fn set_monitor(&self, trigger: &UnitX) {
+        self.0.set_monitor(&trigger.0)
+    }
+
+    pub(in crate::manager::unit) fn clear_monitor(&self) {
+        self.0.clear_monitor()
+    }
+
     pub fn get_config(&self) -> Rc<UeConfig> {
         self.0.get_config()
     }
@@ -19,6 +19,14 @@ impl UeCgroup {
         self.data.borrow_mut().setup_cg_path(id);
     }
 
+    pub(super) fn set_slice(&self, slice: &str) {
+        self.data.borrow_mut().set_slice(slice);
+    }
+
+    pub(super) fn slice(&self) -> Option<String> {
+        self.data.borrow().slice()
+    }
+
     pub(super) fn prepare_cg_exec(&self) -> Result<()> {
         self.data.borrow_mut().prepare_cg_exec()
     }
@@ -32,12 +40,14 @@ impl UeCgroup {
 
 struct UnitCgroupData {
     cg_path: PathBuf,
+    slice: Option<String>,
 }
 
 impl UnitCgroupData {
     pub(self) fn new() -> UnitCgroupData {
         UnitCgroupData {
             cg_path: PathBuf::from(""),
+            slice: None,
         }
     }
 
@@ -50,11 +60,26 @@ impl UnitCgroupData {
     }
 
     fn set_default_cg_path(&mut self, id: &str) {
-        let cg_tree_name = PathBuf::from(cgroup::cg_escape(id));
+        // the slice is nested in its parent slices by its name, and the other units
+        // are nested in the slice they are put into.
+        let cg_tree_name = if id.ends_with(".slice") {
+            cgroup::cg_slice_to_path(id)
+        } else {
+            let slice = self.slice.as_deref().unwrap_or("-.slice");
+            cgroup::cg_slice_to_path(slice).join(cgroup::cg_escape(id))
+        };
 
         self.cg_path = cg_tree_name;
     }
 
+    pub(self) fn set_slice(&mut self, slice: &str) {
+        self.slice = Some(slice.to_string());
+    }
+
+    pub(self) fn slice(&self) -> Option<String> {
+        self.slice.clone()
+    }
+
     pub(self) fn prepare_cg_exec(&mut self) -> Result<()> {
         log::debug!("cgroup: prepare cg exec");
         cgroup::cg_create(&self.cg_path).map_err(|_e| Error::Other {
@@ -2,13 +2,6 @@ use std::cell::RefCell;
 
 use utils::condition::{Condition, ConditionType};
 
-pub(super) const CONDITION_PATH_EXISTS: &str = "ConditionPathExists";
-pub(super) const CONDITION_FILE_NOT_EMPTY: &str = "ConditionFileNotEmpty";
-pub(super) const CONDITION_NEEDS_UPDATE: &str = "ConditionNeedsUpdate";
-
-pub(super) const ASSERT_PATH_EXISTS: &str = "AssertPathExists";
-pub(super) const ASSERT_FILE_NOT_EMPTY: &str = "AssertFileNotEmpty";
-
 pub(super) struct UeCondition {
     init_flag: RefCell<i8>,
     conditions: RefCell<Conditions>,
@@ -56,36 +49,17 @@ impl UeCondition {
         *self.init_flag.borrow()
     }
 
-    pub(super) fn add_condition(&self, condop: &str, _params: String) {
-        if _params.is_empty() {
+    pub(super) fn add_condition(&self, c_type: ConditionType, _params: String) {
+        if _params.is_empty() || c_type == ConditionType::_MAX {
             return;
         }
 
-        let c_type = match condop {
-            CONDITION_PATH_EXISTS => ConditionType::PathExists,
-            CONDITION_FILE_NOT_EMPTY => ConditionType::FileNotEmpty,
-            CONDITION_NEEDS_UPDATE => ConditionType::NeedsUpdate,
-            _ => ConditionType::_MAX,
-        };
-
-        if c_type == ConditionType::_MAX {
-            return;
-        }
         let condition = self.new_condition(c_type, _params);
         self.conditions.borrow_mut().0.push(condition);
     }
 
-    pub(super) fn add_assert(&self, assertop: &str, _params: String) {
-        if _params.is_empty() {
-            return;
-        }
-        let c_type = match assertop {
-            ASSERT_PATH_EXISTS => ConditionType::PathExists,
-            ASSERT_FILE_NOT_EMPTY => ConditionType::FileNotEmpty,
-            _ => ConditionType::_MAX,
-        };
-
-        if c_type == ConditionType::_MAX {
+    pub(super) fn add_assert(&self, c_type: ConditionType, _params: String) {
+        if _params.is_empty() || c_type == ConditionType::_MAX {
             return;
         }
 
@@ -93,11 +67,26 @@ impl UeCondition {
         self.asserts.borrow_mut().0.push(condition);
     }
 
+    // drop all the conditions and asserts, they are rebuilt from the
+    // configuration the next time they are needed
+    pub(super) fn reset(&self) {
+        self.conditions.borrow_mut().0.clear();
+        self.asserts.borrow_mut().0.clear();
+        self.set_init_flag(0);
+    }
+
     fn condition_vec_test(conditions: &Vec<Condition>) -> bool {
         let mut trigger_flag = 0;
         let mut ret = true;
         for cond in conditions {
             let r = cond.test();
+            if !r {
+                log::debug!(
+                    "condition {:?}={} is not satisfied",
+                    cond.c_type(),
+                    cond.params()
+                );
+            }
             if cond.trigger() == 0 && !r {
                 return false;
             }
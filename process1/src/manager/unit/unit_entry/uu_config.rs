@@ -5,9 +5,11 @@ use std::rc::Rc;
 use confique::Config;
 use std::error::Error as stdError;
 
+use crate::manager::data::UnitEmergencyAction;
 use crate::manager::unit::uload_util::UnitFile;
 use crate::manager::unit::unit_base::JobMode;
 use crate::manager::unit::DeserializeWith;
+use utils::condition::ConditionType;
 
 pub(crate) struct UeConfig {
     data: Rc<RefCell<UeConfigData>>,
@@ -43,15 +45,19 @@ impl UeConfig {
 
         let mut configer = builder.load()?;
 
-        // dropin
+        // dropin, the symlinks in .wants/.requires are named after the units they pull in
         for v in files.get_unit_id_dropin_wants(name) {
-            configer.Unit.Wants.push(v.to_string_lossy().to_string());
-            configer.Unit.After.push(v.to_string_lossy().to_string());
+            if let Some(n) = v.file_name() {
+                configer.Unit.Wants.push(n.to_string_lossy().to_string());
+                configer.Unit.After.push(n.to_string_lossy().to_string());
+            }
         }
 
         for v in files.get_unit_id_dropin_requires(name) {
-            configer.Unit.Requires.push(v.to_string_lossy().to_string());
-            configer.Unit.After.push(v.to_string_lossy().to_string());
+            if let Some(n) = v.file_name() {
+                configer.Unit.Requires.push(n.to_string_lossy().to_string());
+                configer.Unit.After.push(n.to_string_lossy().to_string());
+            }
         }
 
         *self.data.borrow_mut() = configer;
@@ -64,6 +70,31 @@ impl UeConfig {
     }
 }
 
+// load the template unit {name} and return its DefaultInstance=
+pub(in crate::manager::unit) fn unit_default_instance(
+    files: &UnitFile,
+    name: &str,
+) -> Option<String> {
+    files.build_name_map(name.to_string(), false);
+    let config = UeConfig::new();
+    if let Err(e) = config.load_fragment_and_dropin(files, &name.to_string()) {
+        log::error!("failed to load template unit {}: {}", name, e);
+        return None;
+    }
+
+    let instance = config
+        .config_data()
+        .borrow()
+        .Install
+        .DefaultInstance
+        .clone();
+    if instance.is_empty() {
+        return None;
+    }
+
+    Some(instance)
+}
+
 #[derive(Config, Default, Debug)]
 pub(crate) struct UeConfigData {
     #[config(nested)]
@@ -85,20 +116,59 @@ pub(crate) struct UeConfigUnit {
     pub IgnoreOnIsolate: bool,
     #[config(default = true)]
     pub DefaultDependencies: bool,
-    // #[config(deserialize_with = JobMode::deserialize_with)]
-    // #[config(default = "replace")]
-    // pub on_success_job_mode: JobMode,
+    #[config(default = false)]
+    pub StopWhenUnneeded: bool,
+    #[config(deserialize_with = JobMode::deserialize_with)]
+    #[config(default = "replace")]
+    pub OnSuccessJobMode: JobMode,
     #[config(deserialize_with = JobMode::deserialize_with)]
     #[config(default = "replace")]
     pub OnFailureJobMode: JobMode,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
+    pub OnSuccess: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub OnFailure: Vec<String>,
+    #[config(deserialize_with = UnitEmergencyAction::deserialize_with)]
+    #[config(default = "none")]
+    pub FailureAction: UnitEmergencyAction,
+    #[config(deserialize_with = UnitEmergencyAction::deserialize_with)]
+    #[config(default = "none")]
+    pub SuccessAction: UnitEmergencyAction,
+    #[config(deserialize_with = UnitEmergencyAction::deserialize_with)]
+    #[config(default = "none")]
+    pub StartLimitAction: UnitEmergencyAction,
+    #[config(deserialize_with = UnitEmergencyAction::deserialize_with)]
+    #[config(default = "none")]
+    pub JobTimeoutAction: UnitEmergencyAction,
+    #[config(default = "")]
+    pub RebootArgument: String,
+    // the unit may be started StartLimitBurst= times in StartLimitIntervalSec= seconds, 0 means no limit
+    #[config(default = 10)]
+    pub StartLimitIntervalSec: u64,
+    #[config(default = 5)]
+    pub StartLimitBurst: u32,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
     pub Wants: Vec<String>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
     pub Requires: Vec<String>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
+    pub Requisite: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub BindsTo: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub PartOf: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub Upholds: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
     pub Before: Vec<String>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
@@ -106,28 +176,274 @@ pub(crate) struct UeConfigUnit {
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
     pub Conflicts: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub JoinsNamespaceOf: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub PropagatesReloadTo: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ReloadPropagatedFrom: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub RequiresMountsFor: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionPathExists: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionPathExistsGlob: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionPathIsDirectory: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionPathIsSymbolicLink: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionPathIsMountPoint: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionPathIsReadWrite: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionDirectoryNotEmpty: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionFileNotEmpty: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionFileIsExecutable: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionNeedsUpdate: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionKernelCommandLine: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionKernelVersion: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionVirtualization: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionArchitecture: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
-    pub ConditionFileNotEmpty: String,
+    pub ConditionHost: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
-    pub ConditionNeedsUpdate: String,
+    pub ConditionFirstBoot: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
-    pub ConditionPathExists: String,
+    pub ConditionSecurity: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
-    pub AssertPathExists: String,
+    pub ConditionCapability: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionACPower: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionUser: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionGroup: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionControlGroupController: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub ConditionEnvironment: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertPathExists: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertPathExistsGlob: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertPathIsDirectory: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertPathIsSymbolicLink: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertPathIsMountPoint: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertPathIsReadWrite: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertDirectoryNotEmpty: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertFileNotEmpty: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertFileIsExecutable: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertNeedsUpdate: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertKernelCommandLine: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertKernelVersion: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertVirtualization: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertArchitecture: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertHost: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertFirstBoot: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertSecurity: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertCapability: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertACPower: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertUser: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertGroup: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertControlGroupController: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
+    #[config(default = "")]
+    pub AssertEnvironment: Vec<String>,
+}
+
+impl UeConfigUnit {
+    // the Condition*= settings, a failed condition skips the unit
+    pub(super) fn conditions(&self) -> Vec<(ConditionType, &Vec<String>)> {
+        vec![
+            (ConditionType::PathExists, &self.ConditionPathExists),
+            (ConditionType::PathExistsGlob, &self.ConditionPathExistsGlob),
+            (
+                ConditionType::PathIsDirectory,
+                &self.ConditionPathIsDirectory,
+            ),
+            (
+                ConditionType::PathIsSymbolicLink,
+                &self.ConditionPathIsSymbolicLink,
+            ),
+            (
+                ConditionType::PathIsMountPoint,
+                &self.ConditionPathIsMountPoint,
+            ),
+            (
+                ConditionType::PathIsReadWrite,
+                &self.ConditionPathIsReadWrite,
+            ),
+            (
+                ConditionType::DirectoryNotEmpty,
+                &self.ConditionDirectoryNotEmpty,
+            ),
+            (ConditionType::FileNotEmpty, &self.ConditionFileNotEmpty),
+            (
+                ConditionType::FileIsExecutable,
+                &self.ConditionFileIsExecutable,
+            ),
+            (ConditionType::NeedsUpdate, &self.ConditionNeedsUpdate),
+            (
+                ConditionType::KernelCommandLine,
+                &self.ConditionKernelCommandLine,
+            ),
+            (ConditionType::KernelVersion, &self.ConditionKernelVersion),
+            (ConditionType::Virtualization, &self.ConditionVirtualization),
+            (ConditionType::Architecture, &self.ConditionArchitecture),
+            (ConditionType::Host, &self.ConditionHost),
+            (ConditionType::FirstBoot, &self.ConditionFirstBoot),
+            (ConditionType::Security, &self.ConditionSecurity),
+            (ConditionType::Capability, &self.ConditionCapability),
+            (ConditionType::ACPower, &self.ConditionACPower),
+            (ConditionType::User, &self.ConditionUser),
+            (ConditionType::Group, &self.ConditionGroup),
+            (
+                ConditionType::ControlGroupController,
+                &self.ConditionControlGroupController,
+            ),
+            (ConditionType::Environment, &self.ConditionEnvironment),
+        ]
+    }
+
+    // the Assert*= settings, a failed assert fails the start job
+    pub(super) fn asserts(&self) -> Vec<(ConditionType, &Vec<String>)> {
+        vec![
+            (ConditionType::PathExists, &self.AssertPathExists),
+            (ConditionType::PathExistsGlob, &self.AssertPathExistsGlob),
+            (ConditionType::PathIsDirectory, &self.AssertPathIsDirectory),
+            (
+                ConditionType::PathIsSymbolicLink,
+                &self.AssertPathIsSymbolicLink,
+            ),
+            (
+                ConditionType::PathIsMountPoint,
+                &self.AssertPathIsMountPoint,
+            ),
+            (ConditionType::PathIsReadWrite, &self.AssertPathIsReadWrite),
+            (
+                ConditionType::DirectoryNotEmpty,
+                &self.AssertDirectoryNotEmpty,
+            ),
+            (ConditionType::FileNotEmpty, &self.AssertFileNotEmpty),
+            (
+                ConditionType::FileIsExecutable,
+                &self.AssertFileIsExecutable,
+            ),
+            (ConditionType::NeedsUpdate, &self.AssertNeedsUpdate),
+            (
+                ConditionType::KernelCommandLine,
+                &self.AssertKernelCommandLine,
+            ),
+            (ConditionType::KernelVersion, &self.AssertKernelVersion),
+            (ConditionType::Virtualization, &self.AssertVirtualization),
+            (ConditionType::Architecture, &self.AssertArchitecture),
+            (ConditionType::Host, &self.AssertHost),
+            (ConditionType::FirstBoot, &self.AssertFirstBoot),
+            (ConditionType::Security, &self.AssertSecurity),
+            (ConditionType::Capability, &self.AssertCapability),
+            (ConditionType::ACPower, &self.AssertACPower),
+            (ConditionType::User, &self.AssertUser),
+            (ConditionType::Group, &self.AssertGroup),
+            (
+                ConditionType::ControlGroupController,
+                &self.AssertControlGroupController,
+            ),
+            (ConditionType::Environment, &self.AssertEnvironment),
+        ]
+    }
 }
 
 #[derive(Config, Default, Debug)]
 pub(crate) struct UeConfigInstall {
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
-    pub Alias: String,
+    pub Alias: Vec<String>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
     pub WantedBy: Vec<String>,
     #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
     pub RequiredBy: Vec<String>,
+    #[config(deserialize_with = Vec::<String>::deserialize_with)]
     #[config(default = "")]
-    pub Also: String,
+    pub Also: Vec<String>,
     #[config(default = "")]
     pub DefaultInstance: String,
     // #[config(default = "")]
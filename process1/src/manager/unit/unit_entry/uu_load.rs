@@ -3,8 +3,9 @@ use crate::manager::unit::uload_util::UnitFile;
 use crate::manager::unit::unit_base::UnitLoadState;
 use std::cell::RefCell;
 use std::error::Error as stdError;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use utils::{mount_util, unit_name};
 
 use super::uu_config::UeConfig;
 //#[derive(Debug)]
@@ -45,6 +46,14 @@ impl UeLoad {
         self.file.get_unit_id_fragment_pathbuf(&self.id)
     }
 
+    pub(super) fn get_unit_id_source_pathbuf(&self) -> Vec<PathBuf> {
+        self.file.get_unit_id_source_pathbuf(&self.id)
+    }
+
+    pub(super) fn masked(&self) -> bool {
+        self.file.unit_masked(&self.id)
+    }
+
     pub(super) fn set_load_state(&self, load_state: UnitLoadState) {
         *self.load_state.borrow_mut() = load_state;
     }
@@ -87,18 +96,28 @@ impl UeLoad {
             UnitRelations::UnitWants,
             self.config.config_data().borrow().Unit.Wants.clone(),
         );
-        ud_conf.deps.insert(
-            UnitRelations::UnitAfter,
-            self.config.config_data().borrow().Unit.After.clone(),
-        );
+        // the mount units needed by RequiresMountsFor= are required and started before
+        let paths = self
+            .config
+            .config_data()
+            .borrow()
+            .Unit
+            .RequiresMountsFor
+            .clone();
+        let mounts: Vec<String> = paths
+            .iter()
+            .flat_map(|p| self.mounts_for(Path::new(p)))
+            .collect();
+        let mut after = self.config.config_data().borrow().Unit.After.clone();
+        after.extend(mounts.iter().cloned());
+        ud_conf.deps.insert(UnitRelations::UnitAfter, after);
         ud_conf.deps.insert(
             UnitRelations::UnitBefore,
             self.config.config_data().borrow().Unit.Before.clone(),
         );
-        ud_conf.deps.insert(
-            UnitRelations::UnitRequires,
-            self.config.config_data().borrow().Unit.Requires.clone(),
-        );
+        let mut requires = self.config.config_data().borrow().Unit.Requires.clone();
+        requires.extend(mounts);
+        ud_conf.deps.insert(UnitRelations::UnitRequires, requires);
 
         ud_conf.deps.insert(
             UnitRelations::UnitWantsBy,
@@ -114,11 +133,85 @@ impl UeLoad {
                 .clone(),
         );
 
+        ud_conf.deps.insert(
+            UnitRelations::UnitRequisite,
+            self.config.config_data().borrow().Unit.Requisite.clone(),
+        );
+        ud_conf.deps.insert(
+            UnitRelations::UnitBindsTo,
+            self.config.config_data().borrow().Unit.BindsTo.clone(),
+        );
+        ud_conf.deps.insert(
+            UnitRelations::UnitPartOf,
+            self.config.config_data().borrow().Unit.PartOf.clone(),
+        );
+        ud_conf.deps.insert(
+            UnitRelations::UnitUpHolds,
+            self.config.config_data().borrow().Unit.Upholds.clone(),
+        );
+
         ud_conf.deps.insert(
             UnitRelations::UnitConflicts,
             self.config.config_data().borrow().Unit.Conflicts.clone(),
         );
 
+        ud_conf.deps.insert(
+            UnitRelations::UnitJoinsNameSpaceOf,
+            self.config
+                .config_data()
+                .borrow()
+                .Unit
+                .JoinsNamespaceOf
+                .clone(),
+        );
+
+        ud_conf.deps.insert(
+            UnitRelations::UnitPropagatesReloadTo,
+            self.config
+                .config_data()
+                .borrow()
+                .Unit
+                .PropagatesReloadTo
+                .clone(),
+        );
+        ud_conf.deps.insert(
+            UnitRelations::UnitReloadPropagatedFrom,
+            self.config
+                .config_data()
+                .borrow()
+                .Unit
+                .ReloadPropagatedFrom
+                .clone(),
+        );
+
+        ud_conf.deps.insert(
+            UnitRelations::UnitOnSuccess,
+            self.config.config_data().borrow().Unit.OnSuccess.clone(),
+        );
+        ud_conf.deps.insert(
+            UnitRelations::UnitOnFailure,
+            self.config.config_data().borrow().Unit.OnFailure.clone(),
+        );
+
         self.dm.insert_ud_config(self.id.clone(), ud_conf);
     }
+
+    // the mount units of the path and all its parents, which have their unit
+    // files or are mounted already, the others are not known to process1.
+    pub(super) fn mounts_for(&self, path: &Path) -> Vec<String> {
+        path.ancestors()
+            .filter(|p| p.is_absolute())
+            .filter_map(|p| {
+                let name = unit_name::unit_name_from_path(&p.to_string_lossy(), "mount");
+                if name == self.id {
+                    return None;
+                }
+                if self.file.unit_file_exists(&name) || mount_util::path_is_mount_point(p) {
+                    Some(name)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
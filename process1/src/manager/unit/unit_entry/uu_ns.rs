@@ -0,0 +1,114 @@
+use crate::manager::unit::execute::ExecNsStorage;
+use nix::errno::Errno;
+use nix::sched::CloneFlags;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub(super) struct UeNamespace {
+    data: RefCell<UnitNamespaceData>,
+}
+
+impl UeNamespace {
+    pub(super) fn new() -> UeNamespace {
+        UeNamespace {
+            data: RefCell::new(UnitNamespaceData::new()),
+        }
+    }
+
+    pub(super) fn storage(&self, nstype: CloneFlags) -> Result<Rc<ExecNsStorage>, Errno> {
+        self.data.borrow_mut().storage(nstype)
+    }
+
+    pub(super) fn join(&self, other: &UeNamespace) {
+        if std::ptr::eq(self, other) {
+            return;
+        }
+
+        let mut s_data = self.data.borrow_mut();
+        let mut o_data = other.data.borrow_mut();
+        s_data.join(&o_data);
+        o_data.join(&s_data);
+    }
+}
+
+struct UnitNamespaceData {
+    netns: Option<Rc<ExecNsStorage>>,
+    ipcns: Option<Rc<ExecNsStorage>>,
+}
+
+// the declaration "pub(self)" is for identification only.
+impl UnitNamespaceData {
+    pub(self) fn new() -> UnitNamespaceData {
+        UnitNamespaceData {
+            netns: None,
+            ipcns: None,
+        }
+    }
+
+    pub(self) fn storage(&mut self, nstype: CloneFlags) -> Result<Rc<ExecNsStorage>, Errno> {
+        let storage = match nstype {
+            CloneFlags::CLONE_NEWNET => &mut self.netns,
+            CloneFlags::CLONE_NEWIPC => &mut self.ipcns,
+            _ => return Err(Errno::EINVAL),
+        };
+
+        if storage.is_none() {
+            *storage = Some(Rc::new(ExecNsStorage::new()?));
+        }
+
+        Ok(Rc::clone(storage.as_ref().unwrap()))
+    }
+
+    pub(self) fn join(&mut self, other: &Self) {
+        // the storage of the unit joined is used if the unit has no one,
+        // the join is done in both directions, so either unit can create it first.
+        if self.netns.is_none() {
+            self.netns = other.netns.clone();
+        }
+
+        if self.ipcns.is_none() {
+            self.ipcns = other.ipcns.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ns_join() {
+        let a = UeNamespace::new();
+        let b = UeNamespace::new();
+        let a_net = a.storage(CloneFlags::CLONE_NEWNET).unwrap();
+
+        // the joining unit uses the namespace of the joined one
+        b.join(&a);
+        let b_net = b.storage(CloneFlags::CLONE_NEWNET).unwrap();
+        assert!(Rc::ptr_eq(&a_net, &b_net));
+
+        // the namespace not created yet is owned by whoever creates it first
+        let b_ipc = b.storage(CloneFlags::CLONE_NEWIPC).unwrap();
+        a.join(&b);
+        let a_ipc = a.storage(CloneFlags::CLONE_NEWIPC).unwrap();
+        assert!(Rc::ptr_eq(&a_ipc, &b_ipc));
+    }
+
+    #[test]
+    fn test_ns_join_symmetric() {
+        let a = UeNamespace::new();
+        let b = UeNamespace::new();
+
+        // a joins b, but a is the one creating the namespace
+        let a_net = a.storage(CloneFlags::CLONE_NEWNET).unwrap();
+        a.join(&b);
+        let b_net = b.storage(CloneFlags::CLONE_NEWNET).unwrap();
+        assert!(Rc::ptr_eq(&a_net, &b_net));
+
+        // the units not joined have their own namespaces
+        let c = UeNamespace::new();
+        let c_net = c.storage(CloneFlags::CLONE_NEWNET).unwrap();
+        assert!(!Rc::ptr_eq(&a_net, &c_net));
+        assert!(c.storage(CloneFlags::CLONE_NEWUTS).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+/// the rate limit of starting the unit, configured by StartLimitIntervalSec= and StartLimitBurst=.
+pub(super) struct UeStartLimit {
+    // the beginning of the current interval
+    begin: RefCell<Option<Instant>>,
+    // the starts in the current interval
+    num: RefCell<u32>,
+    // the last start is refused
+    hit: RefCell<bool>,
+}
+
+impl UeStartLimit {
+    pub(super) fn new() -> UeStartLimit {
+        UeStartLimit {
+            begin: RefCell::new(None),
+            num: RefCell::new(0),
+            hit: RefCell::new(false),
+        }
+    }
+
+    /// count the start at {now}, return false if the unit is started more than {burst}
+    /// times in {interval}, zero of which means no limit.
+    pub(super) fn test(&self, interval: Duration, burst: u32, now: Instant) -> bool {
+        if interval.is_zero() || burst == 0 {
+            return true;
+        }
+
+        let expired = match *self.begin.borrow() {
+            Some(begin) => now.saturating_duration_since(begin) > interval,
+            None => true,
+        };
+        if expired {
+            *self.begin.borrow_mut() = Some(now);
+            *self.num.borrow_mut() = 0;
+        }
+
+        let mut num = self.num.borrow_mut();
+        if *num >= burst {
+            *self.hit.borrow_mut() = true;
+            return false;
+        }
+        *num += 1;
+        true
+    }
+
+    pub(super) fn take_hit(&self) -> bool {
+        self.hit.replace(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_limit() {
+        let limit = UeStartLimit::new();
+        let interval = Duration::from_secs(10);
+        let now = Instant::now();
+
+        assert!(limit.test(interval, 2, now));
+        assert!(limit.test(interval, 2, now + Duration::from_secs(1)));
+        assert!(!limit.test(interval, 2, now + Duration::from_secs(2)));
+        assert!(limit.take_hit());
+        assert!(!limit.take_hit());
+
+        // a new interval begins
+        assert!(limit.test(interval, 2, now + Duration::from_secs(11)));
+
+        let unlimited = UeStartLimit::new();
+        for _ in 0..10 {
+            assert!(unlimited.test(Duration::ZERO, 2, now));
+        }
+    }
+}
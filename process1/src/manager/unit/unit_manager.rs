@@ -1,11 +1,14 @@
-use super::execute::{ExecCmdError, ExecCommand, ExecParameters, ExecSpawn};
+use super::execute::{ExecCmdError, ExecCommand, ExecIdlePipe, ExecParameters, ExecSpawn};
 use super::job::{JobAffect, JobConf, JobKind, JobManager};
-use super::unit_base::{JobMode, UnitDependencyMask, UnitLoadState, UnitRelationAtom};
+use super::uload_util::DEFAULT_TARGET;
+use super::unit_base::{self, JobMode, UnitDependencyMask, UnitLoadState, UnitRelationAtom};
 use super::unit_datastore::UnitDb;
 use super::unit_entry::{Unit, UnitObj, UnitX};
 use super::unit_runtime::UnitRT;
 use super::{ExecContext, UnitActionError, UnitType};
-use crate::manager::data::{DataManager, UnitState};
+use crate::manager::data::{
+    write_reboot_argument, DataManager, EmergencyAction, UnitEmergencyAction, UnitState,
+};
 use crate::manager::manager_config::ManagerConfig;
 use crate::manager::table::{TableOp, TableSubscribe};
 use crate::manager::{MngErrno, UnitActiveState, UnitRelations};
@@ -22,7 +25,7 @@ use std::rc::Rc;
 use unit_load::UnitLoad;
 
 use utils::error::Error as ServiceError;
-use utils::process_util;
+use utils::{process_util, unit_name, IN_SET};
 
 //#[derive(Debug)]
 pub(in crate::manager) struct UnitManagerX {
@@ -48,6 +51,83 @@ impl UnitManagerX {
         self.data.start_unit(name)
     }
 
+    pub(in crate::manager) fn cat_unit(&self, name: &str) -> Result<String, MngErrno> {
+        self.data.cat_unit(name)
+    }
+
+    pub(in crate::manager) fn enable_unit(
+        &self,
+        name: &str,
+        runtime: bool,
+    ) -> Result<String, MngErrno> {
+        self.data.enable_unit(name, runtime)
+    }
+
+    pub(in crate::manager) fn disable_unit(
+        &self,
+        name: &str,
+        runtime: bool,
+    ) -> Result<String, MngErrno> {
+        self.data.disable_unit(name, runtime)
+    }
+
+    pub(in crate::manager) fn mask_unit(
+        &self,
+        name: &str,
+        runtime: bool,
+    ) -> Result<String, MngErrno> {
+        self.data.mask_unit(name, runtime)
+    }
+
+    pub(in crate::manager) fn unmask_unit(
+        &self,
+        name: &str,
+        runtime: bool,
+    ) -> Result<String, MngErrno> {
+        self.data.unmask_unit(name, runtime)
+    }
+
+    pub(in crate::manager) fn preset_unit(
+        &self,
+        name: &str,
+        runtime: bool,
+    ) -> Result<String, MngErrno> {
+        self.data.preset_unit(name, runtime)
+    }
+
+    pub(in crate::manager) fn preset_all_units(&self) -> String {
+        self.data.preset_all_units()
+    }
+
+    pub(in crate::manager) fn write_builtin_units(&self) {
+        self.data.write_builtin_units()
+    }
+
+    pub(in crate::manager) fn get_default_target(&self) -> Result<String, MngErrno> {
+        self.data.get_default_target()
+    }
+
+    pub(in crate::manager) fn set_default_target(&self, name: &str) -> Result<String, MngErrno> {
+        self.data.set_default_target(name)
+    }
+
+    pub(in crate::manager) fn isolate_unit(&self, name: &str) -> Result<(), MngErrno> {
+        self.data.isolate_unit(name)
+    }
+
+    pub(in crate::manager) fn start_scope(
+        &self,
+        name: &str,
+        pids: &[i32],
+        properties: &[String],
+    ) -> Result<(), MngErrno> {
+        self.data.start_scope(name, pids, properties)
+    }
+
+    pub(in crate::manager) fn unit_status(&self, name: &str) -> Result<String, MngErrno> {
+        self.data.unit_status(name)
+    }
+
     pub(in crate::manager) fn stop_unit(&self, name: &str) -> Result<(), MngErrno> {
         self.data.stop_unit(name)
     }
@@ -60,6 +140,14 @@ impl UnitManagerX {
         self.data.dispatch_mountinfo()
     }
 
+    pub(in crate::manager) fn dispatch_uevent(
+        &self,
+        action: &str,
+        props: &HashMap<String, String>,
+    ) {
+        self.data.dispatch_uevent(action, props)
+    }
+
     pub(in crate::manager) fn dispatch_load_queue(&self) {
         self.data.rt.dispatch_load_queue()
     }
@@ -72,6 +160,9 @@ impl UnitManagerX {
         } else {
             log::info!("register  TableSubcribe for {}  sucessfull", &self.sub_name);
         }
+
+        let subscriber = Rc::clone(&self.data);
+        dm.register_emergency_action(&self.sub_name, subscriber);
     }
 
     pub(crate) fn notify_message(
@@ -95,12 +186,114 @@ pub struct UnitManager {
     config: Rc<ManagerConfig>,
 }
 
+// describe the symlinks created or removed by the install operations
+fn install_changes(changes: &[PathBuf]) -> String {
+    changes
+        .iter()
+        .map(|p| match std::fs::read_link(p) {
+            Ok(target) => format!(
+                "Created symlink {} -> {}.\n",
+                p.to_string_lossy(),
+                target.to_string_lossy()
+            ),
+            Err(_) => format!("Removed {}.\n", p.to_string_lossy()),
+        })
+        .collect()
+}
+
+fn install_result(
+    op: &str,
+    name: &str,
+    ret: Result<Vec<PathBuf>, String>,
+) -> Result<String, MngErrno> {
+    match ret {
+        Ok(changes) => Ok(install_changes(&changes)),
+        Err(e) => {
+            log::error!("failed to {} unit {}: {}", op, name, e);
+            Err(MngErrno::MngErrInput)
+        }
+    }
+}
+
 fn mount_point_to_unit_name(mount_point: &str) -> String {
-    let mut res = String::from(mount_point).replace("/", "-") + ".mount";
-    if res != "-.mount" {
-        res = String::from(&res[1..])
+    unit_name::unit_name_from_path(mount_point, "mount")
+}
+
+// the devices tagged with process1 by the udev rules are exported as device units
+const DEVICE_TAG: &str = "process1";
+
+// a device is named by its sysfs path, its device node and the symlinks to the node,
+// such as sys-devices-...-sda1.device, dev-sda1.device and dev-disk-by\x2duuid-....device.
+fn uevent_to_unit_names(props: &HashMap<String, String>) -> Vec<String> {
+    let mut paths = Vec::new();
+    if let Some(devpath) = props.get("DEVPATH") {
+        paths.push(format!("/sys{}", devpath));
+    }
+    if let Some(devname) = props.get("DEVNAME") {
+        // the kernel reports the node without /dev
+        match devname.starts_with('/') {
+            true => paths.push(devname.to_string()),
+            false => paths.push(format!("/dev/{}", devname)),
+        }
+    }
+    if let Some(devlinks) = props.get("DEVLINKS") {
+        paths.extend(devlinks.split_whitespace().map(|l| l.to_string()));
+    }
+
+    paths
+        .iter()
+        .map(|p| unit_name::unit_name_from_path(p, "device"))
+        .collect()
+}
+
+// the properties of a scope which belong to the [Unit] section, the others are in [Scope]
+const SCOPE_UNIT_PROPERTIES: [&str; 9] = [
+    "Description",
+    "Documentation",
+    "Wants",
+    "Requires",
+    "BindsTo",
+    "PartOf",
+    "Before",
+    "After",
+    "Conflicts",
+];
+
+// the transient unit file of the scope, the processes are listed in PIDs=, and each
+// property in the form of "Key=Value" is put into the section it belongs to.
+fn scope_unit_file(pids: &[i32], properties: &[String]) -> Result<String, String> {
+    let mut unit = toml::value::Table::new();
+    let mut scope = toml::value::Table::new();
+    scope.insert(
+        "PIDs".to_string(),
+        toml::Value::Array(pids.iter().map(|p| toml::Value::from(*p)).collect()),
+    );
+
+    for property in properties {
+        let (key, value) = match property.split_once('=') {
+            Some((k, v)) if !k.trim().is_empty() => (k.trim(), v.trim()),
+            _ => return Err(format!("invalid property {}", property)),
+        };
+
+        if SCOPE_UNIT_PROPERTIES.contains(&key) {
+            unit.insert(key.to_string(), toml::Value::from(value));
+            continue;
+        }
+
+        let value = if let Ok(v) = value.parse::<i64>() {
+            toml::Value::from(v)
+        } else if let Ok(v) = value.parse::<bool>() {
+            toml::Value::from(v)
+        } else {
+            toml::Value::from(value)
+        };
+        scope.insert(key.to_string(), value);
     }
-    res
+
+    let mut file = toml::value::Table::new();
+    file.insert("Unit".to_string(), toml::Value::Table(unit));
+    file.insert("Scope".to_string(), toml::Value::Table(scope));
+    toml::to_string(&file).map_err(|e| e.to_string())
 }
 
 // the declaration "pub(self)" is for identification only.
@@ -124,6 +317,16 @@ impl UnitManager {
         params: &ExecParameters,
         ctx: Rc<ExecContext>,
     ) -> Result<Pid, ExecCmdError> {
+        // JoinsNamespaceOf=
+        if let Some(ux) = self.db.units_get(unit.get_id()) {
+            for other in self
+                .db
+                .dep_gets_atom(&ux, UnitRelationAtom::UnitAtomJoinsNameSpaceOf)
+            {
+                unit.join_namespace_of(&other);
+            }
+        }
+
         self.exec.spawn(unit, cmdline, params, ctx.clone())
     }
 
@@ -257,6 +460,47 @@ impl UnitManager {
         self.jm.has_stop_job(&u)
     }
 
+    // check if the unit is being restarted by a restart job
+    pub fn has_restart_job(&self, name: &str) -> bool {
+        let u = if let Some(unit) = self.db.units_get(name) {
+            unit
+        } else {
+            return false;
+        };
+
+        self.jm.has_restart_job(&u)
+    }
+
+    // reload the units which the unit {name} propagates reload to, the reload is not triggered by a job.
+    pub fn propagate_reload(&self, name: &str) {
+        let u = if let Some(unit) = self.db.units_get(name) {
+            unit
+        } else {
+            return;
+        };
+
+        let config = JobConf::new(u, JobKind::JobReload);
+        if let Err(e) = self.jm.notify(&config, JobMode::JobFail) {
+            log::debug!("failed to propagate reload of unit {}: {:?}", name, e);
+        }
+    }
+
+    // restart the unit {name} automatically by a restart job, such as the service with Restart= configured.
+    pub fn restart_unit(&self, name: &str) -> Result<(), MngErrno> {
+        let u = if let Some(unit) = self.db.units_get(name) {
+            unit
+        } else {
+            return Err(MngErrno::MngErrInternel);
+        };
+
+        self.jm.exec(
+            &JobConf::new(u, JobKind::JobRestart),
+            JobMode::JobReplace,
+            &mut JobAffect::new(false),
+        )?;
+        Ok(())
+    }
+
     // return the fds that trigger the unit {name};
     pub fn collect_socket_fds(&self, name: &str) -> Vec<i32> {
         let deps = self.db.dep_gets(name, UnitRelations::UnitTriggeredBy);
@@ -304,8 +548,120 @@ impl UnitManager {
         false
     }
 
+    // create the install symlinks of the unit {name}, and describe the changes
+    pub fn enable_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        install_result("enable", name, self.load.enable_unit(name, runtime))
+    }
+
+    // remove the install symlinks of the unit {name}, and describe the changes
+    pub fn disable_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        install_result("disable", name, self.load.disable_unit(name, runtime))
+    }
+
+    pub fn mask_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        install_result("mask", name, self.load.mask_unit(name, runtime))
+    }
+
+    pub fn unmask_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        install_result("unmask", name, self.load.unmask_unit(name, runtime))
+    }
+
+    pub fn preset_unit(&self, name: &str, runtime: bool) -> Result<String, MngErrno> {
+        install_result("preset", name, self.load.preset_unit(name, runtime))
+    }
+
+    // apply the preset files to all the units, on the first boot or after installing packages
+    pub fn preset_all_units(&self) -> String {
+        install_changes(&self.load.preset_all_units())
+    }
+
+    // the emergency and rescue units, which are needed even if nothing is installed
+    pub fn write_builtin_units(&self) {
+        self.load.write_builtin_units()
+    }
+
+    pub fn get_default_target(&self) -> Result<String, MngErrno> {
+        match self.load.get_default_target() {
+            Some(name) => Ok(name),
+            None => {
+                log::error!("{} is not found", DEFAULT_TARGET);
+                Err(MngErrno::MngErrNotExisted)
+            }
+        }
+    }
+
+    pub fn set_default_target(&self, name: &str) -> Result<String, MngErrno> {
+        install_result("set default to", name, self.load.set_default_target(name))
+    }
+
+    // start the unit {name} and stop all the others that are not needed by it
+    pub fn isolate_unit(&self, name: &str) -> Result<(), MngErrno> {
+        let unit = match self.load_unit(name) {
+            Some(u) => u,
+            None => return Err(MngErrno::MngErrNotExisted),
+        };
+        if !unit.get_config().config_data().borrow().Unit.AllowIsolate {
+            log::error!(
+                "unit {} may not be isolated, AllowIsolate= is not set",
+                name
+            );
+            return Err(MngErrno::MngErrInput);
+        }
+
+        self.jm.exec(
+            &JobConf::new(Rc::clone(&unit), JobKind::JobStart),
+            JobMode::JobIsolate,
+            &mut JobAffect::new(false),
+        )?;
+        Ok(())
+    }
+
+    // show the names and states of the unit {name}
+    pub fn unit_status(&self, name: &str) -> Result<String, MngErrno> {
+        let unit = match self.load_unit(name) {
+            Some(u) => u,
+            None => return Err(MngErrno::MngErrNotExisted),
+        };
+
+        let mut names = vec![unit.get_id().to_string()];
+        names.append(&mut self.db.units_get_aliases(unit.get_id()));
+        Ok(format!(
+            "{}\n    Names: {}\n   Loaded: {:?}\n   Active: {:?}\n",
+            unit.get_id(),
+            names.join(" "),
+            unit.load_state(),
+            unit.active_state()
+        ))
+    }
+
+    // show the unit files of {name}, each one is headed by its path
+    pub fn cat_unit(&self, name: &str) -> Result<String, MngErrno> {
+        let unit = match self.load_unit(name) {
+            Some(u) => u,
+            None => return Err(MngErrno::MngErrNotExisted),
+        };
+
+        let mut content = String::new();
+        for path in unit.source_paths() {
+            let file = match std::fs::read_to_string(&path) {
+                Ok(f) => f,
+                Err(e) => {
+                    log::error!("failed to read unit file {:?}: {}", path, e);
+                    return Err(MngErrno::MngErrInternel);
+                }
+            };
+            content.push_str(&format!("# {}\n{}\n", path.to_string_lossy(), file));
+        }
+
+        Ok(content)
+    }
+
     pub fn start_unit(&self, name: &str) -> Result<(), MngErrno> {
         if let Some(unit) = self.load_unit(name) {
+            if unit.load_state() == UnitLoadState::UnitMasked {
+                log::error!("unit {} is masked, refuse to start it", name);
+                return Err(MngErrno::MngErrInput);
+            }
             log::debug!("load unit success, send to job manager");
             self.jm.exec(
                 &JobConf::new(Rc::clone(&unit), JobKind::JobStart),
@@ -319,6 +675,67 @@ impl UnitManager {
         }
     }
 
+    // create the scope {name} for the processes {pids} forked by others, the
+    // {properties} are written into its transient unit file.
+    pub fn start_scope(
+        &self,
+        name: &str,
+        pids: &[i32],
+        properties: &[String],
+    ) -> Result<(), MngErrno> {
+        if !unit_name::unit_name_is_valid(name)
+            || unit_base::unit_name_to_type(name) != UnitType::UnitScope
+        {
+            log::error!("{} is not a valid scope name", name);
+            return Err(MngErrno::MngErrInput);
+        }
+        if pids.is_empty() {
+            log::error!("scope {} is created without any process", name);
+            return Err(MngErrno::MngErrInput);
+        }
+
+        // the scope which is gone can be created again
+        if let Some(unit) = self.db.units_get(name) {
+            if !IN_SET!(
+                unit.active_state(),
+                UnitActiveState::UnitInActive,
+                UnitActiveState::UnitFailed
+            ) {
+                log::error!("scope {} already exists", name);
+                return Err(MngErrno::MngErrInput);
+            }
+            self.db.unit_remove(name);
+        }
+
+        let content = match scope_unit_file(pids, properties) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("failed to create scope {}: {}", name, e);
+                return Err(MngErrno::MngErrInput);
+            }
+        };
+        if let Err(e) = self.load.write_transient_unit(name, &content) {
+            log::error!("failed to create scope {}: {}", name, e);
+            return Err(MngErrno::MngErrInternel);
+        }
+
+        let unit = match self.load_unit(name) {
+            Some(u) if u.load_state() == UnitLoadState::UnitLoaded => u,
+            _ => {
+                log::error!("failed to load scope {}", name);
+                self.load.remove_transient_unit(name);
+                return Err(MngErrno::MngErrInput);
+            }
+        };
+
+        self.jm.exec(
+            &JobConf::new(Rc::clone(&unit), JobKind::JobStart),
+            JobMode::JobReplace,
+            &mut JobAffect::new(false),
+        )?;
+        Ok(())
+    }
+
     pub fn notify_socket(&self) -> Option<PathBuf> {
         self.config.notify_sock()
     }
@@ -327,7 +744,7 @@ impl UnitManager {
         self.db.get_unit_by_pid(pid)
     }
 
-    pub(self) fn stop_unit(&self, name: &str) -> Result<(), MngErrno> {
+    pub fn stop_unit(&self, name: &str) -> Result<(), MngErrno> {
         if let Some(unit) = self.load_unit(name) {
             self.jm.exec(
                 &JobConf::new(Rc::clone(&unit), JobKind::JobStop),
@@ -341,22 +758,13 @@ impl UnitManager {
     }
 
     pub(self) fn dispatch_mountinfo(&self) -> Result<(), MngErrno> {
-        // First mark all active mount point we have as dead.
-        let mut dead_mount_set: HashSet<String> = HashSet::new();
-        for unit in self.db.units_get_all().iter() {
-            if unit.unit_type() == UnitType::UnitMount
-                && unit.active_state() == UnitActiveState::UnitActive
-            {
-                dead_mount_set.insert(String::from(unit.get_id()));
-            }
-        }
-
-        // Then start mount point we don't know.
+        // collect the mount points in /proc/self/mountinfo first.
         let mut mountinfo_content = String::new();
         File::open("/proc/self/mountinfo")
             .unwrap()
             .read_to_string(&mut mountinfo_content)
             .unwrap();
+        let mut mounted_set: HashSet<String> = HashSet::new();
         let parser = mountinfo::Parser::new(mountinfo_content.as_bytes());
         for mount_result in parser {
             match mount_result {
@@ -366,20 +774,9 @@ impl UnitManager {
                     if mount.fstype.to_str() == Some("autofs") {
                         continue;
                     }
-                    let unit_name = mount_point_to_unit_name(mount.mount_point.to_str().unwrap());
-                    if dead_mount_set.contains(unit_name.as_str()) {
-                        dead_mount_set.remove(unit_name.as_str());
-                    } else {
-                        let unit = self.load.load_unit(unit_name.as_str()).unwrap();
-                        match unit.start() {
-                            Ok(_) => {
-                                log::debug!("{} change to mounted.", unit_name)
-                            }
-                            Err(_) => {
-                                log::error!("Failed to start {}", unit_name)
-                            }
-                        }
-                    }
+                    mounted_set.insert(mount_point_to_unit_name(
+                        mount.mount_point.to_str().unwrap(),
+                    ));
                 }
                 Err(err) => {
                     log::error!("Failed to parse /proc/self/mountinfo: {}", err);
@@ -387,22 +784,60 @@ impl UnitManager {
             }
         }
 
-        // Finally stop mount point in dead_mount_set.
-        for unit_name in dead_mount_set.into_iter() {
-            if let Some(unit) = self.db.units_get(unit_name.as_str()) {
-                match unit.stop() {
-                    Ok(_) => {
-                        log::debug!("{} change to dead.", unit_name)
-                    }
-                    Err(_) => {
-                        log::error!("Failed to stop {}.", unit_name)
-                    }
+        // Then update the mount units we have, which are mounted or unmounted
+        // by themselves or by others.
+        for unit in self.db.units_get_all().iter() {
+            if unit.unit_type() == UnitType::UnitMount {
+                unit.mountinfo_update(mounted_set.remove(unit.get_id()));
+            }
+        }
+
+        // Finally load the mount points we don't know.
+        for unit_name in mounted_set.into_iter() {
+            match self.load_unit(unit_name.as_str()) {
+                Some(unit) => {
+                    log::debug!("{} change to mounted.", unit_name);
+                    unit.mountinfo_update(true);
                 }
+                None => log::error!("Failed to load {}", unit_name),
             }
         }
         Ok(())
     }
 
+    pub(self) fn dispatch_uevent(&self, action: &str, props: &HashMap<String, String>) {
+        let found = action != "remove";
+
+        // the removed devices are handled whatever they are tagged, the units are dropped
+        // only if they have been loaded.
+        if !found {
+            for name in uevent_to_unit_names(props) {
+                if let Some(unit) = self.db.units_get(&name) {
+                    log::debug!("{} is unplugged.", name);
+                    unit.device_update(false);
+                }
+            }
+            return;
+        }
+
+        let tagged = props
+            .get("TAGS")
+            .map_or(false, |tags| tags.split(':').any(|t| t == DEVICE_TAG));
+        if !tagged {
+            return;
+        }
+
+        for name in uevent_to_unit_names(props) {
+            match self.load_unit(&name) {
+                Some(unit) => {
+                    log::debug!("{} is plugged.", name);
+                    unit.device_update(true);
+                }
+                None => log::error!("Failed to load {}", name),
+            }
+        }
+    }
+
     pub(self) fn new(
         dmr: &Rc<DataManager>,
         eventr: &Rc<Events>,
@@ -410,12 +845,15 @@ impl UnitManager {
     ) -> Rc<UnitManager> {
         let _db = Rc::new(UnitDb::new());
         let _rt = Rc::new(UnitRT::new(&_db));
+        let _idle = Rc::new(ExecIdlePipe::new());
+        let _jm = JobManager::new(&_db, eventr);
+        _jm.attach_idle_pipe(&_idle);
         let um = Rc::new(UnitManager {
             load: UnitLoad::new(dmr, &_db, &_rt),
             db: Rc::clone(&_db),
             rt: Rc::clone(&_rt),
-            jm: JobManager::new(&_db, eventr),
-            exec: ExecSpawn::new(),
+            jm: _jm,
+            exec: ExecSpawn::new(&_idle),
             events: eventr.clone(),
             config: configm.clone(),
         });
@@ -468,6 +906,15 @@ impl UnitManager {
             // debug
         }
 
+        // the transient unit is gone once it is inactive, the others are not touched
+        if IN_SET!(
+            state.ns,
+            UnitActiveState::UnitInActive,
+            UnitActiveState::UnitFailed
+        ) {
+            self.load.remove_transient_unit(unitx.get_id());
+        }
+
         for other in self
             .db
             .dep_gets_atom(&unitx, UnitRelationAtom::UnitAtomTriggeredBy)
@@ -481,6 +928,56 @@ impl UnitManager {
     }
 }
 
+impl TableSubscribe<String, EmergencyAction> for UnitManager {
+    fn notify(&self, op: &TableOp<String, EmergencyAction>) {
+        if let TableOp::TableInsert(name, action) = op {
+            self.start_emergency_target(name, action);
+        }
+    }
+}
+
+impl UnitManager {
+    // the graceful emergency actions start the target, the others are taken by the manager
+    fn start_emergency_target(&self, source: &str, action: &EmergencyAction) {
+        let target = match action.action.target() {
+            Some(t) => t,
+            None => return,
+        };
+        let unit = match self.load_unit(target) {
+            Some(u) => u,
+            None => {
+                log::error!("failed to load {} requested by {}", target, source);
+                return;
+            }
+        };
+
+        // the emergency shell replaces everything, and the shutdown can not be cancelled
+        let mode = if action.action == UnitEmergencyAction::Emergency {
+            JobMode::JobIsolate
+        } else {
+            JobMode::JobReplaceIrreversible
+        };
+        if let Err(e) = self.jm.exec(
+            &JobConf::new(Rc::clone(&unit), JobKind::JobStart),
+            mode,
+            &mut JobAffect::new(false),
+        ) {
+            log::error!(
+                "failed to start {} requested by {}: {:?}",
+                target,
+                source,
+                e
+            );
+            return;
+        }
+
+        // the argument is saved once the reboot is queued, and dropped if the job does not finish
+        if action.action.is_reboot() {
+            write_reboot_argument(&action.reboot_argument);
+        }
+    }
+}
+
 pub trait UnitMngUtil {
     fn attach(&self, um: Rc<UnitManager>);
 }
@@ -512,14 +1009,16 @@ mod unit_load {
     use super::UnitManager;
     use crate::manager::data::{DataManager, UnitDepConf};
     use crate::manager::table::{TableOp, TableSubscribe};
-    use crate::manager::unit::uload_util::UnitFile;
+    use crate::manager::unit::uload_util::{UnitFile, DEFAULT_TARGET};
     use crate::manager::unit::unit_base::{self, UnitType};
     use crate::manager::unit::unit_datastore::UnitDb;
-    use crate::manager::unit::unit_entry::UnitX;
+    use crate::manager::unit::unit_entry::{unit_default_instance, UnitX};
     use crate::manager::unit::unit_runtime::UnitRT;
     use crate::plugin::Plugin;
     use std::cell::RefCell;
+    use std::path::PathBuf;
     use std::rc::{Rc, Weak};
+    use utils::unit_name;
 
     //#[derive(Debug)]
     pub(super) struct UnitLoad {
@@ -541,6 +1040,70 @@ mod unit_load {
             self.data.load_unit(name)
         }
 
+        pub(super) fn enable_unit(
+            &self,
+            name: &str,
+            runtime: bool,
+        ) -> Result<Vec<PathBuf>, String> {
+            self.data.enable_unit(name, runtime)
+        }
+
+        pub(super) fn disable_unit(
+            &self,
+            name: &str,
+            runtime: bool,
+        ) -> Result<Vec<PathBuf>, String> {
+            self.data.disable_unit(name, runtime)
+        }
+
+        pub(super) fn mask_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+            self.data.mask_unit(name, runtime)
+        }
+
+        pub(super) fn unmask_unit(
+            &self,
+            name: &str,
+            runtime: bool,
+        ) -> Result<Vec<PathBuf>, String> {
+            self.data.unmask_unit(name, runtime)
+        }
+
+        pub(super) fn preset_unit(
+            &self,
+            name: &str,
+            runtime: bool,
+        ) -> Result<Vec<PathBuf>, String> {
+            self.data.preset_unit(name, runtime)
+        }
+
+        pub(super) fn preset_all_units(&self) -> Vec<PathBuf> {
+            self.data.preset_all_units()
+        }
+
+        pub(super) fn write_builtin_units(&self) {
+            self.data.write_builtin_units()
+        }
+
+        pub(super) fn get_default_target(&self) -> Option<String> {
+            self.data.get_default_target()
+        }
+
+        pub(super) fn set_default_target(&self, name: &str) -> Result<Vec<PathBuf>, String> {
+            self.data.set_default_target(name)
+        }
+
+        pub(super) fn write_transient_unit(
+            &self,
+            name: &str,
+            content: &str,
+        ) -> Result<PathBuf, String> {
+            self.data.write_transient_unit(name, content)
+        }
+
+        pub(super) fn remove_transient_unit(&self, name: &str) {
+            self.data.remove_transient_unit(name)
+        }
+
         pub(super) fn set_um(&self, um: &Rc<UnitManager>) {
             self.data.set_um(um);
         }
@@ -587,6 +1150,17 @@ mod unit_load {
         }
 
         pub(self) fn prepare_unit(&self, name: &str) -> Option<Rc<UnitX>> {
+            // an alias shares the unit of the unit file its symlink points to
+            let id = self.file.unit_name_resolve(name);
+            if id != name {
+                let unit = match self.db.units_get(&id) {
+                    Some(u) => u,
+                    None => self.prepare_unit(&id)?,
+                };
+                self.db.units_insert_alias(name.to_string(), id);
+                return Some(unit);
+            }
+
             match self.try_new_unit(name) {
                 Some(unit) => {
                     self.db.units_insert(name.to_string(), Rc::clone(&unit));
@@ -605,18 +1179,20 @@ mod unit_load {
         }
 
         pub(self) fn push_dep_unit_into_load_queue(&self, name: &str) -> Option<Rc<UnitX>> {
-            if let Some(unit) = self.db.units_get(name) {
+            let name = self.instance_name(name)?;
+            if let Some(unit) = self.db.units_get(&name) {
                 return Some(Rc::clone(&unit));
             };
-            let unit = self.prepare_unit(name);
+            let unit = self.prepare_unit(&name);
             unit
         }
 
         pub(self) fn load_unit(&self, name: &str) -> Option<Rc<UnitX>> {
-            if let Some(unit) = self.db.units_get(name) {
+            let name = self.instance_name(name)?;
+            if let Some(unit) = self.db.units_get(&name) {
                 return Some(Rc::clone(&unit));
             };
-            let unit = self.prepare_unit(name);
+            let unit = self.prepare_unit(&name);
             let u = if let Some(u) = unit {
                 u
             } else {
@@ -631,6 +1207,103 @@ mod unit_load {
             self.um.replace(Rc::downgrade(um));
         }
 
+        pub(self) fn enable_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+            let id = self.file.unit_name_resolve(name);
+            let changes = self.file.enable_unit(&id, runtime)?;
+            self.update_aliases(&id);
+            Ok(changes)
+        }
+
+        pub(self) fn disable_unit(
+            &self,
+            name: &str,
+            runtime: bool,
+        ) -> Result<Vec<PathBuf>, String> {
+            let id = self.file.unit_name_resolve(name);
+            let changes = self.file.disable_unit(&id, runtime)?;
+            self.update_aliases(&id);
+            Ok(changes)
+        }
+
+        // the name itself is masked, not the unit it may be an alias of
+        pub(self) fn mask_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+            self.file.mask_unit(name, runtime)
+        }
+
+        pub(self) fn unmask_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+            self.file.unmask_unit(name, runtime)
+        }
+
+        pub(self) fn preset_unit(&self, name: &str, runtime: bool) -> Result<Vec<PathBuf>, String> {
+            let id = self.file.unit_name_resolve(name);
+            let changes = self.file.preset_unit(&id, runtime)?;
+            self.update_aliases(&id);
+            Ok(changes)
+        }
+
+        pub(self) fn preset_all_units(&self) -> Vec<PathBuf> {
+            self.file.preset_all_units(false)
+        }
+
+        pub(self) fn write_builtin_units(&self) {
+            self.file.write_builtin_units()
+        }
+
+        pub(self) fn get_default_target(&self) -> Option<String> {
+            self.file.get_default_target()
+        }
+
+        // default.target is resolved again the next time it is loaded
+        pub(self) fn set_default_target(&self, name: &str) -> Result<Vec<PathBuf>, String> {
+            let changes = self.file.set_default_target(name)?;
+            if !changes.is_empty() {
+                self.db.units_remove_alias(DEFAULT_TARGET);
+            }
+            Ok(changes)
+        }
+
+        pub(self) fn write_transient_unit(
+            &self,
+            name: &str,
+            content: &str,
+        ) -> Result<PathBuf, String> {
+            self.file.write_transient_unit(name, content)
+        }
+
+        pub(self) fn remove_transient_unit(&self, name: &str) {
+            self.file.remove_transient_unit(name)
+        }
+
+        // keep the aliases of the loaded units in step with the Alias= symlinks
+        fn update_aliases(&self, id: &str) {
+            for u in self.file.install_units(id) {
+                for alias in self.file.unit_aliases(&u) {
+                    if self.db.units_get(&u).is_some() && self.file.unit_name_resolve(&alias) == u {
+                        self.db.units_insert_alias(alias, u.clone());
+                    } else {
+                        self.db.units_remove_alias(&alias);
+                    }
+                }
+            }
+        }
+
+        // a template can not be loaded itself, it is instantiated with its DefaultInstance=
+        fn instance_name(&self, name: &str) -> Option<String> {
+            if !unit_name::unit_name_is_template(name) {
+                return Some(name.to_string());
+            }
+
+            let instance = match unit_default_instance(&self.file, name) {
+                Some(i) => i,
+                None => {
+                    log::error!("template unit {} has no DefaultInstance=", name);
+                    return None;
+                }
+            };
+
+            unit_name::unit_name_replace_instance(name, &instance)
+        }
+
         fn try_new_unit(&self, name: &str) -> Option<Rc<UnitX>> {
             let unit_type = unit_base::unit_name_to_type(name);
             if unit_type == UnitType::UnitTypeInvalid {
@@ -718,7 +1391,9 @@ mod tests {
     // use services::service::ServiceUnit;
 
     use super::*;
+    use crate::manager::data::UnitNotifyFlags;
     use event::Events;
+    use std::cell::RefCell;
     use utils::logger;
 
     fn init_dm_for_test() -> (Rc<DataManager>, Rc<Events>, Rc<UnitManager>) {
@@ -730,6 +1405,33 @@ mod tests {
         (dm_manager, _event, um)
     }
 
+    #[test]
+    fn test_uevent_to_unit_names() {
+        let mut props = HashMap::new();
+        props.insert(
+            "DEVPATH".to_string(),
+            "/devices/virtual/block/loop0".to_string(),
+        );
+        props.insert("DEVNAME".to_string(), "loop0".to_string());
+        assert_eq!(
+            uevent_to_unit_names(&props),
+            vec![
+                "sys-devices-virtual-block-loop0.device".to_string(),
+                "dev-loop0.device".to_string()
+            ]
+        );
+
+        props.insert("DEVNAME".to_string(), "/dev/sda1".to_string());
+        props.insert(
+            "DEVLINKS".to_string(),
+            "/dev/disk/by-uuid/1234 /dev/disk/by-label/root".to_string(),
+        );
+        let names = uevent_to_unit_names(&props);
+        assert_eq!(names.len(), 4);
+        assert_eq!(names[1], "dev-sda1.device");
+        assert_eq!(names[2], "dev-disk-by\\x2duuid-1234.device");
+    }
+
     #[test]
     fn test_service_unit_load() {
         logger::init_log_with_console("test_service_unit_load", 4);
@@ -784,6 +1486,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_isolate_unit_not_allowed() {
+        let dm = init_dm_for_test();
+        // neither the target nor the service sets AllowIsolate=
+        assert!(matches!(
+            dm.2.isolate_unit("testsunit.target"),
+            Err(MngErrno::MngErrInput)
+        ));
+        assert!(matches!(
+            dm.2.isolate_unit("config.service"),
+            Err(MngErrno::MngErrInput)
+        ));
+    }
+
     #[test]
     fn test_units_load() {
         logger::init_log_with_console("test_units_load", 4);
@@ -828,4 +1544,77 @@ mod tests {
             };
         }
     }
+
+    #[test]
+    fn test_unit_emergency_action() {
+        logger::init_log_with_console("test_unit_emergency_action", 4);
+        let dm = Rc::new(DataManager::new());
+        let event = Rc::new(Events::new().unwrap());
+        let configm = Rc::new(ManagerConfig::new());
+        // the unit state and the emergency action are subscribed by UnitManagerX
+        let umx = UnitManagerX::new(&dm, &event, &configm);
+        let um = Rc::clone(&umx.data);
+        let actions = Rc::new(EmergencyActionsTest::new());
+        dm.register_emergency_action("test", Rc::clone(&actions) as Rc<_>);
+
+        let name = "base.target";
+        let unit = um.load_unit(name).unwrap();
+        {
+            let config = unit.get_config().config_data();
+            let conf = &mut config.borrow_mut().Unit;
+            conf.FailureAction = UnitEmergencyAction::Exit;
+            conf.StartLimitAction = UnitEmergencyAction::Halt;
+            conf.StartLimitIntervalSec = 10;
+            conf.StartLimitBurst = 1;
+        }
+
+        // FailureAction= is taken once the unit fails
+        dm.insert_unit_state(
+            name.to_string(),
+            UnitState::new(
+                UnitActiveState::UnitActive,
+                UnitActiveState::UnitFailed,
+                UnitNotifyFlags::empty(),
+            ),
+        );
+        assert_eq!(actions.get(name), Some(UnitEmergencyAction::Exit));
+
+        // StartLimitAction= is taken once the start job is refused by the start limit
+        um.start_unit(name).unwrap();
+        event.run(0).unwrap();
+        assert_eq!(unit.active_state(), UnitActiveState::UnitActive);
+        unit.stop().unwrap();
+        assert_eq!(actions.get(name), Some(UnitEmergencyAction::Exit));
+
+        um.start_unit(name).unwrap();
+        event.run(0).unwrap();
+        assert_eq!(unit.active_state(), UnitActiveState::UnitInActive);
+        assert_eq!(actions.get(name), Some(UnitEmergencyAction::Halt));
+    }
+
+    struct EmergencyActionsTest {
+        actions: RefCell<HashMap<String, UnitEmergencyAction>>,
+    }
+
+    impl EmergencyActionsTest {
+        fn new() -> EmergencyActionsTest {
+            EmergencyActionsTest {
+                actions: RefCell::new(HashMap::new()),
+            }
+        }
+
+        fn get(&self, name: &str) -> Option<UnitEmergencyAction> {
+            self.actions.borrow().get(name).copied()
+        }
+    }
+
+    impl TableSubscribe<String, EmergencyAction> for EmergencyActionsTest {
+        fn notify(&self, op: &TableOp<String, EmergencyAction>) {
+            if let TableOp::TableInsert(name, action) = op {
+                self.actions
+                    .borrow_mut()
+                    .insert(name.to_string(), action.action);
+            }
+        }
+    }
 }
@@ -360,6 +360,11 @@ impl Plugin {
         }
     }
 
+    /// whether the plugin of the unit type is loaded, the units of an absent type can not be loaded
+    pub fn unit_type_supported(&self, unit_type: UnitType) -> bool {
+        (*self.load_libs.read().unwrap()).contains_key(&unit_type)
+    }
+
     pub fn create_unit_obj(
         &self,
         unit_type: UnitType,
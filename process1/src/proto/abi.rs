@@ -2,7 +2,7 @@
 #[rustfmt::skip]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CommandRequest {
-    #[prost(oneof="command_request::RequestData", tags="1, 2, 3, 4, 5")]
+    #[prost(oneof="command_request::RequestData", tags="1, 2, 3, 4, 5, 6")]
     pub request_data: ::core::option::Option<command_request::RequestData>,
 }
 /// Nested message and enum types in `CommandRequest`.
@@ -25,6 +25,9 @@ pub mod command_request {
         ///system commands, reboot/shutdown/halt
         #[prost(message, tag="5")]
         Syscomm(super::SysComm),
+        ///create scope units for the processes forked by others
+        #[prost(message, tag="6")]
+        Scomm(super::ScopeComm),
     }
 }
 /// 服务端的响应
@@ -58,6 +61,7 @@ pub mod unit_comm {
         Restart = 3,
         Reload = 4,
         Kill = 5,
+        Isolate = 6,
     }
 }
 #[rustfmt::skip]
@@ -67,6 +71,8 @@ pub struct UnitFile {
     pub action: i32,
     #[prost(string, tag="2")]
     pub unitname: ::prost::alloc::string::String,
+    #[prost(bool, tag="3")]
+    pub runtime: bool,
 }
 /// Nested message and enum types in `UnitFile`.
 pub mod unit_file {
@@ -80,6 +86,9 @@ pub mod unit_file {
         Mask = 3,
         Getdef = 4,
         Setdef = 5,
+        Unmask = 6,
+        Preset = 7,
+        PresetAll = 8,
     }
 }
 #[rustfmt::skip]
@@ -136,3 +145,15 @@ pub mod sys_comm {
         Hibernate = 5,
     }
 }
+/// the scope {unitname} is created with the processes {pids}, and the
+/// {properties} in the form of "Key=Value", such as "RuntimeMaxSec=1h".
+#[rustfmt::skip]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScopeComm {
+    #[prost(string, tag="1")]
+    pub unitname: ::prost::alloc::string::String,
+    #[prost(int32, repeated, tag="2")]
+    pub pids: ::prost::alloc::vec::Vec<i32>,
+    #[prost(string, repeated, tag="3")]
+    pub properties: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
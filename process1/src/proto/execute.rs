@@ -1,7 +1,8 @@
 use super::{
-    sys_comm, unit_comm, CommandRequest, CommandResponse, MngrComm, RequestData, SysComm, UnitComm,
+    sys_comm, unit_comm, unit_file, CommandRequest, CommandResponse, MngrComm, RequestData,
+    ScopeComm, SysComm, UnitComm, UnitFile,
 };
-use crate::manager::Manager;
+use crate::manager::{Manager, MngErrno};
 use http::StatusCode;
 use nix::sys::reboot::RebootMode;
 use std::rc::Rc;
@@ -15,8 +16,10 @@ pub(crate) fn dispatch(cmd: CommandRequest, manager: Rc<Manager>) -> CommandResp
     println!("commandRequest :{:?}", cmd);
     let res = match cmd.request_data {
         Some(RequestData::Ucomm(param)) => param.execute(manager),
+        Some(RequestData::Ufile(param)) => param.execute(manager),
         Some(RequestData::Mcomm(param)) => param.execute(manager),
         Some(RequestData::Syscomm(param)) => param.execute(manager),
+        Some(RequestData::Scomm(param)) => param.execute(manager),
         _ => CommandResponse::default(),
     };
     println!("CommandResponse :{:?}", res);
@@ -26,14 +29,45 @@ pub(crate) fn dispatch(cmd: CommandRequest, manager: Rc<Manager>) -> CommandResp
 impl Executer for UnitComm {
     fn execute(self, manager: Rc<Manager>) -> CommandResponse {
         let ret = match self.action() {
-            unit_comm::Action::Start => manager.start_unit(&self.unitname),
-            unit_comm::Action::Stop => manager.stop_unit(&self.unitname),
-            _ => todo!(),
+            unit_comm::Action::Start => manager.start_unit(&self.unitname).map(|_| String::new()),
+            unit_comm::Action::Stop => manager.stop_unit(&self.unitname).map(|_| String::new()),
+            unit_comm::Action::Status => manager.unit_status(&self.unitname),
+            unit_comm::Action::Isolate => {
+                manager.isolate_unit(&self.unitname).map(|_| String::new())
+            }
+            _ => Err(MngErrno::MngErrNotSupported),
         };
         match ret {
-            Ok(_) => CommandResponse {
+            Ok(message) => CommandResponse {
                 status: StatusCode::OK.as_u16() as _,
-                ..Default::default()
+                message,
+            },
+            Err(MngErrno::MngErrNotSupported) => not_supported(self.action),
+            Err(_e) => CommandResponse {
+                status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
+                message: String::from("error."),
+            },
+        }
+    }
+}
+
+impl Executer for UnitFile {
+    fn execute(self, manager: Rc<Manager>) -> CommandResponse {
+        let ret = match self.action() {
+            unit_file::Action::Cat => manager.cat_unit(&self.unitname),
+            unit_file::Action::Enable => manager.enable_unit(&self.unitname, self.runtime),
+            unit_file::Action::Disable => manager.disable_unit(&self.unitname, self.runtime),
+            unit_file::Action::Mask => manager.mask_unit(&self.unitname, self.runtime),
+            unit_file::Action::Unmask => manager.unmask_unit(&self.unitname, self.runtime),
+            unit_file::Action::Preset => manager.preset_unit(&self.unitname, self.runtime),
+            unit_file::Action::PresetAll => Ok(manager.preset_all_units()),
+            unit_file::Action::Getdef => manager.get_default_target(),
+            unit_file::Action::Setdef => manager.set_default_target(&self.unitname),
+        };
+        match ret {
+            Ok(content) => CommandResponse {
+                status: StatusCode::OK.as_u16() as _,
+                message: content,
             },
             Err(_e) => CommandResponse {
                 status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
@@ -45,7 +79,7 @@ impl Executer for UnitComm {
 
 impl Executer for MngrComm {
     fn execute(self, _manager: Rc<Manager>) -> CommandResponse {
-        todo!()
+        not_supported(self.action)
     }
 }
 
@@ -58,7 +92,6 @@ impl Executer for SysComm {
             sys_comm::Action::Poweroff => manager.reboot(RebootMode::RB_POWER_OFF),
             sys_comm::Action::Shutdown => manager.reboot(RebootMode::RB_POWER_OFF),
             sys_comm::Action::Reboot => manager.reboot(RebootMode::RB_AUTOBOOT),
-            _ => todo!(),
         };
         match ret {
             Ok(_) => CommandResponse {
@@ -72,3 +105,26 @@ impl Executer for SysComm {
         }
     }
 }
+
+impl Executer for ScopeComm {
+    fn execute(self, manager: Rc<Manager>) -> CommandResponse {
+        match manager.start_scope(&self.unitname, &self.pids, &self.properties) {
+            Ok(_) => CommandResponse {
+                status: StatusCode::OK.as_u16() as _,
+                ..Default::default()
+            },
+            Err(_e) => CommandResponse {
+                status: StatusCode::INTERNAL_SERVER_ERROR.as_u16() as _,
+                message: String::from("error."),
+            },
+        }
+    }
+}
+
+// the request is valid but the manager can not handle it yet
+fn not_supported(action: i32) -> CommandResponse {
+    CommandResponse {
+        status: StatusCode::NOT_IMPLEMENTED.as_u16() as _,
+        message: format!("action {} is not supported.", action),
+    }
+}
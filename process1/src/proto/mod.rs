@@ -20,6 +20,20 @@ impl CommandRequest {
         }
     }
 
+    pub fn new_unitfile(
+        action: unit_file::Action,
+        unitname: impl Into<String>,
+        runtime: bool,
+    ) -> Self {
+        Self {
+            request_data: Some(RequestData::Ufile(UnitFile {
+                action: action.into(),
+                unitname: unitname.into(),
+                runtime,
+            })),
+        }
+    }
+
     pub fn new_mngrcomm(action: mngr_comm::Action) -> Self {
         Self {
             request_data: Some(RequestData::Mcomm(MngrComm {
@@ -28,6 +42,20 @@ impl CommandRequest {
         }
     }
 
+    pub fn new_scopecomm(
+        unitname: impl Into<String>,
+        pids: Vec<i32>,
+        properties: Vec<String>,
+    ) -> Self {
+        Self {
+            request_data: Some(RequestData::Scomm(ScopeComm {
+                unitname: unitname.into(),
+                pids,
+                properties,
+            })),
+        }
+    }
+
     pub fn new_syscomm(action: sys_comm::Action) -> Self {
         Self {
             request_data: Some(RequestData::Syscomm(SysComm {
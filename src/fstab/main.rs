@@ -1,14 +1,20 @@
 use inotify::{EventMask, Inotify, WatchMask};
 use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::os::unix::fs::symlink;
 use std::path::Path;
 use std::process::Command;
+use toml::value::{Table, Value};
+use utils::{path_lookup, unit_name};
 
 pub mod fstab_item;
 use fstab_item::FSTabItem;
 
 const MOUNT_BIN: &str = "/usr/bin/mount";
-const SWAP_BIN: &str = "/usr/sbin/swapon";
 const FSTAB_PATH: &str = "/etc/fstab";
+const UNIT_DIR: &str = path_lookup::RUN_SYSTEM_PATH;
+const SWAP_WANTS_DIR: &str = "swap.target.wants";
 
 fn mount_one(fstab_item: &FSTabItem) -> i32 {
     let mount_status;
@@ -55,40 +61,59 @@ fn mount_one(fstab_item: &FSTabItem) -> i32 {
     }
 }
 
-fn swap_on(fstab_item: &FSTabItem) -> i32 {
-    match Command::new(SWAP_BIN)
-        .args([&fstab_item.device_spec])
-        .status()
-    {
-        Ok(status) => {
-            if let Some(r) = status.code() {
-                if r != 0 {
-                    log::error!(
-                        "Failed to swapon {}, exitcode: {}",
-                        &fstab_item.device_spec,
-                        r
-                    );
-                } else {
-                    log::info!("Swapped on {}", &fstab_item.device_spec);
-                }
-                r
-            } else {
-                log::error!("Unexpected error when swapon {}", &fstab_item.device_spec);
-                -1
-            }
+// the swap entries are turned into the swap units, which are pulled in by swap.target
+fn swap_unit(fstab_item: &FSTabItem) -> i32 {
+    let name = unit_name::unit_name_from_path(&fstab_item.device_spec, "swap");
+    let content = swap_unit_content(fstab_item);
+
+    let unit_path = Path::new(UNIT_DIR).join(&name);
+    let wants_dir = Path::new(UNIT_DIR).join(SWAP_WANTS_DIR);
+    let result = fs::create_dir_all(&wants_dir)
+        .and_then(|_| fs::write(&unit_path, content))
+        .and_then(|_| match symlink(&unit_path, wants_dir.join(&name)) {
+            Err(e) if e.kind() != io::ErrorKind::AlreadyExists => Err(e),
+            _ => Ok(()),
+        });
+    match result {
+        Ok(_) => {
+            log::info!("Created {} for {}", name, &fstab_item.device_spec);
+            0
         }
-        Err(_) => {
-            log::error!("Failed to executing {}!", SWAP_BIN);
+        Err(e) => {
+            log::error!("Failed to create {}: {}", name, e);
             -1
         }
     }
 }
 
+// the unit file of the swap entry, the values are escaped by the toml serializer
+fn swap_unit_content(fstab_item: &FSTabItem) -> String {
+    let mut unit = Table::new();
+    unit.insert(
+        "Description".to_string(),
+        Value::String(format!("swap {}", fstab_item.device_spec)),
+    );
+
+    let mut swap = Table::new();
+    swap.insert(
+        "What".to_string(),
+        Value::String(fstab_item.device_spec.clone()),
+    );
+    if fstab_item.options != "defaults" {
+        swap.insert(
+            "Options".to_string(),
+            Value::String(fstab_item.options.clone()),
+        );
+    }
+
+    let mut content = Table::new();
+    content.insert("Unit".to_string(), Value::Table(unit));
+    content.insert("Swap".to_string(), Value::Table(swap));
+    Value::Table(content).to_string()
+}
+
 fn consume_one(fstab_item: &mut FSTabItem) {
-    let r = match fstab_item.fs_type.as_str() {
-        "swap" => swap_on(&fstab_item),
-        _ => mount_one(&fstab_item),
-    };
+    let r = mount_one(&fstab_item);
     // set state to 1 if succeeded, -1 if failed.
     fstab_item.state = if r == 0 { 1 } else { -1 };
 }
@@ -110,7 +135,15 @@ fn watch_devices(fstab_items: &Vec<FSTabItem>) -> (Inotify, HashSet<String>) {
 }
 
 fn main() {
-    let mut fstab_items: Vec<FSTabItem> = fstab_item::parse(FSTAB_PATH);
+    let (swap_items, mut fstab_items): (Vec<FSTabItem>, Vec<FSTabItem>) =
+        fstab_item::parse(FSTAB_PATH)
+            .into_iter()
+            .partition(|item| item.fs_type == "swap");
+
+    // the swaps are activated by process1, no need to wait for the devices here.
+    for swap_item in &swap_items {
+        swap_unit(swap_item);
+    }
 
     // inotify: monitor, watch_set: what we care.
     let (mut inotify, watch_set) = watch_devices(&fstab_items);
@@ -147,3 +180,34 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::fstab_item::FSTabItem;
+    use super::swap_unit_content;
+
+    #[test]
+    fn test_swap_unit_content() {
+        let item = FSTabItem::new(vec!["/dev/sda2", "none", "swap", "defaults", "0", "0"]);
+        let value: toml::Value = toml::from_str(&swap_unit_content(&item)).unwrap();
+        assert_eq!(
+            value["Unit"]["Description"].as_str(),
+            Some("swap /dev/sda2")
+        );
+        assert_eq!(value["Swap"]["What"].as_str(), Some("/dev/sda2"));
+        assert!(value["Swap"].get("Options").is_none());
+
+        // the quotes and backslashes are kept as they are
+        let item = FSTabItem::new(vec![
+            "/swap\\\"file",
+            "none",
+            "swap",
+            "pri=1,x-\"a\\b\"",
+            "0",
+            "0",
+        ]);
+        let value: toml::Value = toml::from_str(&swap_unit_content(&item)).unwrap();
+        assert_eq!(value["Swap"]["What"].as_str(), Some("/swap\\\"file"));
+        assert_eq!(value["Swap"]["Options"].as_str(), Some("pri=1,x-\"a\\b\""));
+    }
+}
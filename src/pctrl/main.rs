@@ -2,7 +2,7 @@ use clap::Parser;
 use std::net::TcpStream;
 
 use process1::proto::{
-    abi::{sys_comm, unit_comm, CommandRequest},
+    abi::{sys_comm, unit_comm, unit_file, CommandRequest},
     ProstClientStream,
 };
 use utils::Error;
@@ -35,6 +35,90 @@ enum SubCmd {
     #[clap(display_order = 3)]
     Status { unit_name: Option<String> },
 
+    /// [unit] show the unit files of the unit
+    #[clap(display_order = 4)]
+    Cat { unit_name: Option<String> },
+
+    /// [unit] start the unit and stop all the others not needed by it
+    #[clap(display_order = 4)]
+    Isolate { unit_name: Option<String> },
+
+    /// [unit] enable the unit
+    #[clap(display_order = 5)]
+    Enable {
+        unit_name: Option<String>,
+
+        /// enable the unit until the next reboot only
+        #[clap(long)]
+        runtime: bool,
+    },
+
+    /// [unit] disable the unit
+    #[clap(display_order = 6)]
+    Disable {
+        unit_name: Option<String>,
+
+        /// disable the unit enabled with --runtime
+        #[clap(long)]
+        runtime: bool,
+    },
+
+    /// [unit] mask the unit, which can not be started then
+    #[clap(display_order = 7)]
+    Mask {
+        unit_name: Option<String>,
+
+        /// mask the unit until the next reboot only
+        #[clap(long)]
+        runtime: bool,
+    },
+
+    /// [unit] unmask the unit
+    #[clap(display_order = 8)]
+    Unmask {
+        unit_name: Option<String>,
+
+        /// unmask the unit masked with --runtime
+        #[clap(long)]
+        runtime: bool,
+    },
+
+    /// [unit] enable or disable the unit according to the preset files
+    #[clap(display_order = 9)]
+    Preset {
+        unit_name: Option<String>,
+
+        /// apply the preset until the next reboot only
+        #[clap(long)]
+        runtime: bool,
+    },
+
+    /// [unit] enable or disable all the units according to the preset files
+    #[clap(display_order = 10)]
+    PresetAll {},
+
+    /// [unit] show the target started on boot
+    #[clap(display_order = 11)]
+    GetDefault {},
+
+    /// [unit] set the target started on boot
+    #[clap(display_order = 12)]
+    SetDefault { unit_name: Option<String> },
+
+    /// [unit] create a scope of the existing processes and start it
+    #[clap(display_order = 13)]
+    Scope {
+        unit_name: Option<String>,
+
+        /// the pid of the process moved into the scope, can be given several times
+        #[clap(long = "pid", required = true)]
+        pids: Vec<i32>,
+
+        /// the property of the scope in the form of "Key=Value", such as "RuntimeMaxSec=1h"
+        #[clap(short, long = "property")]
+        properties: Vec<String>,
+    },
+
     /// [system] shutdown the system
     Shutdown {},
 
@@ -45,11 +129,14 @@ enum SubCmd {
 enum CommAction {
     UnitAction(unit_comm::Action),
     SysAction(sys_comm::Action),
+    FileAction(unit_file::Action),
+    ScopeAction(Vec<i32>, Vec<String>),
 }
 
 fn main() -> Result<(), Error> {
     let args = Args::parse();
 
+    let mut runtime = false;
     let (action, unit_name) = match args.subcmd {
         SubCmd::Start { unit_name } => {
             (CommAction::UnitAction(unit_comm::Action::Start), unit_name)
@@ -58,6 +145,65 @@ fn main() -> Result<(), Error> {
         SubCmd::Status { unit_name } => {
             (CommAction::UnitAction(unit_comm::Action::Status), unit_name)
         }
+        SubCmd::Isolate { unit_name } => (
+            CommAction::UnitAction(unit_comm::Action::Isolate),
+            unit_name,
+        ),
+        SubCmd::Cat { unit_name } => (CommAction::FileAction(unit_file::Action::Cat), unit_name),
+        SubCmd::Enable {
+            unit_name,
+            runtime: r,
+        } => {
+            runtime = r;
+            (CommAction::FileAction(unit_file::Action::Enable), unit_name)
+        }
+        SubCmd::Disable {
+            unit_name,
+            runtime: r,
+        } => {
+            runtime = r;
+            (
+                CommAction::FileAction(unit_file::Action::Disable),
+                unit_name,
+            )
+        }
+        SubCmd::Mask {
+            unit_name,
+            runtime: r,
+        } => {
+            runtime = r;
+            (CommAction::FileAction(unit_file::Action::Mask), unit_name)
+        }
+        SubCmd::Unmask {
+            unit_name,
+            runtime: r,
+        } => {
+            runtime = r;
+            (CommAction::FileAction(unit_file::Action::Unmask), unit_name)
+        }
+        SubCmd::Preset {
+            unit_name,
+            runtime: r,
+        } => {
+            runtime = r;
+            (CommAction::FileAction(unit_file::Action::Preset), unit_name)
+        }
+        SubCmd::PresetAll {} => (
+            CommAction::FileAction(unit_file::Action::PresetAll),
+            Some(String::new()),
+        ),
+        SubCmd::GetDefault {} => (
+            CommAction::FileAction(unit_file::Action::Getdef),
+            Some(String::new()),
+        ),
+        SubCmd::SetDefault { unit_name } => {
+            (CommAction::FileAction(unit_file::Action::Setdef), unit_name)
+        }
+        SubCmd::Scope {
+            unit_name,
+            pids,
+            properties,
+        } => (CommAction::ScopeAction(pids, properties), unit_name),
         SubCmd::Shutdown {} => (CommAction::SysAction(sys_comm::Action::Shutdown), None),
         _ => unreachable!(),
     };
@@ -75,6 +221,18 @@ fn main() -> Result<(), Error> {
             let data = client.execute(cmd).unwrap();
             println!("{:?}", data);
         }
+        CommAction::FileAction(a) => {
+            let cmd = CommandRequest::new_unitfile(a, unit_name.unwrap(), runtime);
+            println!("{:?}", cmd);
+            let data = client.execute(cmd).unwrap();
+            println!("{}", data.message);
+        }
+        CommAction::ScopeAction(pids, properties) => {
+            let cmd = CommandRequest::new_scopecomm(unit_name.unwrap(), pids, properties);
+            println!("{:?}", cmd);
+            let data = client.execute(cmd).unwrap();
+            println!("{:?}", data);
+        }
         CommAction::SysAction(a) => {
             let cmd = CommandRequest::new_syscomm(a);
             println!("{:?}", cmd);
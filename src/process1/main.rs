@@ -5,6 +5,8 @@ use process1::manager::{Action, ManagerX, Mode, Stats};
 use process1::mount::mount_setup;
 use utils::logger;
 
+const EMERGENCY_TARGET: &str = "emergency.target";
+
 fn main() -> Result<(), Box<dyn Error>> {
     logger::init_log_with_console("process1", 4);
     info!("process1 running in system mode.");
@@ -30,7 +32,19 @@ fn main() -> Result<(), Box<dyn Error>> {
     manager.startup().unwrap();
     manager.add_job(0).unwrap();
 
-    manager.start_unit("basic.target");
+    let target = manager.boot_target();
+    info!("starting the boot target {}", target);
+    if let Err(e) = manager.start_unit(&target) {
+        // land on the emergency shell instead of an idle loop when the boot target is broken
+        log::error!(
+            "failed to start {}: {:?}, entering emergency mode",
+            target,
+            e
+        );
+        if let Err(e) = manager.start_unit(EMERGENCY_TARGET) {
+            log::error!("failed to start {}: {:?}", EMERGENCY_TARGET, e);
+        }
+    }
     match manager.rloop() {
         Ok(Stats::REEXECUTE) => manager.reexec()?,
         Ok(_) => todo!(),
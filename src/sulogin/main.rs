@@ -0,0 +1,73 @@
+use std::ffi::CString;
+use std::{env, process};
+mod sulogin;
+use crate::sulogin::RootPassword;
+use nix::unistd;
+use utils::logger;
+
+fn exec_shell() -> ! {
+    let shell = sulogin::read_root_shell();
+    let name = shell.rsplit('/').next().unwrap_or("sh");
+
+    // the shell started with a leading '-' is a login shell
+    let path = CString::new(shell.clone()).unwrap_or_default();
+    let arg0 = CString::new(format!("-{}", name)).unwrap_or_default();
+    env::set_var("HOME", "/root");
+    env::set_var("SHELL", &shell);
+    let _ = env::set_current_dir("/root");
+
+    let e = unistd::execv(&path, &[arg0]).unwrap_err();
+    log::error!("failed to execute the shell {}: {}", shell, e);
+    process::exit(1);
+}
+
+fn main() {
+    logger::init_log_with_console("sulogin", 4);
+    let mode = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "emergency".to_string());
+
+    if let Err(e) = sulogin::setup_console() {
+        log::error!("failed to open {}: {}", sulogin::CONSOLE, e);
+        process::exit(1);
+    }
+
+    println!(
+        "\nYou are in {} mode. After logging in, type \"pctrl start default.target\" \
+         or press Control-D to try again to boot into the default mode.",
+        mode
+    );
+
+    let hash = match sulogin::read_root_password() {
+        Some(RootPassword::Empty) => exec_shell(),
+        Some(RootPassword::Locked) => {
+            println!("Cannot open access to console, the root account is locked.");
+            process::exit(1);
+        }
+        Some(RootPassword::Hash(hash)) => hash,
+        None => {
+            log::error!("failed to find the password of root in /etc/shadow or /etc/passwd");
+            process::exit(1);
+        }
+    };
+
+    // keep asking like sulogin, there is nowhere else to go
+    loop {
+        match sulogin::read_password(
+            "Give root password for maintenance\n(or press Control-D to continue): ",
+        ) {
+            Ok(Some(password)) => {
+                if sulogin::password_matches(&password, &hash) {
+                    exec_shell();
+                }
+                println!("Login incorrect\n");
+            }
+            // Control-D leaves the mode, the default target is started after the service exits
+            Ok(None) => process::exit(0),
+            Err(e) => {
+                log::error!("failed to read the password: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use nix::errno::Errno;
+use nix::fcntl::{self, OFlag};
+use nix::sys::stat::Mode;
+use nix::sys::termios::{self, LocalFlags, SetArg};
+use nix::unistd;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::raw::c_char;
+use std::os::unix::io::RawFd;
+
+pub const CONSOLE: &str = "/dev/console";
+const SHADOW_PATH: &str = "/etc/shadow";
+const PASSWD_PATH: &str = "/etc/passwd";
+const DEFAULT_SHELL: &str = "/bin/sh";
+
+#[link(name = "crypt")]
+extern "C" {
+    fn crypt(key: *const c_char, salt: *const c_char) -> *mut c_char;
+}
+
+/// the state of the root password, which decides how the shell is entered
+#[derive(Debug, PartialEq, Eq)]
+pub enum RootPassword {
+    Empty,
+    Locked,
+    Hash(String),
+}
+
+/// find the password of root in the content of /etc/shadow or /etc/passwd,
+/// the second field is the hash, which starts with '!' or '*' when locked.
+/// "x" in /etc/passwd means the password is in /etc/shadow, which is not found.
+pub fn root_password(content: &str) -> Option<RootPassword> {
+    let fields: Vec<&str> = content
+        .lines()
+        .map(|l| l.split(':').collect::<Vec<&str>>())
+        .find(|f| f.len() > 1 && f[0] == "root")?;
+
+    Some(match fields[1] {
+        "x" => return None,
+        "" => RootPassword::Empty,
+        h if h.starts_with('!') || h.starts_with('*') => RootPassword::Locked,
+        h => RootPassword::Hash(h.to_string()),
+    })
+}
+
+/// the login shell of root, which is the last field in /etc/passwd
+pub fn root_shell(content: &str) -> String {
+    content
+        .lines()
+        .map(|l| l.split(':').collect::<Vec<&str>>())
+        .find(|f| f.len() == 7 && f[0] == "root" && !f[6].is_empty())
+        .map_or_else(|| DEFAULT_SHELL.to_string(), |f| f[6].to_string())
+}
+
+/// the password of root, the one in /etc/shadow takes precedence, /etc/passwd
+/// is used on the system without shadow passwords.
+pub fn read_root_password() -> Option<RootPassword> {
+    let shadow = fs::read_to_string(SHADOW_PATH).unwrap_or_default();
+    match root_password(&shadow) {
+        Some(p) => Some(p),
+        None => root_password(&fs::read_to_string(PASSWD_PATH).ok()?),
+    }
+}
+
+pub fn read_root_shell() -> String {
+    root_shell(&fs::read_to_string(PASSWD_PATH).unwrap_or_default())
+}
+
+pub fn password_matches(password: &str, hash: &str) -> bool {
+    let (key, salt) = match (CString::new(password), CString::new(hash)) {
+        (Ok(k), Ok(s)) => (k, s),
+        _ => return false,
+    };
+
+    let ret = unsafe { crypt(key.as_ptr(), salt.as_ptr()) };
+    if ret.is_null() {
+        return false;
+    }
+    unsafe { CStr::from_ptr(ret) }.to_bytes() == hash.as_bytes()
+}
+
+/// take /dev/console as the controlling terminal and the standard input and output,
+/// so that the shell can be interrupted with Control-C.
+pub fn setup_console() -> Result<(), Errno> {
+    let fd = fcntl::open(CONSOLE, OFlag::O_RDWR | OFlag::O_NOCTTY, Mode::empty())?;
+
+    // the session leader has no controlling terminal yet, or the console is stolen from others
+    let _ = unistd::setsid();
+    Errno::result(unsafe { libc::ioctl(fd, libc::TIOCSCTTY, 1) })?;
+
+    for stdfd in 0..3 {
+        unistd::dup2(fd, stdfd)?;
+    }
+    if fd > 2 {
+        unistd::close(fd)?;
+    }
+    Ok(())
+}
+
+/// read a line from the console without echoing, None means Control-D is pressed
+pub fn read_password(prompt: &str) -> io::Result<Option<String>> {
+    let mut stdout = io::stdout();
+    stdout.write_all(prompt.as_bytes())?;
+    stdout.flush()?;
+
+    let fd: RawFd = 0;
+    let saved = termios::tcgetattr(fd).ok();
+    if let Some(t) = &saved {
+        let mut noecho = t.clone();
+        noecho.local_flags.remove(LocalFlags::ECHO);
+        noecho.local_flags.insert(LocalFlags::ECHONL);
+        let _ = termios::tcsetattr(fd, SetArg::TCSANOW, &noecho);
+    }
+
+    let mut line = Vec::new();
+    let mut eof = false;
+    let mut byte = [0u8; 1];
+    loop {
+        match io::stdin().read(&mut byte) {
+            Ok(0) => {
+                eof = line.is_empty();
+                break;
+            }
+            Ok(_) if byte[0] == b'\n' => break,
+            Ok(_) => line.push(byte[0]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                eof = true;
+                log::warn!("failed to read the password: {}", e);
+                break;
+            }
+        }
+    }
+
+    if let Some(t) = &saved {
+        let _ = termios::tcsetattr(fd, SetArg::TCSANOW, t);
+    }
+
+    if eof {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8_lossy(&line).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_password() {
+        let shadow = "bin:*:18000::::::\nroot:$6$salt$hash:18000:0:99999:7:::\n";
+        assert_eq!(
+            root_password(shadow),
+            Some(RootPassword::Hash("$6$salt$hash".to_string()))
+        );
+        assert_eq!(
+            root_password("root:!locked:18000::::::"),
+            Some(RootPassword::Locked)
+        );
+        assert_eq!(
+            root_password("root::18000::::::"),
+            Some(RootPassword::Empty)
+        );
+        assert_eq!(root_password("bin:*:18000::::::"), None);
+        // the password is shadowed, but /etc/shadow is not read
+        assert_eq!(root_password("root:x:0:0:root:/root:/bin/bash"), None);
+    }
+
+    #[test]
+    fn test_root_shell() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nbin:x:1:1:bin:/bin:/sbin/nologin\n";
+        assert_eq!(root_shell(passwd), "/bin/bash");
+        assert_eq!(root_shell("root:x:0:0:root:/root:"), DEFAULT_SHELL);
+        assert_eq!(root_shell(""), DEFAULT_SHELL);
+    }
+}